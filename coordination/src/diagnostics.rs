@@ -0,0 +1,311 @@
+//! Compiler Diagnostics — parse `cargo --message-format=json` output.
+//!
+//! Turns rustc/clippy JSON messages into flat [`CompilerDiagnostic`]s with a
+//! primary location and any suggested replacements, so reviewer exports
+//! (SARIF, review comments) can anchor findings and offer fixes.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let output = Command::new("cargo")
+//!     .args(["clippy", "--message-format=json"])
+//!     .output()?;
+//! let diagnostics = parse_cargo_json(&String::from_utf8_lossy(&output.stdout));
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+/// Severity level of a compiler diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticLevel {
+    /// Hard error (including internal compiler errors).
+    Error,
+    /// Warning (including clippy lints).
+    Warning,
+    /// Note attached to another diagnostic.
+    Note,
+    /// Help message attached to another diagnostic.
+    Help,
+}
+
+impl DiagnosticLevel {
+    fn parse(level: &str) -> Self {
+        match level {
+            "error" | "error: internal compiler error" => Self::Error,
+            "warning" => Self::Warning,
+            "help" => Self::Help,
+            _ => Self::Note,
+        }
+    }
+}
+
+impl std::fmt::Display for DiagnosticLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+            Self::Note => write!(f, "note"),
+            Self::Help => write!(f, "help"),
+        }
+    }
+}
+
+/// How safely a suggestion can be applied (mirrors rustc's `Applicability`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Applicability {
+    /// Can be applied mechanically.
+    MachineApplicable,
+    /// May be incorrect; needs review.
+    MaybeIncorrect,
+    /// Contains placeholders the user must fill in.
+    HasPlaceholders,
+    /// Applicability unknown.
+    Unspecified,
+}
+
+impl Applicability {
+    fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some("MachineApplicable") => Self::MachineApplicable,
+            Some("MaybeIncorrect") => Self::MaybeIncorrect,
+            Some("HasPlaceholders") => Self::HasPlaceholders,
+            _ => Self::Unspecified,
+        }
+    }
+}
+
+/// A suggested replacement for a source span.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticSuggestion {
+    /// Help text explaining the suggestion.
+    pub message: String,
+    /// File the replacement applies to.
+    pub file: String,
+    /// First line of the replaced span (1-indexed).
+    pub line_start: u32,
+    /// First column of the replaced span (1-indexed).
+    pub column_start: u32,
+    /// Last line of the replaced span.
+    pub line_end: u32,
+    /// Column after the replaced span.
+    pub column_end: u32,
+    /// Replacement text.
+    pub replacement: String,
+    /// How safely the replacement can be applied.
+    pub applicability: Applicability,
+}
+
+impl DiagnosticSuggestion {
+    /// Whether the suggestion can be applied without review.
+    pub fn is_machine_applicable(&self) -> bool {
+        self.applicability == Applicability::MachineApplicable
+    }
+}
+
+/// A compiler diagnostic with its primary location.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompilerDiagnostic {
+    /// Severity level.
+    pub level: DiagnosticLevel,
+    /// Error or lint code (e.g., `E0308`, `clippy::unwrap_used`).
+    pub code: Option<String>,
+    /// Top-level message.
+    pub message: String,
+    /// File of the primary span.
+    pub file: String,
+    /// Start line (1-indexed).
+    pub line: u32,
+    /// Start column (1-indexed).
+    pub column: u32,
+    /// End line.
+    pub end_line: u32,
+    /// End column.
+    pub end_column: u32,
+    /// Full human-readable rendering, if provided.
+    pub rendered: Option<String>,
+    /// Suggested replacements from the diagnostic and its children.
+    pub suggestions: Vec<DiagnosticSuggestion>,
+}
+
+impl CompilerDiagnostic {
+    /// Suggestions that can be applied mechanically.
+    pub fn machine_applicable(&self) -> impl Iterator<Item = &DiagnosticSuggestion> {
+        self.suggestions
+            .iter()
+            .filter(|s| s.is_machine_applicable())
+    }
+}
+
+// ── Raw cargo JSON shape ─────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+struct CargoMessage {
+    reason: String,
+    #[serde(default)]
+    message: Option<RawDiagnostic>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawDiagnostic {
+    message: String,
+    #[serde(default)]
+    code: Option<RawCode>,
+    level: String,
+    #[serde(default)]
+    spans: Vec<RawSpan>,
+    #[serde(default)]
+    children: Vec<RawDiagnostic>,
+    #[serde(default)]
+    rendered: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawCode {
+    code: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawSpan {
+    file_name: String,
+    line_start: u32,
+    line_end: u32,
+    column_start: u32,
+    column_end: u32,
+    is_primary: bool,
+    #[serde(default)]
+    suggested_replacement: Option<String>,
+    #[serde(default)]
+    suggestion_applicability: Option<String>,
+}
+
+fn collect_suggestions(diag: &RawDiagnostic, out: &mut Vec<DiagnosticSuggestion>) {
+    for span in &diag.spans {
+        if let Some(replacement) = &span.suggested_replacement {
+            out.push(DiagnosticSuggestion {
+                message: diag.message.clone(),
+                file: span.file_name.clone(),
+                line_start: span.line_start,
+                column_start: span.column_start,
+                line_end: span.line_end,
+                column_end: span.column_end,
+                replacement: replacement.clone(),
+                applicability: Applicability::parse(span.suggestion_applicability.as_deref()),
+            });
+        }
+    }
+    for child in &diag.children {
+        collect_suggestions(child, out);
+    }
+}
+
+/// Parse one rustc diagnostic JSON object (as emitted by `rustc --error-format=json`).
+///
+/// Returns `None` for diagnostics without a source span, such as
+/// "aborting due to previous error" summaries.
+pub fn parse_rustc_diagnostic(value: serde_json::Value) -> Option<CompilerDiagnostic> {
+    let raw: RawDiagnostic = serde_json::from_value(value).ok()?;
+    convert(raw)
+}
+
+fn convert(raw: RawDiagnostic) -> Option<CompilerDiagnostic> {
+    let span = raw
+        .spans
+        .iter()
+        .find(|s| s.is_primary)
+        .or_else(|| raw.spans.first())?;
+    let mut suggestions = Vec::new();
+    collect_suggestions(&raw, &mut suggestions);
+    Some(CompilerDiagnostic {
+        level: DiagnosticLevel::parse(&raw.level),
+        code: raw.code.as_ref().map(|c| c.code.clone()),
+        message: raw.message.clone(),
+        file: span.file_name.clone(),
+        line: span.line_start,
+        column: span.column_start,
+        end_line: span.line_end,
+        end_column: span.column_end,
+        rendered: raw.rendered.clone(),
+        suggestions,
+    })
+}
+
+/// Parse `cargo --message-format=json` output into diagnostics.
+///
+/// Non-JSON lines and non-`compiler-message` records (artifacts, build
+/// script output) are ignored. Duplicate diagnostics, which cargo emits
+/// once per target that shares a source file, are collapsed.
+pub fn parse_cargo_json(output: &str) -> Vec<CompilerDiagnostic> {
+    let mut seen = std::collections::HashSet::new();
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<CargoMessage>(line.trim()).ok())
+        .filter(|m| m.reason == "compiler-message")
+        .filter_map(|m| m.message.and_then(convert))
+        .filter(|d| {
+            seen.insert((
+                d.file.clone(),
+                d.line,
+                d.column,
+                d.code.clone(),
+                d.message.clone(),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIPPY_JSON: &str = r#"{"reason":"compiler-artifact","package_id":"x"}
+{"reason":"compiler-message","package_id":"x","message":{"message":"used `unwrap()` on an `Option` value","code":{"code":"clippy::unwrap_used","explanation":null},"level":"warning","spans":[{"file_name":"src/lib.rs","byte_start":10,"byte_end":20,"line_start":3,"line_end":3,"column_start":5,"column_end":17,"is_primary":true,"text":[],"label":null,"suggested_replacement":null,"suggestion_applicability":null,"expansion":null}],"children":[{"message":"use `?` instead","code":null,"level":"help","spans":[{"file_name":"src/lib.rs","byte_start":10,"byte_end":20,"line_start":3,"line_end":3,"column_start":5,"column_end":17,"is_primary":true,"text":[],"label":null,"suggested_replacement":"opt?","suggestion_applicability":"MachineApplicable","expansion":null}],"children":[],"rendered":null}],"rendered":"warning: used `unwrap()`\n"}}
+{"reason":"compiler-message","package_id":"x","message":{"message":"aborting due to 1 previous error","code":null,"level":"error","spans":[],"children":[],"rendered":"error: aborting\n"}}
+not json
+{"reason":"build-finished","success":false}
+"#;
+
+    #[test]
+    fn test_parse_cargo_json() {
+        let diags = parse_cargo_json(CLIPPY_JSON);
+        assert_eq!(diags.len(), 1);
+        let d = &diags[0];
+        assert_eq!(d.level, DiagnosticLevel::Warning);
+        assert_eq!(d.code.as_deref(), Some("clippy::unwrap_used"));
+        assert_eq!((d.file.as_str(), d.line, d.column), ("src/lib.rs", 3, 5));
+        assert_eq!(d.end_column, 17);
+        assert_eq!(d.suggestions.len(), 1);
+        assert_eq!(d.suggestions[0].replacement, "opt?");
+        assert_eq!(d.machine_applicable().count(), 1);
+    }
+
+    #[test]
+    fn test_duplicates_collapsed() {
+        let line = CLIPPY_JSON.lines().nth(1).unwrap();
+        let doubled = format!("{line}\n{line}\n");
+        assert_eq!(parse_cargo_json(&doubled).len(), 1);
+    }
+
+    #[test]
+    fn test_parse_rustc_diagnostic() {
+        let value = serde_json::json!({
+            "message": "mismatched types",
+            "code": {"code": "E0308"},
+            "level": "error",
+            "spans": [{
+                "file_name": "src/main.rs", "line_start": 2, "line_end": 2,
+                "column_start": 9, "column_end": 12, "is_primary": true,
+                "suggested_replacement": "1u32", "suggestion_applicability": "MaybeIncorrect"
+            }],
+            "children": []
+        });
+        let d = parse_rustc_diagnostic(value).unwrap();
+        assert_eq!(d.level, DiagnosticLevel::Error);
+        assert_eq!(
+            d.suggestions[0].applicability,
+            Applicability::MaybeIncorrect
+        );
+        assert_eq!(d.machine_applicable().count(), 0);
+        assert!(parse_rustc_diagnostic(serde_json::json!({"level": "error"})).is_none());
+    }
+}
@@ -0,0 +1,862 @@
+//! Cross-process event broker over a Unix domain socket
+//!
+//! [`EventBroker`] exposes an in-process [`EventBus`] to other processes
+//! (the Python worker, monitoring scripts). Remote clients subscribe with an
+//! [`EventFilter`] and may publish events back onto the bus.
+//!
+//! # Wire format
+//!
+//! Every message is a frame: a 4-byte big-endian length followed by that
+//! many bytes of JSON. Frames are tagged with `op`:
+//!
+//! ```text
+//! client → broker   {"op":"hello","stream":"ensemble","filter":{"session_id":"s1"}}
+//! broker → client   {"op":"welcome","client_id":1,"stream":"ensemble"}
+//! broker → client   {"op":"event","event":{"type":"task_created",...}}
+//! client → broker   {"op":"publish","seq":7,"event":{"type":"task_failed",...}}
+//! broker → client   {"op":"ack","seq":7}  |  {"op":"rejected","seq":7,"reason":"..."}
+//! broker → client   {"op":"lagged","skipped":12}
+//! broker → client   {"op":"error","message":"..."}   (then closes)
+//! ```
+//!
+//! `lagged` also reports bus events skipped because their frame would exceed
+//! the size limit; the connection stays open.
+//!
+//! The first client frame must be `hello` naming the bus stream. Published
+//! events are parsed as the stream's event type, checked against the clock
+//! and an optional validator, then published like any local event — so the
+//! publisher also receives them if its filter matches.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use super::bus::{EventFilter, SharedEventBus};
+use super::types::{BusEvent, EnsembleEvent};
+
+/// Largest frame accepted by default (1 MiB)
+pub const DEFAULT_MAX_FRAME_BYTES: usize = 1 << 20;
+
+/// Error type for broker operations
+#[derive(Debug, thiserror::Error)]
+pub enum BrokerError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Protocol error: {0}")]
+    Protocol(String),
+
+    #[error("Frame of {0} bytes exceeds the limit")]
+    FrameTooLarge(usize),
+
+    #[error("Broker refused connection: {0}")]
+    Refused(String),
+
+    #[error("Connection closed")]
+    Closed,
+}
+
+/// Result type for broker operations
+pub type BrokerResult<T> = Result<T, BrokerError>;
+
+// ── Frames ──────────────────────────────────────────────────────────
+
+/// Frames sent by clients
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ClientFrame {
+    /// Opening frame: stream name and subscription filter
+    Hello {
+        stream: String,
+        #[serde(default)]
+        filter: EventFilter,
+    },
+    /// Publish an event onto the bus
+    Publish { seq: u64, event: serde_json::Value },
+}
+
+/// Frames sent by the broker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+#[serde(bound(serialize = "E: Serialize", deserialize = "E: DeserializeOwned"))]
+pub enum ServerFrame<E> {
+    Welcome { client_id: u64, stream: String },
+    Event { event: E },
+    Ack { seq: u64 },
+    Rejected { seq: u64, reason: String },
+    Lagged { skipped: u64 },
+    Error { message: String },
+}
+
+async fn write_frame<W, T>(writer: &mut W, message: &T, max_bytes: usize) -> BrokerResult<()>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    let body = serde_json::to_vec(message).map_err(|e| BrokerError::Protocol(e.to_string()))?;
+    if body.len() > max_bytes {
+        return Err(BrokerError::FrameTooLarge(body.len()));
+    }
+    writer.write_u32(body.len() as u32).await?;
+    writer.write_all(&body).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Read one frame; `Ok(None)` on a clean end of stream
+async fn read_frame<R, T>(reader: &mut R, max_bytes: usize) -> BrokerResult<Option<T>>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    let len = match reader.read_u32().await {
+        Ok(len) => len as usize,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    if len > max_bytes {
+        return Err(BrokerError::FrameTooLarge(len));
+    }
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body).await?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| BrokerError::Protocol(format!("malformed frame: {e}")))
+}
+
+// ── Broker ──────────────────────────────────────────────────────────
+
+/// Validator run on every event a client publishes
+pub type EventValidator<E> = Arc<dyn Fn(&E) -> Result<(), String> + Send + Sync>;
+
+/// Broker settings
+#[derive(Debug, Clone)]
+pub struct BrokerConfig {
+    /// Accept `publish` frames from clients
+    pub allow_publish: bool,
+    /// Largest frame in either direction
+    pub max_frame_bytes: usize,
+    /// Reject published events timestamped further than this in the future
+    pub max_clock_skew: Duration,
+    /// Time allowed for the `hello` frame
+    pub handshake_timeout: Duration,
+}
+
+impl BrokerConfig {
+    /// Publishing allowed, 1 MiB frames, 5 minutes of skew
+    pub fn new() -> Self {
+        Self {
+            allow_publish: true,
+            max_frame_bytes: DEFAULT_MAX_FRAME_BYTES,
+            max_clock_skew: Duration::from_secs(300),
+            handshake_timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Enable or disable client publishing
+    pub fn with_allow_publish(mut self, allow: bool) -> Self {
+        self.allow_publish = allow;
+        self
+    }
+
+    /// Set the frame size limit
+    pub fn with_max_frame_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_frame_bytes = max_bytes;
+        self
+    }
+
+    /// Set the tolerated clock skew for published events
+    pub fn with_max_clock_skew(mut self, skew: Duration) -> Self {
+        self.max_clock_skew = skew;
+        self
+    }
+}
+
+impl Default for BrokerConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Broker-wide counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct BrokerStats {
+    pub clients_connected: u64,
+    pub clients_total: u64,
+    pub events_forwarded: u64,
+    /// Matching events not forwarded because their frame was too large
+    pub events_oversized: u64,
+    pub events_published: u64,
+    pub events_rejected: u64,
+}
+
+#[derive(Default)]
+struct BrokerCounters {
+    clients_connected: AtomicU64,
+    clients_total: AtomicU64,
+    events_forwarded: AtomicU64,
+    events_oversized: AtomicU64,
+    events_published: AtomicU64,
+    events_rejected: AtomicU64,
+}
+
+impl BrokerCounters {
+    fn snapshot(&self) -> BrokerStats {
+        BrokerStats {
+            clients_connected: self.clients_connected.load(Ordering::Relaxed),
+            clients_total: self.clients_total.load(Ordering::Relaxed),
+            events_forwarded: self.events_forwarded.load(Ordering::Relaxed),
+            events_oversized: self.events_oversized.load(Ordering::Relaxed),
+            events_published: self.events_published.load(Ordering::Relaxed),
+            events_rejected: self.events_rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Serves an [`EventBus`](super::EventBus) on a Unix socket
+pub struct EventBroker<E: BusEvent = EnsembleEvent> {
+    bus: SharedEventBus<E>,
+    path: PathBuf,
+    config: BrokerConfig,
+    validator: Option<EventValidator<E>>,
+}
+
+impl<E: BusEvent> EventBroker<E> {
+    /// Create a broker for `bus` listening at `path`
+    pub fn new(bus: SharedEventBus<E>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            bus,
+            path: path.into(),
+            config: BrokerConfig::new(),
+            validator: None,
+        }
+    }
+
+    /// Replace the broker settings
+    pub fn with_config(mut self, config: BrokerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Run an extra check on every client-published event
+    pub fn with_validator(
+        mut self,
+        validator: impl Fn(&E) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// Bind the socket and start accepting clients
+    ///
+    /// A stale socket file left by a previous run is replaced. Refuses to
+    /// touch anything else at `path`: a live broker's socket or a file that
+    /// is not a socket.
+    pub async fn bind(self) -> BrokerResult<BrokerHandle> {
+        if let Ok(metadata) = std::fs::symlink_metadata(&self.path) {
+            use std::os::unix::fs::FileTypeExt;
+            if !metadata.file_type().is_socket() {
+                return Err(BrokerError::Refused(format!(
+                    "{} exists and is not a socket",
+                    self.path.display()
+                )));
+            }
+            if UnixStream::connect(&self.path).await.is_ok() {
+                return Err(BrokerError::Refused(format!(
+                    "another broker is listening on {}",
+                    self.path.display()
+                )));
+            }
+            std::fs::remove_file(&self.path)?;
+        }
+        let listener = UnixListener::bind(&self.path)?;
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let counters = Arc::new(BrokerCounters::default());
+
+        info!(path = %self.path.display(), stream = E::STREAM, "Event broker listening");
+        let shared = Arc::new(BrokerShared {
+            bus: self.bus,
+            config: self.config,
+            validator: self.validator,
+            counters: Arc::clone(&counters),
+        });
+        let task = tokio::spawn(accept_loop(listener, shared, shutdown_rx));
+
+        Ok(BrokerHandle {
+            path: self.path,
+            counters,
+            shutdown: shutdown_tx,
+            task: Some(task),
+        })
+    }
+}
+
+/// Handle to a running broker
+///
+/// Dropping the handle stops accepting clients and disconnects them.
+pub struct BrokerHandle {
+    path: PathBuf,
+    counters: Arc<BrokerCounters>,
+    shutdown: watch::Sender<bool>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl BrokerHandle {
+    /// Socket path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Current counters
+    pub fn stats(&self) -> BrokerStats {
+        self.counters.snapshot()
+    }
+
+    /// Disconnect clients, stop listening and remove the socket file
+    pub async fn shutdown(mut self) -> BrokerStats {
+        let _ = self.shutdown.send(true);
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+        let _ = std::fs::remove_file(&self.path);
+        self.counters.snapshot()
+    }
+}
+
+impl Drop for BrokerHandle {
+    fn drop(&mut self) {
+        let _ = self.shutdown.send(true);
+    }
+}
+
+struct BrokerShared<E: BusEvent> {
+    bus: SharedEventBus<E>,
+    config: BrokerConfig,
+    validator: Option<EventValidator<E>>,
+    counters: Arc<BrokerCounters>,
+}
+
+async fn accept_loop<E: BusEvent>(
+    listener: UnixListener,
+    shared: Arc<BrokerShared<E>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut clients = tokio::task::JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    let client_id = shared.counters.clients_total.fetch_add(1, Ordering::Relaxed) + 1;
+                    let shared = Arc::clone(&shared);
+                    let shutdown = shutdown.clone();
+                    clients.spawn(async move {
+                        shared.counters.clients_connected.fetch_add(1, Ordering::Relaxed);
+                        if let Err(e) = serve_client(stream, client_id, &shared, shutdown).await {
+                            debug!(client_id, "Broker client disconnected: {}", e);
+                        }
+                        shared.counters.clients_connected.fetch_sub(1, Ordering::Relaxed);
+                    });
+                }
+                Err(e) => warn!("Broker accept failed: {}", e),
+            },
+            _ = shutdown.changed() => break,
+        }
+    }
+    while clients.join_next().await.is_some() {}
+    debug!("Event broker stopped");
+}
+
+async fn serve_client<E: BusEvent>(
+    stream: UnixStream,
+    client_id: u64,
+    shared: &BrokerShared<E>,
+    mut shutdown: watch::Receiver<bool>,
+) -> BrokerResult<()> {
+    let max = shared.config.max_frame_bytes;
+    let (mut reader, mut writer) = stream.into_split();
+
+    let hello = tokio::time::timeout(
+        shared.config.handshake_timeout,
+        read_frame::<_, ClientFrame>(&mut reader, max),
+    )
+    .await
+    .map_err(|_| BrokerError::Protocol("handshake timed out".to_string()))?;
+    let filter = match hello {
+        Ok(Some(ClientFrame::Hello { stream, filter })) if stream == E::STREAM => filter,
+        Ok(Some(ClientFrame::Hello { stream, .. })) => {
+            let message = format!("unknown stream '{}', broker serves '{}'", stream, E::STREAM);
+            return refuse::<E>(&mut writer, message, max).await;
+        }
+        Ok(Some(_)) => {
+            return refuse::<E>(&mut writer, "expected hello".to_string(), max).await;
+        }
+        Ok(None) => return Ok(()),
+        Err(e) => return refuse::<E>(&mut writer, e.to_string(), max).await,
+    };
+
+    // Subscribe before welcoming so nothing published after the welcome is missed
+    let mut events = shared.bus.subscribe();
+    let welcome: ServerFrame<E> = ServerFrame::Welcome {
+        client_id,
+        stream: E::STREAM.to_string(),
+    };
+    write_frame(&mut writer, &welcome, max).await?;
+    debug!(client_id, stream = E::STREAM, "Broker client connected");
+
+    // Reads run on their own task: read_frame is not cancel-safe in select!
+    let (frames_tx, mut frames) = mpsc::channel(32);
+    let reader_task = tokio::spawn(read_loop(reader, max, frames_tx));
+
+    let result = loop {
+        tokio::select! {
+            frame = frames.recv() => match frame {
+                Some(Ok(ClientFrame::Publish { seq, event })) => {
+                    let reply = handle_publish(shared, seq, event);
+                    write_frame(&mut writer, &reply, max).await?;
+                }
+                Some(Ok(ClientFrame::Hello { .. })) => {
+                    break refuse::<E>(&mut writer, "duplicate hello".to_string(), max).await;
+                }
+                Some(Err(e)) => break refuse::<E>(&mut writer, e.to_string(), max).await,
+                None => break Ok(()),
+            },
+            received = events.recv() => match received {
+                Ok(event) => {
+                    if filter.matches(&event) {
+                        match write_frame(&mut writer, &ServerFrame::Event { event }, max).await {
+                            Ok(()) => {
+                                shared.counters.events_forwarded.fetch_add(1, Ordering::Relaxed);
+                            }
+                            // Nothing was written, so the stream is still in sync
+                            Err(BrokerError::FrameTooLarge(len)) => {
+                                warn!(client_id, len, "Skipped oversized bus event");
+                                shared.counters.events_oversized.fetch_add(1, Ordering::Relaxed);
+                                let notice = ServerFrame::<E>::Lagged { skipped: 1 };
+                                write_frame(&mut writer, &notice, max).await?;
+                            }
+                            Err(e) => return Err(e),
+                        }
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(client_id, skipped, "Broker client lagged behind the bus");
+                    write_frame(&mut writer, &ServerFrame::<E>::Lagged { skipped }, max).await?;
+                }
+                Err(broadcast::error::RecvError::Closed) => break Ok(()),
+            },
+            _ = shutdown.changed() => break Ok(()),
+        }
+    };
+    reader_task.abort();
+    result
+}
+
+async fn read_loop(
+    mut reader: OwnedReadHalf,
+    max: usize,
+    frames: mpsc::Sender<BrokerResult<ClientFrame>>,
+) {
+    loop {
+        match read_frame(&mut reader, max).await {
+            Ok(Some(frame)) => {
+                if frames.send(Ok(frame)).await.is_err() {
+                    return;
+                }
+            }
+            Ok(None) => return,
+            Err(e) => {
+                let _ = frames.send(Err(e)).await;
+                return;
+            }
+        }
+    }
+}
+
+/// Send an error frame and end the connection
+async fn refuse<E: BusEvent>(
+    writer: &mut OwnedWriteHalf,
+    message: String,
+    max: usize,
+) -> BrokerResult<()> {
+    let frame: ServerFrame<E> = ServerFrame::Error {
+        message: message.clone(),
+    };
+    let _ = write_frame(writer, &frame, max).await;
+    Err(BrokerError::Protocol(message))
+}
+
+fn handle_publish<E: BusEvent>(
+    shared: &BrokerShared<E>,
+    seq: u64,
+    event: serde_json::Value,
+) -> ServerFrame<E> {
+    match validate_publish(shared, event) {
+        Ok(()) => {
+            shared
+                .counters
+                .events_published
+                .fetch_add(1, Ordering::Relaxed);
+            ServerFrame::Ack { seq }
+        }
+        Err(reason) => {
+            shared
+                .counters
+                .events_rejected
+                .fetch_add(1, Ordering::Relaxed);
+            debug!(seq, "Rejected published event: {}", reason);
+            ServerFrame::Rejected { seq, reason }
+        }
+    }
+}
+
+fn validate_publish<E: BusEvent>(
+    shared: &BrokerShared<E>,
+    event: serde_json::Value,
+) -> Result<(), String> {
+    if !shared.config.allow_publish {
+        return Err("publishing is disabled on this broker".to_string());
+    }
+    let event: E = serde_json::from_value(event).map_err(|e| format!("invalid event: {e}"))?;
+
+    let skew =
+        chrono::Duration::from_std(shared.config.max_clock_skew).unwrap_or(chrono::Duration::MAX);
+    if event.timestamp() > Utc::now() + skew {
+        return Err(format!("timestamp {} is in the future", event.timestamp()));
+    }
+    if let Some(validator) = &shared.validator {
+        validator(&event)?;
+    }
+    shared.bus.publish(event).map_err(|e| e.to_string())
+}
+
+// ── Client ──────────────────────────────────────────────────────────
+
+/// Message received by a [`BrokerClient`]
+#[derive(Debug, Clone)]
+pub enum BrokerMessage<E> {
+    /// A bus event matching the client's filter
+    Event(E),
+    /// A published event was accepted
+    Ack { seq: u64 },
+    /// A published event was rejected
+    Rejected { seq: u64, reason: String },
+    /// The broker skipped events because this client fell behind or they
+    /// exceeded the frame limit
+    Lagged { skipped: u64 },
+}
+
+/// Rust client for an [`EventBroker`]
+pub struct BrokerClient<E: BusEvent = EnsembleEvent> {
+    reader: OwnedReadHalf,
+    writer: OwnedWriteHalf,
+    client_id: u64,
+    next_seq: u64,
+    max_frame_bytes: usize,
+    _stream: std::marker::PhantomData<fn() -> E>,
+}
+
+impl<E: BusEvent> BrokerClient<E> {
+    /// Connect and subscribe with `filter`
+    pub async fn connect(path: impl AsRef<Path>, filter: EventFilter) -> BrokerResult<Self> {
+        let stream = UnixStream::connect(path.as_ref()).await?;
+        let (mut reader, mut writer) = stream.into_split();
+        let max = DEFAULT_MAX_FRAME_BYTES;
+
+        let hello = ClientFrame::Hello {
+            stream: E::STREAM.to_string(),
+            filter,
+        };
+        write_frame(&mut writer, &hello, max).await?;
+        let client_id = match read_frame::<_, ServerFrame<E>>(&mut reader, max).await? {
+            Some(ServerFrame::Welcome { client_id, .. }) => client_id,
+            Some(ServerFrame::Error { message }) => return Err(BrokerError::Refused(message)),
+            Some(_) => return Err(BrokerError::Protocol("expected welcome".to_string())),
+            None => return Err(BrokerError::Closed),
+        };
+
+        Ok(Self {
+            reader,
+            writer,
+            client_id,
+            next_seq: 1,
+            max_frame_bytes: max,
+            _stream: std::marker::PhantomData,
+        })
+    }
+
+    /// Id the broker assigned to this connection
+    pub fn client_id(&self) -> u64 {
+        self.client_id
+    }
+
+    /// Publish an event; the outcome arrives later as `Ack` or `Rejected`
+    pub async fn publish(&mut self, event: &E) -> BrokerResult<u64> {
+        let event =
+            serde_json::to_value(event).map_err(|e| BrokerError::Protocol(e.to_string()))?;
+        self.publish_raw(event).await
+    }
+
+    /// Publish raw JSON, as a non-Rust client would
+    pub async fn publish_raw(&mut self, event: serde_json::Value) -> BrokerResult<u64> {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        write_frame(
+            &mut self.writer,
+            &ClientFrame::Publish { seq, event },
+            self.max_frame_bytes,
+        )
+        .await?;
+        Ok(seq)
+    }
+
+    /// Receive the next message
+    pub async fn recv(&mut self) -> BrokerResult<BrokerMessage<E>> {
+        let frame = read_frame::<_, ServerFrame<E>>(&mut self.reader, self.max_frame_bytes)
+            .await?
+            .ok_or(BrokerError::Closed)?;
+        match frame {
+            ServerFrame::Event { event } => Ok(BrokerMessage::Event(event)),
+            ServerFrame::Ack { seq } => Ok(BrokerMessage::Ack { seq }),
+            ServerFrame::Rejected { seq, reason } => Ok(BrokerMessage::Rejected { seq, reason }),
+            ServerFrame::Lagged { skipped } => Ok(BrokerMessage::Lagged { skipped }),
+            ServerFrame::Error { message } => Err(BrokerError::Protocol(message)),
+            ServerFrame::Welcome { .. } => Err(BrokerError::Protocol("unexpected welcome".into())),
+        }
+    }
+
+    /// Receive the next event, skipping acknowledgements and notices
+    pub async fn next_event(&mut self) -> BrokerResult<E> {
+        loop {
+            if let BrokerMessage::Event(event) = self.recv().await? {
+                return Ok(event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{EventBus, SwarmEvent};
+    use crate::state::ModelId;
+    use tempfile::tempdir;
+
+    fn task_created(task_id: &str, session_id: &str) -> EnsembleEvent {
+        EnsembleEvent::TaskCreated {
+            task_id: task_id.to_string(),
+            session_id: session_id.to_string(),
+            prompt_preview: "test".to_string(),
+            require_consensus: false,
+            timestamp: Utc::now(),
+        }
+    }
+
+    async fn within<T>(f: impl std::future::Future<Output = T>) -> T {
+        tokio::time::timeout(Duration::from_secs(5), f)
+            .await
+            .expect("timed out")
+    }
+
+    #[tokio::test]
+    async fn test_remote_subscriber_gets_filtered_events() {
+        let dir = tempdir().unwrap();
+        let bus = EventBus::<EnsembleEvent>::new().shared();
+        let broker = EventBroker::new(bus.clone(), dir.path().join("bus.sock"))
+            .bind()
+            .await
+            .unwrap();
+
+        let mut client =
+            BrokerClient::<EnsembleEvent>::connect(broker.path(), EventFilter::new().session("s2"))
+                .await
+                .unwrap();
+        assert_eq!(client.client_id(), 1);
+
+        bus.publish(task_created("t1", "s1")).unwrap();
+        bus.publish(task_created("t2", "s2")).unwrap();
+
+        let event = within(client.next_event()).await.unwrap();
+        assert_eq!(event.task_id(), Some("t2"));
+        assert_eq!(broker.stats().events_forwarded, 1);
+
+        let stats = broker.shutdown().await;
+        assert_eq!(stats.clients_total, 1);
+        assert!(matches!(
+            within(client.recv()).await,
+            Err(BrokerError::Closed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_remote_publish_is_validated() {
+        let dir = tempdir().unwrap();
+        let bus = EventBus::<EnsembleEvent>::new().shared();
+        let mut local = bus.subscribe();
+        let broker = EventBroker::new(bus.clone(), dir.path().join("bus.sock"))
+            .with_validator(|event: &EnsembleEvent| match event {
+                EnsembleEvent::ModelLoaded { .. } => Err("models load locally".to_string()),
+                _ => Ok(()),
+            })
+            .bind()
+            .await
+            .unwrap();
+        let mut client =
+            BrokerClient::<EnsembleEvent>::connect(broker.path(), EventFilter::new().task("none"))
+                .await
+                .unwrap();
+
+        let ok = client.publish(&task_created("t9", "s1")).await.unwrap();
+        assert!(
+            matches!(within(client.recv()).await.unwrap(), BrokerMessage::Ack { seq } if seq == ok)
+        );
+        assert_eq!(within(local.recv()).await.unwrap().task_id(), Some("t9"));
+
+        // Unknown type, validator refusal and future timestamp are all rejected
+        let bad = [
+            serde_json::json!({"type": "not_an_event", "timestamp": Utc::now()}),
+            serde_json::to_value(EnsembleEvent::ModelLoaded {
+                model_id: ModelId::QWEN35,
+                load_time_ms: 1,
+                timestamp: Utc::now(),
+            })
+            .unwrap(),
+            serde_json::to_value(EnsembleEvent::TaskCreated {
+                task_id: "t10".to_string(),
+                session_id: "s1".to_string(),
+                prompt_preview: "later".to_string(),
+                require_consensus: false,
+                timestamp: Utc::now() + chrono::Duration::hours(1),
+            })
+            .unwrap(),
+        ];
+        for event in bad {
+            let seq = client.publish_raw(event).await.unwrap();
+            match within(client.recv()).await.unwrap() {
+                BrokerMessage::Rejected { seq: got, .. } => assert_eq!(got, seq),
+                other => panic!("expected rejection, got {other:?}"),
+            }
+        }
+
+        assert!(local.try_recv().is_err());
+        let stats = broker.shutdown().await;
+        assert_eq!((stats.events_published, stats.events_rejected), (1, 3));
+    }
+
+    #[tokio::test]
+    async fn test_handshake_checks_stream() {
+        let dir = tempdir().unwrap();
+        let bus = EventBus::<SwarmEvent>::new().shared();
+        let broker = EventBroker::new(bus, dir.path().join("swarm.sock"))
+            .bind()
+            .await
+            .unwrap();
+
+        let err = BrokerClient::<EnsembleEvent>::connect(broker.path(), EventFilter::new())
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, BrokerError::Refused(ref m) if m.contains("swarm")));
+        assert!(
+            BrokerClient::<SwarmEvent>::connect(broker.path(), EventFilter::new())
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bind_only_replaces_stale_sockets() {
+        let dir = tempdir().unwrap();
+        let bus = EventBus::<EnsembleEvent>::new().shared();
+
+        let file = dir.path().join("not-a-socket");
+        std::fs::write(&file, "keep me").unwrap();
+        let err = EventBroker::new(bus.clone(), &file)
+            .bind()
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, BrokerError::Refused(ref m) if m.contains("not a socket")));
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "keep me");
+
+        let path = dir.path().join("bus.sock");
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        let broker = EventBroker::new(bus.clone(), &path).bind().await.unwrap();
+
+        let err = EventBroker::new(bus.clone(), &path)
+            .bind()
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, BrokerError::Refused(ref m) if m.contains("listening")));
+        assert!(
+            BrokerClient::<EnsembleEvent>::connect(broker.path(), EventFilter::new())
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oversized_event_is_skipped() {
+        let dir = tempdir().unwrap();
+        let bus = EventBus::<EnsembleEvent>::new().shared();
+        let broker = EventBroker::new(bus.clone(), dir.path().join("bus.sock"))
+            .with_config(BrokerConfig::new().with_max_frame_bytes(512))
+            .bind()
+            .await
+            .unwrap();
+        let mut client = BrokerClient::<EnsembleEvent>::connect(broker.path(), EventFilter::new())
+            .await
+            .unwrap();
+
+        bus.publish(EnsembleEvent::TaskCreated {
+            task_id: "big".to_string(),
+            session_id: "s1".to_string(),
+            prompt_preview: "x".repeat(1024),
+            require_consensus: false,
+            timestamp: Utc::now(),
+        })
+        .unwrap();
+        bus.publish(task_created("t2", "s1")).unwrap();
+
+        assert!(matches!(
+            within(client.recv()).await.unwrap(),
+            BrokerMessage::Lagged { skipped: 1 }
+        ));
+        let event = within(client.next_event()).await.unwrap();
+        assert_eq!(event.task_id(), Some("t2"));
+
+        let stats = broker.shutdown().await;
+        assert_eq!((stats.events_forwarded, stats.events_oversized), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_frame_closes_connection() {
+        let dir = tempdir().unwrap();
+        let bus = EventBus::<EnsembleEvent>::new().shared();
+        let broker = EventBroker::new(bus, dir.path().join("bus.sock"))
+            .with_config(BrokerConfig::new().with_max_frame_bytes(256))
+            .bind()
+            .await
+            .unwrap();
+        let mut client = BrokerClient::<EnsembleEvent>::connect(broker.path(), EventFilter::new())
+            .await
+            .unwrap();
+
+        client
+            .publish_raw(serde_json::json!({"padding": "x".repeat(1024)}))
+            .await
+            .unwrap();
+        let err = within(client.recv()).await.unwrap_err();
+        assert!(err.to_string().contains("exceeds"));
+    }
+}
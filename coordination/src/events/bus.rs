@@ -1,14 +1,18 @@
 //! Event bus for ensemble coordination
 //!
 //! Provides pub/sub messaging using Tokio broadcast channels with
-//! optional persistence to RocksDB for event replay.
+//! optional persistence to a state backend for event replay.
+//!
+//! The bus is generic over the event stream: `EventBus` (the default)
+//! carries [`EnsembleEvent`]s and `EventBus<SwarmEvent>` carries the worker
+//! dispatch lifecycle. Both persist into the same event log.
 
 use std::sync::Arc;
 use tokio::sync::broadcast;
 use tracing::{debug, warn};
 
-use super::types::EnsembleEvent;
-use crate::state::SharedStateStore;
+use super::types::{BusEvent, EnsembleEvent, SwarmEvent};
+use crate::state::{SharedStateStore, StateBackendExt};
 
 /// Channel capacity for broadcast
 const CHANNEL_CAPACITY: usize = 256;
@@ -30,12 +34,15 @@ pub enum EventBusError {
 pub type EventBusResult<T> = Result<T, EventBusError>;
 
 /// Shared reference to EventBus
-pub type SharedEventBus = Arc<EventBus>;
+pub type SharedEventBus<E = EnsembleEvent> = Arc<EventBus<E>>;
+
+/// Event bus for the worker dispatch protocol
+pub type SwarmEventBus = EventBus<SwarmEvent>;
 
 /// Event bus with broadcast channels and optional persistence
-pub struct EventBus {
+pub struct EventBus<E: BusEvent = EnsembleEvent> {
     /// Broadcast sender for publishing events
-    sender: broadcast::Sender<EnsembleEvent>,
+    sender: broadcast::Sender<E>,
 
     /// Optional state store for event persistence
     store: Option<SharedStateStore>,
@@ -44,7 +51,7 @@ pub struct EventBus {
     persist_events: bool,
 }
 
-impl EventBus {
+impl<E: BusEvent> EventBus<E> {
     /// Create a new event bus without persistence
     pub fn new() -> Self {
         let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
@@ -66,7 +73,7 @@ impl EventBus {
     }
 
     /// Create a shared reference to this event bus
-    pub fn shared(self) -> SharedEventBus {
+    pub fn shared(self) -> SharedEventBus<E> {
         Arc::new(self)
     }
 
@@ -76,7 +83,8 @@ impl EventBus {
     }
 
     /// Publish an event to all subscribers
-    pub fn publish(&self, event: EnsembleEvent) -> EventBusResult<()> {
+    pub fn publish(&self, event: E) -> EventBusResult<()> {
+        let stream = E::STREAM;
         let event_type = event.event_type();
         let timestamp = event.timestamp();
 
@@ -87,29 +95,29 @@ impl EventBus {
                 let timestamp_nanos = timestamp.timestamp_nanos_opt().unwrap_or(0);
 
                 if let Err(e) = store.put_event(timestamp_nanos, &event_id, &event) {
-                    warn!(event_type, "Failed to persist event: {}", e);
+                    warn!(stream, event_type, "Failed to persist event: {}", e);
                     return Err(EventBusError::PersistFailed(e.to_string()));
                 }
-                debug!(event_type, event_id, "Event persisted");
+                debug!(stream, event_type, event_id, "Event persisted");
             }
         }
 
         // Broadcast to subscribers (ignore if no receivers)
         match self.sender.send(event) {
             Ok(count) => {
-                debug!(event_type, receivers = count, "Event published");
+                debug!(stream, event_type, receivers = count, "Event published");
                 Ok(())
             }
             Err(_) => {
                 // No receivers is OK - we still persisted
-                debug!(event_type, "Event published (no receivers)");
+                debug!(stream, event_type, "Event published (no receivers)");
                 Ok(())
             }
         }
     }
 
     /// Subscribe to receive events
-    pub fn subscribe(&self) -> broadcast::Receiver<EnsembleEvent> {
+    pub fn subscribe(&self) -> broadcast::Receiver<E> {
         self.sender.subscribe()
     }
 
@@ -124,18 +132,24 @@ impl EventBus {
     }
 }
 
-impl Default for EventBus {
+impl<E: BusEvent> Default for EventBus<E> {
     fn default() -> Self {
         Self::new()
     }
 }
 
 /// Event filter for selective subscription
+///
+/// Serializable so remote subscribers can send one when they connect.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct EventFilter {
     /// Filter by session ID
     pub session_id: Option<String>,
     /// Filter by task ID
     pub task_id: Option<String>,
+    /// Filter by work order ID
+    pub order_id: Option<String>,
     /// Filter by event types
     pub event_types: Option<Vec<String>>,
 }
@@ -146,6 +160,7 @@ impl EventFilter {
         Self {
             session_id: None,
             task_id: None,
+            order_id: None,
             event_types: None,
         }
     }
@@ -162,6 +177,12 @@ impl EventFilter {
         self
     }
 
+    /// Filter by work order ID
+    pub fn order(mut self, order_id: &str) -> Self {
+        self.order_id = Some(order_id.to_string());
+        self
+    }
+
     /// Filter by event types
     pub fn types(mut self, event_types: Vec<&str>) -> Self {
         self.event_types = Some(event_types.into_iter().map(String::from).collect());
@@ -169,7 +190,10 @@ impl EventFilter {
     }
 
     /// Check if an event matches this filter
-    pub fn matches(&self, event: &EnsembleEvent) -> bool {
+    ///
+    /// Scope filters (session, task, order) only reject events that carry
+    /// that scope with a different value.
+    pub fn matches<E: BusEvent>(&self, event: &E) -> bool {
         // Check session filter
         if let Some(ref sid) = self.session_id {
             if let Some(event_sid) = event.session_id() {
@@ -188,6 +212,15 @@ impl EventFilter {
             }
         }
 
+        // Check order filter
+        if let Some(ref oid) = self.order_id {
+            if let Some(event_oid) = event.order_id() {
+                if event_oid != oid {
+                    return false;
+                }
+            }
+        }
+
         // Check event type filter
         if let Some(ref types) = self.event_types {
             if !types.contains(&event.event_type().to_string()) {
@@ -206,19 +239,19 @@ impl Default for EventFilter {
 }
 
 /// Filtered event receiver that only yields matching events
-pub struct FilteredReceiver {
-    receiver: broadcast::Receiver<EnsembleEvent>,
+pub struct FilteredReceiver<E: BusEvent = EnsembleEvent> {
+    receiver: broadcast::Receiver<E>,
     filter: EventFilter,
 }
 
-impl FilteredReceiver {
+impl<E: BusEvent> FilteredReceiver<E> {
     /// Create a new filtered receiver
-    pub fn new(receiver: broadcast::Receiver<EnsembleEvent>, filter: EventFilter) -> Self {
+    pub fn new(receiver: broadcast::Receiver<E>, filter: EventFilter) -> Self {
         Self { receiver, filter }
     }
 
     /// Receive the next matching event
-    pub async fn recv(&mut self) -> Result<EnsembleEvent, broadcast::error::RecvError> {
+    pub async fn recv(&mut self) -> Result<E, broadcast::error::RecvError> {
         loop {
             let event = self.receiver.recv().await?;
             if self.filter.matches(&event) {
@@ -229,19 +262,19 @@ impl FilteredReceiver {
 }
 
 /// Extension trait for subscribing with filters
-pub trait EventBusExt {
+pub trait EventBusExt<E: BusEvent> {
     /// Subscribe with a filter
-    fn subscribe_filtered(&self, filter: EventFilter) -> FilteredReceiver;
+    fn subscribe_filtered(&self, filter: EventFilter) -> FilteredReceiver<E>;
 }
 
-impl EventBusExt for EventBus {
-    fn subscribe_filtered(&self, filter: EventFilter) -> FilteredReceiver {
+impl<E: BusEvent> EventBusExt<E> for EventBus<E> {
+    fn subscribe_filtered(&self, filter: EventFilter) -> FilteredReceiver<E> {
         FilteredReceiver::new(self.subscribe(), filter)
     }
 }
 
-impl EventBusExt for SharedEventBus {
-    fn subscribe_filtered(&self, filter: EventFilter) -> FilteredReceiver {
+impl<E: BusEvent> EventBusExt<E> for SharedEventBus<E> {
+    fn subscribe_filtered(&self, filter: EventFilter) -> FilteredReceiver<E> {
         FilteredReceiver::new(self.subscribe(), filter)
     }
 }
@@ -278,7 +311,7 @@ mod tests {
         assert_eq!(bus.subscriber_count(), 2);
 
         let event = EnsembleEvent::ModelLoaded {
-            model_id: ModelId::Opus45,
+            model_id: ModelId::OPUS_45,
             load_time_ms: 1000,
             timestamp: Utc::now(),
         };
@@ -314,7 +347,7 @@ mod tests {
         };
 
         let non_matching_type = EnsembleEvent::ModelLoaded {
-            model_id: ModelId::Opus45,
+            model_id: ModelId::OPUS_45,
             load_time_ms: 1000,
             timestamp: Utc::now(),
         };
@@ -348,7 +381,7 @@ mod tests {
             bus_clone
                 .publish(EnsembleEvent::ResultSubmitted {
                     task_id: "target-task".to_string(),
-                    model_id: ModelId::Opus45,
+                    model_id: ModelId::OPUS_45,
                     confidence: 0.9,
                     tokens_used: 100,
                     latency_ms: 500,
@@ -361,4 +394,35 @@ mod tests {
         let event = filtered.recv().await.unwrap();
         assert_eq!(event.task_id(), Some("target-task"));
     }
+
+    #[tokio::test]
+    async fn test_swarm_bus_filters_by_order() {
+        let bus = SwarmEventBus::new().shared();
+        let mut filtered = bus.subscribe_filtered(EventFilter::new().order("wo-2"));
+
+        for order_id in ["wo-1", "wo-2"] {
+            bus.publish(SwarmEvent::WorkOrderCreated {
+                order_id: order_id.to_string(),
+                issue_id: "issue-1".to_string(),
+                objective_preview: "fix".to_string(),
+                target_files: vec!["src/lib.rs".to_string()],
+                worker_tier: None,
+                iteration: 0,
+                timestamp: Utc::now(),
+            })
+            .unwrap();
+        }
+
+        let event = filtered.recv().await.unwrap();
+        assert_eq!(event.order_id(), "wo-2");
+
+        // Ensemble events carry no order id, so an order filter passes them
+        assert!(EventFilter::new()
+            .order("wo-2")
+            .matches(&EnsembleEvent::ModelLoaded {
+                model_id: ModelId::OPUS_45,
+                load_time_ms: 1,
+                timestamp: Utc::now(),
+            }));
+    }
 }
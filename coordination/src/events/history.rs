@@ -1,13 +1,15 @@
 //! Event history and replay functionality
 //!
-//! Provides the ability to replay events from RocksDB for recovery
+//! Provides the ability to replay events from the state backend for recovery
 //! and debugging purposes.
 
+use std::marker::PhantomData;
+
 use chrono::{DateTime, Duration, Utc};
 use tracing::{debug, info};
 
-use super::types::EnsembleEvent;
-use crate::state::SharedStateStore;
+use super::types::{BusEvent, EnsembleEvent};
+use crate::state::{SharedStateStore, StateBackendExt};
 
 /// Error type for history operations
 #[derive(Debug, thiserror::Error)]
@@ -23,34 +25,49 @@ pub enum HistoryError {
 pub type HistoryResult<T> = Result<T, HistoryError>;
 
 /// Event history manager for replay and querying
-pub struct EventHistory {
+///
+/// The event log is shared by every [`BusEvent`] stream; a history only
+/// yields records whose `type` tag belongs to its own stream.
+pub struct EventHistory<E: BusEvent = EnsembleEvent> {
     store: SharedStateStore,
+    _stream: PhantomData<fn() -> E>,
 }
 
-impl EventHistory {
+impl<E: BusEvent> EventHistory<E> {
     /// Create a new event history manager
     pub fn new(store: SharedStateStore) -> Self {
-        Self { store }
+        Self {
+            store,
+            _stream: PhantomData,
+        }
     }
 
     /// Get all events in a time range
-    pub fn get_events(
-        &self,
-        start: DateTime<Utc>,
-        end: DateTime<Utc>,
-    ) -> HistoryResult<Vec<EnsembleEvent>> {
+    pub fn get_events(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> HistoryResult<Vec<E>> {
         let start_nanos = start.timestamp_nanos_opt().unwrap_or(0);
         let end_nanos = end.timestamp_nanos_opt().unwrap_or(i64::MAX);
 
-        let events: Vec<EnsembleEvent> = self
+        let raw: Vec<(i64, serde_json::Value)> = self
             .store
             .get_events_range(start_nanos, end_nanos)
-            .map_err(|e| HistoryError::StoreError(e.to_string()))?
-            .into_iter()
-            .map(|(_, event)| event)
-            .collect();
+            .map_err(|e| HistoryError::StoreError(e.to_string()))?;
+
+        let mut events = Vec::new();
+        for (_, value) in raw {
+            let ours = value
+                .get("type")
+                .and_then(|t| t.as_str())
+                .is_some_and(|t| E::EVENT_TYPES.contains(&t));
+            if !ours {
+                continue;
+            }
+            let event: E = serde_json::from_value(value)
+                .map_err(|e| HistoryError::ParseError(e.to_string()))?;
+            events.push(event);
+        }
 
         debug!(
+            stream = E::STREAM,
             count = events.len(),
             "Retrieved {} events from history",
             events.len()
@@ -60,19 +77,19 @@ impl EventHistory {
     }
 
     /// Get events for the last N minutes
-    pub fn get_recent_events(&self, minutes: i64) -> HistoryResult<Vec<EnsembleEvent>> {
+    pub fn get_recent_events(&self, minutes: i64) -> HistoryResult<Vec<E>> {
         let end = Utc::now();
         let start = end - Duration::minutes(minutes);
         self.get_events(start, end)
     }
 
     /// Get events for a specific session
-    pub fn get_session_events(&self, session_id: &str) -> HistoryResult<Vec<EnsembleEvent>> {
+    pub fn get_session_events(&self, session_id: &str) -> HistoryResult<Vec<E>> {
         // Get all events and filter by session
         // In a production system, we might want a secondary index
         let all_events = self.get_recent_events(60 * 24)?; // Last 24 hours
 
-        let session_events: Vec<EnsembleEvent> = all_events
+        let session_events: Vec<E> = all_events
             .into_iter()
             .filter(|e| e.session_id() == Some(session_id))
             .collect();
@@ -81,10 +98,10 @@ impl EventHistory {
     }
 
     /// Get events for a specific task
-    pub fn get_task_events(&self, task_id: &str) -> HistoryResult<Vec<EnsembleEvent>> {
+    pub fn get_task_events(&self, task_id: &str) -> HistoryResult<Vec<E>> {
         let all_events = self.get_recent_events(60 * 24)?;
 
-        let task_events: Vec<EnsembleEvent> = all_events
+        let task_events: Vec<E> = all_events
             .into_iter()
             .filter(|e| e.task_id() == Some(task_id))
             .collect();
@@ -92,6 +109,18 @@ impl EventHistory {
         Ok(task_events)
     }
 
+    /// Get events for a specific work order
+    pub fn get_order_events(&self, order_id: &str) -> HistoryResult<Vec<E>> {
+        let all_events = self.get_recent_events(60 * 24)?;
+
+        let order_events: Vec<E> = all_events
+            .into_iter()
+            .filter(|e| e.order_id() == Some(order_id))
+            .collect();
+
+        Ok(order_events)
+    }
+
     /// Replay events through a callback
     pub async fn replay<F, Fut>(
         &self,
@@ -100,13 +129,13 @@ impl EventHistory {
         mut callback: F,
     ) -> HistoryResult<ReplayStats>
     where
-        F: FnMut(EnsembleEvent) -> Fut,
+        F: FnMut(E) -> Fut,
         Fut: std::future::Future<Output = ()>,
     {
         let events = self.get_events(start, end)?;
         let total = events.len();
 
-        info!(total, "Starting event replay");
+        info!(stream = E::STREAM, total, "Starting event replay");
 
         let mut stats = ReplayStats::new();
         for event in events {
@@ -118,6 +147,7 @@ impl EventHistory {
             total = stats.total_events,
             sessions = stats.sessions_seen,
             tasks = stats.tasks_seen,
+            orders = stats.orders_seen,
             "Event replay complete"
         );
 
@@ -135,7 +165,9 @@ impl EventHistory {
         info!(count, cutoff = %cutoff, "Pruned old events");
         Ok(count)
     }
+}
 
+impl EventHistory<EnsembleEvent> {
     /// Get event statistics for a time range
     pub fn get_stats(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> HistoryResult<EventStats> {
         let events = self.get_events(start, end)?;
@@ -149,9 +181,11 @@ pub struct ReplayStats {
     pub total_events: usize,
     pub sessions_seen: usize,
     pub tasks_seen: usize,
+    pub orders_seen: usize,
     pub errors_seen: usize,
     sessions: std::collections::HashSet<String>,
     tasks: std::collections::HashSet<String>,
+    orders: std::collections::HashSet<String>,
 }
 
 impl ReplayStats {
@@ -159,7 +193,7 @@ impl ReplayStats {
         Self::default()
     }
 
-    pub fn record_event(&mut self, event: &EnsembleEvent) {
+    pub fn record_event<E: BusEvent>(&mut self, event: &E) {
         self.total_events += 1;
 
         if let Some(session_id) = event.session_id() {
@@ -174,7 +208,13 @@ impl ReplayStats {
             }
         }
 
-        if matches!(event, EnsembleEvent::TaskFailed { .. }) {
+        if let Some(order_id) = event.order_id() {
+            if self.orders.insert(order_id.to_string()) {
+                self.orders_seen += 1;
+            }
+        }
+
+        if event.is_failure() {
             self.errors_seen += 1;
         }
     }
@@ -231,16 +271,18 @@ impl EventStats {
 }
 
 /// Builder for replaying events with transformations
-pub struct ReplayBuilder {
+pub struct ReplayBuilder<E: BusEvent = EnsembleEvent> {
     store: SharedStateStore,
     start: DateTime<Utc>,
     end: DateTime<Utc>,
     filter_session: Option<String>,
     filter_task: Option<String>,
+    filter_order: Option<String>,
     filter_types: Option<Vec<String>>,
+    _stream: PhantomData<fn() -> E>,
 }
 
-impl ReplayBuilder {
+impl<E: BusEvent> ReplayBuilder<E> {
     /// Create a new replay builder
     pub fn new(store: SharedStateStore) -> Self {
         let now = Utc::now();
@@ -250,7 +292,9 @@ impl ReplayBuilder {
             end: now,
             filter_session: None,
             filter_task: None,
+            filter_order: None,
             filter_types: None,
+            _stream: PhantomData,
         }
     }
 
@@ -273,6 +317,12 @@ impl ReplayBuilder {
         self
     }
 
+    /// Filter by work order ID
+    pub fn order(mut self, order_id: &str) -> Self {
+        self.filter_order = Some(order_id.to_string());
+        self
+    }
+
     /// Filter by event types
     pub fn event_types(mut self, types: Vec<&str>) -> Self {
         self.filter_types = Some(types.into_iter().map(String::from).collect());
@@ -280,8 +330,8 @@ impl ReplayBuilder {
     }
 
     /// Execute replay and collect events
-    pub fn collect(self) -> HistoryResult<Vec<EnsembleEvent>> {
-        let history = EventHistory::new(self.store);
+    pub fn collect(self) -> HistoryResult<Vec<E>> {
+        let history = EventHistory::<E>::new(self.store);
         let mut events = history.get_events(self.start, self.end)?;
 
         // Apply filters
@@ -293,6 +343,10 @@ impl ReplayBuilder {
             events.retain(|e| e.task_id() == Some(task_id.as_str()));
         }
 
+        if let Some(ref order_id) = self.filter_order {
+            events.retain(|e| e.order_id() == Some(order_id.as_str()));
+        }
+
         if let Some(ref types) = self.filter_types {
             events.retain(|e| types.contains(&e.event_type().to_string()));
         }
@@ -322,7 +376,7 @@ mod tests {
                 timestamp: Utc::now(),
             },
             EnsembleEvent::ModelLoaded {
-                model_id: ModelId::Opus45,
+                model_id: ModelId::OPUS_45,
                 load_time_ms: 1000,
                 timestamp: Utc::now(),
             },
@@ -363,4 +417,93 @@ mod tests {
         assert_eq!(stats.sessions_seen, 1);
         assert_eq!(stats.tasks_seen, 2);
     }
+
+    #[test]
+    fn test_replay_from_file_backend() {
+        use crate::events::EventBus;
+        use crate::state::FileStateStore;
+
+        let store = FileStateStore::in_memory().shared();
+        let bus = EventBus::with_persistence(store.clone());
+        for task_id in ["t1", "t2"] {
+            bus.publish(EnsembleEvent::TaskCreated {
+                task_id: task_id.to_string(),
+                session_id: "s1".to_string(),
+                prompt_preview: "test".to_string(),
+                require_consensus: false,
+                timestamp: Utc::now(),
+            })
+            .unwrap();
+        }
+
+        let replayed = ReplayBuilder::<EnsembleEvent>::new(store)
+            .task("t2")
+            .collect()
+            .unwrap();
+        assert_eq!(replayed.len(), 1);
+        assert_eq!(replayed[0].task_id(), Some("t2"));
+    }
+
+    #[test]
+    fn test_swarm_stream_replays_by_order() {
+        use crate::events::{EventBus, SwarmEvent};
+        use crate::state::FileStateStore;
+
+        let store = FileStateStore::in_memory().shared();
+        let ensemble = EventBus::with_persistence(store.clone());
+        let swarm = EventBus::<SwarmEvent>::with_persistence(store.clone());
+
+        ensemble
+            .publish(EnsembleEvent::SessionCreated {
+                session_id: "s1".to_string(),
+                harness_session_id: None,
+                timestamp: Utc::now(),
+            })
+            .unwrap();
+        for order_id in ["wo-1", "wo-2"] {
+            swarm
+                .publish(SwarmEvent::WorkerStarted {
+                    order_id: order_id.to_string(),
+                    worker_name: "coder".to_string(),
+                    worker_tier: "worker".to_string(),
+                    timestamp: Utc::now(),
+                })
+                .unwrap();
+        }
+        swarm
+            .publish(SwarmEvent::VerificationRan {
+                order_id: "wo-1".to_string(),
+                all_green: false,
+                gates_passed: 2,
+                gates_total: 3,
+                error_count: 1,
+                timestamp: Utc::now(),
+            })
+            .unwrap();
+
+        // Each history only sees its own stream in the shared log
+        let ensemble_events = EventHistory::<EnsembleEvent>::new(store.clone())
+            .get_recent_events(5)
+            .unwrap();
+        assert_eq!(ensemble_events.len(), 1);
+
+        let history = EventHistory::<SwarmEvent>::new(store.clone());
+        assert_eq!(history.get_recent_events(5).unwrap().len(), 3);
+        let wo1 = history.get_order_events("wo-1").unwrap();
+        let types: Vec<&str> = wo1.iter().map(|e| e.event_type()).collect();
+        assert_eq!(types, vec!["worker_started", "verification_ran"]);
+
+        let replayed = ReplayBuilder::<SwarmEvent>::new(store)
+            .order("wo-2")
+            .collect()
+            .unwrap();
+        assert_eq!(replayed.len(), 1);
+
+        let mut stats = ReplayStats::new();
+        for event in &wo1 {
+            stats.record_event(event);
+        }
+        assert_eq!(stats.orders_seen, 1);
+        assert_eq!(stats.errors_seen, 1);
+    }
 }
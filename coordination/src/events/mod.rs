@@ -5,17 +5,28 @@
 //!
 //! # Architecture
 //!
-//! The event system consists of three main components:
+//! The event system consists of six main components:
 //!
-//! 1. **Event Types** (`types.rs`): Defines the 13 event types that
-//!    drive ensemble coordination, from task creation to arbitration.
+//! 1. **Event Types** (`types.rs`): `EnsembleEvent` drives ensemble
+//!    coordination, from task creation to arbitration; `SwarmEvent` tracks
+//!    the worker dispatch lifecycle. Both implement `BusEvent`.
 //!
 //! 2. **Event Bus** (`bus.rs`): Tokio broadcast-based pub/sub with
-//!    optional persistence to RocksDB.
+//!    optional persistence to any `StateBackend` (RocksDB or file).
 //!
 //! 3. **Event History** (`history.rs`): Query and replay capabilities
 //!    for debugging and recovery.
 //!
+//! 4. **Event Sinks** (`sink.rs`): Filtered, batched outputs attached to a
+//!    bus: rotating JSONL files, stdout, and HTTP webhooks.
+//!
+//! 5. **Projections** (`projection.rs`): Read models (task board, model
+//!    scoreboard, escalations, work orders) kept current from the bus and
+//!    rebuildable from history.
+//!
+//! 6. **Event Broker** (`broker.rs`, Unix only): Serves a bus to other
+//!    processes over a Unix socket with length-prefixed JSON frames.
+//!
 //! # Event Flow
 //!
 //! ```text
@@ -26,7 +37,7 @@
 //!                             │
 //!                             ▼
 //!                      ┌──────────────┐
-//!                      │   RocksDB    │
+//!                      │ StateBackend │
 //!                      │  (persist)   │
 //!                      └──────────────┘
 //! ```
@@ -34,7 +45,10 @@
 //! # Usage
 //!
 //! ```ignore
-//! use rust_cluster_mcp::events::{EventBus, EnsembleEvent, EventHistory};
+//! use rust_cluster_mcp::events::{
+//!     EnsembleEvent, EventBus, EventFilter, EventHistory, JsonlFileSink, Projector,
+//!     ReplayBuilder, SinkOptions, SwarmEvent, TaskBoard,
+//! };
 //! use chrono::Utc;
 //!
 //! // Create event bus with persistence
@@ -56,23 +70,56 @@
 //! let event = receiver.recv().await?;
 //!
 //! // Replay history
-//! let history = EventHistory::new(store);
+//! let history = EventHistory::<EnsembleEvent>::new(store.clone());
 //! let recent = history.get_recent_events(60)?; // Last hour
+//!
+//! // Worker lifecycle events share the log and are keyed by order id
+//! let swarm = EventBus::<SwarmEvent>::with_persistence(store.clone());
+//! let order = EventHistory::<SwarmEvent>::new(store).get_order_events("wo-1")?;
+//!
+//! // Mirror failures to a rotating file on a dedicated thread
+//! let sink = bus.attach_sink(
+//!     JsonlFileSink::new("logs/failures.jsonl").with_max_bytes(10 << 20),
+//!     SinkOptions::new().with_filter(EventFilter::new().types(vec!["task_failed"])),
+//! );
+//!
+//! // Serve status queries from a precomputed view
+//! let board = Projector::<TaskBoard>::new();
+//! let _live = board.attach(&bus);
+//! board.rebuild(ReplayBuilder::new(store.clone()))?;
+//! let open = board.read(|b| b.session("session-1").map(|s| s.open_tasks().count()));
 //! ```
 
+#[cfg(unix)]
+pub mod broker;
 pub mod bus;
 pub mod history;
+pub mod projection;
+pub mod sink;
 pub mod types;
 
 // Re-export core types
+#[cfg(unix)]
+pub use broker::{
+    BrokerClient, BrokerConfig, BrokerError, BrokerHandle, BrokerMessage, BrokerResult,
+    BrokerStats, EventBroker,
+};
 pub use bus::{
     EventBus, EventBusError, EventBusExt, EventBusResult, EventFilter, FilteredReceiver,
-    SharedEventBus,
+    SharedEventBus, SwarmEventBus,
 };
 pub use history::{
     EventHistory, EventStats, HistoryError, HistoryResult, ReplayBuilder, ReplayStats,
 };
+pub use projection::{
+    EscalationCounts, ModelPerformance, ModelScoreboard, OrderCard, OrderPhase, Projection,
+    Projector, SessionBoard, TaskBoard, TaskCard, WorkOrderBoard,
+};
+pub use sink::{
+    EventSink, JsonlFileSink, SinkError, SinkHandle, SinkOptions, SinkResult, SinkStats,
+    StdoutSink, WebhookConfig, WebhookSink,
+};
 pub use types::{
-    ArbitrationReason, ContextUpdater, EnsembleEvent, EventId, SessionEndReason, SwarmEvent,
-    UnloadReason, VoteSummary,
+    ArbitrationReason, BusEvent, ContextUpdater, EnsembleEvent, EventId, SessionEndReason,
+    SwarmEvent, UnloadReason, VoteSummary,
 };
@@ -0,0 +1,945 @@
+//! Materialized projections over the event stream
+//!
+//! A projection folds events into a read model that can be queried without
+//! touching the event log. [`Projector`] keeps one up to date from a live bus
+//! and can rebuild it from history through a [`ReplayBuilder`], so status
+//! views stay cheap no matter how long the log grows.
+//!
+//! Read models:
+//! - [`TaskBoard`]: per-session task cards and their status
+//! - [`ModelScoreboard`]: per-model win rate, latency and failures
+//! - [`EscalationCounts`]: arbitrations and worker escalations
+//! - [`WorkOrderBoard`]: work orders and which are still active
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::bus::EventBus;
+use super::history::{HistoryResult, ReplayBuilder};
+use super::sink::{EventSink, SinkHandle, SinkOptions, SinkResult};
+use super::types::{ArbitrationReason, BusEvent, EnsembleEvent, SwarmEvent};
+use crate::state::{ModelId, SessionId, TaskId, TaskStatus};
+
+/// A read model built by folding events in publish order
+///
+/// `Default` is the empty view; rebuilding starts from it.
+pub trait Projection<E: BusEvent = EnsembleEvent>: Default + Send + Sync {
+    /// Short name for logs
+    fn name(&self) -> &'static str;
+
+    /// Fold one event into the view
+    fn apply(&mut self, event: &E);
+}
+
+// ── Projector ───────────────────────────────────────────────────────
+
+struct Projected<P> {
+    view: P,
+    /// Timestamp of the newest replayed event; live events no newer than it
+    /// are skipped until the first newer one arrives
+    watermark: Option<DateTime<Utc>>,
+    applied: u64,
+}
+
+/// Shared, thread-safe holder for a projection
+///
+/// Clones share the same view. Typical startup is [`attach`](Self::attach)
+/// followed by [`rebuild`](Self::rebuild): the subscription buffers live
+/// events while history replays, and buffered events no newer than the last
+/// replayed one are treated as already applied. The first newer event ends
+/// that window; from then on every live event is applied.
+pub struct Projector<P> {
+    state: Arc<RwLock<Projected<P>>>,
+}
+
+impl<P> Clone for Projector<P> {
+    fn clone(&self) -> Self {
+        Self {
+            state: Arc::clone(&self.state),
+        }
+    }
+}
+
+impl<P: Default> Projector<P> {
+    /// Create a projector holding an empty view
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(RwLock::new(Projected {
+                view: P::default(),
+                watermark: None,
+                applied: 0,
+            })),
+        }
+    }
+}
+
+impl<P: Default> Default for Projector<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P> Projector<P> {
+    /// Query the current view
+    pub fn read<R>(&self, f: impl FnOnce(&P) -> R) -> R {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        f(&state.view)
+    }
+
+    /// Copy of the current view
+    pub fn snapshot(&self) -> P
+    where
+        P: Clone,
+    {
+        self.read(P::clone)
+    }
+
+    /// Number of events folded into the view since the last rebuild
+    pub fn applied(&self) -> u64 {
+        self.state.read().unwrap_or_else(|e| e.into_inner()).applied
+    }
+
+    /// Fold a live event into the view
+    pub fn apply<E: BusEvent>(&self, event: &E)
+    where
+        P: Projection<E>,
+    {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(watermark) = state.watermark {
+            if event.timestamp() <= watermark {
+                return;
+            }
+            state.watermark = None;
+        }
+        state.view.apply(event);
+        state.applied += 1;
+    }
+
+    /// Reset the view and replay history into it
+    ///
+    /// Returns the number of events replayed.
+    pub fn rebuild<E: BusEvent>(&self, replay: ReplayBuilder<E>) -> HistoryResult<usize>
+    where
+        P: Projection<E>,
+    {
+        let events = replay.collect()?;
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        state.view = P::default();
+        for event in &events {
+            state.view.apply(event);
+        }
+        state.watermark = events.iter().map(BusEvent::timestamp).max();
+        state.applied = events.len() as u64;
+        tracing::debug!(
+            projection = state.view.name(),
+            events = events.len(),
+            "Projection rebuilt"
+        );
+        Ok(events.len())
+    }
+
+    /// Keep the view current from a bus
+    ///
+    /// Runs as an event sink, so lag shows up in [`SinkHandle::stats`]; a
+    /// projection that lagged should be rebuilt.
+    pub fn attach<E: BusEvent>(&self, bus: &EventBus<E>) -> SinkHandle
+    where
+        P: Projection<E> + 'static,
+    {
+        bus.attach_sink(ProjectionSink(self.clone()), SinkOptions::new())
+    }
+}
+
+struct ProjectionSink<P>(Projector<P>);
+
+impl<E: BusEvent, P: Projection<E>> EventSink<E> for ProjectionSink<P> {
+    fn name(&self) -> &str {
+        "projection"
+    }
+
+    fn write_batch(&mut self, events: &[E]) -> SinkResult<()> {
+        for event in events {
+            self.0.apply(event);
+        }
+        Ok(())
+    }
+}
+
+// ── Task board ──────────────────────────────────────────────────────
+
+/// Current state of one task
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskCard {
+    pub task_id: TaskId,
+    pub session_id: SessionId,
+    pub prompt_preview: String,
+    pub status: TaskStatus,
+    /// Models the task was assigned to, in assignment order
+    pub assigned: Vec<ModelId>,
+    pub results: usize,
+    /// Consensus winner or arbitration decision
+    pub winner: Option<ModelId>,
+    pub arbitration: Option<String>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Tasks belonging to one session
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionBoard {
+    pub session_id: SessionId,
+    pub active: bool,
+    pub tasks: BTreeMap<TaskId, TaskCard>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl SessionBoard {
+    /// Number of tasks in a given status
+    pub fn count(&self, status: TaskStatus) -> usize {
+        self.tasks.values().filter(|t| t.status == status).count()
+    }
+
+    /// Tasks that are neither completed nor failed
+    pub fn open_tasks(&self) -> impl Iterator<Item = &TaskCard> {
+        self.tasks
+            .values()
+            .filter(|t| !matches!(t.status, TaskStatus::Completed | TaskStatus::Failed))
+    }
+}
+
+/// Per-session task board
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TaskBoard {
+    pub sessions: BTreeMap<SessionId, SessionBoard>,
+    /// Most task events carry only the task id
+    #[serde(skip)]
+    task_sessions: HashMap<TaskId, SessionId>,
+}
+
+impl TaskBoard {
+    /// Board for one session
+    pub fn session(&self, session_id: &str) -> Option<&SessionBoard> {
+        self.sessions.get(session_id)
+    }
+
+    /// Card for one task
+    pub fn task(&self, task_id: &str) -> Option<&TaskCard> {
+        let session_id = self.task_sessions.get(task_id)?;
+        self.sessions.get(session_id)?.tasks.get(task_id)
+    }
+
+    /// Sessions that have not ended
+    pub fn active_sessions(&self) -> impl Iterator<Item = &SessionBoard> {
+        self.sessions.values().filter(|s| s.active)
+    }
+
+    fn session_mut(&mut self, session_id: &str) -> &mut SessionBoard {
+        self.sessions
+            .entry(session_id.to_string())
+            .or_insert_with(|| SessionBoard {
+                session_id: session_id.to_string(),
+                active: true,
+                ..Default::default()
+            })
+    }
+
+    fn card_mut(&mut self, task_id: &str, at: DateTime<Utc>) -> Option<&mut TaskCard> {
+        let session_id = self.task_sessions.get(task_id)?;
+        let card = self.sessions.get_mut(session_id)?.tasks.get_mut(task_id)?;
+        card.updated_at = at;
+        Some(card)
+    }
+}
+
+impl Projection<EnsembleEvent> for TaskBoard {
+    fn name(&self) -> &'static str {
+        "task_board"
+    }
+
+    fn apply(&mut self, event: &EnsembleEvent) {
+        let at = event.timestamp();
+        match event {
+            EnsembleEvent::SessionCreated { session_id, .. } => {
+                let session = self.session_mut(session_id);
+                session.active = true;
+                session.started_at = Some(at);
+            }
+            EnsembleEvent::SessionEnded { session_id, .. } => {
+                let session = self.session_mut(session_id);
+                session.active = false;
+                session.ended_at = Some(at);
+            }
+            EnsembleEvent::TaskCreated {
+                task_id,
+                session_id,
+                prompt_preview,
+                ..
+            } => {
+                self.task_sessions
+                    .insert(task_id.clone(), session_id.clone());
+                self.session_mut(session_id).tasks.insert(
+                    task_id.clone(),
+                    TaskCard {
+                        task_id: task_id.clone(),
+                        session_id: session_id.clone(),
+                        prompt_preview: prompt_preview.clone(),
+                        status: TaskStatus::Pending,
+                        assigned: Vec::new(),
+                        results: 0,
+                        winner: None,
+                        arbitration: None,
+                        error: None,
+                        created_at: at,
+                        updated_at: at,
+                    },
+                );
+            }
+            EnsembleEvent::TaskAssigned {
+                task_id, model_id, ..
+            } => {
+                if let Some(card) = self.card_mut(task_id, at) {
+                    card.status = TaskStatus::InProgress;
+                    if !card.assigned.contains(model_id) {
+                        card.assigned.push(*model_id);
+                    }
+                }
+            }
+            EnsembleEvent::ResultSubmitted { task_id, .. } => {
+                if let Some(card) = self.card_mut(task_id, at) {
+                    card.results += 1;
+                }
+            }
+            EnsembleEvent::VotingStarted { task_id, .. } => {
+                if let Some(card) = self.card_mut(task_id, at) {
+                    card.status = TaskStatus::Voting;
+                }
+            }
+            EnsembleEvent::ConsensusReached {
+                task_id, winner, ..
+            } => {
+                if let Some(card) = self.card_mut(task_id, at) {
+                    card.status = TaskStatus::Completed;
+                    card.winner = Some(*winner);
+                }
+            }
+            EnsembleEvent::ArbitrationRequested {
+                task_id, reason, ..
+            } => {
+                if let Some(card) = self.card_mut(task_id, at) {
+                    card.status = TaskStatus::AwaitingArbitration;
+                    card.arbitration = Some(reason.to_string());
+                }
+            }
+            EnsembleEvent::ArbitrationCompleted {
+                task_id, decision, ..
+            } => {
+                if let Some(card) = self.card_mut(task_id, at) {
+                    card.status = TaskStatus::Completed;
+                    card.winner = Some(*decision);
+                }
+            }
+            EnsembleEvent::TaskFailed { task_id, error, .. } => {
+                if let Some(card) = self.card_mut(task_id, at) {
+                    card.status = TaskStatus::Failed;
+                    card.error = Some(error.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// ── Model scoreboard ────────────────────────────────────────────────
+
+/// Aggregate performance of one model
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ModelPerformance {
+    pub results: u64,
+    pub tokens_used: u64,
+    pub total_latency_ms: u64,
+    pub max_latency_ms: u64,
+    /// Votes the model took part in
+    pub votes: u64,
+    /// Consensus wins plus arbitration decisions
+    pub wins: u64,
+    pub arbitration_wins: u64,
+    pub failures: u64,
+    pub loads: u64,
+    pub total_load_ms: u64,
+}
+
+impl ModelPerformance {
+    /// Mean result latency in milliseconds
+    pub fn avg_latency_ms(&self) -> f64 {
+        if self.results == 0 {
+            0.0
+        } else {
+            self.total_latency_ms as f64 / self.results as f64
+        }
+    }
+
+    /// Share of votes the model won
+    pub fn win_rate(&self) -> f64 {
+        if self.votes == 0 {
+            0.0
+        } else {
+            self.wins as f64 / self.votes as f64
+        }
+    }
+}
+
+/// Per-model win rate and latency
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ModelScoreboard {
+    pub models: HashMap<ModelId, ModelPerformance>,
+}
+
+impl ModelScoreboard {
+    /// Performance of one model
+    pub fn get(&self, model: ModelId) -> Option<&ModelPerformance> {
+        self.models.get(&model)
+    }
+
+    /// Models by win rate, highest first (ties in `ModelId::all()` order)
+    pub fn ranked(&self) -> Vec<(ModelId, ModelPerformance)> {
+        let mut ranked: Vec<_> = ModelId::all()
+            .iter()
+            .filter_map(|m| self.models.get(m).map(|p| (*m, *p)))
+            .collect();
+        ranked.sort_by(|a, b| b.1.win_rate().total_cmp(&a.1.win_rate()));
+        ranked
+    }
+
+    fn model_mut(&mut self, model: ModelId) -> &mut ModelPerformance {
+        self.models.entry(model).or_default()
+    }
+}
+
+impl Projection<EnsembleEvent> for ModelScoreboard {
+    fn name(&self) -> &'static str {
+        "model_scoreboard"
+    }
+
+    fn apply(&mut self, event: &EnsembleEvent) {
+        match event {
+            EnsembleEvent::ResultSubmitted {
+                model_id,
+                tokens_used,
+                latency_ms,
+                ..
+            } => {
+                let perf = self.model_mut(*model_id);
+                perf.results += 1;
+                perf.tokens_used += u64::from(*tokens_used);
+                perf.total_latency_ms += latency_ms;
+                perf.max_latency_ms = perf.max_latency_ms.max(*latency_ms);
+            }
+            EnsembleEvent::VotingStarted {
+                participating_models,
+                ..
+            } => {
+                for model in participating_models {
+                    self.model_mut(*model).votes += 1;
+                }
+            }
+            EnsembleEvent::ConsensusReached { winner, .. } => {
+                self.model_mut(*winner).wins += 1;
+            }
+            EnsembleEvent::ArbitrationCompleted { decision, .. } => {
+                let perf = self.model_mut(*decision);
+                perf.wins += 1;
+                perf.arbitration_wins += 1;
+            }
+            EnsembleEvent::TaskFailed {
+                model_id: Some(model_id),
+                ..
+            } => {
+                self.model_mut(*model_id).failures += 1;
+            }
+            EnsembleEvent::ModelLoaded {
+                model_id,
+                load_time_ms,
+                ..
+            } => {
+                let perf = self.model_mut(*model_id);
+                perf.loads += 1;
+                perf.total_load_ms += load_time_ms;
+            }
+            _ => {}
+        }
+    }
+}
+
+// ── Escalations ─────────────────────────────────────────────────────
+
+/// Arbitrations on the ensemble stream and escalations on the swarm stream
+///
+/// One instance can be fed from both buses.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EscalationCounts {
+    /// Arbitration requests keyed by reason kind (e.g. `tie_vote`)
+    pub arbitrations_by_reason: BTreeMap<String, u64>,
+    pub arbitrations_resolved: u64,
+    pub councils_convened: u64,
+    pub worker_escalations: u64,
+    pub escalations_by_order: BTreeMap<String, u64>,
+    /// Worker completions that flagged `needs_escalation`
+    pub completions_flagged: u64,
+}
+
+impl EscalationCounts {
+    /// Total arbitration requests
+    pub fn arbitrations(&self) -> u64 {
+        self.arbitrations_by_reason.values().sum()
+    }
+
+    /// Arbitrations requested but not yet completed
+    pub fn pending_arbitrations(&self) -> u64 {
+        self.arbitrations()
+            .saturating_sub(self.arbitrations_resolved)
+    }
+
+    /// Arbitrations plus worker escalations
+    pub fn total(&self) -> u64 {
+        self.arbitrations() + self.worker_escalations
+    }
+}
+
+fn arbitration_kind(reason: &ArbitrationReason) -> &'static str {
+    match reason {
+        ArbitrationReason::TieVote { .. } => "tie_vote",
+        ArbitrationReason::LowConfidence { .. } => "low_confidence",
+        ArbitrationReason::ConflictingResponses { .. } => "conflicting_responses",
+        ArbitrationReason::ExplicitRequest { .. } => "explicit_request",
+    }
+}
+
+impl Projection<EnsembleEvent> for EscalationCounts {
+    fn name(&self) -> &'static str {
+        "escalation_counts"
+    }
+
+    fn apply(&mut self, event: &EnsembleEvent) {
+        match event {
+            EnsembleEvent::ArbitrationRequested { reason, .. } => {
+                *self
+                    .arbitrations_by_reason
+                    .entry(arbitration_kind(reason).to_string())
+                    .or_insert(0) += 1;
+            }
+            EnsembleEvent::ArbitrationCompleted { .. } => self.arbitrations_resolved += 1,
+            EnsembleEvent::CouncilConvened { .. } => self.councils_convened += 1,
+            _ => {}
+        }
+    }
+}
+
+impl Projection<SwarmEvent> for EscalationCounts {
+    fn name(&self) -> &'static str {
+        "escalation_counts"
+    }
+
+    fn apply(&mut self, event: &SwarmEvent) {
+        match event {
+            SwarmEvent::EscalationRequested { order_id, .. } => {
+                self.worker_escalations += 1;
+                *self
+                    .escalations_by_order
+                    .entry(order_id.clone())
+                    .or_insert(0) += 1;
+            }
+            SwarmEvent::WorkerCompleted {
+                needs_escalation: true,
+                ..
+            } => self.completions_flagged += 1,
+            _ => {}
+        }
+    }
+}
+
+// ── Work orders ─────────────────────────────────────────────────────
+
+/// Where a work order is in the dispatch lifecycle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "phase", rename_all = "snake_case")]
+pub enum OrderPhase {
+    Created,
+    Running,
+    /// Worker finished with a status label, awaiting verification
+    Completed {
+        status: String,
+    },
+    Verified {
+        all_green: bool,
+    },
+    Escalated,
+}
+
+/// Current state of one work order
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderCard {
+    pub order_id: String,
+    pub issue_id: String,
+    pub objective_preview: String,
+    pub worker: Option<String>,
+    pub worker_tier: Option<String>,
+    pub iteration: usize,
+    pub phase: OrderPhase,
+    pub turns: usize,
+    pub tool_calls: usize,
+    pub files_modified: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OrderCard {
+    /// Still needs work: not verified green and not failed outright
+    pub fn is_active(&self) -> bool {
+        match &self.phase {
+            OrderPhase::Verified { all_green } => !all_green,
+            OrderPhase::Completed { status } => status != "failed",
+            _ => true,
+        }
+    }
+}
+
+/// Work orders keyed by order id
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WorkOrderBoard {
+    pub orders: BTreeMap<String, OrderCard>,
+}
+
+impl WorkOrderBoard {
+    /// One work order
+    pub fn get(&self, order_id: &str) -> Option<&OrderCard> {
+        self.orders.get(order_id)
+    }
+
+    /// Orders that still need work
+    pub fn active(&self) -> impl Iterator<Item = &OrderCard> {
+        self.orders.values().filter(|o| o.is_active())
+    }
+
+    fn card_mut(&mut self, order_id: &str, at: DateTime<Utc>) -> Option<&mut OrderCard> {
+        let card = self.orders.get_mut(order_id)?;
+        card.updated_at = at;
+        Some(card)
+    }
+}
+
+impl Projection<SwarmEvent> for WorkOrderBoard {
+    fn name(&self) -> &'static str {
+        "work_order_board"
+    }
+
+    fn apply(&mut self, event: &SwarmEvent) {
+        let at = event.timestamp();
+        match event {
+            SwarmEvent::WorkOrderCreated {
+                order_id,
+                issue_id,
+                objective_preview,
+                worker_tier,
+                iteration,
+                ..
+            } => {
+                // A re-dispatch of the same order starts a fresh card
+                self.orders.insert(
+                    order_id.clone(),
+                    OrderCard {
+                        order_id: order_id.clone(),
+                        issue_id: issue_id.clone(),
+                        objective_preview: objective_preview.clone(),
+                        worker: None,
+                        worker_tier: worker_tier.clone(),
+                        iteration: *iteration,
+                        phase: OrderPhase::Created,
+                        turns: 0,
+                        tool_calls: 0,
+                        files_modified: Vec::new(),
+                        created_at: at,
+                        updated_at: at,
+                    },
+                );
+            }
+            SwarmEvent::WorkerStarted {
+                order_id,
+                worker_name,
+                worker_tier,
+                ..
+            } => {
+                if let Some(card) = self.card_mut(order_id, at) {
+                    card.phase = OrderPhase::Running;
+                    card.worker = Some(worker_name.clone());
+                    card.worker_tier = Some(worker_tier.clone());
+                }
+            }
+            SwarmEvent::WorkerProgress {
+                order_id,
+                turns_completed,
+                tool_calls,
+                files_modified,
+                ..
+            } => {
+                if let Some(card) = self.card_mut(order_id, at) {
+                    card.turns = *turns_completed;
+                    card.tool_calls = *tool_calls;
+                    card.files_modified = files_modified.clone();
+                }
+            }
+            SwarmEvent::WorkerCompleted {
+                order_id,
+                status,
+                files_modified,
+                tool_calls,
+                turns_used,
+                ..
+            } => {
+                if let Some(card) = self.card_mut(order_id, at) {
+                    card.phase = OrderPhase::Completed {
+                        status: status.clone(),
+                    };
+                    card.turns = *turns_used;
+                    card.tool_calls = *tool_calls;
+                    card.files_modified = files_modified.clone();
+                }
+            }
+            SwarmEvent::VerificationRan {
+                order_id,
+                all_green,
+                ..
+            } => {
+                if let Some(card) = self.card_mut(order_id, at) {
+                    card.phase = OrderPhase::Verified {
+                        all_green: *all_green,
+                    };
+                }
+            }
+            SwarmEvent::EscalationRequested { order_id, .. } => {
+                if let Some(card) = self.card_mut(order_id, at) {
+                    card.phase = OrderPhase::Escalated;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::VoteSummary;
+    use crate::state::{FileStateStore, VotingStrategy};
+    use chrono::Duration;
+
+    fn ts(offset_ms: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap() + Duration::milliseconds(offset_ms)
+    }
+
+    fn task_lifecycle() -> Vec<EnsembleEvent> {
+        vec![
+            EnsembleEvent::SessionCreated {
+                session_id: "s1".to_string(),
+                harness_session_id: None,
+                timestamp: ts(0),
+            },
+            EnsembleEvent::TaskCreated {
+                task_id: "t1".to_string(),
+                session_id: "s1".to_string(),
+                prompt_preview: "refactor".to_string(),
+                require_consensus: true,
+                timestamp: ts(1),
+            },
+            EnsembleEvent::TaskCreated {
+                task_id: "t2".to_string(),
+                session_id: "s1".to_string(),
+                prompt_preview: "review".to_string(),
+                require_consensus: true,
+                timestamp: ts(2),
+            },
+            EnsembleEvent::ResultSubmitted {
+                task_id: "t1".to_string(),
+                model_id: ModelId::OPUS_45,
+                confidence: 0.9,
+                tokens_used: 100,
+                latency_ms: 400,
+                timestamp: ts(3),
+            },
+            EnsembleEvent::ResultSubmitted {
+                task_id: "t1".to_string(),
+                model_id: ModelId::QWEN35,
+                confidence: 0.7,
+                tokens_used: 80,
+                latency_ms: 200,
+                timestamp: ts(4),
+            },
+            EnsembleEvent::VotingStarted {
+                task_id: "t1".to_string(),
+                strategy: VotingStrategy::Majority,
+                participating_models: vec![ModelId::OPUS_45, ModelId::QWEN35],
+                timestamp: ts(5),
+            },
+            EnsembleEvent::ConsensusReached {
+                task_id: "t1".to_string(),
+                winner: ModelId::QWEN35,
+                vote_summary: VoteSummary::new(vec![(ModelId::QWEN35, 2)]),
+                timestamp: ts(6),
+            },
+            EnsembleEvent::ArbitrationRequested {
+                task_id: "t2".to_string(),
+                reason: ArbitrationReason::TieVote {
+                    tied_models: vec![ModelId::OPUS_45, ModelId::QWEN35],
+                },
+                timestamp: ts(7),
+            },
+        ]
+    }
+
+    fn order(order_id: &str, offset: i64) -> Vec<SwarmEvent> {
+        vec![
+            SwarmEvent::WorkOrderCreated {
+                order_id: order_id.to_string(),
+                issue_id: "issue-1".to_string(),
+                objective_preview: "fix".to_string(),
+                target_files: vec![],
+                worker_tier: None,
+                iteration: 0,
+                timestamp: ts(offset),
+            },
+            SwarmEvent::WorkerStarted {
+                order_id: order_id.to_string(),
+                worker_name: "coder".to_string(),
+                worker_tier: "worker".to_string(),
+                timestamp: ts(offset + 1),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_task_board_tracks_status() {
+        let mut board = TaskBoard::default();
+        for event in task_lifecycle() {
+            board.apply(&event);
+        }
+
+        let session = board.session("s1").unwrap();
+        assert!(session.active);
+        assert_eq!(session.count(TaskStatus::Completed), 1);
+        assert_eq!(session.open_tasks().count(), 1);
+
+        let t1 = board.task("t1").unwrap();
+        assert_eq!(t1.results, 2);
+        assert_eq!(t1.winner, Some(ModelId::QWEN35));
+        let t2 = board.task("t2").unwrap();
+        assert_eq!(t2.status, TaskStatus::AwaitingArbitration);
+        assert_eq!(t2.updated_at, ts(7));
+    }
+
+    #[test]
+    fn test_scoreboard_win_rate_and_latency() {
+        let mut scores = ModelScoreboard::default();
+        let mut escalations = EscalationCounts::default();
+        for event in task_lifecycle() {
+            scores.apply(&event);
+            Projection::<EnsembleEvent>::apply(&mut escalations, &event);
+        }
+
+        let qwen = scores.get(ModelId::QWEN35).unwrap();
+        assert_eq!((qwen.votes, qwen.wins), (1, 1));
+        assert_eq!(qwen.avg_latency_ms(), 200.0);
+        let opus = scores.get(ModelId::OPUS_45).unwrap();
+        assert_eq!(opus.win_rate(), 0.0);
+        assert_eq!(scores.ranked()[0].0, ModelId::QWEN35);
+
+        assert_eq!(escalations.arbitrations_by_reason["tie_vote"], 1);
+        assert_eq!(escalations.pending_arbitrations(), 1);
+    }
+
+    #[test]
+    fn test_work_order_board_and_escalations() {
+        let mut board = WorkOrderBoard::default();
+        let mut escalations = EscalationCounts::default();
+        let mut events = order("wo-1", 0);
+        events.extend(order("wo-2", 10));
+        events.push(SwarmEvent::VerificationRan {
+            order_id: "wo-1".to_string(),
+            all_green: true,
+            gates_passed: 3,
+            gates_total: 3,
+            error_count: 0,
+            timestamp: ts(20),
+        });
+        events.push(SwarmEvent::EscalationRequested {
+            order_id: "wo-2".to_string(),
+            reason: "stuck".to_string(),
+            suggested_action: "split".to_string(),
+            blocking_files: vec![],
+            timestamp: ts(21),
+        });
+        for event in &events {
+            board.apply(event);
+            Projection::<SwarmEvent>::apply(&mut escalations, event);
+        }
+
+        let active: Vec<_> = board.active().map(|o| o.order_id.as_str()).collect();
+        assert_eq!(active, vec!["wo-2"]);
+        assert_eq!(board.get("wo-2").unwrap().phase, OrderPhase::Escalated);
+        assert_eq!(board.get("wo-1").unwrap().worker.as_deref(), Some("coder"));
+        assert_eq!(escalations.worker_escalations, 1);
+        assert_eq!(escalations.total(), 1);
+    }
+
+    #[test]
+    fn test_rebuild_then_live_skips_replayed_events() {
+        let store = FileStateStore::in_memory().shared();
+        let bus = EventBus::with_persistence(store.clone());
+        for event in task_lifecycle() {
+            bus.publish(event).unwrap();
+        }
+
+        let projector = Projector::<TaskBoard>::new();
+        let handle = projector.attach(&bus);
+        let replayed = projector
+            .rebuild(ReplayBuilder::new(store).time_range(ts(-1), ts(1_000)))
+            .unwrap();
+        assert_eq!(replayed, 8);
+
+        // Re-published history is ignored; newer events apply
+        bus.publish(task_lifecycle().remove(1)).unwrap();
+        bus.publish(EnsembleEvent::ArbitrationCompleted {
+            task_id: "t2".to_string(),
+            decision: ModelId::OPUS_45,
+            rationale: "clearer".to_string(),
+            timestamp: ts(8),
+        })
+        .unwrap();
+        // Past the watermark, late events with older timestamps still apply
+        bus.publish(EnsembleEvent::TaskCreated {
+            task_id: "t3".to_string(),
+            session_id: "s1".to_string(),
+            prompt_preview: "late".to_string(),
+            require_consensus: false,
+            timestamp: ts(5),
+        })
+        .unwrap();
+        let stats = handle.shutdown();
+        assert_eq!(stats.delivered, 3);
+
+        assert_eq!(projector.applied(), 10);
+        assert!(projector.read(|b| b.task("t3").is_some()));
+        let t2 = projector.read(|b| b.task("t2").cloned()).unwrap();
+        assert_eq!(t2.status, TaskStatus::Completed);
+        assert_eq!(
+            projector
+                .snapshot()
+                .session("s1")
+                .unwrap()
+                .count(TaskStatus::Completed),
+            2
+        );
+    }
+}
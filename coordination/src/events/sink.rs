@@ -0,0 +1,996 @@
+//! Event sinks: durable and external outputs attached to an [`EventBus`]
+//!
+//! A sink is a blocking consumer that receives batches of events. Attaching
+//! one with [`EventBus::attach_sink`] gives it its own bus subscription and a
+//! dedicated thread, so slow disks or webhooks never stall publishers or other
+//! subscribers. Each attachment carries its own [`EventFilter`] and batching.
+//!
+//! Implementations:
+//! - [`JsonlFileSink`]: append-only JSON lines with size/age rotation
+//! - [`StdoutSink`]: human-readable one-line rendering (optionally with body)
+//! - [`WebhookSink`]: batched HTTP POST with retries and a dead-letter file
+//!
+//! # Backpressure
+//!
+//! The bus is a bounded broadcast channel. When a sink falls more than the
+//! channel capacity behind, the oldest events are skipped for that sink only;
+//! the number skipped is logged and counted in [`SinkStats::lagged`], and the
+//! sink resumes from the oldest event still buffered.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use serde::Serialize;
+use tokio::sync::broadcast::{self, error::TryRecvError};
+use tracing::{debug, warn};
+
+use super::bus::{EventBus, EventFilter};
+use super::types::{BusEvent, EnsembleEvent};
+
+/// Error type for sink operations
+#[derive(Debug, thiserror::Error)]
+pub enum SinkError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+
+    #[error("Delivery failed: {0}")]
+    Delivery(String),
+}
+
+/// Result type for sink operations
+pub type SinkResult<T> = Result<T, SinkError>;
+
+/// A consumer of published events
+///
+/// Sinks run on their own thread, so blocking I/O is fine. A failed batch is
+/// counted and dropped; sinks that must not lose events handle retries (and
+/// dead-lettering) themselves, as [`WebhookSink`] does.
+pub trait EventSink<E: BusEvent = EnsembleEvent>: Send {
+    /// Short name for logs and stats
+    fn name(&self) -> &str;
+
+    /// Deliver a batch of matching events, oldest first
+    fn write_batch(&mut self, events: &[E]) -> SinkResult<()>;
+
+    /// Flush buffered output (called on shutdown)
+    fn flush(&mut self) -> SinkResult<()> {
+        Ok(())
+    }
+}
+
+// ── Attachment ──────────────────────────────────────────────────────
+
+/// Per-attachment filtering and batching
+pub struct SinkOptions {
+    /// Only events matching this filter reach the sink
+    pub filter: EventFilter,
+    /// Deliver once this many events are pending
+    pub batch_size: usize,
+    /// Deliver a partial batch after this long
+    pub flush_interval: Duration,
+    /// Sleep between polls of an idle subscription
+    pub poll_interval: Duration,
+}
+
+impl SinkOptions {
+    /// Deliver every event individually
+    pub fn new() -> Self {
+        Self {
+            filter: EventFilter::new(),
+            batch_size: 1,
+            flush_interval: Duration::from_secs(1),
+            poll_interval: Duration::from_millis(10),
+        }
+    }
+
+    /// Set the event filter
+    pub fn with_filter(mut self, filter: EventFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Set the batch size (minimum 1)
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Set the partial-batch flush interval
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+}
+
+impl Default for SinkOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Delivery counters for one attached sink
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SinkStats {
+    /// Events taken off the bus
+    pub received: u64,
+    /// Events rejected by the filter
+    pub filtered_out: u64,
+    /// Events in batches the sink accepted
+    pub delivered: u64,
+    /// Events in batches the sink rejected
+    pub failed: u64,
+    /// Events skipped because the sink fell behind the bus
+    pub lagged: u64,
+    /// Batches handed to the sink
+    pub batches: u64,
+}
+
+impl SinkStats {
+    /// One-line summary for logs
+    pub fn summary_line(&self) -> String {
+        format!(
+            "received={} delivered={} failed={} filtered_out={} lagged={} batches={}",
+            self.received,
+            self.delivered,
+            self.failed,
+            self.filtered_out,
+            self.lagged,
+            self.batches
+        )
+    }
+}
+
+#[derive(Default)]
+struct SinkCounters {
+    received: AtomicU64,
+    filtered_out: AtomicU64,
+    delivered: AtomicU64,
+    failed: AtomicU64,
+    lagged: AtomicU64,
+    batches: AtomicU64,
+}
+
+impl SinkCounters {
+    fn snapshot(&self) -> SinkStats {
+        SinkStats {
+            received: self.received.load(Ordering::Relaxed),
+            filtered_out: self.filtered_out.load(Ordering::Relaxed),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            lagged: self.lagged.load(Ordering::Relaxed),
+            batches: self.batches.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Handle to a running sink
+///
+/// Dropping the handle (or calling [`shutdown`](Self::shutdown)) drains the
+/// events already buffered for the sink, flushes it, and joins its thread.
+/// The sink also stops on its own once the bus is dropped.
+pub struct SinkHandle {
+    name: String,
+    counters: Arc<SinkCounters>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SinkHandle {
+    /// Name of the attached sink
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Current delivery counters
+    pub fn stats(&self) -> SinkStats {
+        self.counters.snapshot()
+    }
+
+    /// Drain, flush and stop the sink, returning its final counters
+    pub fn shutdown(mut self) -> SinkStats {
+        self.stop_and_join();
+        self.counters.snapshot()
+    }
+
+    fn stop_and_join(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                warn!(sink = %self.name, "Sink thread panicked");
+            }
+        }
+    }
+}
+
+impl Drop for SinkHandle {
+    fn drop(&mut self) {
+        self.stop_and_join();
+    }
+}
+
+impl<E: BusEvent> EventBus<E> {
+    /// Attach a sink on its own subscription and thread
+    ///
+    /// Only events published after this call reach the sink.
+    pub fn attach_sink<S>(&self, sink: S, options: SinkOptions) -> SinkHandle
+    where
+        S: EventSink<E> + 'static,
+    {
+        let name = sink.name().to_string();
+        let counters = Arc::new(SinkCounters::default());
+        let stop = Arc::new(AtomicBool::new(false));
+        let receiver = self.subscribe();
+
+        let worker = SinkWorker {
+            sink,
+            receiver,
+            options,
+            counters: Arc::clone(&counters),
+            stop: Arc::clone(&stop),
+        };
+        let thread = std::thread::Builder::new()
+            .name(format!("event-sink-{}", name))
+            .spawn(move || worker.run())
+            .expect("failed to spawn event sink thread");
+
+        debug!(sink = %name, stream = E::STREAM, "Event sink attached");
+        SinkHandle {
+            name,
+            counters,
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+struct SinkWorker<E: BusEvent, S: EventSink<E>> {
+    sink: S,
+    receiver: broadcast::Receiver<E>,
+    options: SinkOptions,
+    counters: Arc<SinkCounters>,
+    stop: Arc<AtomicBool>,
+}
+
+impl<E: BusEvent, S: EventSink<E>> SinkWorker<E, S> {
+    fn run(mut self) {
+        let mut batch: Vec<E> = Vec::with_capacity(self.options.batch_size);
+        let mut last_delivery = Instant::now();
+
+        loop {
+            match self.receiver.try_recv() {
+                Ok(event) => {
+                    self.counters.received.fetch_add(1, Ordering::Relaxed);
+                    if !self.options.filter.matches(&event) {
+                        self.counters.filtered_out.fetch_add(1, Ordering::Relaxed);
+                        continue;
+                    }
+                    batch.push(event);
+                    if batch.len() >= self.options.batch_size {
+                        self.deliver(&mut batch);
+                        last_delivery = Instant::now();
+                    }
+                }
+                Err(TryRecvError::Lagged(skipped)) => {
+                    self.counters.lagged.fetch_add(skipped, Ordering::Relaxed);
+                    warn!(
+                        sink = self.sink.name(),
+                        skipped, "Event sink lagged behind the bus"
+                    );
+                }
+                Err(TryRecvError::Closed) => break,
+                Err(TryRecvError::Empty) => {
+                    // Stop only once the buffered backlog is drained
+                    if self.stop.load(Ordering::Acquire) {
+                        break;
+                    }
+                    if !batch.is_empty() && last_delivery.elapsed() >= self.options.flush_interval {
+                        self.deliver(&mut batch);
+                        last_delivery = Instant::now();
+                    }
+                    std::thread::sleep(self.options.poll_interval);
+                }
+            }
+        }
+
+        self.deliver(&mut batch);
+        if let Err(e) = self.sink.flush() {
+            warn!(sink = self.sink.name(), "Event sink flush failed: {}", e);
+        }
+        debug!(
+            sink = self.sink.name(),
+            stats = %self.counters.snapshot().summary_line(),
+            "Event sink stopped"
+        );
+    }
+
+    fn deliver(&mut self, batch: &mut Vec<E>) {
+        if batch.is_empty() {
+            return;
+        }
+        let count = batch.len() as u64;
+        self.counters.batches.fetch_add(1, Ordering::Relaxed);
+        match self.sink.write_batch(batch) {
+            Ok(()) => {
+                self.counters.delivered.fetch_add(count, Ordering::Relaxed);
+            }
+            Err(e) => {
+                self.counters.failed.fetch_add(count, Ordering::Relaxed);
+                warn!(
+                    sink = self.sink.name(),
+                    count, "Event sink rejected batch: {}", e
+                );
+            }
+        }
+        batch.clear();
+    }
+}
+
+// ── JSONL file sink ─────────────────────────────────────────────────
+
+/// Append-only JSON-lines file with size and age rotation
+///
+/// Rotation renames `events.jsonl` to `events.jsonl.1`, shifting older files
+/// up by one and deleting anything beyond `max_files`. Age is measured from
+/// when the sink opened the current file.
+pub struct JsonlFileSink {
+    path: PathBuf,
+    max_bytes: Option<u64>,
+    max_age: Option<Duration>,
+    max_files: usize,
+    file: Option<BufWriter<File>>,
+    bytes: u64,
+    opened_at: Instant,
+    rotations: u64,
+}
+
+impl JsonlFileSink {
+    /// Create a sink writing to `path` (opened on first write)
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes: None,
+            max_age: None,
+            max_files: 5,
+            file: None,
+            bytes: 0,
+            opened_at: Instant::now(),
+            rotations: 0,
+        }
+    }
+
+    /// Rotate before a write would push the file past this size
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Rotate once the current file has been open this long
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Number of rotated files to keep (0 discards rotated output)
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    /// Path of the live file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path of the `n`th rotated file (1 = most recent)
+    pub fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    /// Number of rotations performed by this sink
+    pub fn rotations(&self) -> u64 {
+        self.rotations
+    }
+
+    fn open(&mut self) -> SinkResult<&mut BufWriter<File>> {
+        if self.file.is_none() {
+            if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)?;
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            self.bytes = file.metadata()?.len();
+            self.opened_at = Instant::now();
+            self.file = Some(BufWriter::new(file));
+        }
+        Ok(self.file.as_mut().expect("file opened above"))
+    }
+
+    fn should_rotate(&self, next_len: u64) -> bool {
+        if self.bytes == 0 {
+            return false;
+        }
+        let too_big = self
+            .max_bytes
+            .is_some_and(|max| self.bytes + next_len > max);
+        let too_old = self
+            .max_age
+            .is_some_and(|age| self.opened_at.elapsed() >= age);
+        too_big || too_old
+    }
+
+    fn rotate(&mut self) -> SinkResult<()> {
+        if let Some(mut file) = self.file.take() {
+            file.flush()?;
+        }
+        if self.max_files == 0 {
+            std::fs::remove_file(&self.path)?;
+        } else {
+            let oldest = self.rotated_path(self.max_files);
+            if oldest.exists() {
+                std::fs::remove_file(&oldest)?;
+            }
+            for n in (1..self.max_files).rev() {
+                let from = self.rotated_path(n);
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated_path(n + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+        }
+        self.rotations += 1;
+        debug!(path = %self.path.display(), "Rotated event log");
+        Ok(())
+    }
+}
+
+impl<E: BusEvent> EventSink<E> for JsonlFileSink {
+    fn name(&self) -> &str {
+        "jsonl_file"
+    }
+
+    fn write_batch(&mut self, events: &[E]) -> SinkResult<()> {
+        for event in events {
+            let mut line =
+                serde_json::to_vec(event).map_err(|e| SinkError::Serialization(e.to_string()))?;
+            line.push(b'\n');
+
+            self.open()?;
+            if self.should_rotate(line.len() as u64) {
+                self.rotate()?;
+            }
+            self.open()?.write_all(&line)?;
+            self.bytes += line.len() as u64;
+        }
+        if let Some(file) = self.file.as_mut() {
+            file.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> SinkResult<()> {
+        if let Some(file) = self.file.as_mut() {
+            file.flush()?;
+            file.get_ref().sync_data()?;
+        }
+        Ok(())
+    }
+}
+
+// ── Stdout sink ─────────────────────────────────────────────────────
+
+/// Human-readable event log, one line per event
+///
+/// ```text
+/// 12:04:05.120 swarm    worker_completed       order=wo-7
+/// ```
+pub struct StdoutSink {
+    out: Box<dyn Write + Send>,
+    verbose: bool,
+}
+
+impl StdoutSink {
+    /// Write to standard output
+    pub fn new() -> Self {
+        Self::with_writer(Box::new(std::io::stdout()))
+    }
+
+    /// Write to any writer (useful for stderr or tests)
+    pub fn with_writer(out: Box<dyn Write + Send>) -> Self {
+        Self {
+            out,
+            verbose: false,
+        }
+    }
+
+    /// Also print each event's pretty-printed JSON body
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Render the one-line header for an event
+    pub fn render<E: BusEvent>(event: &E) -> String {
+        let mut scope = Vec::new();
+        if let Some(session) = event.session_id() {
+            scope.push(format!("session={}", session));
+        }
+        if let Some(task) = event.task_id() {
+            scope.push(format!("task={}", task));
+        }
+        if let Some(order) = event.order_id() {
+            scope.push(format!("order={}", order));
+        }
+        format!(
+            "{} {:<8} {:<22} {}",
+            event.timestamp().format("%H:%M:%S%.3f"),
+            E::STREAM,
+            event.event_type(),
+            scope.join(" ")
+        )
+        .trim_end()
+        .to_string()
+    }
+}
+
+impl Default for StdoutSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: BusEvent> EventSink<E> for StdoutSink {
+    fn name(&self) -> &str {
+        "stdout"
+    }
+
+    fn write_batch(&mut self, events: &[E]) -> SinkResult<()> {
+        for event in events {
+            writeln!(self.out, "{}", Self::render(event))?;
+            if self.verbose {
+                let body = serde_json::to_string_pretty(event)
+                    .map_err(|e| SinkError::Serialization(e.to_string()))?;
+                for line in body.lines() {
+                    writeln!(self.out, "    {}", line)?;
+                }
+            }
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> SinkResult<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+// ── Webhook sink ────────────────────────────────────────────────────
+
+/// Configuration for [`WebhookSink`]
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    /// Endpoint receiving `POST` requests
+    pub url: String,
+    /// Extra request headers
+    pub headers: Vec<(String, String)>,
+    /// Per-request timeout
+    pub timeout_ms: u64,
+    /// Retries after the first attempt for retryable failures
+    pub max_retries: u32,
+    /// Initial retry delay, doubled on each retry
+    pub backoff_ms: u64,
+    /// JSON-lines file receiving batches that could not be delivered
+    pub dead_letter: Option<PathBuf>,
+}
+
+impl WebhookConfig {
+    /// Create a config for `url` with 3 retries and no dead-letter file
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            headers: Vec::new(),
+            timeout_ms: 5_000,
+            max_retries: 3,
+            backoff_ms: 200,
+            dead_letter: None,
+        }
+    }
+
+    /// Add a request header
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set the per-request timeout
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    /// Set the retry count
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the initial retry delay
+    pub fn with_backoff_ms(mut self, backoff_ms: u64) -> Self {
+        self.backoff_ms = backoff_ms;
+        self
+    }
+
+    /// Write undeliverable events to this JSON-lines file
+    pub fn with_dead_letter(mut self, path: impl Into<PathBuf>) -> Self {
+        self.dead_letter = Some(path.into());
+        self
+    }
+}
+
+/// One undeliverable event in the dead-letter file
+#[derive(Debug, Serialize)]
+struct DeadLetter<'a, E> {
+    failed_at: chrono::DateTime<Utc>,
+    url: &'a str,
+    error: &'a str,
+    event: &'a E,
+}
+
+/// Batched HTTP delivery with retries and dead-lettering
+///
+/// Each batch is sent as `{"stream": ..., "events": [...]}`. Network errors,
+/// timeouts, 5xx, 408 and 429 are retried with exponential backoff; other
+/// statuses fail immediately. Failed batches go to the dead-letter file.
+pub struct WebhookSink {
+    config: WebhookConfig,
+    // Built lazily on the sink thread: the blocking client must not be
+    // created or dropped inside an async context.
+    client: Option<reqwest::blocking::Client>,
+    dead_lettered: u64,
+}
+
+impl WebhookSink {
+    /// Create a sink from its config
+    pub fn new(config: WebhookConfig) -> Self {
+        Self {
+            config,
+            client: None,
+            dead_lettered: 0,
+        }
+    }
+
+    /// Sink configuration
+    pub fn config(&self) -> &WebhookConfig {
+        &self.config
+    }
+
+    /// Events written to the dead-letter file so far
+    pub fn dead_lettered(&self) -> u64 {
+        self.dead_lettered
+    }
+
+    /// Send one request; `Err((retryable, reason))` on failure
+    fn send(&mut self, body: &serde_json::Value) -> Result<(), (bool, String)> {
+        if self.client.is_none() {
+            let client = reqwest::blocking::Client::builder()
+                .timeout(Duration::from_millis(self.config.timeout_ms))
+                .build()
+                .map_err(|e| (false, format!("failed to build HTTP client: {e}")))?;
+            self.client = Some(client);
+        }
+        let client = self.client.as_ref().expect("client built above");
+
+        let mut request = client.post(&self.config.url).json(body);
+        for (name, value) in &self.config.headers {
+            request = request.header(name, value);
+        }
+        let response = request
+            .send()
+            .map_err(|e| (true, format!("HTTP request failed: {e}")))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let retryable =
+            status.is_server_error() || status.as_u16() == 408 || status.as_u16() == 429;
+        Err((retryable, format!("webhook returned HTTP {status}")))
+    }
+
+    fn dead_letter<E: BusEvent>(&mut self, events: &[E], error: &str) -> SinkResult<()> {
+        let Some(path) = &self.config.dead_letter else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?);
+        let failed_at = Utc::now();
+        for event in events {
+            let record = DeadLetter {
+                failed_at,
+                url: &self.config.url,
+                error,
+                event,
+            };
+            serde_json::to_writer(&mut file, &record)
+                .map_err(|e| SinkError::Serialization(e.to_string()))?;
+            file.write_all(b"\n")?;
+        }
+        file.flush()?;
+        self.dead_lettered += events.len() as u64;
+        Ok(())
+    }
+}
+
+impl<E: BusEvent> EventSink<E> for WebhookSink {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    fn write_batch(&mut self, events: &[E]) -> SinkResult<()> {
+        let body = serde_json::json!({
+            "stream": E::STREAM,
+            "count": events.len(),
+            "events": events,
+        });
+
+        let mut attempt = 0;
+        let error = loop {
+            match self.send(&body) {
+                Ok(()) => return Ok(()),
+                Err((retryable, reason)) => {
+                    if !retryable || attempt >= self.config.max_retries {
+                        break reason;
+                    }
+                    let delay = self.config.backoff_ms.saturating_mul(1 << attempt.min(16));
+                    debug!(
+                        attempt,
+                        delay_ms = delay,
+                        "Retrying webhook delivery: {}",
+                        reason
+                    );
+                    std::thread::sleep(Duration::from_millis(delay));
+                    attempt += 1;
+                }
+            }
+        };
+
+        self.dead_letter(events, &error)?;
+        Err(SinkError::Delivery(format!(
+            "{} ({} attempts)",
+            error,
+            attempt + 1
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::SwarmEvent;
+    use crate::state::ModelId;
+    use crate::test_utils::mock_server;
+    use std::sync::{mpsc, Mutex};
+    use tempfile::tempdir;
+
+    fn loaded(n: u64) -> EnsembleEvent {
+        EnsembleEvent::ModelLoaded {
+            model_id: ModelId::QWEN35,
+            load_time_ms: n,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn started(order_id: &str) -> SwarmEvent {
+        SwarmEvent::WorkerStarted {
+            order_id: order_id.to_string(),
+            worker_name: "coder".to_string(),
+            worker_tier: "worker".to_string(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn lines(path: &Path) -> Vec<serde_json::Value> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    /// Writer that appends into a shared buffer
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_jsonl_sink_with_filter() {
+        let dir = tempdir().unwrap();
+        let all = dir.path().join("all.jsonl");
+        let orders = dir.path().join("wo-2.jsonl");
+
+        let bus = EventBus::<SwarmEvent>::new();
+        let all_sink = bus.attach_sink(JsonlFileSink::new(&all), SinkOptions::new());
+        let order_sink = bus.attach_sink(
+            JsonlFileSink::new(&orders),
+            SinkOptions::new().with_filter(EventFilter::new().order("wo-2")),
+        );
+        for order_id in ["wo-1", "wo-2", "wo-1"] {
+            bus.publish(started(order_id)).unwrap();
+        }
+
+        let stats = all_sink.shutdown();
+        assert_eq!(stats.delivered, 3);
+        let stats = order_sink.shutdown();
+        assert_eq!((stats.delivered, stats.filtered_out), (1, 2));
+
+        assert_eq!(lines(&all).len(), 3);
+        let filtered = lines(&orders);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0]["order_id"], "wo-2");
+        assert_eq!(filtered[0]["type"], "worker_started");
+    }
+
+    #[test]
+    fn test_jsonl_size_rotation_keeps_max_files() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("logs").join("events.jsonl");
+        let mut sink = JsonlFileSink::new(&path)
+            .with_max_bytes(1)
+            .with_max_files(2);
+
+        for n in 0..4 {
+            EventSink::<EnsembleEvent>::write_batch(&mut sink, &[loaded(n)]).unwrap();
+        }
+
+        // Every write after the first rotates; only two rotated files survive
+        assert_eq!(sink.rotations(), 3);
+        assert_eq!(lines(&path)[0]["load_time_ms"], 3);
+        assert_eq!(lines(&sink.rotated_path(1))[0]["load_time_ms"], 2);
+        assert_eq!(lines(&sink.rotated_path(2))[0]["load_time_ms"], 1);
+        assert!(!sink.rotated_path(3).exists());
+    }
+
+    #[test]
+    fn test_jsonl_age_rotation() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let mut sink = JsonlFileSink::new(&path).with_max_age(Duration::ZERO);
+
+        EventSink::<EnsembleEvent>::write_batch(&mut sink, &[loaded(1), loaded(2)]).unwrap();
+        assert_eq!(sink.rotations(), 1);
+        assert_eq!(lines(&path).len(), 1);
+        assert_eq!(lines(&sink.rotated_path(1)).len(), 1);
+    }
+
+    #[test]
+    fn test_stdout_sink_renders_scope() {
+        let buf = SharedBuf::default();
+        let mut sink = StdoutSink::with_writer(Box::new(buf.clone())).with_verbose(true);
+        sink.write_batch(&[started("wo-9")]).unwrap();
+
+        let out = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        let mut lines = out.lines();
+        let header = lines.next().unwrap();
+        assert!(header.contains("swarm"));
+        assert!(header.contains("worker_started"));
+        assert!(header.ends_with("order=wo-9"));
+        assert!(out.contains("\"worker_name\": \"coder\""));
+    }
+
+    #[test]
+    fn test_webhook_batches_and_retries() {
+        let (url, seen) = mock_server("/hook", vec![(503, String::new()), (200, String::new())]);
+        let bus = EventBus::new();
+        let handle = bus.attach_sink(
+            WebhookSink::new(WebhookConfig::new(url).with_backoff_ms(1)),
+            SinkOptions::new().with_batch_size(3),
+        );
+        for n in 0..3 {
+            bus.publish(loaded(n)).unwrap();
+        }
+        let stats = handle.shutdown();
+
+        assert_eq!((stats.delivered, stats.batches, stats.failed), (3, 1, 0));
+        let requests = seen.lock().unwrap();
+        assert_eq!(requests.len(), 2, "one failure, one retry");
+        assert_eq!(requests[1]["stream"], "ensemble");
+        assert_eq!(requests[1]["events"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_webhook_dead_letters_client_errors() {
+        let dir = tempdir().unwrap();
+        let dead = dir.path().join("dead.jsonl");
+        let (url, seen) = mock_server("/hook", vec![(400, String::new())]);
+        let mut sink = WebhookSink::new(
+            WebhookConfig::new(url.clone())
+                .with_backoff_ms(1)
+                .with_dead_letter(&dead),
+        );
+
+        let err = sink
+            .write_batch(&[started("wo-1"), started("wo-2")])
+            .unwrap_err();
+        assert!(matches!(err, SinkError::Delivery(_)));
+        assert_eq!(seen.lock().unwrap().len(), 1, "4xx is not retried");
+        assert_eq!(sink.dead_lettered(), 2);
+
+        let letters = lines(&dead);
+        assert_eq!(letters.len(), 2);
+        assert_eq!(letters[1]["event"]["order_id"], "wo-2");
+        assert_eq!(letters[0]["url"], url);
+        assert!(letters[0]["error"].as_str().unwrap().contains("400"));
+    }
+
+    /// Sink that blocks its first batch until released
+    struct GateSink {
+        entered: mpsc::Sender<()>,
+        release: Option<mpsc::Receiver<()>>,
+    }
+
+    impl EventSink<EnsembleEvent> for GateSink {
+        fn name(&self) -> &str {
+            "gate"
+        }
+
+        fn write_batch(&mut self, _events: &[EnsembleEvent]) -> SinkResult<()> {
+            if let Some(release) = self.release.take() {
+                self.entered.send(()).unwrap();
+                release.recv().unwrap();
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_lagging_sink_skips_and_counts() {
+        let (entered_tx, entered_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel();
+        let bus = EventBus::new();
+        let handle = bus.attach_sink(
+            GateSink {
+                entered: entered_tx,
+                release: Some(release_rx),
+            },
+            SinkOptions::new(),
+        );
+
+        bus.publish(loaded(0)).unwrap();
+        entered_rx.recv().unwrap();
+        // The sink is stuck on event 0 while far more than the channel holds arrive
+        let flood = 300;
+        for n in 1..=flood {
+            bus.publish(loaded(n)).unwrap();
+        }
+        release_tx.send(()).unwrap();
+
+        let stats = handle.shutdown();
+        assert!(stats.lagged > 0);
+        assert_eq!(stats.delivered + stats.lagged, flood + 1);
+        assert_eq!(stats.failed, 0);
+    }
+}
@@ -3,13 +3,55 @@
 //! These events drive the pub/sub system and are persisted for replay.
 
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+pub use crate::state::ArbitrationReason;
 use crate::state::{ModelId, SessionId, TaskId, VotingStrategy};
 
 /// Unique identifier for events
 pub type EventId = String;
 
+/// An event stream that can travel on the [`EventBus`](super::EventBus)
+///
+/// Every stream persists into the same event log, so each one lists the
+/// `type` tags it serializes with; history replay uses them to pick its own
+/// records out of the shared log.
+pub trait BusEvent:
+    Clone + std::fmt::Debug + Send + Sync + Serialize + DeserializeOwned + 'static
+{
+    /// Stream name, used in logs
+    const STREAM: &'static str;
+
+    /// Every `type` tag this stream serializes with
+    const EVENT_TYPES: &'static [&'static str];
+
+    /// When the event happened
+    fn timestamp(&self) -> DateTime<Utc>;
+
+    /// The event's `type` tag
+    fn event_type(&self) -> &'static str;
+
+    /// Session the event belongs to, if session-scoped
+    fn session_id(&self) -> Option<&str> {
+        None
+    }
+
+    /// Task the event belongs to, if task-scoped
+    fn task_id(&self) -> Option<&str> {
+        None
+    }
+
+    /// Work order the event belongs to, if order-scoped
+    fn order_id(&self) -> Option<&str> {
+        None
+    }
+
+    /// Whether the event reports a failure (counted by replay stats)
+    fn is_failure(&self) -> bool {
+        false
+    }
+}
+
 /// All ensemble coordination events
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -218,6 +260,49 @@ impl EnsembleEvent {
     }
 }
 
+impl BusEvent for EnsembleEvent {
+    const STREAM: &'static str = "ensemble";
+
+    const EVENT_TYPES: &'static [&'static str] = &[
+        "task_created",
+        "task_assigned",
+        "model_loaded",
+        "model_unloaded",
+        "result_submitted",
+        "voting_started",
+        "consensus_reached",
+        "arbitration_requested",
+        "arbitration_completed",
+        "context_updated",
+        "session_created",
+        "session_ended",
+        "task_failed",
+        "manager_delegated",
+        "council_convened",
+        "council_decided",
+    ];
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        EnsembleEvent::timestamp(self)
+    }
+
+    fn event_type(&self) -> &'static str {
+        EnsembleEvent::event_type(self)
+    }
+
+    fn session_id(&self) -> Option<&str> {
+        EnsembleEvent::session_id(self)
+    }
+
+    fn task_id(&self) -> Option<&str> {
+        EnsembleEvent::task_id(self)
+    }
+
+    fn is_failure(&self) -> bool {
+        matches!(self, EnsembleEvent::TaskFailed { .. })
+    }
+}
+
 /// Reason for unloading a model
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -265,7 +350,7 @@ impl VoteSummary {
         let total_votes: u32 = vote_counts.iter().map(|(_, c)| *c).sum();
         let sorted: Vec<_> = {
             let mut v = vote_counts.clone();
-            v.sort_by(|a, b| b.1.cmp(&a.1));
+            v.sort_by_key(|b| std::cmp::Reverse(b.1));
             v
         };
         let margin = if sorted.len() >= 2 {
@@ -291,39 +376,6 @@ impl VoteSummary {
     }
 }
 
-/// Reason for requesting arbitration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum ArbitrationReason {
-    /// Tie vote between models
-    TieVote { tied_models: Vec<ModelId> },
-    /// Low confidence from all models
-    LowConfidence { max_confidence: f32 },
-    /// Conflicting responses
-    ConflictingResponses { description: String },
-    /// Explicit arbitration request
-    ExplicitRequest { requester: String },
-}
-
-impl std::fmt::Display for ArbitrationReason {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ArbitrationReason::TieVote { tied_models } => {
-                write!(f, "tie_vote({} models)", tied_models.len())
-            }
-            ArbitrationReason::LowConfidence { max_confidence } => {
-                write!(f, "low_confidence(max={:.2})", max_confidence)
-            }
-            ArbitrationReason::ConflictingResponses { .. } => {
-                write!(f, "conflicting_responses")
-            }
-            ArbitrationReason::ExplicitRequest { requester } => {
-                write!(f, "explicit_request({})", requester)
-            }
-        }
-    }
-}
-
 /// What updated the shared context
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -352,8 +404,8 @@ impl std::fmt::Display for ContextUpdater {
 // ── SwarmEvent: Worker dispatch protocol ─────────────────────────────────────
 //
 // These events carry structured metadata for the worker dispatch lifecycle.
-// Phase 2 will wire them into the EventBus; for now they are defined as types
-// for the work_protocol module to publish and the orchestrator to consume.
+// They travel on an `EventBus<SwarmEvent>` and share the persisted event log
+// with `EnsembleEvent`; filters and replay key them by order id.
 
 /// Events for the worker dispatch protocol.
 ///
@@ -361,7 +413,7 @@ impl std::fmt::Display for ContextUpdater {
 /// because the worker protocol has a different lifecycle:
 ///   WorkOrderCreated → WorkerStarted → WorkerProgress* → WorkerCompleted → VerificationRan
 ///
-/// In Phase 2, the EventBus will be made generic or dual-channel to carry both.
+/// Both streams use the generic `EventBus`; see [`BusEvent`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum SwarmEvent {
@@ -466,6 +518,39 @@ impl SwarmEvent {
     }
 }
 
+impl BusEvent for SwarmEvent {
+    const STREAM: &'static str = "swarm";
+
+    const EVENT_TYPES: &'static [&'static str] = &[
+        "work_order_created",
+        "worker_started",
+        "worker_progress",
+        "worker_completed",
+        "verification_ran",
+        "escalation_requested",
+    ];
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        SwarmEvent::timestamp(self)
+    }
+
+    fn event_type(&self) -> &'static str {
+        SwarmEvent::event_type(self)
+    }
+
+    fn order_id(&self) -> Option<&str> {
+        Some(SwarmEvent::order_id(self))
+    }
+
+    fn is_failure(&self) -> bool {
+        match self {
+            SwarmEvent::WorkerCompleted { status, .. } => status == "failed",
+            SwarmEvent::VerificationRan { all_green, .. } => !all_green,
+            _ => false,
+        }
+    }
+}
+
 /// Reason for session ending
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -518,7 +603,7 @@ mod tests {
     fn test_event_accessors() {
         let event = EnsembleEvent::ResultSubmitted {
             task_id: "task-1".to_string(),
-            model_id: ModelId::Opus45,
+            model_id: ModelId::OPUS_45,
             confidence: 0.9,
             tokens_used: 100,
             latency_ms: 500,
@@ -532,9 +617,80 @@ mod tests {
 
     #[test]
     fn test_vote_summary() {
-        let summary = VoteSummary::new(vec![(ModelId::Opus45, 2), (ModelId::HydraCoder, 1)]);
+        let summary = VoteSummary::new(vec![(ModelId::OPUS_45, 2), (ModelId::HYDRA_CODER, 1)]);
 
         assert_eq!(summary.total_votes, 3);
         assert_eq!(summary.margin, 1);
     }
+
+    #[test]
+    fn test_swarm_event_types_are_listed() {
+        let now = Utc::now();
+        let events = vec![
+            SwarmEvent::WorkOrderCreated {
+                order_id: "wo-1".to_string(),
+                issue_id: "i-1".to_string(),
+                objective_preview: String::new(),
+                target_files: vec![],
+                worker_tier: None,
+                iteration: 0,
+                timestamp: now,
+            },
+            SwarmEvent::WorkerStarted {
+                order_id: "wo-1".to_string(),
+                worker_name: "w".to_string(),
+                worker_tier: "t".to_string(),
+                timestamp: now,
+            },
+            SwarmEvent::WorkerProgress {
+                order_id: "wo-1".to_string(),
+                turns_completed: 1,
+                tool_calls: 2,
+                files_modified: vec![],
+                has_written: false,
+                timestamp: now,
+            },
+            SwarmEvent::WorkerCompleted {
+                order_id: "wo-1".to_string(),
+                status: "failed".to_string(),
+                files_modified: vec![],
+                tool_calls: 2,
+                turns_used: 1,
+                wall_time_ms: 10,
+                confidence: 0.1,
+                needs_escalation: true,
+                timestamp: now,
+            },
+            SwarmEvent::VerificationRan {
+                order_id: "wo-1".to_string(),
+                all_green: true,
+                gates_passed: 3,
+                gates_total: 3,
+                error_count: 0,
+                timestamp: now,
+            },
+            SwarmEvent::EscalationRequested {
+                order_id: "wo-1".to_string(),
+                reason: "stuck".to_string(),
+                suggested_action: "split".to_string(),
+                blocking_files: vec![],
+                timestamp: now,
+            },
+        ];
+
+        for event in &events {
+            let json = serde_json::to_value(event).unwrap();
+            assert_eq!(json["type"], event.event_type());
+            assert!(<SwarmEvent as BusEvent>::EVENT_TYPES.contains(&event.event_type()));
+            assert_eq!(BusEvent::order_id(event), Some("wo-1"));
+        }
+        assert_eq!(SwarmEvent::EVENT_TYPES.len(), events.len());
+        let failures = events.iter().filter(|e| e.is_failure()).count();
+        assert_eq!(failures, 1);
+
+        // Streams never claim each other's tags
+        for t in SwarmEvent::EVENT_TYPES {
+            assert!(!EnsembleEvent::EVENT_TYPES.contains(t));
+        }
+    }
 }
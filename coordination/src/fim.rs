@@ -3,7 +3,6 @@
 //! This module provides an async function to call the llama-server /v1/completions
 //! endpoint with a suffix parameter for FIM-style code completion.
 
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 /// Request body for FIM completion endpoint.
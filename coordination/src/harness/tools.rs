@@ -6,6 +6,7 @@
 //! - harness_complete_feature: Mark a feature as complete
 //! - harness_checkpoint: Create a git checkpoint
 //! - harness_rollback: Rollback to a previous checkpoint
+//! - harness_repo_map: Ranked, token-bounded repository outline (`full` feature)
 
 use crate::harness::error::HarnessResult;
 use crate::harness::feature_registry::FeatureRegistry;
@@ -563,6 +564,40 @@ pub struct HarnessRetrospectiveResponse {
     pub retrospective: crate::harness::types::SessionRetrospective,
 }
 
+/// Request for harness_repo_map tool
+#[cfg(feature = "full")]
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct HarnessRepoMapRequest {
+    /// Issue text used to seed ranking with mentioned files and identifiers
+    #[schemars(description = "Issue text; mentioned files and identifiers seed the ranking")]
+    pub issue: Option<String>,
+    /// Files the task targets (in addition to those found in the issue)
+    #[schemars(description = "Target files to seed the ranking")]
+    pub target_files: Option<Vec<String>>,
+    /// Identifiers the task targets (in addition to those found in the issue)
+    #[schemars(description = "Target identifiers (e.g. Store::open) to seed the ranking")]
+    pub identifiers: Option<Vec<String>>,
+    /// Token budget for the rendered map (default: 1024)
+    #[schemars(description = "Maximum tokens for the rendered map (default: 1024)")]
+    pub token_budget: Option<u32>,
+    /// Include symbols from test files (default: false)
+    #[schemars(description = "Include symbols from test files (default: false)")]
+    pub include_tests: Option<bool>,
+}
+
+/// Response for harness_repo_map tool
+#[cfg(feature = "full")]
+#[derive(Debug, Serialize)]
+pub struct HarnessRepoMapResponse {
+    pub success: bool,
+    pub map: String,
+    pub files: usize,
+    pub symbols_included: usize,
+    pub symbols_total: usize,
+    pub estimated_tokens: u32,
+    pub truncated: bool,
+}
+
 // ============================================================================
 // Tool Implementation Functions
 // ============================================================================
@@ -1765,6 +1800,48 @@ pub fn harness_retrospective(
     })
 }
 
+/// Generate a ranked repository map of the working directory
+///
+/// Does not require an active session, so scouts can call it before
+/// `harness_start`.
+#[cfg(feature = "full")]
+pub fn harness_repo_map(
+    state: &HarnessState,
+    req: HarnessRepoMapRequest,
+) -> HarnessResult<HarnessRepoMapResponse> {
+    use crate::repo_map::{generate_repo_map, RepoMapConfig, RepoMapSeeds};
+
+    let mut seeds = req
+        .issue
+        .as_deref()
+        .map(RepoMapSeeds::from_issue)
+        .unwrap_or_default();
+    seeds.files.extend(req.target_files.unwrap_or_default());
+    seeds
+        .identifiers
+        .extend(req.identifiers.unwrap_or_default());
+
+    let defaults = RepoMapConfig::default();
+    let config = RepoMapConfig {
+        token_budget: req.token_budget.unwrap_or(defaults.token_budget),
+        include_tests: req.include_tests.unwrap_or(defaults.include_tests),
+        ..defaults
+    };
+
+    let map = generate_repo_map(&state.config.working_directory, &seeds, &config)
+        .map_err(crate::harness::error::HarnessError::validation)?;
+
+    Ok(HarnessRepoMapResponse {
+        success: true,
+        files: map.files.len(),
+        symbols_included: map.symbols_included,
+        symbols_total: map.symbols_total,
+        estimated_tokens: map.estimated_tokens,
+        truncated: map.truncated,
+        map: map.rendered,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1892,4 +1969,34 @@ mod tests {
         assert!(status.session.is_some());
         assert!(!status.git_status.branch.is_empty());
     }
+
+    #[cfg(feature = "full")]
+    #[test]
+    fn test_harness_repo_map() {
+        let (dir, state) = setup_test_harness();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/lib.rs"),
+            "pub fn parse_config() {}\npub fn unrelated() {}\n",
+        )
+        .unwrap();
+
+        let resp = harness_repo_map(
+            &state,
+            HarnessRepoMapRequest {
+                issue: Some("parse_config panics on empty input".to_string()),
+                target_files: None,
+                identifiers: None,
+                token_budget: Some(12),
+                include_tests: None,
+            },
+        )
+        .unwrap();
+
+        assert!(resp.success);
+        assert_eq!(resp.files, 1);
+        assert_eq!(resp.symbols_included, 1);
+        assert!(resp.truncated);
+        assert!(resp.map.contains("pub fn parse_config()"));
+    }
 }
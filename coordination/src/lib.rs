@@ -19,7 +19,9 @@ pub mod state;
 // ── Full-only modules (MCP binary surface; not linked by default) ──
 #[cfg(feature = "full")]
 pub mod agent_profile;
-#[cfg(feature = "heavy-state")]
+#[cfg(feature = "full")]
+pub mod diagnostics;
+#[cfg(feature = "full")]
 pub mod events;
 #[cfg(feature = "full")]
 pub mod memory;
@@ -30,12 +32,20 @@ pub mod perf_control;
 #[cfg(feature = "full")]
 pub mod registry;
 #[cfg(feature = "full")]
+pub mod repo_map;
+#[cfg(feature = "full")]
 pub mod resilience;
 #[cfg(feature = "full")]
+pub mod review_comments;
+#[cfg(feature = "full")]
+pub mod review_executor;
+#[cfg(feature = "full")]
 pub mod reviewer_policy;
 #[cfg(feature = "full")]
 pub mod reviewer_tools;
 #[cfg(feature = "full")]
+pub mod sarif;
+#[cfg(feature = "full")]
 pub mod shell_safety;
 #[cfg(feature = "full")]
 pub mod slurm;
@@ -44,6 +54,9 @@ pub mod tool_bundle;
 #[cfg(feature = "full")]
 pub mod tool_schema;
 
+#[cfg(all(test, feature = "full"))]
+pub(crate) mod test_utils;
+
 // ── Re-exports ──
 
 pub use harness::{load_session_state, save_session_state};
@@ -55,24 +68,26 @@ pub use harness::{
 };
 
 pub use state::{
-    EnsembleSession, EnsembleTask, ModelId, ModelResult, SharedContext, TaskStatus, VoteRecord,
-    VotingStrategy,
+    ArbitrationReason, EnsembleSession, EnsembleTask, ModelCatalog, ModelId, ModelResult,
+    SharedContext, TaskStatus, VoteOutcome, VoteRecord, VotingEngine, VotingStrategy,
 };
 
-#[cfg(feature = "heavy-state")]
-pub use state::{SharedStateStore, StateStore};
+#[cfg(feature = "full")]
+pub use state::{FileStateStore, SharedStateStore, StateBackend};
 
 #[cfg(feature = "heavy-state")]
+pub use state::StateStore;
+
+#[cfg(feature = "full")]
 pub use events::{
-    ArbitrationReason, ContextUpdater, EnsembleEvent, EventBus, EventHistory, SessionEndReason,
-    SharedEventBus,
+    ContextUpdater, EnsembleEvent, EventBus, EventHistory, SessionEndReason, SharedEventBus,
 };
 
 #[cfg(feature = "full")]
 pub use slurm::{
-    EndpointHealth, EndpointHealthDetails, EndpointInfo, HealthCheckConfig,
-    HealthCheckMetricsSnapshot, InferenceTier, JobInfo, JobState, SlurmConfig, SlurmError,
-    SlurmInferenceManager,
+    CommandExecutor, EndpointHealth, EndpointHealthDetails, EndpointInfo, FakeSlurm,
+    HealthCheckConfig, HealthCheckMetricsSnapshot, InferenceTier, JobInfo, JobState,
+    ProcessExecutor, SlurmConfig, SlurmError, SlurmInferenceManager,
 };
 
 pub use benchmark::{load_beefcake_lx2o_manifest, BenchmarkManifest};
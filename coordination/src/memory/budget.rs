@@ -3,7 +3,10 @@
 //! Determines when compaction should occur based on token counts,
 //! with configurable thresholds and a pluggable token estimation strategy.
 
+use super::tokenizer::{BpeEstimator, TokenizerError};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
 
 /// Trait for estimating token counts from text.
 pub trait TokenEstimator {
@@ -67,6 +70,96 @@ impl TokenEstimator for CharCountEstimator {
     }
 }
 
+/// Serializable estimator choice, e.g. from a model's config entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EstimatorConfig {
+    /// [`WordCountEstimator`].
+    WordCount {
+        /// Tokens per word multiplier.
+        factor: f64,
+    },
+    /// [`CharCountEstimator`].
+    CharCount {
+        /// Characters per token.
+        chars_per_token: f64,
+    },
+    /// [`BpeEstimator`] loaded from a local `tokenizer.json`.
+    Bpe {
+        /// Path to the model's `tokenizer.json`.
+        tokenizer_path: PathBuf,
+        /// LRU cache size in words.
+        #[serde(default = "default_cache_capacity")]
+        cache_capacity: usize,
+    },
+}
+
+fn default_cache_capacity() -> usize {
+    BpeEstimator::DEFAULT_CACHE_CAPACITY
+}
+
+impl EstimatorConfig {
+    /// Construct the configured estimator.
+    pub fn build(&self) -> Result<Box<dyn TokenEstimator + Send + Sync>, TokenizerError> {
+        Ok(match self {
+            Self::WordCount { factor } => Box::new(WordCountEstimator { factor: *factor }),
+            Self::CharCount { chars_per_token } => Box::new(CharCountEstimator {
+                chars_per_token: *chars_per_token,
+            }),
+            Self::Bpe {
+                tokenizer_path,
+                cache_capacity,
+            } => Box::new(BpeEstimator::from_file(tokenizer_path, *cache_capacity)?),
+        })
+    }
+}
+
+impl Default for EstimatorConfig {
+    fn default() -> Self {
+        Self::CharCount {
+            chars_per_token: 4.0,
+        }
+    }
+}
+
+/// Per-model estimator selection with a fallback.
+///
+/// ```yaml
+/// default: { kind: char_count, chars_per_token: 4.0 }
+/// models:
+///   qwen35: { kind: bpe, tokenizer_path: /models/qwen3.5/tokenizer.json }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelEstimators {
+    /// Estimator for models without an entry.
+    #[serde(default)]
+    pub default: EstimatorConfig,
+    /// Estimators keyed by model id.
+    #[serde(default)]
+    pub models: HashMap<String, EstimatorConfig>,
+}
+
+impl ModelEstimators {
+    /// Set the estimator for a model.
+    pub fn with_model(mut self, model: &str, config: EstimatorConfig) -> Self {
+        self.models.insert(model.to_string(), config);
+        self
+    }
+
+    /// Estimator config for `model`, falling back to the default.
+    pub fn for_model(&self, model: &str) -> &EstimatorConfig {
+        self.models.get(model).unwrap_or(&self.default)
+    }
+
+    /// Build the estimator for `model`.
+    pub fn build(
+        &self,
+        model: &str,
+    ) -> Result<Box<dyn TokenEstimator + Send + Sync>, TokenizerError> {
+        self.for_model(model).build()
+    }
+}
+
 /// Token budget configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenBudget {
@@ -262,6 +355,36 @@ mod tests {
         assert_eq!(est.name(), "char_count");
     }
 
+    #[test]
+    fn test_model_estimators_select_per_model() {
+        let yaml = r#"
+models:
+  qwen35:
+    kind: bpe
+    tokenizer_path: tests/fixtures/tiny_tokenizer.json
+  hydra_coder:
+    kind: word_count
+    factor: 2.0
+"#;
+        let mut config: ModelEstimators = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.default, EstimatorConfig::default());
+        assert_eq!(config.build("hydra_coder").unwrap().estimate("a b"), 4);
+        assert_eq!(config.build("unknown").unwrap().name(), "char_count");
+
+        let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/tiny_tokenizer.json");
+        config = config.with_model(
+            "qwen35",
+            EstimatorConfig::Bpe {
+                tokenizer_path: fixture,
+                cache_capacity: 16,
+            },
+        );
+        let bpe = config.build("qwen35").unwrap();
+        assert_eq!(bpe.name(), "bpe");
+        assert_eq!(bpe.estimate("hello world"), 2);
+    }
+
     #[test]
     fn test_budget_defaults() {
         let budget = TokenBudget::default();
@@ -5,13 +5,19 @@
 //!
 //! Also provides event-driven compaction support for integration
 //! with the orchestration event bus.
+//!
+//! Compaction is hierarchical: every sentinel records a
+//! [`SummaryProvenance`], a summary that folds earlier sentinels is one
+//! level above the highest of them, and [`MemoryCompactor::fold_summaries`]
+//! rolls a full level up into the next.
 
 use serde::{Deserialize, Serialize};
 
 use super::budget::{BudgetDecision, CompactionTrigger, TokenBudget};
 use super::errors::{CompactionError, CompactionErrorKind};
-use super::store::{MemoryEntry, SwarmMemory};
+use super::store::{MemoryEntry, MemoryEntryKind, SummaryProvenance, SwarmMemory};
 use super::summarizer::{build_summary_request, Summarizer};
+use std::collections::BTreeMap;
 
 /// Result of a compaction operation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +34,9 @@ pub struct CompactionResult {
     pub compacted_range: (u64, u64),
     /// Whether compaction was triggered by budget or event.
     pub trigger: CompactionTriggerKind,
+    /// Level of the inserted summary (1 = folded only original entries).
+    #[serde(default)]
+    pub summary_level: u32,
 }
 
 /// What triggered the compaction.
@@ -129,6 +138,8 @@ pub struct MemoryCompactor {
     session_context: String,
     /// Max tokens for generated summaries.
     max_summary_tokens: u32,
+    /// Active summaries of one level that trigger folding into the next.
+    fold_threshold: usize,
 }
 
 impl MemoryCompactor {
@@ -139,6 +150,7 @@ impl MemoryCompactor {
             policy: CompactionPolicy::default(),
             session_context: session_context.to_string(),
             max_summary_tokens: 2000,
+            fold_threshold: 4,
         }
     }
 
@@ -154,9 +166,16 @@ impl MemoryCompactor {
             policy,
             session_context: session_context.to_string(),
             max_summary_tokens,
+            fold_threshold: 4,
         }
     }
 
+    /// Fold summaries once this many of one level are active (0 disables).
+    pub fn with_fold_threshold(mut self, threshold: usize) -> Self {
+        self.fold_threshold = threshold;
+        self
+    }
+
     /// Check whether compaction should run based on current token count.
     pub fn check_budget(&self, current_tokens: u64) -> BudgetDecision {
         self.trigger.evaluate(current_tokens)
@@ -203,13 +222,76 @@ impl MemoryCompactor {
         }
 
         // Collect the entries to summarize
-        let to_summarize: Vec<&MemoryEntry> = active.into_iter().take(entries_to_compact).collect();
+        let to_summarize: Vec<MemoryEntry> = active
+            .into_iter()
+            .take(entries_to_compact)
+            .cloned()
+            .collect();
+        self.summarize_into(store, summarizer, &to_summarize, trigger_kind)
+    }
+
+    /// Lowest summary level with at least `fold_threshold` active sentinels.
+    pub fn fold_due(&self, store: &dyn SwarmMemory) -> Option<u32> {
+        if self.fold_threshold == 0 {
+            return None;
+        }
+        let mut counts: BTreeMap<u32, usize> = BTreeMap::new();
+        for entry in store.active_entries() {
+            if entry.kind == MemoryEntryKind::Summary {
+                *counts.entry(entry.level()).or_default() += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .find(|(_, n)| *n >= self.fold_threshold)
+            .map(|(level, _)| level)
+    }
 
-        let last_seq = to_summarize.last().map(|e| e.seq).unwrap_or(0);
+    /// Fold the active summaries of the level reported by [`fold_due`](Self::fold_due)
+    /// into one summary of the next level.
+    pub fn fold_summaries(
+        &self,
+        store: &mut dyn SwarmMemory,
+        summarizer: &dyn Summarizer,
+        trigger_kind: CompactionTriggerKind,
+    ) -> Result<CompactionResult, CompactionError> {
+        let level = self.fold_due(store).ok_or_else(|| {
+            CompactionError::new(
+                CompactionErrorKind::EmptyInput,
+                "no summary level has reached the fold threshold",
+            )
+        })?;
+        let to_fold: Vec<MemoryEntry> = store
+            .active_entries()
+            .into_iter()
+            .filter(|e| e.kind == MemoryEntryKind::Summary && e.level() == level)
+            .cloned()
+            .collect();
+        self.summarize_into(store, summarizer, &to_fold, trigger_kind)
+    }
 
+    /// Summarize `entries`, validate, and insert a sentinel with provenance.
+    fn summarize_into(
+        &self,
+        store: &mut dyn SwarmMemory,
+        summarizer: &dyn Summarizer,
+        to_summarize: &[MemoryEntry],
+        trigger_kind: CompactionTriggerKind,
+    ) -> Result<CompactionResult, CompactionError> {
+        let to_summarize: Vec<&MemoryEntry> = to_summarize.iter().collect();
         let first_seq = to_summarize.first().map(|e| e.seq).unwrap_or(0);
 
+        // Summaries are folded through provenance, not by seq range.
+        let last_seq = to_summarize
+            .iter()
+            .filter(|e| e.kind != MemoryEntryKind::Summary)
+            .map(|e| e.seq)
+            .max()
+            .unwrap_or(0);
+
         let tokens_in_range: u64 = to_summarize.iter().map(|e| e.estimated_tokens as u64).sum();
+        let provenance = SummaryProvenance::from_folded(&to_summarize);
+        let range_end = to_summarize.last().map(|e| e.seq).unwrap_or(0);
 
         // Build and execute summary request
         let request = build_summary_request(
@@ -220,25 +302,28 @@ impl MemoryCompactor {
 
         let response = summarizer.summarize(&request).map_err(|e| {
             CompactionError::new(CompactionErrorKind::SummarizationFailed, &e.to_string())
-                .with_range(first_seq, last_seq)
+                .with_range(first_seq, range_end)
         })?;
 
         // Validate the response
         response
             .validate(&request)
-            .map_err(|e| e.with_range(first_seq, last_seq))?;
+            .map_err(|e| e.with_range(first_seq, range_end))?;
 
         // Insert summary sentinel and compact old entries
-        let summary_entry = MemoryEntry::summary(&response.summary, response.summary_tokens);
+        let summary_level = provenance.level;
+        let summary_entry = MemoryEntry::summary(&response.summary, response.summary_tokens)
+            .with_provenance(provenance);
         store.insert_summary(summary_entry, last_seq);
 
         Ok(CompactionResult {
-            entries_compacted: entries_to_compact,
+            entries_compacted: to_summarize.len(),
             tokens_freed: tokens_in_range,
             summary_tokens_added: response.summary_tokens,
             compression_ratio: response.compression_ratio,
-            compacted_range: (first_seq, last_seq),
+            compacted_range: (first_seq, range_end),
             trigger: trigger_kind,
+            summary_level,
         })
     }
 
@@ -251,7 +336,7 @@ impl MemoryCompactor {
 #[cfg(test)]
 mod tests {
     use super::super::budget::TokenBudget;
-    use super::super::store::{MemoryEntryKind, SwarmMemoryStore};
+    use super::super::store::{expand_summary, MemoryEntryKind, SwarmMemoryStore};
     use super::super::summarizer::MockSummarizer;
     use super::*;
 
@@ -615,6 +700,99 @@ mod tests {
         assert_eq!(summary_count, 2);
     }
 
+    #[test]
+    fn test_recompaction_records_provenance() {
+        let budget = TokenBudget {
+            max_tokens: 200,
+            target_tokens: 50,
+            min_retained_entries: 1,
+            system_reserve: 0,
+        };
+        let compactor =
+            MemoryCompactor::with_config(budget, CompactionPolicy::default(), "test", 500);
+        let mut store = make_store_with_entries(10, 20);
+        let summarizer = MockSummarizer::new();
+
+        let first = compactor
+            .compact(&mut store, &summarizer, CompactionTriggerKind::Manual)
+            .unwrap();
+        assert_eq!(first.summary_level, 1);
+
+        for i in 0..10 {
+            store.append(MemoryEntry::new(
+                MemoryEntryKind::ToolResult,
+                &format!("Later {}", i),
+                "verifier",
+                20,
+            ));
+        }
+        let second = compactor
+            .compact(&mut store, &summarizer, CompactionTriggerKind::Manual)
+            .unwrap();
+
+        // The first summary is folded in rather than left active.
+        let summaries: Vec<_> = store
+            .active_entries()
+            .into_iter()
+            .filter(|e| e.kind == MemoryEntryKind::Summary)
+            .collect();
+        assert_eq!(summaries.len(), 1);
+        let provenance = summaries[0].provenance.as_ref().unwrap();
+        assert_eq!(provenance.level, second.summary_level);
+        assert_eq!(provenance.level, 2);
+        assert_eq!(provenance.sources, vec!["coder", "verifier"]);
+        assert_eq!(provenance.covered[0].0, 1);
+
+        let expansion = expand_summary(&store, summaries[0].seq).unwrap();
+        assert!(expansion.is_complete());
+        assert_eq!(expansion.originals.len() as u64, provenance.covered_count());
+    }
+
+    #[test]
+    fn test_fold_summaries_builds_next_level() {
+        let compactor = MemoryCompactor::new("test").with_fold_threshold(3);
+        let mut store = SwarmMemoryStore::new();
+        let summarizer = MockSummarizer::new();
+        for i in 0..3 {
+            let seq = store.append(MemoryEntry::new(
+                MemoryEntryKind::AgentTurn,
+                &format!("turn {}", i),
+                "coder",
+                30,
+            ));
+            let provenance = SummaryProvenance::from_folded(&[store.get(seq).unwrap()]);
+            store.insert_summary(
+                MemoryEntry::summary(&format!("summary {}", i), 10).with_provenance(provenance),
+                seq,
+            );
+        }
+        assert_eq!(compactor.fold_due(&store), Some(1));
+
+        let result = compactor
+            .fold_summaries(&mut store, &summarizer, CompactionTriggerKind::Event)
+            .unwrap();
+        assert_eq!(result.summary_level, 2);
+        assert_eq!(result.entries_compacted, 3);
+        assert_eq!(compactor.fold_due(&store), None);
+
+        let active = store.active_entries();
+        assert_eq!(active.len(), 1);
+        let provenance = active[0].provenance.as_ref().unwrap();
+        assert_eq!(provenance.covered, vec![(1, 1), (3, 3), (5, 5)]);
+        assert_eq!(provenance.folded, vec![2, 4, 6]);
+
+        let err = compactor
+            .fold_summaries(&mut store, &summarizer, CompactionTriggerKind::Event)
+            .unwrap_err();
+        assert_eq!(err.kind, CompactionErrorKind::EmptyInput);
+        assert_eq!(
+            MemoryCompactor::new("test")
+                .with_fold_threshold(0)
+                .fold_due(&store),
+            None
+        );
+    }
+
     // --- Serde tests ---
 
     #[test]
@@ -626,6 +804,7 @@ mod tests {
             compression_ratio: 10.0,
             compacted_range: (1, 5),
             trigger: CompactionTriggerKind::BudgetThreshold,
+            summary_level: 1,
         };
         let json = serde_json::to_string(&result).unwrap();
         let parsed: CompactionResult = serde_json::from_str(&json).unwrap();
@@ -0,0 +1,555 @@
+//! Extractive summarizer — offline, deterministic baseline.
+//!
+//! Picks the most salient lines and sentences from the compacted entries
+//! instead of generating new text, so it needs no model and always
+//! succeeds for a sane budget. Each required section is mapped to one or
+//! more [`SalienceKind`]s (by keywords in the section name) and filled
+//! round-robin with that kind's best-scoring units until the token budget
+//! is spent.
+//!
+//! Used as the free default and as the fallback when an LLM summarizer
+//! fails or returns an invalid summary.
+
+use regex::Regex;
+use std::collections::HashSet;
+
+use super::budget::{CharCountEstimator, TokenEstimator};
+use super::errors::SummarizationError;
+use super::summarizer::{Summarizer, SummaryRequest, SummaryResponse};
+
+/// Longest unit kept verbatim; longer ones are cut at a char boundary.
+const MAX_UNIT_CHARS: usize = 240;
+
+/// What a line or sentence is evidence of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SalienceKind {
+    /// A choice that was made ("decided", "switched to", "approved").
+    Decision,
+    /// Compiler errors, panics, failing tests.
+    Error,
+    /// Mentions of source files, optionally with a line.
+    FileReference,
+    /// Questions, TODOs, blockers.
+    OpenQuestion,
+    /// Tool and verifier output (test results, gate status).
+    ToolResult,
+}
+
+impl SalienceKind {
+    /// Kinds that feed a section with the given heading.
+    pub fn for_section(section: &str) -> Vec<Self> {
+        let s = section.to_lowercase();
+        let mut kinds = Vec::new();
+        if s.contains("decision") {
+            kinds.push(Self::Decision);
+        }
+        if s.contains("error") || s.contains("failure") || s.contains("issue") {
+            kinds.push(Self::Error);
+        }
+        if s.contains("file") {
+            kinds.push(Self::FileReference);
+        }
+        if s.contains("question") || s.contains("open") || s.contains("todo") {
+            kinds.push(Self::OpenQuestion);
+        }
+        if s.contains("state") || s.contains("status") || s.contains("progress") {
+            kinds.push(Self::ToolResult);
+        }
+        kinds
+    }
+}
+
+impl std::fmt::Display for SalienceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Decision => write!(f, "decision"),
+            Self::Error => write!(f, "error"),
+            Self::FileReference => write!(f, "file_reference"),
+            Self::OpenQuestion => write!(f, "open_question"),
+            Self::ToolResult => write!(f, "tool_result"),
+        }
+    }
+}
+
+/// A scored line or sentence from one input entry.
+#[derive(Debug, Clone)]
+struct Unit {
+    text: String,
+    source: String,
+    entry_index: usize,
+    kinds: Vec<(SalienceKind, f64)>,
+}
+
+impl Unit {
+    fn score(&self, kinds: &[SalienceKind]) -> Option<f64> {
+        self.kinds
+            .iter()
+            .filter(|(k, _)| kinds.is_empty() || kinds.contains(k))
+            .map(|(_, s)| *s)
+            .reduce(f64::max)
+    }
+}
+
+/// Model-free [`Summarizer`] that extracts salient lines per section.
+pub struct ExtractiveSummarizer {
+    estimator: Box<dyn TokenEstimator + Send + Sync>,
+    error: Regex,
+    error_code: Regex,
+    file: Regex,
+    decision: Regex,
+    question: Regex,
+    tool_result: Regex,
+}
+
+impl ExtractiveSummarizer {
+    /// Model name reported in errors.
+    pub const MODEL_NAME: &'static str = "extractive";
+
+    /// Create a summarizer that measures output with [`CharCountEstimator`].
+    pub fn new() -> Self {
+        Self {
+            estimator: Box::new(CharCountEstimator::default()),
+            error: Regex::new(
+                r"(?i)\berror(\[E\d{4}\])?:|\bpanicked at\b|\bFAILED\b|\bfailed\b|\btraceback\b|\bexception\b|\bwarning:",
+            )
+            .expect("static regex"),
+            error_code: Regex::new(r"\b[EW]\d{4}\b").expect("static regex"),
+            file: Regex::new(
+                r"(?:^|[\s`'(\[])((?:[\w.-]+/)*[\w.-]+\.(?:rs|py|ts|tsx|js|go|toml|json|ya?ml|md|sh))(?::\d+)?\b",
+            )
+            .expect("static regex"),
+            decision: Regex::new(
+                r"(?i)\b(decided|decision|chose|choose|going with|switched to|will use|agreed|approved|approve|settled on|plan is|implemented|fixed|refactored|replaced)\b",
+            )
+            .expect("static regex"),
+            question: Regex::new(
+                r"(?i)\?\s*$|\b(TODO|FIXME|open question|unresolved|unclear|blocked|still failing|not yet|need to|needs to)\b",
+            )
+            .expect("static regex"),
+            tool_result: Regex::new(
+                r"(?i)\btest result\b|\b\d+ (passed|failed)\b|\bexit (code|status)\b|\bcargo (build|test|clippy|check)\b|\bfinished\b",
+            )
+            .expect("static regex"),
+        }
+    }
+
+    /// Measure output tokens with `estimator` (should match the budget's).
+    pub fn with_estimator(mut self, estimator: Box<dyn TokenEstimator + Send + Sync>) -> Self {
+        self.estimator = estimator;
+        self
+    }
+
+    /// Salience of `text` per kind; empty when nothing stands out.
+    pub fn classify(&self, text: &str, source: &str) -> Vec<(SalienceKind, f64)> {
+        let mut kinds = Vec::new();
+        if self.error.is_match(text) {
+            let bonus = if self.error_code.is_match(text) {
+                1.0
+            } else {
+                0.0
+            };
+            kinds.push((SalienceKind::Error, 3.0 + bonus));
+        }
+        let files = self.file.find_iter(text).count();
+        if files > 0 {
+            let line_bonus = if text.contains(".rs:") || text.contains("--> ") {
+                0.5
+            } else {
+                0.0
+            };
+            kinds.push((
+                SalienceKind::FileReference,
+                1.5 + (files.min(3) as f64) * 0.5 + line_bonus,
+            ));
+        }
+        if self.decision.is_match(text) {
+            kinds.push((SalienceKind::Decision, 2.0));
+        }
+        if self.question.is_match(text) {
+            kinds.push((SalienceKind::OpenQuestion, 2.0));
+        }
+        if self.tool_result.is_match(text) {
+            let tool_source = ["tool", "verifier", "cargo", "gate"]
+                .iter()
+                .any(|s| source.to_lowercase().contains(s));
+            kinds.push((
+                SalienceKind::ToolResult,
+                if tool_source { 1.5 } else { 1.0 },
+            ));
+        }
+        kinds
+    }
+
+    /// Split entries into scored units, newest entries weighted higher.
+    fn units(&self, request: &SummaryRequest) -> Vec<Unit> {
+        let n = request.entries.len().max(1) as f64;
+        let mut seen = HashSet::new();
+        let mut units = Vec::new();
+        for (i, entry) in request.entries.iter().enumerate() {
+            let recency = (i + 1) as f64 / n * 0.5;
+            for text in split_units(&entry.content) {
+                let mut kinds = self.classify(&text, &entry.source);
+                if kinds.is_empty() || !seen.insert(text.to_lowercase()) {
+                    continue;
+                }
+                for (_, score) in &mut kinds {
+                    *score += recency;
+                }
+                units.push(Unit {
+                    text,
+                    source: entry.source.clone(),
+                    entry_index: i,
+                    kinds,
+                });
+            }
+        }
+        units
+    }
+
+    fn tokens(&self, text: &str) -> u32 {
+        self.estimator.estimate(text)
+    }
+
+    fn error(&self, request: &SummaryRequest, reason: &str) -> SummarizationError {
+        SummarizationError::new(
+            Self::MODEL_NAME,
+            reason,
+            request.entries.len(),
+            request.entries.iter().map(|e| e.tokens as u64).sum(),
+        )
+        .non_retryable()
+    }
+}
+
+impl Default for ExtractiveSummarizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lines, with long prose lines further split into sentences.
+fn split_units(content: &str) -> Vec<String> {
+    let mut units = Vec::new();
+    for line in content.lines() {
+        let line = line.trim().trim_start_matches(['-', '*', '>']).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.len() <= MAX_UNIT_CHARS {
+            units.push(line.to_string());
+            continue;
+        }
+        let mut start = 0;
+        for (i, c) in line.char_indices() {
+            let next = line[i + c.len_utf8()..].chars().next();
+            if matches!(c, '.' | '?' | '!') && next.is_none_or(char::is_whitespace) {
+                let sentence = line[start..=i].trim();
+                if !sentence.is_empty() {
+                    units.push(clip(sentence));
+                }
+                start = i + 1;
+            }
+        }
+        let rest = line[start..].trim();
+        if !rest.is_empty() {
+            units.push(clip(rest));
+        }
+    }
+    units
+}
+
+fn clip(text: &str) -> String {
+    if text.len() <= MAX_UNIT_CHARS {
+        return text.to_string();
+    }
+    let mut end = MAX_UNIT_CHARS;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}…", &text[..end])
+}
+
+struct Section<'a> {
+    name: &'a str,
+    candidates: Vec<usize>,
+    next: usize,
+    picked: Vec<usize>,
+}
+
+fn render(header: &str, sections: &[Section<'_>], units: &[Unit]) -> String {
+    let mut out = header.to_string();
+    for section in sections {
+        out.push_str(&format!("\n\n### {}\n", section.name));
+        if section.picked.is_empty() {
+            out.push_str("- none recorded");
+            continue;
+        }
+        // Chronological order reads better than score order.
+        let mut picked = section.picked.clone();
+        picked.sort_by_key(|i| (units[*i].entry_index, *i));
+        let lines: Vec<String> = picked
+            .iter()
+            .map(|i| format!("- [{}] {}", units[*i].source, units[*i].text))
+            .collect();
+        out.push_str(&lines.join("\n"));
+    }
+    out
+}
+
+impl Summarizer for ExtractiveSummarizer {
+    fn summarize(&self, request: &SummaryRequest) -> Result<SummaryResponse, SummarizationError> {
+        if request.entries.is_empty() {
+            return Err(self.error(request, "no entries to summarize"));
+        }
+
+        let default_sections = [
+            "Key Decisions",
+            "Errors",
+            "File References",
+            "Open Questions",
+        ];
+        let names: Vec<&str> = if request.required_sections.is_empty() {
+            default_sections.to_vec()
+        } else {
+            request
+                .required_sections
+                .iter()
+                .map(String::as_str)
+                .collect()
+        };
+
+        let units = self.units(request);
+        let mut sections: Vec<Section<'_>> = names
+            .iter()
+            .map(|name| {
+                let kinds = SalienceKind::for_section(name);
+                let mut candidates: Vec<(usize, f64)> = units
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, u)| u.score(&kinds).map(|s| (i, s)))
+                    .collect();
+                candidates.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+                Section {
+                    name,
+                    candidates: candidates.into_iter().map(|(i, _)| i).collect(),
+                    next: 0,
+                    picked: Vec::new(),
+                }
+            })
+            .collect();
+
+        let header = format!(
+            "## Session Summary\n\nContext: {}",
+            clip(request.session_context.trim())
+        );
+        let mut summary = render(&header, &sections, &units);
+        if self.tokens(&summary) > request.max_output_tokens {
+            return Err(self.error(
+                request,
+                &format!(
+                    "budget of {} tokens cannot fit the required sections",
+                    request.max_output_tokens
+                ),
+            ));
+        }
+
+        // Round-robin so one noisy section cannot starve the others.
+        let mut used: HashSet<usize> = HashSet::new();
+        loop {
+            let mut progressed = false;
+            for s in 0..sections.len() {
+                while let Some(&unit) = sections[s].candidates.get(sections[s].next) {
+                    sections[s].next += 1;
+                    // Each unit appears in at most one section.
+                    if used.contains(&unit) {
+                        continue;
+                    }
+                    sections[s].picked.push(unit);
+                    let candidate = render(&header, &sections, &units);
+                    if self.tokens(&candidate) <= request.max_output_tokens {
+                        summary = candidate;
+                        used.insert(unit);
+                        progressed = true;
+                    } else {
+                        sections[s].picked.pop();
+                    }
+                    break;
+                }
+            }
+            if !progressed && sections.iter().all(|s| s.next >= s.candidates.len()) {
+                break;
+            }
+        }
+
+        let summary_tokens = self.tokens(&summary);
+        let input_tokens: u64 = request.entries.iter().map(|e| e.tokens as u64).sum();
+        Ok(SummaryResponse {
+            summary,
+            summary_tokens,
+            sections_included: names.iter().map(|s| s.to_string()).collect(),
+            entries_summarized: request.entries.len(),
+            input_tokens_compressed: input_tokens,
+            compression_ratio: if summary_tokens > 0 {
+                input_tokens as f64 / summary_tokens as f64
+            } else {
+                0.0
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::budget::WordCountEstimator;
+    use super::super::store::{MemoryEntry, MemoryEntryKind};
+    use super::super::summarizer::build_summary_request;
+    use super::*;
+
+    fn entries() -> Vec<MemoryEntry> {
+        vec![
+            MemoryEntry::new(
+                MemoryEntryKind::AgentTurn,
+                "Looked at the parser.\nDecided to switch the lexer to a table-driven design.",
+                "planner",
+                60,
+            ),
+            MemoryEntry::new(
+                MemoryEntryKind::ToolResult,
+                "error[E0502]: cannot borrow `tokens` as mutable\n  --> src/lexer.rs:42:9\nsome noise line",
+                "cargo",
+                80,
+            ),
+            MemoryEntry::new(
+                MemoryEntryKind::AgentTurn,
+                "Fixed the borrow in src/lexer.rs by cloning the span. Should we also cache spans?",
+                "coder",
+                50,
+            ),
+            MemoryEntry::new(
+                MemoryEntryKind::ToolResult,
+                "test result: ok. 12 passed; 0 failed",
+                "verifier",
+                20,
+            ),
+        ]
+    }
+
+    fn request(max: u32) -> SummaryRequest {
+        let entries = entries();
+        build_summary_request(&entries.iter().collect::<Vec<_>>(), max, "lexer rewrite")
+    }
+
+    #[test]
+    fn test_summary_passes_validation() {
+        let req = request(400);
+        let response = ExtractiveSummarizer::new().summarize(&req).unwrap();
+        assert!(response.validate(&req).is_ok());
+        assert_eq!(response.entries_summarized, 4);
+        assert!(response.compression_ratio > 0.0);
+        let s = &response.summary;
+        assert!(s.contains("### Key Decisions"));
+        assert!(s.contains("Decided to switch the lexer"));
+        assert!(s.contains("error[E0502]"));
+        assert!(s.contains("Should we also cache spans?"));
+        assert!(s.contains("test result: ok"));
+        assert!(!s.contains("some noise line"));
+    }
+
+    #[test]
+    fn test_default_sections_when_none_required() {
+        let mut req = request(400);
+        req.required_sections.clear();
+        let response = ExtractiveSummarizer::new().summarize(&req).unwrap();
+        assert_eq!(
+            response.sections_included,
+            vec![
+                "Key Decisions",
+                "Errors",
+                "File References",
+                "Open Questions"
+            ]
+        );
+        assert!(response.summary.contains("src/lexer.rs:42"));
+    }
+
+    #[test]
+    fn test_respects_token_budget() {
+        let full = ExtractiveSummarizer::new()
+            .summarize(&request(400))
+            .unwrap();
+        let req = request(full.summary_tokens - 15);
+        let response = ExtractiveSummarizer::new().summarize(&req).unwrap();
+        assert!(response.summary_tokens <= req.max_output_tokens);
+        assert!(response.summary.len() < full.summary.len());
+        assert!(response.validate(&req).is_ok());
+    }
+
+    #[test]
+    fn test_budget_too_small_for_headings() {
+        let err = ExtractiveSummarizer::new()
+            .summarize(&request(5))
+            .unwrap_err();
+        assert_eq!(err.model, ExtractiveSummarizer::MODEL_NAME);
+        assert!(!err.retryable);
+    }
+
+    #[test]
+    fn test_empty_section_placeholder() {
+        let entries = [MemoryEntry::new(
+            MemoryEntryKind::AgentTurn,
+            "We agreed to approve the change.",
+            "reviewer",
+            10,
+        )];
+        let req = build_summary_request(&entries.iter().collect::<Vec<_>>(), 200, "review");
+        let response = ExtractiveSummarizer::new().summarize(&req).unwrap();
+        assert!(response
+            .summary
+            .contains("### Open Issues\n- none recorded"));
+        assert!(response.validate(&req).is_ok());
+    }
+
+    #[test]
+    fn test_custom_estimator_and_determinism() {
+        let req = request(300);
+        let a = ExtractiveSummarizer::new()
+            .with_estimator(Box::new(WordCountEstimator::default()))
+            .summarize(&req)
+            .unwrap();
+        let b = ExtractiveSummarizer::new()
+            .with_estimator(Box::new(WordCountEstimator::default()))
+            .summarize(&req)
+            .unwrap();
+        assert_eq!(a.summary, b.summary);
+        assert_eq!(
+            a.summary_tokens,
+            WordCountEstimator::default().estimate(&a.summary)
+        );
+    }
+
+    #[test]
+    fn test_classify_and_section_mapping() {
+        let s = ExtractiveSummarizer::new();
+        let kinds: Vec<SalienceKind> = s
+            .classify("thread 'main' panicked at src/main.rs:3", "coder")
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        assert!(kinds.contains(&SalienceKind::Error));
+        assert!(kinds.contains(&SalienceKind::FileReference));
+        assert!(s.classify("hello there", "coder").is_empty());
+        assert_eq!(
+            SalienceKind::for_section("Open Issues"),
+            vec![SalienceKind::Error, SalienceKind::OpenQuestion]
+        );
+        assert_eq!(SalienceKind::Decision.to_string(), "decision");
+    }
+
+    #[test]
+    fn test_split_long_lines_into_sentences() {
+        let long = format!("{} First point. Second point? Third", "x".repeat(230));
+        let units = split_units(&long);
+        assert_eq!(units.len(), 3);
+        assert!(units[1] == "Second point?");
+    }
+}
@@ -0,0 +1,445 @@
+//! LLM-backed summarizer over an OpenAI-compatible chat endpoint.
+//!
+//! Renders a [`SummaryRequest`] into a strict sectioned prompt, POSTs it to
+//! `<endpoint>/chat/completions` (llama-server, TensorZero's OpenAI route,
+//! or any compatible gateway) and parses the Markdown reply back into a
+//! [`SummaryResponse`].
+//!
+//! When the reply exceeds `max_output_tokens` the request is retried once
+//! with a tighter budget in the prompt; every other failure is returned as
+//! a [`SummarizationError`] whose `retryable` flag reflects the cause.
+//!
+//! Uses the blocking HTTP client: call from a blocking context (e.g.
+//! `tokio::task::spawn_blocking`), not directly on an async executor.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::budget::{CharCountEstimator, TokenEstimator};
+use super::errors::{CompactionErrorKind, SummarizationError};
+use super::summarizer::{Summarizer, SummaryRequest, SummaryResponse};
+
+/// Endpoint and sampling settings for [`LlmSummarizer`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmSummarizerConfig {
+    /// Base URL including the API prefix, e.g. `http://vasp-02:8080/v1`.
+    pub endpoint: String,
+    /// Model name sent in the request body.
+    pub model: String,
+    /// Bearer token, if the endpoint requires one.
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Request timeout in milliseconds.
+    pub timeout_ms: u64,
+    /// Sampling temperature.
+    pub temperature: f32,
+    /// Fraction of the previous budget requested on the oversize retry.
+    pub retry_shrink: f64,
+}
+
+impl LlmSummarizerConfig {
+    /// Config for `model` at `endpoint` with conservative defaults.
+    pub fn new(endpoint: &str, model: &str) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            api_key: None,
+            timeout_ms: 120_000,
+            temperature: 0.2,
+            retry_shrink: 0.6,
+        }
+    }
+
+    /// Set the bearer token.
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    /// Set the request timeout.
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    /// Set the sampling temperature.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    /// Set the budget fraction used for the oversize retry.
+    pub fn with_retry_shrink(mut self, retry_shrink: f64) -> Self {
+        self.retry_shrink = retry_shrink;
+        self
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+/// Prompt pair sent to the model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SummaryPrompt {
+    /// System message with the output contract.
+    pub system: String,
+    /// User message with the transcript.
+    pub user: String,
+}
+
+/// Render the strict summarization prompt for `request` at `budget` tokens.
+pub fn render_prompt(request: &SummaryRequest, budget: u32) -> SummaryPrompt {
+    let headings: Vec<String> = request
+        .required_sections
+        .iter()
+        .map(|s| format!("### {s}"))
+        .collect();
+    let system = format!(
+        "You compress a coding-agent session transcript into a summary that replaces it.\n\
+         Rules:\n\
+         - Output Markdown only, no preamble.\n\
+         - Use exactly these headings, in this order, each followed by bullet points:\n{}\n\
+         - Write \"- none\" under a heading with nothing to report.\n\
+         - Keep file paths, error codes and test names verbatim.\n\
+         - Do not invent facts that are not in the transcript.\n\
+         - Stay under {budget} tokens.",
+        headings.join("\n")
+    );
+    let mut user = format!("Session: {}\n\nTranscript:\n", request.session_context);
+    for (i, entry) in request.entries.iter().enumerate() {
+        user.push_str(&format!(
+            "\n[{}] {}:\n{}\n",
+            i + 1,
+            entry.source,
+            entry.content
+        ));
+    }
+    SummaryPrompt { system, user }
+}
+
+/// Drop `<think>…</think>` reasoning blocks some models emit.
+fn strip_reasoning(content: &str) -> String {
+    let mut out = String::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("<think>") {
+        out.push_str(&rest[..start]);
+        match rest[start..].find("</think>") {
+            Some(end) => rest = &rest[start + end + "</think>".len()..],
+            None => {
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out.trim().to_string()
+}
+
+/// Required sections that appear as headings (`#`..`####` or `**bold**` lines).
+pub fn parse_sections(summary: &str, required: &[String]) -> Vec<String> {
+    let headings: Vec<String> = summary
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.starts_with('#') {
+                Some(line.trim_start_matches('#').trim())
+            } else if line.starts_with("**") && line.ends_with("**") && line.len() > 4 {
+                Some(line.trim_matches('*').trim())
+            } else {
+                None
+            }
+        })
+        .map(|h| h.trim_end_matches(':').to_lowercase())
+        .collect();
+    required
+        .iter()
+        .filter(|s| headings.contains(&s.to_lowercase()))
+        .cloned()
+        .collect()
+}
+
+/// [`Summarizer`] that calls an OpenAI-compatible chat completions endpoint.
+pub struct LlmSummarizer {
+    config: LlmSummarizerConfig,
+    estimator: Box<dyn TokenEstimator + Send + Sync>,
+}
+
+impl LlmSummarizer {
+    /// Create a summarizer that measures replies with [`CharCountEstimator`].
+    pub fn new(config: LlmSummarizerConfig) -> Self {
+        Self {
+            config,
+            estimator: Box::new(CharCountEstimator::default()),
+        }
+    }
+
+    /// Measure replies with `estimator` (should match the model's tokenizer).
+    pub fn with_estimator(mut self, estimator: Box<dyn TokenEstimator + Send + Sync>) -> Self {
+        self.estimator = estimator;
+        self
+    }
+
+    /// Endpoint configuration.
+    pub fn config(&self) -> &LlmSummarizerConfig {
+        &self.config
+    }
+
+    fn error(&self, request: &SummaryRequest, reason: &str, retryable: bool) -> SummarizationError {
+        let err = SummarizationError::new(
+            &self.config.model,
+            reason,
+            request.entries.len(),
+            request.entries.iter().map(|e| e.tokens as u64).sum(),
+        );
+        if retryable {
+            err
+        } else {
+            err.non_retryable()
+        }
+    }
+
+    /// One chat completion call; returns the reply text.
+    fn complete(
+        &self,
+        request: &SummaryRequest,
+        budget: u32,
+    ) -> Result<String, SummarizationError> {
+        let prompt = render_prompt(request, budget);
+        let body = serde_json::json!({
+            "model": self.config.model,
+            "messages": [
+                {"role": "system", "content": prompt.system},
+                {"role": "user", "content": prompt.user},
+            ],
+            "max_tokens": budget,
+            "temperature": self.config.temperature,
+            "stream": false,
+        });
+
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_millis(self.config.timeout_ms))
+            .build()
+            .map_err(|e| {
+                self.error(request, &format!("failed to build HTTP client: {e}"), false)
+            })?;
+        let mut http = client
+            .post(format!("{}/chat/completions", self.config.endpoint))
+            .json(&body);
+        if let Some(key) = &self.config.api_key {
+            http = http.bearer_auth(key);
+        }
+
+        let response = http.send().map_err(|e| {
+            let reason = if e.is_timeout() {
+                format!("request timed out after {}ms", self.config.timeout_ms)
+            } else {
+                format!("HTTP request failed: {e}")
+            };
+            self.error(request, &reason, true)
+        })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            // Client errors other than rate limiting will not fix themselves.
+            let retryable = status.is_server_error() || status.as_u16() == 429;
+            let detail = response.text().unwrap_or_default();
+            return Err(self.error(
+                request,
+                &format!("endpoint returned HTTP {status}: {}", detail.trim()),
+                retryable,
+            ));
+        }
+
+        let parsed: ChatResponse = response
+            .json()
+            .map_err(|e| self.error(request, &format!("malformed chat response: {e}"), true))?;
+        let content = parsed
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|c| c.message.content)
+            .unwrap_or_default();
+        Ok(strip_reasoning(&content))
+    }
+
+    fn build_response(&self, request: &SummaryRequest, summary: String) -> SummaryResponse {
+        let summary_tokens = self.estimator.estimate(&summary);
+        let input_tokens: u64 = request.entries.iter().map(|e| e.tokens as u64).sum();
+        SummaryResponse {
+            sections_included: parse_sections(&summary, &request.required_sections),
+            summary,
+            summary_tokens,
+            entries_summarized: request.entries.len(),
+            input_tokens_compressed: input_tokens,
+            compression_ratio: if summary_tokens > 0 {
+                input_tokens as f64 / summary_tokens as f64
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+impl Summarizer for LlmSummarizer {
+    fn summarize(&self, request: &SummaryRequest) -> Result<SummaryResponse, SummarizationError> {
+        if request.entries.is_empty() {
+            return Err(self.error(request, "no entries to summarize", false));
+        }
+
+        let mut budget = request.max_output_tokens;
+        let mut retried = false;
+        loop {
+            let summary = self.complete(request, budget)?;
+            let response = self.build_response(request, summary);
+            match response.validate(request) {
+                Ok(()) => return Ok(response),
+                Err(e) if e.kind == CompactionErrorKind::SummaryTooLarge && !retried => {
+                    retried = true;
+                    budget = ((budget as f64 * self.config.retry_shrink) as u32).max(1);
+                }
+                Err(e) => {
+                    let reason = if retried {
+                        format!("{} (after retry at {budget} tokens)", e.detail)
+                    } else {
+                        e.detail
+                    };
+                    return Err(self.error(
+                        request,
+                        &reason,
+                        e.kind.is_retryable() || e.kind == CompactionErrorKind::IntegrityViolation,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::store::{MemoryEntry, MemoryEntryKind};
+    use super::super::summarizer::build_summary_request;
+    use super::*;
+    use crate::test_utils::mock_server;
+    use std::net::TcpListener;
+
+    fn chat(content: &str) -> String {
+        serde_json::json!({"choices": [{"message": {"role": "assistant", "content": content}}]})
+            .to_string()
+    }
+
+    fn request(max: u32) -> SummaryRequest {
+        let entries = [
+            MemoryEntry::new(MemoryEntryKind::AgentTurn, "Switched to tokio", "coder", 40),
+            MemoryEntry::new(MemoryEntryKind::ToolResult, "error[E0277]", "cargo", 20),
+        ];
+        build_summary_request(&entries.iter().collect::<Vec<_>>(), max, "runtime port")
+    }
+
+    const GOOD: &str = "<think>plan</think>\n### Key Decisions\n- Switched to tokio\n### Current State\n- E0277 open\n### Open Issues\n- none";
+
+    #[test]
+    fn test_summarize_parses_sections() {
+        let (url, seen) = mock_server("/v1", vec![(200, chat(GOOD))]);
+        let summarizer =
+            LlmSummarizer::new(LlmSummarizerConfig::new(&url, "qwen").with_api_key("k"));
+        let req = request(200);
+        let response = summarizer.summarize(&req).unwrap();
+        assert!(response.validate(&req).is_ok());
+        assert!(!response.summary.contains("<think>"));
+        assert_eq!(response.sections_included.len(), 3);
+
+        let body = &seen.lock().unwrap()[0];
+        assert_eq!(body["model"], "qwen");
+        assert_eq!(body["max_tokens"], 200);
+        let system = body["messages"][0]["content"].as_str().unwrap();
+        assert!(system.contains("### Open Issues"));
+        let user = body["messages"][1]["content"].as_str().unwrap();
+        assert!(user.contains("[2] cargo:\nerror[E0277]"));
+    }
+
+    #[test]
+    fn test_oversize_retries_with_tighter_budget() {
+        let long = format!("{GOOD}\n{}", "- filler line\n".repeat(40));
+        let (url, seen) = mock_server("/v1", vec![(200, chat(&long)), (200, chat(GOOD))]);
+        let summarizer = LlmSummarizer::new(LlmSummarizerConfig::new(&url, "qwen"));
+        let response = summarizer.summarize(&request(100)).unwrap();
+        assert_eq!(response.summary, GOOD.replace("<think>plan</think>\n", ""));
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[1]["max_tokens"], 60);
+    }
+
+    #[test]
+    fn test_oversize_twice_is_not_retryable() {
+        let long = format!("{GOOD}\n{}", "- filler line\n".repeat(40));
+        let (url, _) = mock_server("/v1", vec![(200, chat(&long)), (200, chat(&long))]);
+        let summarizer = LlmSummarizer::new(LlmSummarizerConfig::new(&url, "qwen"));
+        let err = summarizer.summarize(&request(100)).unwrap_err();
+        assert!(!err.retryable);
+        assert!(err.reason.contains("after retry"));
+    }
+
+    #[test]
+    fn test_missing_section_is_retryable() {
+        let (url, _) = mock_server("/v1", vec![(200, chat("### Key Decisions\n- x"))]);
+        let summarizer = LlmSummarizer::new(LlmSummarizerConfig::new(&url, "qwen"));
+        let err = summarizer.summarize(&request(200)).unwrap_err();
+        assert!(err.retryable);
+        assert!(err.reason.contains("required section missing"));
+    }
+
+    #[test]
+    fn test_http_status_retryability() {
+        let (url, _) = mock_server(
+            "/v1",
+            vec![(503, "{}".to_string()), (400, "{}".to_string())],
+        );
+        let summarizer = LlmSummarizer::new(LlmSummarizerConfig::new(&url, "qwen"));
+        let err = summarizer.summarize(&request(200)).unwrap_err();
+        assert!(err.retryable);
+        assert!(err.reason.contains("503"));
+        let err = summarizer.summarize(&request(200)).unwrap_err();
+        assert!(!err.retryable);
+    }
+
+    #[test]
+    fn test_unreachable_endpoint_is_retryable() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1", listener.local_addr().unwrap());
+        drop(listener);
+        let summarizer =
+            LlmSummarizer::new(LlmSummarizerConfig::new(&url, "qwen").with_timeout_ms(2_000));
+        let err = summarizer.summarize(&request(200)).unwrap_err();
+        assert!(err.retryable);
+        assert_eq!(err.model, "qwen");
+    }
+
+    #[test]
+    fn test_parse_sections_variants() {
+        let required = vec!["Key Decisions".to_string(), "Open Issues".to_string()];
+        let text = "## key decisions\n- a\n**Open Issues:**\n- b";
+        assert_eq!(parse_sections(text, &required), required);
+        assert!(parse_sections("Key Decisions: a", &required).is_empty());
+    }
+
+    #[test]
+    fn test_strip_reasoning() {
+        assert_eq!(
+            strip_reasoning("<think>x</think> a <think>y</think>b"),
+            "a b"
+        );
+        assert_eq!(strip_reasoning("a<think>unterminated"), "a");
+    }
+}
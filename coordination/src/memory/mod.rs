@@ -8,23 +8,42 @@
 //! - [`store`] — SwarmMemory trait, MemoryEntry, in-memory implementation
 //! - [`errors`] — Typed error taxonomy for compaction and summarization
 //! - [`budget`] — Token budgeting with pluggable estimators and compaction triggers
+//! - [`tokenizer`] — Exact BPE token counting from a Hugging Face `tokenizer.json`
+//! - [`recall`] — BM25 recall of compacted entries for re-injection
 //! - [`summarizer`] — Bounded summarizer contract and mock implementation
+//! - [`extractive`] — Model-free extractive summarizer (offline baseline and fallback)
+//! - [`llm_summarizer`] — Summarizer over an OpenAI-compatible chat endpoint
 //! - [`compactor`] — Compaction orchestrator with event-driven triggers
+//! - [`persistent`] — File-backed store with segment log, snapshots and audit archive
 
 pub mod budget;
 pub mod compactor;
 pub mod errors;
+pub mod extractive;
+pub mod llm_summarizer;
 pub mod observability;
+pub mod persistent;
+pub mod recall;
 pub mod store;
 pub mod summarizer;
+pub mod tokenizer;
 
 pub use budget::{
-    BudgetDecision, CompactionTrigger, TokenBudget, TokenEstimator, WordCountEstimator,
+    BudgetDecision, CharCountEstimator, CompactionTrigger, EstimatorConfig, ModelEstimators,
+    TokenBudget, TokenEstimator, WordCountEstimator,
 };
 pub use compactor::{
     CompactionEvent, CompactionPolicy, CompactionResult, CompactionTriggerKind, MemoryCompactor,
 };
 pub use errors::{CompactionError, CompactionErrorKind, SummarizationError};
+pub use extractive::{ExtractiveSummarizer, SalienceKind};
+pub use llm_summarizer::{LlmSummarizer, LlmSummarizerConfig, SummaryPrompt};
 pub use observability::{CompactionMetrics, CompactionObserver, CompactionStats};
-pub use store::{MemoryEntry, MemoryEntryKind, MemorySnapshot, SwarmMemory, SwarmMemoryStore};
+pub use persistent::{FsyncPolicy, PersistentMemoryConfig, PersistentMemoryStore, RecoveryReport};
+pub use recall::{RecallConfig, RecallHit, RecallIndex};
+pub use store::{
+    expand_summary, MemoryEntry, MemoryEntryKind, MemorySnapshot, SummaryExpansion,
+    SummaryProvenance, SwarmMemory, SwarmMemoryStore,
+};
 pub use summarizer::{MockSummarizer, Summarizer, SummaryRequest, SummaryResponse};
+pub use tokenizer::{BpeEstimator, BpeTokenizer, CacheStats, TokenizerError};
@@ -0,0 +1,731 @@
+//! File-backed SwarmMemory — append-only segment log with snapshots.
+//!
+//! Every mutation is written as one JSON line to the current log segment
+//! before the call returns, so a killed process loses at most the record
+//! being written. On open the store loads the latest snapshot and replays
+//! the segments after it.
+//!
+//! ```text
+//! <dir>/
+//!   snapshot.json            entries + next_seq + first segment to replay
+//!   segment-000003.jsonl     mutations since the snapshot
+//!   audit.jsonl              compacted entries archived at snapshot time
+//! ```
+//!
+//! Snapshots are written to a temporary file and renamed into place, then
+//! older segments are deleted. A torn final record in the last segment
+//! (the only place a crash can leave one) is truncated on open; damage
+//! anywhere else is reported as an integrity violation.
+
+use super::errors::{CompactionError, CompactionErrorKind};
+use super::store::{MemoryEntry, MemorySnapshot, SwarmMemory, SwarmMemoryStore};
+use crate::state::jsonl;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const SNAPSHOT_FILE: &str = "snapshot.json";
+const AUDIT_FILE: &str = "audit.jsonl";
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// When log writes are flushed to stable storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FsyncPolicy {
+    /// `fdatasync` after every record (no acknowledged write is lost).
+    Always,
+    /// `fdatasync` after every N records.
+    EveryN(u32),
+    /// Leave flushing to the OS (survives process crashes, not power loss).
+    Never,
+}
+
+/// Configuration for [`PersistentMemoryStore`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistentMemoryConfig {
+    /// Directory holding the snapshot, segments and audit log.
+    pub dir: PathBuf,
+    /// Fsync policy for log writes.
+    pub fsync: FsyncPolicy,
+    /// Records between automatic snapshots (0 = only on [`PersistentMemoryStore::checkpoint`]).
+    pub snapshot_every: u64,
+    /// Segment size that triggers rotation to a new segment.
+    pub segment_max_bytes: u64,
+    /// Move compacted entries to the audit log at snapshot time instead of
+    /// keeping them in memory.
+    pub archive_compacted: bool,
+}
+
+impl PersistentMemoryConfig {
+    /// Defaults: fsync every record, snapshot every 1000 records, 8 MiB
+    /// segments, archive compacted entries.
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            fsync: FsyncPolicy::Always,
+            snapshot_every: 1000,
+            segment_max_bytes: 8 * 1024 * 1024,
+            archive_compacted: true,
+        }
+    }
+
+    /// Set the fsync policy.
+    pub fn with_fsync(mut self, fsync: FsyncPolicy) -> Self {
+        self.fsync = fsync;
+        self
+    }
+
+    /// Set the automatic snapshot interval.
+    pub fn with_snapshot_every(mut self, records: u64) -> Self {
+        self.snapshot_every = records;
+        self
+    }
+
+    /// Set the segment rotation size.
+    pub fn with_segment_max_bytes(mut self, bytes: u64) -> Self {
+        self.segment_max_bytes = bytes;
+        self
+    }
+
+    /// Keep compacted entries in memory instead of archiving them.
+    pub fn with_archive_compacted(mut self, archive: bool) -> Self {
+        self.archive_compacted = archive;
+        self
+    }
+}
+
+/// What [`PersistentMemoryStore::open`] found on disk.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveryReport {
+    /// Whether a snapshot was loaded.
+    pub snapshot_loaded: bool,
+    /// Segments replayed after the snapshot.
+    pub segments_replayed: usize,
+    /// Log records replayed.
+    pub records_replayed: usize,
+    /// Bytes of torn tail removed from the last segment.
+    pub truncated_bytes: u64,
+}
+
+/// One logged mutation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum LogRecord {
+    Append { entry: MemoryEntry },
+    Compact { up_to: u64 },
+    Summary { entry: MemoryEntry, up_to: u64 },
+    Clear,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotFile {
+    version: u32,
+    created_at: DateTime<Utc>,
+    /// First segment whose records are not reflected in `entries`.
+    first_segment: u64,
+    next_seq: u64,
+    entries: Vec<MemoryEntry>,
+}
+
+fn persistence_error(detail: String) -> CompactionError {
+    CompactionError::new(CompactionErrorKind::PersistenceFailed, &detail)
+}
+
+fn segment_name(id: u64) -> String {
+    format!("segment-{id:06}.jsonl")
+}
+
+fn segment_id(name: &str) -> Option<u64> {
+    name.strip_prefix("segment-")?
+        .strip_suffix(".jsonl")?
+        .parse()
+        .ok()
+}
+
+/// Best-effort directory fsync so renames and new files are durable.
+fn sync_dir(dir: &Path) {
+    if let Ok(d) = File::open(dir) {
+        let _ = d.sync_all();
+    }
+}
+
+/// Parse newline-delimited JSON records, reporting corruption against `name`.
+fn parse_lines<T: serde::de::DeserializeOwned>(
+    bytes: &[u8],
+    name: &str,
+) -> Result<(Vec<T>, usize), CompactionError> {
+    jsonl::parse(bytes).map_err(|e| {
+        CompactionError::new(
+            CompactionErrorKind::IntegrityViolation,
+            &format!("{name}: {e}"),
+        )
+    })
+}
+
+/// Crash-safe, file-backed [`SwarmMemory`].
+///
+/// The [`SwarmMemory`] methods cannot fail, so write errors are kept and
+/// surfaced through [`take_error`](Self::take_error); the in-memory state
+/// stays authoritative for the running process.
+pub struct PersistentMemoryStore {
+    config: PersistentMemoryConfig,
+    inner: SwarmMemoryStore,
+    segment_id: u64,
+    segment: File,
+    segment_bytes: u64,
+    unsynced: u32,
+    records_since_snapshot: u64,
+    recovery: RecoveryReport,
+    error: Option<CompactionError>,
+}
+
+impl PersistentMemoryStore {
+    /// Open (or create) a store, recovering state from disk.
+    pub fn open(config: PersistentMemoryConfig) -> Result<Self, CompactionError> {
+        let dir = config.dir.clone();
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| persistence_error(format!("create {}: {e}", dir.display())))?;
+
+        let mut recovery = RecoveryReport::default();
+        let mut inner = SwarmMemoryStore::new();
+        let mut first_segment = 1;
+
+        let snapshot_path = dir.join(SNAPSHOT_FILE);
+        if snapshot_path.exists() {
+            let raw = std::fs::read(&snapshot_path)
+                .map_err(|e| persistence_error(format!("read snapshot: {e}")))?;
+            let snapshot: SnapshotFile = serde_json::from_slice(&raw).map_err(|e| {
+                CompactionError::new(
+                    CompactionErrorKind::IntegrityViolation,
+                    &format!("corrupt snapshot: {e}"),
+                )
+            })?;
+            inner = SwarmMemoryStore::from_entries(snapshot.entries);
+            inner.reserve_seq(snapshot.next_seq);
+            first_segment = snapshot.first_segment;
+            recovery.snapshot_loaded = true;
+        }
+
+        let mut segments: Vec<u64> = std::fs::read_dir(&dir)
+            .map_err(|e| persistence_error(format!("list {}: {e}", dir.display())))?
+            .flatten()
+            .filter_map(|e| segment_id(&e.file_name().to_string_lossy()))
+            .collect();
+        segments.sort_unstable();
+
+        // Segments older than the snapshot are leftovers from an
+        // interrupted checkpoint.
+        for id in segments.iter().filter(|id| **id < first_segment) {
+            let _ = std::fs::remove_file(dir.join(segment_name(*id)));
+        }
+        segments.retain(|id| *id >= first_segment);
+
+        let last = segments.last().copied();
+        for id in &segments {
+            let path = dir.join(segment_name(*id));
+            let bytes = std::fs::read(&path)
+                .map_err(|e| persistence_error(format!("read {}: {e}", path.display())))?;
+            let (records, good) = parse_lines::<LogRecord>(&bytes, &segment_name(*id))?;
+            if Some(*id) != last {
+                if good < bytes.len() {
+                    return Err(CompactionError::new(
+                        CompactionErrorKind::IntegrityViolation,
+                        &format!(
+                            "{}: truncated record before the last segment",
+                            path.display()
+                        ),
+                    ));
+                }
+            } else {
+                recovery.truncated_bytes = jsonl::repair_tail(&path, &bytes, good)
+                    .map_err(|e| persistence_error(format!("repair {}: {e}", path.display())))?;
+            }
+            recovery.records_replayed += records.len();
+            for record in records {
+                Self::replay(&mut inner, record);
+            }
+            recovery.segments_replayed += 1;
+        }
+
+        let segment_id = last.unwrap_or(first_segment);
+        let path = dir.join(segment_name(segment_id));
+        let segment = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| persistence_error(format!("open {}: {e}", path.display())))?;
+        let segment_bytes = segment
+            .metadata()
+            .map_err(|e| persistence_error(format!("stat {}: {e}", path.display())))?
+            .len();
+        sync_dir(&dir);
+
+        Ok(Self {
+            config,
+            inner,
+            segment_id,
+            segment,
+            segment_bytes,
+            unsynced: 0,
+            records_since_snapshot: recovery.records_replayed as u64,
+            recovery,
+            error: None,
+        })
+    }
+
+    fn replay(inner: &mut SwarmMemoryStore, record: LogRecord) {
+        match record {
+            LogRecord::Append { entry } => inner.restore(entry),
+            LogRecord::Compact { up_to } => inner.compact_up_to(up_to),
+            LogRecord::Summary { entry, up_to } => {
+                inner.compact_up_to(up_to);
+                inner.fold(&entry);
+                inner.restore(entry);
+            }
+            LogRecord::Clear => inner.clear(),
+        }
+    }
+
+    /// What was recovered when the store was opened.
+    pub fn recovery(&self) -> &RecoveryReport {
+        &self.recovery
+    }
+
+    /// Store configuration.
+    pub fn config(&self) -> &PersistentMemoryConfig {
+        &self.config
+    }
+
+    /// First write error since the last call, if any.
+    pub fn take_error(&mut self) -> Option<CompactionError> {
+        self.error.take()
+    }
+
+    /// Flush the current segment to stable storage.
+    pub fn sync(&mut self) -> Result<(), CompactionError> {
+        self.segment
+            .sync_data()
+            .map_err(|e| persistence_error(format!("fsync segment: {e}")))?;
+        self.unsynced = 0;
+        Ok(())
+    }
+
+    fn segment_path(&self, id: u64) -> PathBuf {
+        self.config.dir.join(segment_name(id))
+    }
+
+    fn open_segment(&self, id: u64) -> Result<File, CompactionError> {
+        let path = self.segment_path(id);
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| persistence_error(format!("open {}: {e}", path.display())))
+    }
+
+    /// Close the current segment and start the next one.
+    fn rotate(&mut self) -> Result<(), CompactionError> {
+        self.sync()?;
+        let next = self.segment_id + 1;
+        self.segment = self.open_segment(next)?;
+        self.segment_id = next;
+        self.segment_bytes = 0;
+        sync_dir(&self.config.dir);
+        Ok(())
+    }
+
+    fn try_write(&mut self, record: &LogRecord) -> Result<(), CompactionError> {
+        if self.segment_bytes >= self.config.segment_max_bytes {
+            self.rotate()?;
+        }
+        let mut line = serde_json::to_vec(record)
+            .map_err(|e| persistence_error(format!("encode record: {e}")))?;
+        line.push(b'\n');
+        self.segment
+            .write_all(&line)
+            .map_err(|e| persistence_error(format!("append record: {e}")))?;
+        self.segment_bytes += line.len() as u64;
+        self.unsynced += 1;
+        match self.config.fsync {
+            FsyncPolicy::Always => self.sync()?,
+            FsyncPolicy::EveryN(n) if self.unsynced >= n.max(1) => self.sync()?,
+            _ => {}
+        }
+        self.records_since_snapshot += 1;
+        if self.config.snapshot_every > 0
+            && self.records_since_snapshot >= self.config.snapshot_every
+        {
+            self.checkpoint()?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, record: LogRecord) {
+        if let Err(e) = self.try_write(&record) {
+            self.error.get_or_insert(e);
+        }
+    }
+
+    /// Write a snapshot and drop the segments it covers.
+    ///
+    /// With `archive_compacted`, compacted entries are appended to the
+    /// audit log and removed from memory first.
+    pub fn checkpoint(&mut self) -> Result<(), CompactionError> {
+        self.rotate()?;
+        let dir = self.config.dir.clone();
+
+        if self.config.archive_compacted {
+            let archived = self.inner.drain_compacted();
+            if !archived.is_empty() {
+                let mut buf = Vec::new();
+                for entry in &archived {
+                    serde_json::to_writer(&mut buf, entry)
+                        .map_err(|e| persistence_error(format!("encode audit entry: {e}")))?;
+                    buf.push(b'\n');
+                }
+                let mut audit = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(dir.join(AUDIT_FILE))
+                    .map_err(|e| persistence_error(format!("open audit log: {e}")))?;
+                audit
+                    .write_all(&buf)
+                    .and_then(|_| audit.sync_data())
+                    .map_err(|e| persistence_error(format!("write audit log: {e}")))?;
+            }
+        }
+
+        let snapshot = SnapshotFile {
+            version: SNAPSHOT_VERSION,
+            created_at: Utc::now(),
+            first_segment: self.segment_id,
+            next_seq: self.inner.next_seq(),
+            entries: self.inner.all_entries().into_iter().cloned().collect(),
+        };
+        let tmp = dir.join(format!("{SNAPSHOT_FILE}.tmp"));
+        let bytes = serde_json::to_vec(&snapshot)
+            .map_err(|e| persistence_error(format!("encode snapshot: {e}")))?;
+        File::create(&tmp)
+            .and_then(|mut f| f.write_all(&bytes).and_then(|_| f.sync_all()))
+            .and_then(|_| std::fs::rename(&tmp, dir.join(SNAPSHOT_FILE)))
+            .map_err(|e| persistence_error(format!("write snapshot: {e}")))?;
+        sync_dir(&dir);
+
+        for id in 1..self.segment_id {
+            let path = self.segment_path(id);
+            if path.exists() {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+        self.records_since_snapshot = 0;
+        Ok(())
+    }
+
+    /// Compacted entries archived to the audit log, oldest first.
+    pub fn audit_entries(&self) -> Result<Vec<MemoryEntry>, CompactionError> {
+        let path = self.config.dir.join(AUDIT_FILE);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let bytes =
+            std::fs::read(&path).map_err(|e| persistence_error(format!("read audit log: {e}")))?;
+        let (mut entries, _) = parse_lines::<MemoryEntry>(&bytes, AUDIT_FILE)?;
+        // A checkpoint interrupted after archiving re-archives on retry.
+        let mut seen = std::collections::HashSet::new();
+        entries.retain(|e| seen.insert((e.seq, e.created_at)));
+        Ok(entries)
+    }
+}
+
+impl Drop for PersistentMemoryStore {
+    fn drop(&mut self) {
+        if self.unsynced > 0 && self.config.fsync != FsyncPolicy::Never {
+            let _ = self.segment.sync_data();
+        }
+    }
+}
+
+impl SwarmMemory for PersistentMemoryStore {
+    fn append(&mut self, entry: MemoryEntry) -> u64 {
+        let seq = self.inner.append(entry);
+        if let Some(entry) = self.inner.get(seq).cloned() {
+            self.write(LogRecord::Append { entry });
+        }
+        seq
+    }
+
+    fn active_entries(&self) -> Vec<&MemoryEntry> {
+        self.inner.active_entries()
+    }
+
+    fn active_token_count(&self) -> u64 {
+        self.inner.active_token_count()
+    }
+
+    fn compact_up_to(&mut self, seq: u64) {
+        self.inner.compact_up_to(seq);
+        self.write(LogRecord::Compact { up_to: seq });
+    }
+
+    fn insert_summary(&mut self, summary: MemoryEntry, compact_up_to_seq: u64) {
+        self.inner.insert_summary(summary, compact_up_to_seq);
+        let seq = self.inner.next_seq() - 1;
+        if let Some(entry) = self.inner.get(seq).cloned() {
+            self.write(LogRecord::Summary {
+                entry,
+                up_to: compact_up_to_seq,
+            });
+        }
+    }
+
+    fn snapshot(&self) -> MemorySnapshot {
+        self.inner.snapshot()
+    }
+
+    fn get(&self, seq: u64) -> Option<&MemoryEntry> {
+        self.inner.get(seq)
+    }
+
+    fn all_entries(&self) -> Vec<&MemoryEntry> {
+        self.inner.all_entries()
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+        self.write(LogRecord::Clear);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::store::MemoryEntryKind;
+
+    fn entry(content: &str, tokens: u32) -> MemoryEntry {
+        MemoryEntry::new(MemoryEntryKind::AgentTurn, content, "coder", tokens)
+    }
+
+    fn config(dir: &Path) -> PersistentMemoryConfig {
+        PersistentMemoryConfig::new(dir).with_snapshot_every(0)
+    }
+
+    fn segments(dir: &Path) -> Vec<u64> {
+        let mut ids: Vec<u64> = std::fs::read_dir(dir)
+            .unwrap()
+            .flatten()
+            .filter_map(|e| segment_id(&e.file_name().to_string_lossy()))
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    #[test]
+    fn test_reopen_restores_state() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let mut store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+            store.append(entry("a", 10));
+            store.append(entry("b", 20));
+            store.append(entry("c", 30));
+            store.insert_summary(MemoryEntry::summary("a+b", 5), 2);
+            assert!(store.take_error().is_none());
+        }
+
+        let mut store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+        assert_eq!(store.recovery().records_replayed, 4);
+        assert!(!store.recovery().snapshot_loaded);
+        let snap = store.snapshot();
+        assert_eq!(snap.total_entries, 4);
+        assert_eq!(snap.active_entries, 2);
+        assert_eq!(snap.summary_count, 1);
+        assert!(store.get(1).unwrap().compacted);
+        assert_eq!(store.append(entry("d", 1)), 5);
+    }
+
+    #[test]
+    fn test_folded_summaries_replayed() {
+        use crate::memory::store::SummaryProvenance;
+
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let mut store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+            store.append(entry("a", 10));
+            let p1 = SummaryProvenance::from_folded(&[store.get(1).unwrap()]);
+            store.insert_summary(MemoryEntry::summary("s1", 5).with_provenance(p1), 1);
+            let p2 = SummaryProvenance::from_folded(&[store.get(2).unwrap()]);
+            store.insert_summary(MemoryEntry::summary("s2", 5).with_provenance(p2), 0);
+        }
+        let store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+        assert!(store.get(2).unwrap().compacted);
+        let active = store.active_entries();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].level(), 2);
+    }
+
+    #[test]
+    fn test_clear_is_replayed() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let mut store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+            store.append(entry("a", 10));
+            store.clear();
+            store.append(entry("b", 10));
+        }
+        let store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+        assert_eq!(store.all_entries().len(), 1);
+        assert_eq!(store.get(1).unwrap().content, "b");
+    }
+
+    #[test]
+    fn test_torn_tail_is_truncated() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let mut store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+            store.append(entry("a", 10));
+            store.append(entry("b", 10));
+        }
+        let path = dir.path().join(segment_name(1));
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"op":"append","entry":{"seq":3,"ki"#)
+            .unwrap();
+        drop(file);
+
+        let mut store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+        assert_eq!(store.recovery().records_replayed, 2);
+        assert!(store.recovery().truncated_bytes > 0);
+        assert_eq!(store.append(entry("c", 10)), 3);
+        drop(store);
+
+        let store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+        assert_eq!(store.all_entries().len(), 3);
+        assert_eq!(store.recovery().truncated_bytes, 0);
+    }
+
+    #[test]
+    fn test_missing_newline_is_repaired() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let mut store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+            store.append(entry("a", 10));
+        }
+        let path = dir.path().join(segment_name(1));
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+
+        {
+            let mut store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+            assert_eq!(store.recovery().truncated_bytes, 0);
+            store.append(entry("b", 10));
+        }
+        let store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+        assert_eq!(store.all_entries().len(), 2);
+    }
+
+    #[test]
+    fn test_mid_log_corruption_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let mut store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+            store.append(entry("a", 10));
+            store.append(entry("b", 10));
+        }
+        let path = dir.path().join(segment_name(1));
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, format!("garbage\n{text}")).unwrap();
+
+        let err = PersistentMemoryStore::open(config(dir.path()))
+            .err()
+            .unwrap();
+        assert_eq!(err.kind, CompactionErrorKind::IntegrityViolation);
+    }
+
+    #[test]
+    fn test_checkpoint_archives_compacted_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let mut store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+            store.append(entry("a", 10));
+            store.append(entry("b", 20));
+            store.insert_summary(MemoryEntry::summary("a+b", 5), 2);
+            store.checkpoint().unwrap();
+            assert_eq!(store.all_entries().len(), 1);
+            assert_eq!(segments(dir.path()), vec![2]);
+            store.append(entry("c", 30));
+        }
+
+        let store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+        assert!(store.recovery().snapshot_loaded);
+        assert_eq!(store.recovery().records_replayed, 1);
+        let seqs: Vec<u64> = store.all_entries().iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![3, 4]);
+        let audit = store.audit_entries().unwrap();
+        assert_eq!(audit.len(), 2);
+        assert!(audit.iter().all(|e| e.compacted));
+    }
+
+    #[test]
+    fn test_checkpoint_without_archive_keeps_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = config(dir.path()).with_archive_compacted(false);
+        {
+            let mut store = PersistentMemoryStore::open(cfg.clone()).unwrap();
+            store.append(entry("a", 10));
+            store.compact_up_to(1);
+            store.checkpoint().unwrap();
+        }
+        let store = PersistentMemoryStore::open(cfg).unwrap();
+        assert_eq!(store.all_entries().len(), 1);
+        assert!(store.get(1).unwrap().compacted);
+        assert!(store.audit_entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_automatic_snapshot_and_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = config(dir.path())
+            .with_snapshot_every(5)
+            .with_segment_max_bytes(1)
+            .with_fsync(FsyncPolicy::EveryN(2));
+        {
+            let mut store = PersistentMemoryStore::open(cfg.clone()).unwrap();
+            for i in 0..7 {
+                store.append(entry(&format!("e{i}"), 1));
+            }
+            assert!(store.take_error().is_none());
+            assert!(dir.path().join(SNAPSHOT_FILE).exists());
+            // Every record rotates; the snapshot dropped the first five.
+            assert!(segments(dir.path())[0] > 5);
+        }
+        let store = PersistentMemoryStore::open(cfg).unwrap();
+        assert_eq!(store.all_entries().len(), 7);
+        assert_eq!(store.recovery().records_replayed, 2);
+    }
+
+    #[test]
+    fn test_stale_segments_removed_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let mut store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+            store.append(entry("a", 10));
+            store.checkpoint().unwrap();
+        }
+        // Simulate a crash between snapshot rename and segment cleanup.
+        std::fs::write(dir.path().join(segment_name(1)), "not json\n").unwrap();
+        let store = PersistentMemoryStore::open(config(dir.path())).unwrap();
+        assert_eq!(store.all_entries().len(), 1);
+        assert_eq!(segments(dir.path()), vec![2]);
+    }
+
+    #[test]
+    fn test_fsync_policy_serde() {
+        let json = serde_json::to_string(&FsyncPolicy::EveryN(8)).unwrap();
+        assert_eq!(json, r#"{"every_n":8}"#);
+        let cfg = PersistentMemoryConfig::new(Path::new("/tmp/mem"));
+        let parsed: PersistentMemoryConfig =
+            serde_json::from_str(&serde_json::to_string(&cfg).unwrap()).unwrap();
+        assert_eq!(parsed.fsync, FsyncPolicy::Always);
+        assert!(parsed.archive_compacted);
+    }
+}
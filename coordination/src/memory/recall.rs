@@ -0,0 +1,465 @@
+//! Lexical recall over compacted memory — BM25 with code-aware tokens.
+//!
+//! Compaction keeps the gist but drops exact error messages, paths and
+//! identifiers. [`RecallIndex`] keeps every original entry searchable so
+//! an agent can pull a forgotten fact back into context without replaying
+//! the whole history.
+//!
+//! Tokenization keeps compound tokens (`src/memory/store.rs`,
+//! `std::io::Error`, `E0502`) whole and also indexes their parts, with
+//! `snake_case` and `camelCase` identifiers split into words, so both
+//! `"store.rs"` and `"memory store"` find the same entry.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::store::{MemoryEntry, MemoryEntryKind, SwarmMemory};
+
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "in", "is", "it", "of", "on",
+    "or", "that", "the", "this", "to", "was", "with",
+];
+
+/// Split an identifier into lowercase words on `_` and case changes.
+fn identifier_words(ident: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in ident.split('_').filter(|p| !p.is_empty()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (prev, cur) = (chars[i - 1], chars[i]);
+            let next_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            let boundary = (prev.is_lowercase() && cur.is_uppercase())
+                || (prev.is_uppercase() && cur.is_uppercase() && next_lower)
+                || (prev.is_alphabetic() != cur.is_alphabetic());
+            if boundary {
+                words.push(chars[start..i].iter().collect::<String>().to_lowercase());
+                start = i;
+            }
+        }
+        words.push(chars[start..].iter().collect::<String>().to_lowercase());
+    }
+    words
+}
+
+/// Code-aware tokenization used for both documents and queries.
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let is_joiner = |c: char| matches!(c, '.' | '/' | ':' | '-');
+
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        if !is_word(chars[i].1) {
+            i += 1;
+            continue;
+        }
+        // Extend over word chars and single joiners between word chars.
+        let start = chars[i].0;
+        let mut j = i;
+        while j < chars.len() {
+            if is_word(chars[j].1) {
+                j += 1;
+            } else if is_joiner(chars[j].1) {
+                let mut k = j;
+                while k < chars.len() && is_joiner(chars[k].1) {
+                    k += 1;
+                }
+                if k < chars.len() && is_word(chars[k].1) {
+                    j = k;
+                } else {
+                    break;
+                }
+            } else {
+                break;
+            }
+        }
+        let end = chars.get(j).map(|c| c.0).unwrap_or(text.len());
+        let compound = &text[start..end];
+        let parts: Vec<&str> = compound
+            .split(|c: char| !is_word(c))
+            .filter(|p| !p.is_empty())
+            .collect();
+        if parts.len() > 1 {
+            terms.push(compound.to_lowercase());
+        }
+        for part in parts {
+            let lower = part.to_lowercase();
+            let words = identifier_words(part);
+            if words.len() > 1 {
+                terms.extend(words.into_iter().filter(|w| w.len() > 1));
+            }
+            if !STOP_WORDS.contains(&lower.as_str()) {
+                terms.push(lower);
+            }
+        }
+        i = j;
+    }
+    terms
+}
+
+/// BM25 parameters and recall filters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecallConfig {
+    /// Term-frequency saturation.
+    pub k1: f64,
+    /// Length normalization.
+    pub b: f64,
+    /// Also return entries that are still active (already in context).
+    pub include_active: bool,
+}
+
+impl Default for RecallConfig {
+    fn default() -> Self {
+        Self {
+            k1: 1.2,
+            b: 0.75,
+            include_active: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Document {
+    entry: MemoryEntry,
+    terms: HashMap<String, u32>,
+    len: u32,
+}
+
+/// One recalled entry.
+#[derive(Debug, Clone)]
+pub struct RecallHit<'a> {
+    /// The original entry.
+    pub entry: &'a MemoryEntry,
+    /// BM25 score.
+    pub score: f64,
+    /// Query terms found in the entry, in query order.
+    pub matched_terms: Vec<String>,
+}
+
+impl RecallHit<'_> {
+    /// Text block for re-injecting the entry into a prompt.
+    pub fn render(&self) -> String {
+        format!(
+            "[recalled #{} from {}]\n{}",
+            self.entry.seq, self.entry.source, self.entry.content
+        )
+    }
+}
+
+/// BM25 index over original (non-summary) memory entries.
+///
+/// The index keeps its own copy of each entry, so recall keeps working
+/// after a store archives compacted entries.
+#[derive(Debug, Clone, Default)]
+pub struct RecallIndex {
+    config: RecallConfig,
+    docs: HashMap<u64, Document>,
+    doc_freq: HashMap<String, u32>,
+    total_len: u64,
+}
+
+impl RecallIndex {
+    /// Create an empty index with default parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set BM25 parameters and filters.
+    pub fn with_config(mut self, config: RecallConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Build an index over every entry in `memory`.
+    pub fn build<M: SwarmMemory + ?Sized>(memory: &M) -> Self {
+        let mut index = Self::new();
+        index.sync(memory);
+        index
+    }
+
+    /// Number of indexed entries.
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    /// Whether the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Index new entries and refresh compaction flags from `memory`.
+    ///
+    /// Entries the store no longer holds stay indexed.
+    pub fn sync<M: SwarmMemory + ?Sized>(&mut self, memory: &M) {
+        for entry in memory.all_entries() {
+            match self.docs.get_mut(&entry.seq) {
+                // Same entry: only the compaction flag can change.
+                Some(doc) if doc.entry.created_at == entry.created_at => {
+                    doc.entry.compacted = entry.compacted;
+                }
+                _ => self.add(entry),
+            }
+        }
+    }
+
+    /// Index one entry (e.g. from an audit archive), replacing any entry
+    /// with the same seq. Summary sentinels are skipped.
+    pub fn add(&mut self, entry: &MemoryEntry) {
+        self.remove(entry.seq);
+        if entry.kind == MemoryEntryKind::Summary {
+            return;
+        }
+        let mut terms: HashMap<String, u32> = HashMap::new();
+        let tokens = tokenize(&format!("{} {}", entry.source, entry.content));
+        for t in &tokens {
+            *terms.entry(t.clone()).or_default() += 1;
+        }
+        for t in terms.keys() {
+            *self.doc_freq.entry(t.clone()).or_default() += 1;
+        }
+        let len = tokens.len() as u32;
+        self.total_len += len as u64;
+        self.docs.insert(
+            entry.seq,
+            Document {
+                entry: entry.clone(),
+                terms,
+                len,
+            },
+        );
+    }
+
+    /// Drop entry `seq` from the index.
+    pub fn remove(&mut self, seq: u64) {
+        let Some(doc) = self.docs.remove(&seq) else {
+            return;
+        };
+        self.total_len -= doc.len as u64;
+        for t in doc.terms.keys() {
+            if let Some(df) = self.doc_freq.get_mut(t) {
+                *df -= 1;
+                if *df == 0 {
+                    self.doc_freq.remove(t);
+                }
+            }
+        }
+    }
+
+    /// Drop everything (e.g. after `SwarmMemory::clear`).
+    pub fn clear(&mut self) {
+        self.docs.clear();
+        self.doc_freq.clear();
+        self.total_len = 0;
+    }
+
+    fn idf(&self, term: &str) -> f64 {
+        let n = self.docs.len() as f64;
+        let df = self.doc_freq.get(term).copied().unwrap_or(0) as f64;
+        ((n - df + 0.5) / (df + 0.5) + 1.0).ln()
+    }
+
+    /// Top `k` entries for `query` by BM25 score, best first.
+    pub fn search(&self, query: &str, k: usize) -> Vec<RecallHit<'_>> {
+        let mut seen = HashSet::new();
+        let terms: Vec<String> = tokenize(query)
+            .into_iter()
+            .filter(|t| seen.insert(t.clone()))
+            .collect();
+        if terms.is_empty() || self.docs.is_empty() {
+            return Vec::new();
+        }
+        let avg_len = (self.total_len as f64 / self.docs.len() as f64).max(1.0);
+        let idf: Vec<f64> = terms.iter().map(|t| self.idf(t)).collect();
+
+        let mut hits: Vec<RecallHit<'_>> = self
+            .docs
+            .values()
+            .filter(|d| self.config.include_active || d.entry.compacted)
+            .filter_map(|doc| {
+                let norm = self.config.k1
+                    * (1.0 - self.config.b + self.config.b * doc.len as f64 / avg_len);
+                let mut score = 0.0;
+                let mut matched = Vec::new();
+                for (term, idf) in terms.iter().zip(&idf) {
+                    let Some(&tf) = doc.terms.get(term) else {
+                        continue;
+                    };
+                    let tf = tf as f64;
+                    score += idf * tf * (self.config.k1 + 1.0) / (tf + norm);
+                    matched.push(term.clone());
+                }
+                (score > 0.0).then_some(RecallHit {
+                    entry: &doc.entry,
+                    score,
+                    matched_terms: matched,
+                })
+            })
+            .collect();
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(b.entry.seq.cmp(&a.entry.seq))
+        });
+        hits.truncate(k);
+        hits
+    }
+
+    /// Best matches for `query` that fit in `token_budget`, at most `k`.
+    ///
+    /// Hits are taken in score order; one that does not fit is skipped so
+    /// a smaller, lower-ranked entry can still be recalled.
+    pub fn recall(&self, query: &str, k: usize, token_budget: u64) -> Vec<RecallHit<'_>> {
+        let mut used = 0u64;
+        let mut out = Vec::new();
+        for hit in self.search(query, self.docs.len()) {
+            if out.len() >= k {
+                break;
+            }
+            let cost = hit.entry.estimated_tokens as u64;
+            if used + cost <= token_budget {
+                used += cost;
+                out.push(hit);
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::store::SwarmMemoryStore;
+    use super::*;
+
+    fn store() -> SwarmMemoryStore {
+        let mut store = SwarmMemoryStore::new();
+        let entries = [
+            (
+                MemoryEntryKind::ToolResult,
+                "error[E0502]: cannot borrow `self.entries` as mutable\n --> src/memory/store.rs:88:9",
+                "cargo",
+                40,
+            ),
+            (
+                MemoryEntryKind::AgentTurn,
+                "Refactored the TokenBudget trigger to use available()",
+                "coder",
+                20,
+            ),
+            (
+                MemoryEntryKind::AgentTurn,
+                "Reviewed the parser; the lexer handles unicode fine",
+                "reviewer",
+                25,
+            ),
+            (
+                MemoryEntryKind::ToolResult,
+                "test result: FAILED. 3 passed; 1 failed (compaction_property_tests)",
+                "verifier",
+                30,
+            ),
+        ];
+        for (kind, content, source, tokens) in entries {
+            store.append(MemoryEntry::new(kind, content, source, tokens));
+        }
+        store
+    }
+
+    #[test]
+    fn test_tokenize_code() {
+        let terms = tokenize("see src/memory/store.rs and HashMap::new for E0502");
+        for expected in [
+            "src/memory/store.rs",
+            "store",
+            "rs",
+            "hashmap::new",
+            "hash",
+            "map",
+            "hashmap",
+            "e0502",
+        ] {
+            assert!(terms.contains(&expected.to_string()), "missing {expected}");
+        }
+        assert!(!terms.contains(&"and".to_string()));
+        assert_eq!(
+            identifier_words("parseJSONValue"),
+            vec!["parse", "json", "value"]
+        );
+        assert_eq!(identifier_words("max_tokens"), vec!["max", "tokens"]);
+    }
+
+    #[test]
+    fn test_recall_finds_compacted_detail() {
+        let mut store = store();
+        store.compact_up_to(4);
+        let index = RecallIndex::build(&store);
+        assert_eq!(index.len(), 4);
+
+        let hits = index.recall("E0502 borrow in store.rs", 2, 1_000);
+        assert_eq!(hits[0].entry.seq, 1);
+        assert!(hits[0].matched_terms.contains(&"e0502".to_string()));
+        assert!(hits[0].render().starts_with("[recalled #1 from cargo]"));
+
+        let hits = index.search("token budget", 1);
+        assert_eq!(hits[0].entry.seq, 2);
+    }
+
+    #[test]
+    fn test_active_entries_excluded_by_default() {
+        let mut store = store();
+        store.compact_up_to(2);
+        let index = RecallIndex::build(&store);
+        assert!(index.search("lexer unicode", 5).is_empty());
+
+        let index = RecallIndex::build(&store).with_config(RecallConfig {
+            include_active: true,
+            ..RecallConfig::default()
+        });
+        assert_eq!(index.search("lexer unicode", 5)[0].entry.seq, 3);
+    }
+
+    #[test]
+    fn test_recall_respects_budget_and_k() {
+        let mut store = store();
+        store.compact_up_to(4);
+        let index = RecallIndex::build(&store);
+        // "failed" matches seq 4 (30 tokens); "borrow" matches seq 1 (40 tokens).
+        let hits = index.recall("failed borrow", 5, 35);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entry.seq, 4);
+        assert_eq!(index.recall("failed borrow", 1, 1_000).len(), 1);
+        assert!(index
+            .recall("nothing matches here zzz", 5, 1_000)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_sync_tracks_compaction_and_summaries() {
+        let mut store = store();
+        let mut index = RecallIndex::build(&store);
+        assert!(index.search("E0502", 1).is_empty());
+
+        store.insert_summary(MemoryEntry::summary("borrow fixed E0502", 5), 1);
+        index.sync(&store);
+        let hits = index.search("E0502", 5);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entry.kind, MemoryEntryKind::ToolResult);
+        assert_eq!(index.len(), 4);
+    }
+
+    #[test]
+    fn test_index_survives_archived_entries() {
+        let mut store = store();
+        store.compact_up_to(1);
+        let mut index = RecallIndex::build(&store);
+        store.drain_compacted();
+        index.sync(&store);
+        assert_eq!(index.search("E0502", 1)[0].entry.seq, 1);
+
+        index.remove(1);
+        assert!(index.search("E0502", 1).is_empty());
+        index.clear();
+        assert!(index.is_empty());
+    }
+}
@@ -6,6 +6,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use super::errors::{CompactionError, CompactionErrorKind};
+
 /// Kind of memory entry.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -54,6 +56,9 @@ pub struct MemoryEntry {
     pub compacted: bool,
     /// Source identifier (agent name, tool name, etc.).
     pub source: String,
+    /// What a summary sentinel folded in (None for regular entries).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<SummaryProvenance>,
 }
 
 impl MemoryEntry {
@@ -67,6 +72,7 @@ impl MemoryEntry {
             created_at: Utc::now(),
             compacted: false,
             source: source.to_string(),
+            provenance: None,
         }
     }
 
@@ -80,8 +86,93 @@ impl MemoryEntry {
             created_at: Utc::now(),
             compacted: false,
             source: "compactor".to_string(),
+            provenance: None,
         }
     }
+
+    /// Attach provenance to a summary sentinel.
+    pub fn with_provenance(mut self, provenance: SummaryProvenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    /// Compaction level: 0 for regular entries, 1 for summaries without
+    /// provenance.
+    pub fn level(&self) -> u32 {
+        match (&self.provenance, self.kind) {
+            (Some(p), _) => p.level,
+            (None, MemoryEntryKind::Summary) => 1,
+            (None, _) => 0,
+        }
+    }
+}
+
+/// What a summary sentinel covers.
+///
+/// Level-1 summaries fold original entries; a level-N summary folds at
+/// least one level-(N-1) sentinel. `covered` is transitive, so any summary
+/// can be expanded straight back to its originals.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SummaryProvenance {
+    /// Compaction level of the summary.
+    pub level: u32,
+    /// Inclusive seq ranges of the original (non-summary) entries covered.
+    pub covered: Vec<(u64, u64)>,
+    /// Distinct sources of the covered originals, sorted.
+    pub sources: Vec<String>,
+    /// Seqs of the entries folded directly into the summary.
+    pub folded: Vec<u64>,
+}
+
+impl SummaryProvenance {
+    /// Provenance for a summary of `entries`.
+    pub fn from_folded(entries: &[&MemoryEntry]) -> Self {
+        let mut level = 0;
+        let mut seqs = Vec::new();
+        let mut ranges = Vec::new();
+        let mut sources = Vec::new();
+        for entry in entries {
+            level = level.max(entry.level());
+            match (&entry.provenance, entry.kind) {
+                (Some(p), _) => {
+                    ranges.extend(p.covered.iter().copied());
+                    sources.extend(p.sources.iter().cloned());
+                }
+                (None, MemoryEntryKind::Summary) => {}
+                (None, _) => {
+                    seqs.push(entry.seq);
+                    sources.push(entry.source.clone());
+                }
+            }
+        }
+        ranges.extend(seqs.into_iter().map(|s| (s, s)));
+        ranges.sort_unstable();
+        let mut covered: Vec<(u64, u64)> = Vec::new();
+        for (start, end) in ranges {
+            match covered.last_mut() {
+                Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+                _ => covered.push((start, end)),
+            }
+        }
+        sources.sort();
+        sources.dedup();
+        Self {
+            level: level + 1,
+            covered,
+            sources,
+            folded: entries.iter().map(|e| e.seq).collect(),
+        }
+    }
+
+    /// Whether original entry `seq` is covered.
+    pub fn covers(&self, seq: u64) -> bool {
+        self.covered.iter().any(|(s, e)| (*s..=*e).contains(&seq))
+    }
+
+    /// Number of original entries covered.
+    pub fn covered_count(&self) -> u64 {
+        self.covered.iter().map(|(s, e)| e - s + 1).sum()
+    }
 }
 
 /// Snapshot of the memory store for inspection.
@@ -129,6 +220,70 @@ pub trait SwarmMemory {
     fn clear(&mut self);
 }
 
+/// A summary resolved back to the entries it replaced.
+#[derive(Debug, Clone)]
+pub struct SummaryExpansion<'a> {
+    /// The summary sentinel.
+    pub summary: &'a MemoryEntry,
+    /// Directly folded entries still held by the store.
+    pub folded: Vec<&'a MemoryEntry>,
+    /// Covered original entries still held by the store, in seq order.
+    pub originals: Vec<&'a MemoryEntry>,
+    /// Covered original seqs the store no longer holds.
+    pub missing: Vec<u64>,
+}
+
+impl SummaryExpansion<'_> {
+    /// Whether every covered original was found.
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Expand summary `seq` into its folded entries and covered originals.
+pub fn expand_summary<M: SwarmMemory + ?Sized>(
+    memory: &M,
+    seq: u64,
+) -> Result<SummaryExpansion<'_>, CompactionError> {
+    let summary = memory.get(seq).ok_or_else(|| {
+        CompactionError::new(
+            CompactionErrorKind::InvalidRange,
+            &format!("no entry {seq}"),
+        )
+    })?;
+    let provenance = match (&summary.provenance, summary.kind) {
+        (Some(p), MemoryEntryKind::Summary) => p,
+        _ => {
+            return Err(CompactionError::new(
+                CompactionErrorKind::InvalidRange,
+                &format!("entry {seq} is not a summary with provenance"),
+            ))
+        }
+    };
+
+    let folded = provenance
+        .folded
+        .iter()
+        .filter_map(|s| memory.get(*s))
+        .collect();
+    let mut originals = Vec::new();
+    let mut missing = Vec::new();
+    for (start, end) in &provenance.covered {
+        for s in *start..=*end {
+            match memory.get(s) {
+                Some(entry) if entry.kind != MemoryEntryKind::Summary => originals.push(entry),
+                _ => missing.push(s),
+            }
+        }
+    }
+    Ok(SummaryExpansion {
+        summary,
+        folded,
+        originals,
+        missing,
+    })
+}
+
 /// In-memory implementation of SwarmMemory.
 pub struct SwarmMemoryStore {
     entries: Vec<MemoryEntry>,
@@ -149,6 +304,43 @@ impl SwarmMemoryStore {
         let next_seq = entries.iter().map(|e| e.seq).max().unwrap_or(0) + 1;
         Self { entries, next_seq }
     }
+
+    /// Sequence number the next appended entry will receive.
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// Raise the next sequence number (never lowers it).
+    pub(crate) fn reserve_seq(&mut self, next_seq: u64) {
+        self.next_seq = self.next_seq.max(next_seq);
+    }
+
+    /// Insert an entry that already carries its sequence number.
+    pub(crate) fn restore(&mut self, entry: MemoryEntry) {
+        self.reserve_seq(entry.seq + 1);
+        self.entries.push(entry);
+    }
+
+    /// Mark the summaries folded into `summary` as compacted.
+    pub(crate) fn fold(&mut self, summary: &MemoryEntry) {
+        let Some(provenance) = &summary.provenance else {
+            return;
+        };
+        for entry in &mut self.entries {
+            if entry.kind == MemoryEntryKind::Summary && provenance.folded.contains(&entry.seq) {
+                entry.compacted = true;
+            }
+        }
+    }
+
+    /// Remove and return all compacted entries.
+    pub(crate) fn drain_compacted(&mut self) -> Vec<MemoryEntry> {
+        let (compacted, active) = std::mem::take(&mut self.entries)
+            .into_iter()
+            .partition(|e| e.compacted);
+        self.entries = active;
+        compacted
+    }
 }
 
 impl Default for SwarmMemoryStore {
@@ -188,6 +380,7 @@ impl SwarmMemory for SwarmMemoryStore {
 
     fn insert_summary(&mut self, summary: MemoryEntry, compact_up_to_seq: u64) {
         self.compact_up_to(compact_up_to_seq);
+        self.fold(&summary);
         self.append(summary);
     }
 
@@ -363,6 +556,100 @@ mod tests {
         assert_eq!(seq, 11);
     }
 
+    #[test]
+    fn test_provenance_merges_ranges_and_levels() {
+        let mut a = agent_entry("a", 10);
+        a.seq = 1;
+        let mut b = MemoryEntry::new(MemoryEntryKind::ToolResult, "b", "cargo", 10);
+        b.seq = 2;
+        let mut inner = MemoryEntry::summary("s", 5).with_provenance(SummaryProvenance {
+            level: 1,
+            covered: vec![(3, 6)],
+            sources: vec!["reviewer".to_string()],
+            folded: vec![3, 4, 5, 6],
+        });
+        inner.seq = 7;
+        let mut c = agent_entry("c", 10);
+        c.seq = 9;
+
+        let p = SummaryProvenance::from_folded(&[&a, &b, &inner, &c]);
+        assert_eq!(p.level, 2);
+        assert_eq!(p.covered, vec![(1, 6), (9, 9)]);
+        assert_eq!(p.sources, vec!["cargo", "coder", "reviewer"]);
+        assert_eq!(p.folded, vec![1, 2, 7, 9]);
+        assert_eq!(p.covered_count(), 7);
+        assert!(p.covers(4) && !p.covers(8));
+        assert_eq!(a.level(), 0);
+        assert_eq!(MemoryEntry::summary("legacy", 1).level(), 1);
+    }
+
+    #[test]
+    fn test_insert_summary_folds_child_summaries() {
+        let mut store = SwarmMemoryStore::new();
+        store.append(agent_entry("a", 10));
+        let first = MemoryEntry::summary("s1", 5)
+            .with_provenance(SummaryProvenance::from_folded(&[store.get(1).unwrap()]));
+        store.insert_summary(first, 1);
+        store.append(agent_entry("b", 10));
+
+        let folded: Vec<&MemoryEntry> = vec![store.get(2).unwrap(), store.get(3).unwrap()];
+        let second =
+            MemoryEntry::summary("s2", 5).with_provenance(SummaryProvenance::from_folded(&folded));
+        store.insert_summary(second, 3);
+
+        let active = store.active_entries();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].level(), 2);
+        assert!(store.get(2).unwrap().compacted);
+    }
+
+    #[test]
+    fn test_expand_summary() {
+        let mut store = SwarmMemoryStore::new();
+        store.append(agent_entry("a", 10));
+        store.append(agent_entry("b", 10));
+        let entries: Vec<&MemoryEntry> = store.active_entries();
+        let summary = MemoryEntry::summary("a+b", 5)
+            .with_provenance(SummaryProvenance::from_folded(&entries));
+        store.insert_summary(summary, 2);
+
+        let expansion = expand_summary(&store, 3).unwrap();
+        assert!(expansion.is_complete());
+        let contents: Vec<&str> = expansion
+            .originals
+            .iter()
+            .map(|e| e.content.as_str())
+            .collect();
+        assert_eq!(contents, vec!["a", "b"]);
+        assert_eq!(expansion.folded.len(), 2);
+
+        // Originals dropped from the store are reported, not silently skipped.
+        store.drain_compacted();
+        let expansion = expand_summary(&store, 3).unwrap();
+        assert_eq!(expansion.missing, vec![1, 2]);
+        assert!(expansion.originals.is_empty());
+
+        let err = expand_summary(&store, 99).unwrap_err();
+        assert_eq!(err.kind, CompactionErrorKind::InvalidRange);
+        store.append(agent_entry("c", 1));
+        assert!(expand_summary(&store, 4).is_err());
+    }
+
+    #[test]
+    fn test_provenance_serde_omitted_for_regular_entries() {
+        let json = serde_json::to_string(&agent_entry("x", 1)).unwrap();
+        assert!(!json.contains("provenance"));
+        let summary = MemoryEntry::summary("s", 1).with_provenance(SummaryProvenance {
+            level: 2,
+            covered: vec![(1, 4)],
+            sources: vec!["coder".to_string()],
+            folded: vec![5],
+        });
+        let parsed: MemoryEntry =
+            serde_json::from_str(&serde_json::to_string(&summary).unwrap()).unwrap();
+        assert_eq!(parsed.provenance, summary.provenance);
+    }
+
     #[test]
     fn test_memory_entry_kind_display() {
         assert_eq!(MemoryEntryKind::SystemPrompt.to_string(), "system_prompt");
@@ -0,0 +1,680 @@
+//! Exact BPE token counting from a Hugging Face `tokenizer.json`.
+//!
+//! Heuristic estimators drift by 10–30% on code and routinely push Qwen
+//! sessions past the 65K window. [`BpeEstimator`] loads the model's own
+//! tokenizer file and counts tokens the way the server will.
+//!
+//! Supported subset of the `tokenizer.json` format:
+//!
+//! - `model.type = "BPE"` with `vocab`, `merges` (`"a b"` or `["a", "b"]`),
+//!   `byte_fallback` and `ignore_merges`
+//! - pre-tokenizers `Split` (regex or literal pattern), `ByteLevel`,
+//!   `Whitespace` and `Sequence` of those
+//! - `added_tokens`, each counted as a single token
+//!
+//! Normalizers are not applied; every normalizer shipped with current BPE
+//! models (NFC, or none) is a no-op on already-composed text.
+
+use fancy_regex::Regex;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::budget::TokenEstimator;
+
+/// GPT-2 pre-tokenizer regex used by `ByteLevel` when `use_regex` is set.
+const BYTE_LEVEL_PATTERN: &str =
+    r"'s|'t|'re|'ve|'m|'ll|'d| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+(?!\S)|\s+";
+
+/// `Whitespace` pre-tokenizer regex.
+const WHITESPACE_PATTERN: &str = r"\w+|[^\w\s]+";
+
+/// Error loading a `tokenizer.json`.
+#[derive(Debug, thiserror::Error)]
+pub enum TokenizerError {
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("invalid tokenizer.json: {0}")]
+    Parse(String),
+
+    #[error("unsupported tokenizer: {0}")]
+    Unsupported(String),
+}
+
+/// How a `Split` pre-tokenizer treats regex matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SplitBehavior {
+    Isolated,
+    Removed,
+    MergedWithPrevious,
+    MergedWithNext,
+    /// Inverted `Removed`: keep the matches, drop the text between them.
+    MatchesOnly,
+}
+
+#[derive(Debug)]
+enum PreTokenizer {
+    Split {
+        pattern: Regex,
+        behavior: SplitBehavior,
+    },
+    ByteLevel {
+        add_prefix_space: bool,
+        pattern: Option<Regex>,
+    },
+}
+
+fn compile(pattern: &str) -> Result<Regex, TokenizerError> {
+    Regex::new(pattern).map_err(|e| TokenizerError::Parse(format!("bad pattern {pattern:?}: {e}")))
+}
+
+/// Split `text` at regex matches according to `behavior`.
+fn split(text: &str, pattern: &Regex, behavior: SplitBehavior) -> Vec<String> {
+    let mut pieces: Vec<String> = Vec::new();
+    let mut last = 0;
+    let mut carry = String::new();
+    for m in pattern.find_iter(text) {
+        // Backtracking limits only trip on pathological input; keep the rest whole.
+        let Ok(m) = m else { break };
+        if m.start() == m.end() {
+            continue;
+        }
+        let gap = &text[last..m.start()];
+        match behavior {
+            SplitBehavior::Isolated | SplitBehavior::Removed => {
+                if !gap.is_empty() {
+                    pieces.push(gap.to_string());
+                }
+                if behavior == SplitBehavior::Isolated {
+                    pieces.push(m.as_str().to_string());
+                }
+            }
+            SplitBehavior::MergedWithPrevious => {
+                pieces.push(format!("{gap}{}", m.as_str()));
+            }
+            SplitBehavior::MatchesOnly => pieces.push(m.as_str().to_string()),
+            SplitBehavior::MergedWithNext => {
+                if !gap.is_empty() || !carry.is_empty() {
+                    pieces.push(format!("{carry}{gap}"));
+                }
+                carry = m.as_str().to_string();
+            }
+        }
+        last = m.end();
+    }
+    let rest = format!("{carry}{}", &text[last..]);
+    if !rest.is_empty() && behavior != SplitBehavior::MatchesOnly {
+        pieces.push(rest);
+    }
+    pieces.retain(|p| !p.is_empty());
+    pieces
+}
+
+/// GPT-2 byte → printable char table used by byte-level BPE.
+fn byte_level_alphabet() -> [char; 256] {
+    let mut table = ['\0'; 256];
+    let mut extra = 0u32;
+    for b in 0..=255u8 {
+        let printable = matches!(b, b'!'..=b'~' | 0xA1..=0xAC | 0xAE..=0xFF);
+        table[b as usize] = if printable {
+            b as char
+        } else {
+            extra += 1;
+            char::from_u32(255 + extra).unwrap_or('\0')
+        };
+    }
+    table
+}
+
+/// A BPE model loaded from `tokenizer.json`.
+#[derive(Debug)]
+pub struct BpeTokenizer {
+    vocab: HashMap<String, u32>,
+    merges: HashMap<(String, String), usize>,
+    added: Option<Regex>,
+    pre_tokenizers: Vec<PreTokenizer>,
+    byte_level: bool,
+    byte_fallback: bool,
+    ignore_merges: bool,
+    alphabet: [char; 256],
+}
+
+impl BpeTokenizer {
+    /// Load a tokenizer from a `tokenizer.json` file.
+    pub fn from_file(path: &Path) -> Result<Self, TokenizerError> {
+        let raw = std::fs::read_to_string(path).map_err(|source| TokenizerError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_json(&raw)
+    }
+
+    /// Parse a tokenizer from `tokenizer.json` contents.
+    pub fn from_json(raw: &str) -> Result<Self, TokenizerError> {
+        let root: Value =
+            serde_json::from_str(raw).map_err(|e| TokenizerError::Parse(e.to_string()))?;
+        let model = &root["model"];
+        match model["type"].as_str() {
+            Some("BPE") => {}
+            // Older files omit the type for BPE models.
+            None if model.get("merges").is_some() => {}
+            other => {
+                return Err(TokenizerError::Unsupported(format!(
+                    "model type {}",
+                    other.unwrap_or("<missing>")
+                )))
+            }
+        }
+
+        let vocab: HashMap<String, u32> = serde_json::from_value(model["vocab"].clone())
+            .map_err(|e| TokenizerError::Parse(format!("model.vocab: {e}")))?;
+
+        let mut merges = HashMap::new();
+        for (rank, merge) in model["merges"].as_array().into_iter().flatten().enumerate() {
+            let pair = match merge {
+                Value::String(s) => s
+                    .split_once(' ')
+                    .map(|(a, b)| (a.to_string(), b.to_string())),
+                Value::Array(parts) => match (parts.first(), parts.get(1)) {
+                    (Some(Value::String(a)), Some(Value::String(b))) => {
+                        Some((a.clone(), b.clone()))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            };
+            let pair = pair.ok_or_else(|| TokenizerError::Parse(format!("bad merge {merge}")))?;
+            merges.entry(pair).or_insert(rank);
+        }
+
+        let mut added: Vec<String> = root["added_tokens"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|t| t["content"].as_str())
+            .filter(|c| !c.is_empty())
+            .map(str::to_string)
+            .collect();
+        // Longest first so overlapping tokens match greedily.
+        added.sort_by_key(|t| std::cmp::Reverse(t.len()));
+        let added = if added.is_empty() {
+            None
+        } else {
+            let alternation: Vec<String> = added
+                .iter()
+                .map(|t| fancy_regex::escape(t).into_owned())
+                .collect();
+            Some(compile(&alternation.join("|"))?)
+        };
+
+        let mut pre_tokenizers = Vec::new();
+        Self::parse_pre_tokenizer(&root["pre_tokenizer"], &mut pre_tokenizers)?;
+        let byte_level = pre_tokenizers
+            .iter()
+            .any(|p| matches!(p, PreTokenizer::ByteLevel { .. }));
+
+        Ok(Self {
+            vocab,
+            merges,
+            added,
+            pre_tokenizers,
+            byte_level,
+            byte_fallback: model["byte_fallback"].as_bool().unwrap_or(false),
+            ignore_merges: model["ignore_merges"].as_bool().unwrap_or(false),
+            alphabet: byte_level_alphabet(),
+        })
+    }
+
+    fn parse_pre_tokenizer(
+        spec: &Value,
+        out: &mut Vec<PreTokenizer>,
+    ) -> Result<(), TokenizerError> {
+        if spec.is_null() {
+            return Ok(());
+        }
+        match spec["type"].as_str().unwrap_or_default() {
+            "Sequence" => {
+                for inner in spec["pretokenizers"].as_array().into_iter().flatten() {
+                    Self::parse_pre_tokenizer(inner, out)?;
+                }
+            }
+            "Split" => {
+                let pattern = match (
+                    spec["pattern"]["Regex"].as_str(),
+                    spec["pattern"]["String"].as_str(),
+                ) {
+                    (Some(re), _) => compile(re)?,
+                    (None, Some(lit)) => compile(&fancy_regex::escape(lit))?,
+                    _ => return Err(TokenizerError::Parse("Split without pattern".to_string())),
+                };
+                let invert = spec["invert"].as_bool().unwrap_or(false);
+                let behavior = match spec["behavior"].as_str().unwrap_or("Isolated") {
+                    "Removed" if invert => SplitBehavior::MatchesOnly,
+                    _ if invert => {
+                        return Err(TokenizerError::Unsupported("inverted Split".to_string()))
+                    }
+                    "Isolated" | "Contiguous" => SplitBehavior::Isolated,
+                    "Removed" => SplitBehavior::Removed,
+                    "MergedWithPrevious" => SplitBehavior::MergedWithPrevious,
+                    "MergedWithNext" => SplitBehavior::MergedWithNext,
+                    other => {
+                        return Err(TokenizerError::Unsupported(format!(
+                            "Split behavior {other}"
+                        )))
+                    }
+                };
+                out.push(PreTokenizer::Split { pattern, behavior });
+            }
+            "ByteLevel" => {
+                let pattern = if spec["use_regex"].as_bool().unwrap_or(true) {
+                    Some(compile(BYTE_LEVEL_PATTERN)?)
+                } else {
+                    None
+                };
+                out.push(PreTokenizer::ByteLevel {
+                    add_prefix_space: spec["add_prefix_space"].as_bool().unwrap_or(false),
+                    pattern,
+                });
+            }
+            "Whitespace" => out.push(PreTokenizer::Split {
+                pattern: compile(WHITESPACE_PATTERN)?,
+                behavior: SplitBehavior::MatchesOnly,
+            }),
+            other => {
+                return Err(TokenizerError::Unsupported(format!(
+                    "pre-tokenizer {other}"
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Vocabulary size (excluding added tokens).
+    pub fn vocab_size(&self) -> usize {
+        self.vocab.len()
+    }
+
+    /// Split `text` into pre-tokenized words, in byte-level alphabet when
+    /// the tokenizer is byte-level. Added tokens are not included.
+    pub fn pre_tokenize(&self, text: &str) -> Vec<String> {
+        let mut pieces = vec![text.to_string()];
+        for step in &self.pre_tokenizers {
+            pieces = match step {
+                PreTokenizer::Split { pattern, behavior } => pieces
+                    .iter()
+                    .flat_map(|p| split(p, pattern, *behavior))
+                    .collect(),
+                PreTokenizer::ByteLevel {
+                    add_prefix_space,
+                    pattern,
+                } => pieces
+                    .into_iter()
+                    .enumerate()
+                    .flat_map(|(i, p)| {
+                        let p = if *add_prefix_space && i == 0 && !p.starts_with(' ') {
+                            format!(" {p}")
+                        } else {
+                            p
+                        };
+                        match pattern {
+                            Some(re) => split(&p, re, SplitBehavior::Isolated),
+                            None => vec![p],
+                        }
+                    })
+                    .collect(),
+            };
+        }
+        if self.byte_level {
+            pieces = pieces
+                .iter()
+                .map(|p| p.bytes().map(|b| self.alphabet[b as usize]).collect())
+                .collect();
+        }
+        pieces
+    }
+
+    /// Apply BPE merges to one pre-tokenized word.
+    pub fn bpe(&self, word: &str) -> Vec<String> {
+        if self.ignore_merges && self.vocab.contains_key(word) {
+            return vec![word.to_string()];
+        }
+        let mut symbols: Vec<String> = word.chars().map(String::from).collect();
+        while symbols.len() > 1 {
+            let best = symbols
+                .windows(2)
+                .enumerate()
+                .filter_map(|(i, pair)| {
+                    self.merges
+                        .get(&(pair[0].clone(), pair[1].clone()))
+                        .map(|rank| (*rank, i))
+                })
+                .min();
+            let Some((_, i)) = best else { break };
+            let right = symbols.remove(i + 1);
+            symbols[i].push_str(&right);
+        }
+        symbols
+    }
+
+    /// Token count for one pre-tokenized word.
+    fn count_word(&self, word: &str) -> usize {
+        self.bpe(word)
+            .iter()
+            .map(|s| {
+                if self.byte_fallback && !self.vocab.contains_key(s) {
+                    s.len()
+                } else {
+                    1
+                }
+            })
+            .sum()
+    }
+
+    /// Split text around added tokens: `(segment, is_added_token)`.
+    fn segments<'t>(&self, text: &'t str) -> Vec<(&'t str, bool)> {
+        let Some(added) = &self.added else {
+            return vec![(text, false)];
+        };
+        let mut out = Vec::new();
+        let mut last = 0;
+        for m in added.find_iter(text).flatten() {
+            if m.start() > last {
+                out.push((&text[last..m.start()], false));
+            }
+            out.push((m.as_str(), true));
+            last = m.end();
+        }
+        if last < text.len() {
+            out.push((&text[last..], false));
+        }
+        out
+    }
+
+    /// Tokenize `text` into token strings.
+    pub fn tokenize(&self, text: &str) -> Vec<String> {
+        let mut tokens = Vec::new();
+        for (segment, is_added) in self.segments(text) {
+            if is_added {
+                tokens.push(segment.to_string());
+            } else {
+                for word in self.pre_tokenize(segment) {
+                    tokens.extend(self.bpe(&word));
+                }
+            }
+        }
+        tokens
+    }
+
+    /// Exact token count for `text`.
+    pub fn count(&self, text: &str) -> usize {
+        self.segments(text)
+            .into_iter()
+            .map(|(segment, is_added)| {
+                if is_added {
+                    1
+                } else {
+                    self.pre_tokenize(segment)
+                        .iter()
+                        .map(|w| self.count_word(w))
+                        .sum()
+                }
+            })
+            .sum()
+    }
+}
+
+/// Least-recently-used map from pre-tokenized word to token count.
+#[derive(Debug, Default)]
+struct WordCache {
+    capacity: usize,
+    tick: u64,
+    counts: HashMap<String, (usize, u64)>,
+    order: BTreeMap<u64, String>,
+    hits: u64,
+    misses: u64,
+}
+
+impl WordCache {
+    fn get(&mut self, word: &str) -> Option<usize> {
+        self.tick += 1;
+        let tick = self.tick;
+        match self.counts.get_mut(word) {
+            Some((count, used)) => {
+                self.order.remove(used);
+                *used = tick;
+                self.order.insert(tick, word.to_string());
+                self.hits += 1;
+                Some(*count)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, word: String, count: usize) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.counts.len() >= self.capacity {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            self.counts.remove(&oldest);
+        }
+        self.tick += 1;
+        self.order.insert(self.tick, word.clone());
+        self.counts.insert(word, (count, self.tick));
+    }
+}
+
+/// Cache statistics for [`BpeEstimator`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Cached words.
+    pub entries: usize,
+    /// Lookups served from the cache.
+    pub hits: u64,
+    /// Lookups that ran BPE.
+    pub misses: u64,
+}
+
+/// [`TokenEstimator`] backed by an exact BPE tokenizer with an LRU word cache.
+#[derive(Debug)]
+pub struct BpeEstimator {
+    tokenizer: BpeTokenizer,
+    cache: Mutex<WordCache>,
+}
+
+impl BpeEstimator {
+    /// Default number of cached words.
+    pub const DEFAULT_CACHE_CAPACITY: usize = 16_384;
+
+    /// Wrap a loaded tokenizer.
+    pub fn new(tokenizer: BpeTokenizer, cache_capacity: usize) -> Self {
+        Self {
+            tokenizer,
+            cache: Mutex::new(WordCache {
+                capacity: cache_capacity,
+                ..WordCache::default()
+            }),
+        }
+    }
+
+    /// Load `tokenizer.json` from `path`.
+    pub fn from_file(path: &Path, cache_capacity: usize) -> Result<Self, TokenizerError> {
+        Ok(Self::new(BpeTokenizer::from_file(path)?, cache_capacity))
+    }
+
+    /// Underlying tokenizer.
+    pub fn tokenizer(&self) -> &BpeTokenizer {
+        &self.tokenizer
+    }
+
+    /// Current cache statistics.
+    pub fn cache_stats(&self) -> CacheStats {
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        CacheStats {
+            entries: cache.counts.len(),
+            hits: cache.hits,
+            misses: cache.misses,
+        }
+    }
+
+    fn count(&self, text: &str) -> usize {
+        let mut total = 0;
+        for (segment, is_added) in self.tokenizer.segments(text) {
+            if is_added {
+                total += 1;
+                continue;
+            }
+            for word in self.tokenizer.pre_tokenize(segment) {
+                let cached = self
+                    .cache
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .get(&word);
+                total += match cached {
+                    Some(count) => count,
+                    None => {
+                        let count = self.tokenizer.count_word(&word);
+                        self.cache
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .insert(word, count);
+                        count
+                    }
+                };
+            }
+        }
+        total
+    }
+}
+
+impl TokenEstimator for BpeEstimator {
+    fn estimate(&self, text: &str) -> u32 {
+        self.count(text).min(u32::MAX as usize) as u32
+    }
+
+    fn name(&self) -> &str {
+        "bpe"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tiny_tokenizer.json")
+    }
+
+    fn tokenizer() -> BpeTokenizer {
+        BpeTokenizer::from_file(&fixture()).unwrap()
+    }
+
+    #[test]
+    fn test_load_fixture() {
+        let tok = tokenizer();
+        assert_eq!(tok.vocab_size(), 256 + 11);
+        assert!(tok.byte_level);
+    }
+
+    #[test]
+    fn test_merges_applied_in_rank_order() {
+        let tok = tokenizer();
+        assert_eq!(tok.tokenize("hello world"), vec!["hello", "Ġworld"]);
+        assert_eq!(tok.tokenize("hellx"), vec!["hell", "x"]);
+        assert_eq!(tok.count("hello world!"), 3);
+    }
+
+    #[test]
+    fn test_lookahead_whitespace_split() {
+        // `\s+(?!\S)` leaves the last space to prefix the next word.
+        let tok = tokenizer();
+        assert_eq!(tok.pre_tokenize("a  b"), vec!["a", "Ġ", "Ġb"]);
+        assert_eq!(tok.count("a  b"), 3);
+    }
+
+    #[test]
+    fn test_byte_level_maps_non_ascii() {
+        let tok = tokenizer();
+        // "é" is two UTF-8 bytes, each its own byte-level symbol.
+        assert_eq!(tok.count("é"), 2);
+        assert_eq!(tok.pre_tokenize("\n"), vec!["Ċ"]);
+    }
+
+    #[test]
+    fn test_added_tokens_count_once() {
+        let tok = tokenizer();
+        let text = "<|im_start|>hello<|im_end|>";
+        assert_eq!(
+            tok.tokenize(text),
+            vec!["<|im_start|>", "hello", "<|im_end|>"]
+        );
+        assert_eq!(tok.count(text), 3);
+    }
+
+    #[test]
+    fn test_estimator_caches_words() {
+        let est = BpeEstimator::from_file(&fixture(), 8).unwrap();
+        // "hello", "Ġworld", "Ġhello" (2 tokens), "Ġworld"
+        assert_eq!(est.estimate("hello world hello world"), 5);
+        let stats = est.cache_stats();
+        assert_eq!(stats.misses, 3);
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.entries, 3);
+        assert_eq!(est.name(), "bpe");
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recent() {
+        let est = BpeEstimator::new(tokenizer(), 2);
+        est.estimate("a b");
+        est.estimate("a");
+        est.estimate("c");
+        let stats = est.cache_stats();
+        assert_eq!(stats.entries, 2);
+        // "a" was used most recently before "c", so "Ġb" was evicted.
+        est.estimate("a");
+        assert_eq!(est.cache_stats().hits, stats.hits + 1);
+        est.estimate(" b");
+        assert_eq!(est.cache_stats().misses, stats.misses + 1);
+    }
+
+    #[test]
+    fn test_merges_as_pairs() {
+        let json = r#"{
+            "model": {"type": "BPE", "vocab": {"a": 0, "b": 1, "ab": 2},
+                      "merges": [["a", "b"]]},
+            "pre_tokenizer": {"type": "Whitespace"}
+        }"#;
+        let tok = BpeTokenizer::from_json(json).unwrap();
+        assert_eq!(tok.tokenize("ab ba"), vec!["ab", "b", "a"]);
+    }
+
+    #[test]
+    fn test_byte_fallback_counts_bytes() {
+        let json = r#"{
+            "model": {"type": "BPE", "vocab": {"a": 0}, "merges": [],
+                      "byte_fallback": true},
+            "pre_tokenizer": null
+        }"#;
+        let tok = BpeTokenizer::from_json(json).unwrap();
+        assert_eq!(tok.count("aé"), 3);
+    }
+
+    #[test]
+    fn test_rejects_unsupported_model() {
+        let err = BpeTokenizer::from_json(r#"{"model": {"type": "Unigram"}}"#).unwrap_err();
+        assert!(matches!(err, TokenizerError::Unsupported(_)));
+        let err = BpeTokenizer::from_file(Path::new("/nonexistent/tokenizer.json")).unwrap_err();
+        assert!(matches!(err, TokenizerError::Io { .. }));
+    }
+}
@@ -3,6 +3,7 @@
 //! Tracks which providers are available, their capabilities (context window,
 //! supported features), and live health metadata (availability, latency, error rates).
 
+use crate::state::catalog::{ModelCatalog, ModelSpec};
 use crate::state::types::{ModelId, ModelKind};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -36,48 +37,35 @@ pub struct ProviderCapabilities {
 }
 
 impl ProviderCapabilities {
-    /// Default capabilities for each known ModelId
+    /// Capabilities declared in a catalog entry
+    pub fn from_spec(spec: &ModelSpec) -> Self {
+        Self {
+            context_window: spec.context_window,
+            max_output_tokens: spec.max_output_tokens,
+            supports_streaming: spec.capabilities.streaming,
+            supports_tool_calls: spec.capabilities.tool_calls,
+            supports_reasoning: spec.capabilities.reasoning,
+            tokens_per_sec: spec.tokens_per_sec,
+            is_local: spec.local,
+            specialty: spec.specialty.clone(),
+        }
+    }
+
+    /// Capabilities of a model in the active catalog
+    ///
+    /// Uncatalogued models get a conservative 8K-context, text-only profile.
     pub fn for_model(model: ModelId) -> Self {
-        match model {
-            ModelId::Opus45 => Self {
-                context_window: 200_000,
-                max_output_tokens: 32_000,
-                supports_streaming: true,
-                supports_tool_calls: true,
-                supports_reasoning: true,
-                tokens_per_sec: None,
-                is_local: false,
-                specialty: "Architecture, safety, code review".to_string(),
-            },
-            ModelId::Gemini3Pro => Self {
-                context_window: 1_000_000,
-                max_output_tokens: 8_192,
-                supports_streaming: true,
-                supports_tool_calls: true,
+        match ModelCatalog::active().get(model) {
+            Some(spec) => Self::from_spec(spec),
+            None => Self {
+                context_window: 8_192,
+                max_output_tokens: 2_048,
+                supports_streaming: false,
+                supports_tool_calls: false,
                 supports_reasoning: false,
                 tokens_per_sec: None,
                 is_local: false,
-                specialty: "Repository context, documentation, code navigation".to_string(),
-            },
-            ModelId::Qwen35 => Self {
-                context_window: 32_768,
-                max_output_tokens: 8_192,
-                supports_streaming: true,
-                supports_tool_calls: false,
-                supports_reasoning: true,
-                tokens_per_sec: Some(8),
-                is_local: true,
-                specialty: "Reasoning, planning, task decomposition".to_string(),
-            },
-            ModelId::HydraCoder => Self {
-                context_window: 16_384,
-                max_output_tokens: 4_096,
-                supports_streaming: true,
-                supports_tool_calls: false,
-                supports_reasoning: false,
-                tokens_per_sec: Some(40),
-                is_local: true,
-                specialty: "Rust code generation and error fixing".to_string(),
+                specialty: String::new(),
             },
         }
     }
@@ -164,7 +152,7 @@ impl ProviderEntry {
     pub fn new(model_id: ModelId) -> Self {
         Self {
             kind: model_id.kind(),
-            api_name: model_id.api_name().to_string(),
+            api_name: model_id.api_name(),
             capabilities: ProviderCapabilities::for_model(model_id),
             health: ProviderHealth::healthy(),
             model_id,
@@ -183,12 +171,28 @@ pub struct ProviderRegistry {
 }
 
 impl ProviderRegistry {
-    /// Create a registry pre-populated with all known models
+    /// Create a registry pre-populated with the active catalog's models
     pub fn new() -> Self {
-        let mut entries = HashMap::new();
-        for &model in ModelId::all() {
-            entries.insert(model, ProviderEntry::new(model));
-        }
+        Self::from_catalog(&ModelCatalog::active())
+    }
+
+    /// Create a registry for every model in a catalog
+    pub fn from_catalog(catalog: &ModelCatalog) -> Self {
+        let entries = catalog
+            .specs()
+            .iter()
+            .map(|spec| {
+                let model_id = spec.model_id();
+                let entry = ProviderEntry {
+                    model_id,
+                    kind: spec.kind,
+                    api_name: spec.api_name.clone(),
+                    capabilities: ProviderCapabilities::from_spec(spec),
+                    health: ProviderHealth::healthy(),
+                };
+                (model_id, entry)
+            })
+            .collect();
         Self { entries }
     }
 
@@ -260,25 +264,49 @@ mod tests {
     #[test]
     fn test_registry_populated() {
         let registry = ProviderRegistry::new();
-        assert!(registry.get(ModelId::Opus45).is_some());
-        assert!(registry.get(ModelId::Gemini3Pro).is_some());
-        assert!(registry.get(ModelId::Qwen35).is_some());
-        assert!(registry.get(ModelId::HydraCoder).is_some());
+        assert!(registry.get(ModelId::OPUS_45).is_some());
+        assert!(registry.get(ModelId::GEMINI_3_PRO).is_some());
+        assert!(registry.get(ModelId::QWEN35).is_some());
+        assert!(registry.get(ModelId::HYDRA_CODER).is_some());
     }
 
     #[test]
     fn test_provider_capabilities() {
-        let caps = ProviderCapabilities::for_model(ModelId::Opus45);
+        let caps = ProviderCapabilities::for_model(ModelId::OPUS_45);
         assert_eq!(caps.context_window, 200_000);
         assert!(caps.supports_tool_calls);
         assert!(!caps.is_local);
 
-        let caps = ProviderCapabilities::for_model(ModelId::HydraCoder);
+        let caps = ProviderCapabilities::for_model(ModelId::HYDRA_CODER);
         assert_eq!(caps.context_window, 16_384);
         assert_eq!(caps.tokens_per_sec, Some(40));
         assert!(caps.is_local);
     }
 
+    #[test]
+    fn test_registry_from_custom_catalog() {
+        let catalog = ModelCatalog::from_toml_str(
+            r#"
+[[models]]
+id = "gemma-4-31b"
+kind = "worker"
+api_name = "gemma-4-31b-it.gguf"
+local = true
+context_window = 65536
+capabilities = { tool_calls = true }
+"#,
+        )
+        .unwrap();
+        let registry = ProviderRegistry::from_catalog(&catalog);
+        let gemma = catalog.resolve("gemma-4-31b").unwrap();
+
+        let entry = registry.get(gemma).unwrap();
+        assert_eq!(entry.kind, ModelKind::Worker);
+        assert_eq!(entry.api_name, "gemma-4-31b-it.gguf");
+        assert!(entry.capabilities.supports_tool_calls);
+        assert!(registry.get(ModelId::OPUS_45).is_none());
+    }
+
     #[test]
     fn test_health_success_rate() {
         let mut h = ProviderHealth::healthy();
@@ -291,15 +319,15 @@ mod tests {
 
     #[test]
     fn test_provider_entry_usable() {
-        let entry = ProviderEntry::new(ModelId::Opus45);
+        let entry = ProviderEntry::new(ModelId::OPUS_45);
         assert!(entry.is_usable());
     }
 
     #[test]
     fn test_mark_unavailable() {
         let mut registry = ProviderRegistry::new();
-        registry.mark_unavailable(ModelId::HydraCoder, "maintenance");
-        let entry = registry.get(ModelId::HydraCoder).unwrap();
+        registry.mark_unavailable(ModelId::HYDRA_CODER, "maintenance");
+        let entry = registry.get(ModelId::HYDRA_CODER).unwrap();
         assert!(!entry.health.available);
         assert!(!entry.is_usable());
     }
@@ -307,7 +335,7 @@ mod tests {
     #[test]
     fn test_usable_by_kind() {
         let mut registry = ProviderRegistry::new();
-        registry.mark_unavailable(ModelId::HydraCoder, "down");
+        registry.mark_unavailable(ModelId::HYDRA_CODER, "down");
         let workers = registry.usable_by_kind(ModelKind::Worker);
         assert!(workers.is_empty());
 
@@ -319,7 +347,7 @@ mod tests {
     fn test_ranked_by_health() {
         let mut registry = ProviderRegistry::new();
         // Degrade Opus45 by recording failures
-        if let Some(entry) = registry.get_mut(ModelId::Opus45) {
+        if let Some(entry) = registry.get_mut(ModelId::OPUS_45) {
             entry.health.record_failure();
             entry.health.record_failure();
         }
@@ -328,7 +356,7 @@ mod tests {
         // Opus45 should be ranked lower due to failures
         let opus_pos = ranked
             .iter()
-            .position(|e| e.model_id == ModelId::Opus45)
+            .position(|e| e.model_id == ModelId::OPUS_45)
             .unwrap();
         assert!(opus_pos > 0);
     }
@@ -0,0 +1,533 @@
+//! Ranked Repository Map — token-bounded outline of the most relevant symbols.
+//!
+//! Gives scout agents a compact view of a repository instead of letting them
+//! spend turns listing directories. Symbols and their call / reference /
+//! implements edges come from the in-process [`CodeGraph`]; symbols are ranked
+//! with personalized PageRank seeded by the issue's target files and
+//! identifiers, then rendered as per-file signature outlines until the token
+//! budget is spent.
+//!
+//! # Output
+//!
+//! ```text
+//! src/store.rs:
+//!   6│ pub struct Store
+//!  11│ pub fn open() -> Self
+//! src/app.rs:
+//!   4│ pub fn run()
+//! ```
+
+use crate::memory::budget::{CharCountEstimator, TokenEstimator};
+use crate::reviewer_tools::code_graph::CodeGraph;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+/// Configuration for repo map generation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoMapConfig {
+    /// Maximum estimated tokens for the rendered map.
+    pub token_budget: u32,
+    /// PageRank damping factor (probability of following an edge).
+    pub damping: f64,
+    /// Maximum power-iteration rounds.
+    pub max_iterations: usize,
+    /// Convergence threshold on the L1 change between rounds.
+    pub tolerance: f64,
+    /// Whether symbols from test files are eligible.
+    pub include_tests: bool,
+}
+
+impl Default for RepoMapConfig {
+    fn default() -> Self {
+        Self {
+            token_budget: 1_024,
+            damping: 0.85,
+            max_iterations: 50,
+            tolerance: 1e-6,
+            include_tests: false,
+        }
+    }
+}
+
+/// Personalization seeds for ranking.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepoMapSeeds {
+    /// Files the issue targets (suffix-matched against repo paths).
+    pub files: Vec<String>,
+    /// Identifiers mentioned by the issue (bare or `Type::item`).
+    pub identifiers: Vec<String>,
+}
+
+impl RepoMapSeeds {
+    /// Extract seeds from free-form issue text.
+    ///
+    /// Picks up source-file paths (`src/foo.rs`), path-qualified symbols
+    /// (`Store::open`), backticked spans, CamelCase type names, and
+    /// snake_case identifiers containing an underscore.
+    pub fn from_issue(text: &str) -> Self {
+        let mut files = Vec::new();
+        let mut identifiers = Vec::new();
+        let mut seen = HashSet::new();
+
+        for raw in text.split(|c: char| c.is_whitespace() || ",;()[]{}<>\"'".contains(c)) {
+            let token = raw
+                .trim_matches('`')
+                .trim_end_matches(['.', ':', '!', '?'])
+                .trim_end_matches("()");
+            if token.len() < 3 || !seen.insert(token.to_string()) {
+                continue;
+            }
+            if is_source_path(token) {
+                files.push(token.trim_start_matches("./").to_string());
+            } else if is_identifier(token) {
+                let backticked = raw.starts_with('`');
+                let camel = token.chars().next().is_some_and(char::is_uppercase)
+                    && token.chars().any(char::is_lowercase);
+                if backticked || camel || token.contains('_') || token.contains("::") {
+                    identifiers.push(token.to_string());
+                }
+            }
+        }
+        Self { files, identifiers }
+    }
+
+    /// Whether no seeds were provided.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.identifiers.is_empty()
+    }
+}
+
+fn is_source_path(token: &str) -> bool {
+    let Some((_, ext)) = token.rsplit_once('.') else {
+        return false;
+    };
+    matches!(ext, "rs" | "py" | "ts" | "tsx" | "go")
+        && token
+            .chars()
+            .all(|c| c.is_alphanumeric() || "/_-.".contains(c))
+}
+
+fn is_identifier(token: &str) -> bool {
+    token.split("::").all(|seg| {
+        !seg.is_empty()
+            && seg.chars().all(|c| c.is_alphanumeric() || c == '_')
+            && !seg.starts_with(|c: char| c.is_ascii_digit())
+    })
+}
+
+/// A ranked symbol included in the map.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankedSymbol {
+    /// Qualified symbol name.
+    pub symbol: String,
+    /// Symbol kind (function, method, struct, ...).
+    pub kind: String,
+    /// Line number (1-indexed).
+    pub line: u32,
+    /// Declaration signature.
+    pub signature: String,
+    /// Personalized PageRank score.
+    pub rank: f64,
+}
+
+/// A file section of the map.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoMapFile {
+    /// File path relative to the repository root.
+    pub path: String,
+    /// Highest rank among the file's included symbols.
+    pub rank: f64,
+    /// Included symbols in line order.
+    pub symbols: Vec<RankedSymbol>,
+}
+
+/// A rendered, token-bounded repository map.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoMap {
+    /// File sections in descending rank order.
+    pub files: Vec<RepoMapFile>,
+    /// Rendered outline text.
+    pub rendered: String,
+    /// Estimated tokens of `rendered`.
+    pub estimated_tokens: u32,
+    /// Symbols included in the map.
+    pub symbols_included: usize,
+    /// Eligible symbols before the budget was applied.
+    pub symbols_total: usize,
+    /// Whether symbols were dropped to fit the budget.
+    pub truncated: bool,
+}
+
+impl RepoMap {
+    /// Compact summary line.
+    pub fn summary_line(&self) -> String {
+        format!(
+            "[repo-map] {} symbols in {} files (~{} tokens{})",
+            self.symbols_included,
+            self.files.len(),
+            self.estimated_tokens,
+            if self.truncated { ", truncated" } else { "" }
+        )
+    }
+}
+
+fn is_test_path(file: &str) -> bool {
+    file.starts_with("tests/") || file.contains("/tests/") || file.ends_with("_test.rs")
+}
+
+/// Personalized PageRank over the code graph.
+///
+/// `personalization` maps node indices to non-negative weights; it is
+/// normalized internally and falls back to uniform when empty. Dangling
+/// nodes (no outgoing edges) teleport according to the personalization.
+pub fn personalized_page_rank(
+    graph: &CodeGraph,
+    personalization: &HashMap<usize, f64>,
+    config: &RepoMapConfig,
+) -> Vec<f64> {
+    let g = graph.graph();
+    let n = g.node_count();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let mut p = vec![0.0; n];
+    let total: f64 = personalization.values().filter(|w| **w > 0.0).sum();
+    if total > 0.0 {
+        for (&idx, &w) in personalization {
+            if idx < n && w > 0.0 {
+                p[idx] = w / total;
+            }
+        }
+    } else {
+        p.iter_mut().for_each(|v| *v = 1.0 / n as f64);
+    }
+
+    let out_degree: Vec<usize> = g.node_indices().map(|i| g.edges(i).count()).collect();
+
+    let mut rank = p.clone();
+    for _ in 0..config.max_iterations {
+        let dangling: f64 = (0..n)
+            .filter(|&i| out_degree[i] == 0)
+            .map(|i| rank[i])
+            .sum();
+        let mut next: Vec<f64> = p
+            .iter()
+            .map(|pi| (1.0 - config.damping + config.damping * dangling) * pi)
+            .collect();
+        for edge in g.edge_references() {
+            let from = edge.source().index();
+            next[edge.target().index()] += config.damping * rank[from] / out_degree[from] as f64;
+        }
+        let delta: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if delta < config.tolerance {
+            break;
+        }
+    }
+    rank
+}
+
+/// Personalization weights from seeds.
+///
+/// Symbols defined in seed files get weight 1; symbols named by a seed
+/// identifier get weight 2 so an explicitly mentioned item outranks its
+/// neighbours in the same file.
+fn seed_weights(graph: &CodeGraph, seeds: &RepoMapSeeds) -> HashMap<usize, f64> {
+    let mut weights: HashMap<usize, f64> = HashMap::new();
+    for idx in graph.graph().node_indices() {
+        let def = &graph.graph()[idx];
+        if seeds
+            .files
+            .iter()
+            .any(|f| def.file == *f || def.file.ends_with(&format!("/{f}")))
+        {
+            *weights.entry(idx.index()).or_default() += 1.0;
+        }
+    }
+    for ident in &seeds.identifiers {
+        for idx in graph.find_nodes(ident) {
+            *weights.entry(idx.index()).or_default() += 2.0;
+        }
+    }
+    weights
+}
+
+/// Render file sections as an outline.
+fn render(files: &[RepoMapFile]) -> String {
+    let mut out = String::new();
+    for file in files {
+        out.push_str(&file.path);
+        out.push_str(":\n");
+        for sym in &file.symbols {
+            out.push_str(&format!("{:>4}│ {}\n", sym.line, sym.signature));
+        }
+    }
+    out
+}
+
+/// Build a ranked, token-bounded repo map from an existing code graph.
+///
+/// Symbols are added greedily in rank order while the rendered map stays
+/// within `config.token_budget` as measured by `estimator`.
+pub fn build_repo_map(
+    graph: &CodeGraph,
+    seeds: &RepoMapSeeds,
+    config: &RepoMapConfig,
+    estimator: &dyn TokenEstimator,
+) -> RepoMap {
+    let ranks = personalized_page_rank(graph, &seed_weights(graph, seeds), config);
+    let g = graph.graph();
+
+    let mut candidates: Vec<(NodeIndex, f64)> = g
+        .node_indices()
+        .filter(|&i| config.include_tests || !is_test_path(&g[i].file))
+        .map(|i| (i, ranks[i.index()]))
+        .collect();
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let symbols_total = candidates.len();
+
+    let mut by_file: BTreeMap<String, Vec<RankedSymbol>> = BTreeMap::new();
+    let mut used_tokens = 0u32;
+    let mut included = 0usize;
+    for (idx, rank) in candidates {
+        let def = &g[idx];
+        let line = format!("{:>4}│ {}\n", def.line, def.signature);
+        let mut cost = estimator.estimate(&line);
+        if !by_file.contains_key(&def.file) {
+            cost += estimator.estimate(&format!("{}:\n", def.file));
+        }
+        if used_tokens + cost > config.token_budget {
+            continue;
+        }
+        used_tokens += cost;
+        included += 1;
+        by_file
+            .entry(def.file.clone())
+            .or_default()
+            .push(RankedSymbol {
+                symbol: def.qualified.clone(),
+                kind: def.kind.clone(),
+                line: def.line,
+                signature: def.signature.clone(),
+                rank,
+            });
+    }
+
+    let mut files: Vec<RepoMapFile> = by_file
+        .into_iter()
+        .map(|(path, mut symbols)| {
+            symbols.sort_by_key(|s| s.line);
+            let rank = symbols.iter().map(|s| s.rank).fold(0.0, f64::max);
+            RepoMapFile {
+                path,
+                rank,
+                symbols,
+            }
+        })
+        .collect();
+    files.sort_by(|a, b| b.rank.total_cmp(&a.rank).then_with(|| a.path.cmp(&b.path)));
+
+    let rendered = render(&files);
+    RepoMap {
+        estimated_tokens: estimator.estimate(&rendered),
+        files,
+        rendered,
+        symbols_included: included,
+        symbols_total,
+        truncated: included < symbols_total,
+    }
+}
+
+/// Build the code graph for `root` and generate a repo map.
+///
+/// Uses the character-count token estimator.
+pub fn generate_repo_map(
+    root: &Path,
+    seeds: &RepoMapSeeds,
+    config: &RepoMapConfig,
+) -> Result<RepoMap, String> {
+    let graph = CodeGraph::build(root)?;
+    Ok(build_repo_map(
+        &graph,
+        seeds,
+        config,
+        &CharCountEstimator::default(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STORE: &str = r#"
+pub struct Store {
+    items: Vec<String>,
+}
+
+impl Store {
+    pub fn open() -> Self {
+        Store { items: Vec::new() }
+    }
+
+    pub fn insert(&mut self, key: &str) {
+        self.items.push(key.to_string());
+    }
+}
+"#;
+
+    const APP: &str = r#"
+pub fn run() {
+    let mut store = Store::open();
+    store.insert("a");
+    helper();
+}
+
+fn helper() {}
+"#;
+
+    const UTIL: &str = r#"
+pub fn format_bytes(n: u64) -> String {
+    format!("{n}")
+}
+
+pub fn unrelated_thing() {}
+"#;
+
+    fn graph() -> CodeGraph {
+        CodeGraph::from_sources(&[
+            ("src/store.rs", STORE),
+            ("src/app.rs", APP),
+            ("src/util.rs", UTIL),
+            (
+                "tests/store_test.rs",
+                "#[test]\nfn t() { Store::open(); }\n",
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_seeds_from_issue() {
+        let seeds = RepoMapSeeds::from_issue(
+            "Crash in `Store::open` when src/store.rs is empty; see format_bytes and the Store type.",
+        );
+        assert_eq!(seeds.files, vec!["src/store.rs"]);
+        assert!(seeds.identifiers.contains(&"Store::open".to_string()));
+        assert!(seeds.identifiers.contains(&"format_bytes".to_string()));
+        assert!(seeds.identifiers.contains(&"Store".to_string()));
+        assert!(!seeds.identifiers.contains(&"when".to_string()));
+        assert!(RepoMapSeeds::default().is_empty());
+    }
+
+    #[test]
+    fn test_page_rank_sums_to_one() {
+        let g = graph();
+        let ranks = personalized_page_rank(&g, &HashMap::new(), &RepoMapConfig::default());
+        assert_eq!(ranks.len(), g.symbol_count());
+        let total: f64 = ranks.iter().sum();
+        assert!((total - 1.0).abs() < 1e-6, "total = {total}");
+    }
+
+    #[test]
+    fn test_referenced_symbols_rank_higher() {
+        let g = graph();
+        let map = build_repo_map(
+            &g,
+            &RepoMapSeeds::default(),
+            &RepoMapConfig::default(),
+            &CharCountEstimator::default(),
+        );
+        let rank_of = |name: &str| {
+            map.files
+                .iter()
+                .flat_map(|f| &f.symbols)
+                .find(|s| s.symbol == name)
+                .map(|s| s.rank)
+                .unwrap()
+        };
+        assert!(rank_of("Store::open") > rank_of("unrelated_thing"));
+        assert!(rank_of("helper") > rank_of("run"));
+    }
+
+    #[test]
+    fn test_seeds_personalize_ranking() {
+        let g = graph();
+        let seeds = RepoMapSeeds {
+            files: vec!["util.rs".to_string()],
+            identifiers: vec!["format_bytes".to_string()],
+        };
+        let map = build_repo_map(
+            &g,
+            &seeds,
+            &RepoMapConfig::default(),
+            &CharCountEstimator::default(),
+        );
+        assert_eq!(map.files[0].path, "src/util.rs");
+        let top = map.files[0]
+            .symbols
+            .iter()
+            .max_by(|a, b| a.rank.total_cmp(&b.rank))
+            .unwrap();
+        assert_eq!(top.symbol, "format_bytes");
+    }
+
+    #[test]
+    fn test_token_budget_and_test_filtering() {
+        let g = graph();
+        let full = build_repo_map(
+            &g,
+            &RepoMapSeeds::default(),
+            &RepoMapConfig::default(),
+            &CharCountEstimator::default(),
+        );
+        assert!(!full.truncated);
+        assert!(!full.rendered.contains("tests/"));
+        assert!(full.rendered.contains("src/store.rs:\n"));
+        assert!(full.rendered.contains("   2│ pub struct Store\n"));
+
+        let config = RepoMapConfig {
+            token_budget: 20,
+            ..RepoMapConfig::default()
+        };
+        let small = build_repo_map(
+            &g,
+            &RepoMapSeeds::default(),
+            &config,
+            &CharCountEstimator::default(),
+        );
+        assert!(small.truncated);
+        assert!(small.symbols_included < full.symbols_included);
+        assert!(small.estimated_tokens <= 20);
+        assert!(small.summary_line().contains("truncated"));
+
+        let with_tests = build_repo_map(
+            &g,
+            &RepoMapSeeds::default(),
+            &RepoMapConfig {
+                include_tests: true,
+                ..RepoMapConfig::default()
+            },
+            &CharCountEstimator::default(),
+        );
+        assert!(with_tests.rendered.contains("tests/store_test.rs"));
+    }
+
+    #[test]
+    fn test_generate_from_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/store.rs"), STORE).unwrap();
+        std::fs::write(dir.path().join("src/app.rs"), APP).unwrap();
+        let map = generate_repo_map(
+            dir.path(),
+            &RepoMapSeeds::from_issue("fix src/app.rs"),
+            &RepoMapConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(map.files.len(), 2);
+        assert_eq!(map.files[0].path, "src/app.rs");
+    }
+}
@@ -20,8 +20,8 @@
 //!
 //! let chain = FallbackChain::new("ast_analysis")
 //!     .add_tier("ast_grep", 1.0)
-//!     .add_tier("regex_grep", 0.7)
-//!     .add_tier("text_search", 0.4);
+//!     .add_tier("tree_sitter", 0.85)
+//!     .add_tier("regex_grep", 0.5);
 //!
 //! // Try each tier, return first success with appropriate confidence
 //! let response = chain.execute(|tier| try_tool(tier));
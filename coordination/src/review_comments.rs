@@ -0,0 +1,574 @@
+//! Review Comments — line-anchored export of reviewer findings.
+//!
+//! Converts a [`ReviewDecisionResult`] into a [`ReviewPayload`] shaped like
+//! the pull-request review APIs of common forges (`event`, `body`, and
+//! `comments[]` with `path`/`side`/`line`/`start_line`), plus a Markdown
+//! summary for humans.
+//!
+//! Comment bodies are enriched from two sources:
+//!
+//! - **Rule packs** — issues tagged `[rule-id]` get the rule's severity,
+//!   category and description.
+//! - **Compiler diagnostics** — machine-applicable rustc/clippy fixes become
+//!   ```` ```suggestion ```` blocks anchored to the lines they replace.
+//!   Diagnostics that no issue points at are exported as their own comments.
+//!
+//! Issues without a file/line cannot be anchored and go into the review body.
+
+use crate::diagnostics::{CompilerDiagnostic, DiagnosticLevel, DiagnosticSuggestion};
+use crate::reviewer_tools::{RulePackEntry, RulePackRegistry};
+use crate::sarif::{repo_relative, rules_by_id, tagged_rule_id};
+use crate::tool_schema::{ReviewDecisionResult, ReviewIssue, ReviewVerdict};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Which side of the diff a comment is attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CommentSide {
+    /// The base version (deleted or unchanged lines).
+    Left,
+    /// The changed version (added or unchanged lines).
+    Right,
+}
+
+/// Review action requested from the forge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReviewEvent {
+    /// Approve the change.
+    Approve,
+    /// Block the change until issues are fixed.
+    RequestChanges,
+    /// Leave comments without a verdict.
+    Comment,
+}
+
+impl ReviewEvent {
+    /// Map a reviewer verdict to a review event.
+    ///
+    /// Escalations leave comments only, so a human makes the call.
+    pub fn from_verdict(verdict: ReviewVerdict) -> Self {
+        match verdict {
+            ReviewVerdict::Pass => Self::Approve,
+            ReviewVerdict::Fail => Self::RequestChanges,
+            ReviewVerdict::NeedsEscalation => Self::Comment,
+        }
+    }
+}
+
+/// A comment anchored to a line (or line range) of a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewComment {
+    /// File path relative to the repository root.
+    pub path: String,
+    /// Diff side of `line`.
+    pub side: CommentSide,
+    /// Last (or only) line the comment covers (1-indexed).
+    pub line: u32,
+    /// First line of a multi-line comment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    /// Diff side of `start_line`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_side: Option<CommentSide>,
+    /// Markdown body, including any suggestion block.
+    pub body: String,
+    /// Whether the finding blocks merging (not sent to the forge).
+    #[serde(skip)]
+    pub blocking: bool,
+}
+
+impl ReviewComment {
+    fn new(path: String, start: u32, end: u32, body: String, blocking: bool) -> Self {
+        let multi = end > start;
+        Self {
+            path,
+            side: CommentSide::Right,
+            line: end.max(start),
+            start_line: multi.then_some(start),
+            start_side: multi.then_some(CommentSide::Right),
+            body,
+            blocking,
+        }
+    }
+
+    /// `path:line` or `path:start-end`.
+    pub fn location(&self) -> String {
+        match self.start_line {
+            Some(start) => format!("{}:{}-{}", self.path, start, self.line),
+            None => format!("{}:{}", self.path, self.line),
+        }
+    }
+
+    /// Whether the body carries a suggested change.
+    pub fn has_suggestion(&self) -> bool {
+        self.body.contains("```suggestion")
+    }
+}
+
+/// A complete review, ready to post.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewPayload {
+    /// Review action.
+    pub event: ReviewEvent,
+    /// Top-level review body (verdict and unanchored findings).
+    pub body: String,
+    /// Line-anchored comments.
+    pub comments: Vec<ReviewComment>,
+}
+
+/// A replacement for a contiguous block of lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LineSuggestion {
+    start: u32,
+    end: u32,
+    text: String,
+}
+
+impl LineSuggestion {
+    fn block(&self) -> String {
+        format!("```suggestion\n{}\n```", self.text)
+    }
+}
+
+/// Apply span replacements to `content`, returning the rewritten lines.
+///
+/// Returns `None` when a span falls outside the file or spans overlap.
+fn apply_fixes(content: &str, fixes: &[&DiagnosticSuggestion]) -> Option<LineSuggestion> {
+    let start = fixes.iter().map(|f| f.line_start).min()?;
+    let end = fixes.iter().map(|f| f.line_end).max()?;
+    let lines: Vec<&str> = content.lines().collect();
+    if start == 0 || end as usize > lines.len() {
+        return None;
+    }
+    let block: Vec<Vec<char>> = lines[start as usize - 1..end as usize]
+        .iter()
+        .map(|l| l.chars().collect())
+        .collect();
+
+    // Char offset of a 1-indexed (line, column) within the joined block.
+    let offset = |line: u32, column: u32| -> Option<usize> {
+        let row = (line - start) as usize;
+        let col = (column as usize).checked_sub(1)?;
+        if col > block[row].len() {
+            return None;
+        }
+        Some(block[..row].iter().map(|l| l.len() + 1).sum::<usize>() + col)
+    };
+
+    let mut spans = fixes
+        .iter()
+        .map(|f| {
+            Some((
+                offset(f.line_start, f.column_start)?,
+                offset(f.line_end, f.column_end)?,
+                f.replacement.as_str(),
+            ))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    spans.sort_by_key(|s| std::cmp::Reverse(s.0));
+    if spans.windows(2).any(|w| w[1].1 > w[0].0) || spans.iter().any(|s| s.1 < s.0) {
+        return None;
+    }
+
+    let mut text: Vec<char> = block.join(&'\n');
+    for (from, to, replacement) in spans {
+        text.splice(from..to, replacement.chars());
+    }
+    Some(LineSuggestion {
+        start,
+        end,
+        text: text.into_iter().collect(),
+    })
+}
+
+/// Converts reviewer output into line-anchored comments and Markdown.
+pub struct ReviewCommentExporter<'a> {
+    repo_root: PathBuf,
+    rules: BTreeMap<&'a str, &'a RulePackEntry>,
+    diagnostics: &'a [CompilerDiagnostic],
+}
+
+impl<'a> ReviewCommentExporter<'a> {
+    /// Create an exporter; paths are made relative to `repo_root`, and
+    /// suggestion blocks read the current file contents from it.
+    pub fn new(repo_root: &Path) -> Self {
+        Self {
+            repo_root: repo_root.to_path_buf(),
+            rules: BTreeMap::new(),
+            diagnostics: &[],
+        }
+    }
+
+    /// Describe `[rule-id]`-tagged issues using this registry.
+    pub fn with_rules(mut self, registry: &'a RulePackRegistry) -> Self {
+        self.rules = rules_by_id(registry);
+        self
+    }
+
+    /// Source suggestions (and extra comments) from compiler diagnostics.
+    pub fn with_diagnostics(mut self, diagnostics: &'a [CompilerDiagnostic]) -> Self {
+        self.diagnostics = diagnostics;
+        self
+    }
+
+    fn read(&self, path: &str) -> Option<String> {
+        std::fs::read_to_string(self.repo_root.join(path)).ok()
+    }
+
+    /// Suggestion block for a diagnostic's machine-applicable fixes in `path`.
+    fn suggestion_for(
+        &self,
+        diagnostic: &CompilerDiagnostic,
+        path: &str,
+    ) -> Option<LineSuggestion> {
+        let fixes: Vec<&DiagnosticSuggestion> = diagnostic
+            .machine_applicable()
+            .filter(|s| repo_relative(&self.repo_root, &s.file) == path)
+            .collect();
+        if fixes.is_empty() {
+            return None;
+        }
+        apply_fixes(&self.read(path)?, &fixes)
+    }
+
+    /// Index of the first diagnostic covering `path:line`.
+    fn diagnostic_at(&self, path: &str, line: u32) -> Option<usize> {
+        self.diagnostics.iter().position(|d| {
+            repo_relative(&self.repo_root, &d.file) == path && (d.line..=d.end_line).contains(&line)
+        })
+    }
+
+    fn issue_body(&self, issue: &ReviewIssue) -> String {
+        let label = if issue.blocking {
+            "Blocking"
+        } else {
+            "Advisory"
+        };
+        let tagged = tagged_rule_id(&issue.description);
+        let description = match tagged {
+            Some(id) => issue.description[id.len() + 2..].trim_start(),
+            None => issue.description.as_str(),
+        };
+        let mut body = format!("**{label}:** {description}");
+        if let Some(rule) = tagged.and_then(|id| self.rules.get(id)) {
+            body.push_str(&format!(
+                "\n\n> Rule `{}` ({}, {}): {}",
+                rule.rule_id, rule.severity, rule.category, rule.description
+            ));
+        }
+        if let Some(suggestion) = &issue.suggestion {
+            body.push_str(&format!("\n\n{suggestion}"));
+        }
+        body
+    }
+
+    fn diagnostic_body(diagnostic: &CompilerDiagnostic) -> String {
+        let label = match diagnostic.level {
+            DiagnosticLevel::Error => "Blocking",
+            _ => "Advisory",
+        };
+        match &diagnostic.code {
+            Some(code) => format!(
+                "**{label}:** {} `{code}`: {}",
+                diagnostic.level, diagnostic.message
+            ),
+            None => format!("**{label}:** {}: {}", diagnostic.level, diagnostic.message),
+        }
+    }
+
+    /// Anchored comments for a decision, plus issues that could not be anchored.
+    pub fn comments(
+        &self,
+        decision: &ReviewDecisionResult,
+    ) -> (Vec<ReviewComment>, Vec<ReviewIssue>) {
+        let mut comments = Vec::new();
+        let mut general = Vec::new();
+        let mut used = HashSet::new();
+
+        for issue in &decision.issues {
+            // Review comments need a 1-based line; line 0 cannot be anchored.
+            let (Some(file), Some(line)) = (&issue.file, issue.line.filter(|&l| l > 0)) else {
+                general.push(issue.clone());
+                continue;
+            };
+            let path = repo_relative(&self.repo_root, file);
+            let line = line as u32;
+            let mut body = self.issue_body(issue);
+            let mut range = (line, line);
+            if let Some(idx) = self.diagnostic_at(&path, line) {
+                used.insert(idx);
+                if let Some(fix) = self.suggestion_for(&self.diagnostics[idx], &path) {
+                    body.push_str(&format!("\n\n{}", fix.block()));
+                    range = (fix.start, fix.end);
+                }
+            }
+            comments.push(ReviewComment::new(
+                path,
+                range.0,
+                range.1,
+                body,
+                issue.blocking,
+            ));
+        }
+
+        for (idx, diagnostic) in self.diagnostics.iter().enumerate() {
+            if used.contains(&idx)
+                || !matches!(
+                    diagnostic.level,
+                    DiagnosticLevel::Error | DiagnosticLevel::Warning
+                )
+            {
+                continue;
+            }
+            let path = repo_relative(&self.repo_root, &diagnostic.file);
+            let mut body = Self::diagnostic_body(diagnostic);
+            let mut range = (diagnostic.line, diagnostic.end_line);
+            if let Some(fix) = self.suggestion_for(diagnostic, &path) {
+                body.push_str(&format!("\n\n{}", fix.block()));
+                range = (fix.start, fix.end);
+            }
+            let blocking = diagnostic.level == DiagnosticLevel::Error;
+            comments.push(ReviewComment::new(path, range.0, range.1, body, blocking));
+        }
+
+        let mut seen = HashSet::new();
+        comments.retain(|c| seen.insert((c.path.clone(), c.line, c.body.clone())));
+        comments.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+        (comments, general)
+    }
+
+    /// Build the review payload for a decision.
+    pub fn export(&self, decision: &ReviewDecisionResult) -> ReviewPayload {
+        let (comments, general) = self.comments(decision);
+        let mut body = format!(
+            "Reviewer verdict: **{}** (confidence {:.2}). {}",
+            decision.verdict, decision.confidence, decision.next_action
+        );
+        for issue in &general {
+            let label = if issue.blocking {
+                "Blocking"
+            } else {
+                "Advisory"
+            };
+            body.push_str(&format!("\n\n- **{label}:** {}", issue.description));
+            if let Some(suggestion) = &issue.suggestion {
+                body.push_str(&format!(" — {suggestion}"));
+            }
+        }
+        ReviewPayload {
+            event: ReviewEvent::from_verdict(decision.verdict),
+            body,
+            comments,
+        }
+    }
+
+    /// Render a Markdown review summary for a decision.
+    pub fn render_markdown(&self, decision: &ReviewDecisionResult) -> String {
+        let (comments, general) = self.comments(decision);
+        let blocking = comments.iter().filter(|c| c.blocking).count()
+            + general.iter().filter(|i| i.blocking).count();
+        let advisory = comments.len() + general.len() - blocking;
+
+        let mut out = format!(
+            "## Review: {} (confidence {:.2})\n\n{} blocking, {} advisory across {} touched files.\n\n**Next action:** {}\n",
+            decision.verdict,
+            decision.confidence,
+            blocking,
+            advisory,
+            decision.touched_files.len(),
+            decision.next_action
+        );
+
+        for (title, want_blocking) in [("Blocking", true), ("Advisory", false)] {
+            let anchored: Vec<&ReviewComment> = comments
+                .iter()
+                .filter(|c| c.blocking == want_blocking)
+                .collect();
+            let unanchored: Vec<&ReviewIssue> = general
+                .iter()
+                .filter(|i| i.blocking == want_blocking)
+                .collect();
+            if anchored.is_empty() && unanchored.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n### {title}\n\n"));
+            for issue in unanchored {
+                out.push_str(&format!("- {}\n", issue.description));
+            }
+            for comment in anchored {
+                out.push_str(&format!("- `{}`\n\n", comment.location()));
+                for line in comment.body.lines() {
+                    if line.is_empty() {
+                        out.push('\n');
+                    } else {
+                        out.push_str(&format!("  {line}\n"));
+                    }
+                }
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::parse_cargo_json;
+
+    const LIB: &str = "fn f(opt: Option<u32>) -> u32 {\n    let v = opt.unwrap();\n    v\n}\n";
+
+    const CLIPPY: &str = r#"{"reason":"compiler-message","message":{"message":"used `unwrap()` on an `Option` value","code":{"code":"clippy::unwrap_used"},"level":"warning","spans":[{"file_name":"src/lib.rs","line_start":2,"line_end":2,"column_start":13,"column_end":25,"is_primary":true}],"children":[{"message":"use `unwrap_or_default`","code":null,"level":"help","spans":[{"file_name":"src/lib.rs","line_start":2,"line_end":2,"column_start":17,"column_end":25,"is_primary":true,"suggested_replacement":"unwrap_or_default()","suggestion_applicability":"MachineApplicable"}],"children":[]}]}}
+{"reason":"compiler-message","message":{"message":"unused variable: `x`","code":{"code":"unused_variables"},"level":"warning","spans":[{"file_name":"src/other.rs","line_start":5,"line_end":6,"column_start":9,"column_end":10,"is_primary":true}],"children":[]}}"#;
+
+    fn repo() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), LIB).unwrap();
+        dir
+    }
+
+    fn decision() -> ReviewDecisionResult {
+        ReviewDecisionResult::fail(
+            vec![
+                ReviewIssue {
+                    blocking: true,
+                    file: Some("src/lib.rs".to_string()),
+                    line: Some(2),
+                    description: "[no-unwrap] Avoid .unwrap(): `opt.unwrap()`".to_string(),
+                    suggestion: None,
+                },
+                ReviewIssue {
+                    blocking: false,
+                    file: None,
+                    line: None,
+                    description: "high dependency impact".to_string(),
+                    suggestion: Some("check dependents".to_string()),
+                },
+            ],
+            vec!["src/lib.rs".to_string()],
+        )
+    }
+
+    #[test]
+    fn test_apply_fixes() {
+        let fix = |l1, c1, l2, c2, r: &str| DiagnosticSuggestion {
+            message: String::new(),
+            file: "src/lib.rs".to_string(),
+            line_start: l1,
+            column_start: c1,
+            line_end: l2,
+            column_end: c2,
+            replacement: r.to_string(),
+            applicability: crate::diagnostics::Applicability::MachineApplicable,
+        };
+        let a = fix(2, 17, 2, 25, "unwrap_or(0)");
+        let s = apply_fixes(LIB, &[&a]).unwrap();
+        assert_eq!((s.start, s.end), (2, 2));
+        assert_eq!(s.text, "    let v = opt.unwrap_or(0);");
+
+        let b = fix(3, 5, 3, 6, "v + 1");
+        let s = apply_fixes(LIB, &[&b, &a]).unwrap();
+        assert_eq!((s.start, s.end), (2, 3));
+        assert_eq!(s.text, "    let v = opt.unwrap_or(0);\n    v + 1");
+
+        assert!(apply_fixes(LIB, &[&fix(9, 1, 9, 2, "x")]).is_none());
+        assert!(apply_fixes(LIB, &[&a, &fix(2, 20, 2, 22, "x")]).is_none());
+    }
+
+    #[test]
+    fn test_comments_with_rules_and_fixes() {
+        let dir = repo();
+        let registry = RulePackRegistry::with_defaults();
+        let diagnostics = parse_cargo_json(CLIPPY);
+        let exporter = ReviewCommentExporter::new(dir.path())
+            .with_rules(&registry)
+            .with_diagnostics(&diagnostics);
+        let (comments, general) = exporter.comments(&decision());
+
+        assert_eq!(general.len(), 1);
+        assert_eq!(comments.len(), 2);
+
+        let unwrap = &comments[0];
+        assert_eq!(unwrap.path, "src/lib.rs");
+        assert_eq!(unwrap.line, 2);
+        assert!(unwrap.start_line.is_none());
+        assert!(unwrap.blocking);
+        assert!(unwrap
+            .body
+            .starts_with("**Blocking:** Avoid .unwrap(): `opt.unwrap()`"));
+        assert!(unwrap.body.contains("> Rule `no-unwrap` (error, safety)"));
+        assert!(unwrap
+            .body
+            .ends_with("```suggestion\n    let v = opt.unwrap_or_default();\n```"));
+
+        let unused = &comments[1];
+        assert_eq!(unused.location(), "src/other.rs:5-6");
+        assert!(!unused.blocking);
+        assert!(!unused.has_suggestion());
+        assert!(unused.body.contains("warning `unused_variables`"));
+    }
+
+    #[test]
+    fn test_export_payload_json() {
+        let dir = repo();
+        let payload = ReviewCommentExporter::new(dir.path()).export(&decision());
+        assert_eq!(payload.event, ReviewEvent::RequestChanges);
+        assert!(payload
+            .body
+            .contains("- **Advisory:** high dependency impact — check dependents"));
+
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["event"], "REQUEST_CHANGES");
+        let comment = &json["comments"][0];
+        assert_eq!(comment["path"], "src/lib.rs");
+        assert_eq!(comment["side"], "RIGHT");
+        assert_eq!(comment["line"], 2);
+        assert!(comment.get("start_line").is_none());
+        assert!(comment.get("blocking").is_none());
+    }
+
+    #[test]
+    fn test_absolute_paths_are_relativized() {
+        let dir = repo();
+        let mut d = decision();
+        d.issues[0].file = Some(dir.path().join("src/lib.rs").display().to_string());
+        let (comments, _) = ReviewCommentExporter::new(dir.path()).comments(&d);
+        assert_eq!(comments[0].path, "src/lib.rs");
+    }
+
+    #[test]
+    fn test_line_zero_issue_is_not_anchored() {
+        let dir = repo();
+        let mut d = decision();
+        d.issues[0].line = Some(0);
+        let (comments, general) = ReviewCommentExporter::new(dir.path()).comments(&d);
+        assert!(comments.is_empty());
+        assert_eq!(general.len(), 2);
+        assert!(general[0].blocking);
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let dir = repo();
+        let diagnostics = parse_cargo_json(CLIPPY);
+        let md = ReviewCommentExporter::new(dir.path())
+            .with_diagnostics(&diagnostics)
+            .render_markdown(&decision());
+        assert!(md.starts_with("## Review: fail (confidence 0.80)"));
+        assert!(md.contains("1 blocking, 2 advisory across 1 touched files."));
+        let blocking = md.find("### Blocking").unwrap();
+        let advisory = md.find("### Advisory").unwrap();
+        assert!(blocking < advisory);
+        assert!(md.contains("- `src/lib.rs:2`"));
+        assert!(md.contains("  ```suggestion"));
+        assert!(md[advisory..].contains("- high dependency impact"));
+
+        let pass = ReviewDecisionResult::pass(0.9, vec![]);
+        let md = ReviewCommentExporter::new(dir.path()).render_markdown(&pass);
+        assert!(!md.contains("###"));
+    }
+}
@@ -0,0 +1,1284 @@
+//! Reviewer Pipeline Executor — drives [`ReviewerPolicy`] end to end.
+//!
+//! Runs each [`ReviewStage`] against a worktree in policy order, records a
+//! [`ReviewTrace`], honours short-circuiting, and aggregates stage issues
+//! into a [`ReviewDecisionResult`].
+//!
+//! Stages are supplied as [`StageRunner`] trait objects so tests (and
+//! alternative backends) can replace any of them:
+//!
+//! | Stage             | Default runner           | Backend                               |
+//! |-------------------|--------------------------|---------------------------------------|
+//! | `VerifierGates`   | [`CargoVerifierStage`]   | `cargo fmt/clippy/check/test`         |
+//! | `AstAnalysis`     | [`RulePackStage`]        | rule packs via [`StructuralSearch`]   |
+//! | `DependencyCheck` | [`DependencyImpactStage`]| code graph reverse deps + API diff    |
+//!
+//! The `Decision` stage is always computed by the executor itself.
+
+use crate::reviewer_policy::{ReviewStage, ReviewTrace, ReviewerPolicy, StageOutcome};
+use crate::reviewer_tools::code_graph::language_for_path;
+use crate::reviewer_tools::graph_rag::{
+    GraphRagBackend, GraphRagConfig, GraphRagQuery, GraphRagRunner, QueryKind,
+};
+use crate::reviewer_tools::rule_config::{load_rule_docs, CompiledRule};
+use crate::reviewer_tools::{
+    ApiChangeDetector, ApiDiff, AstGrepConfig, AstGrepMatch, AstGrepQuery, RulePackRegistry,
+    RuleSeverity, StructuralSearch,
+};
+use crate::tool_schema::{
+    AffectedFile, DependencyCheckRequest, DependencyCheckResult, GateCheckResult, ImpactLevel,
+    ReviewDecisionResult, ReviewIssue, ReviewVerdict, VerifierGateResult,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Inputs shared by every stage: the worktree under review and its diff.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewContext {
+    /// Root of the worktree being reviewed.
+    pub worktree: PathBuf,
+    /// Commit the changes are compared against.
+    pub base_commit: String,
+    /// Files changed relative to `base_commit` (worktree-relative).
+    pub touched_files: Vec<String>,
+    /// Unified diff against `base_commit`.
+    pub diff: String,
+    /// When the current stage must finish, derived from the policy's
+    /// `max_duration_ms` (set by the executor per stage).
+    #[serde(skip)]
+    pub deadline: Option<Instant>,
+}
+
+impl ReviewContext {
+    /// Create a context with an explicit file list (no git access).
+    pub fn new(worktree: &Path, base_commit: &str, touched_files: Vec<String>) -> Self {
+        Self {
+            worktree: worktree.to_path_buf(),
+            base_commit: base_commit.to_string(),
+            touched_files,
+            diff: String::new(),
+            deadline: None,
+        }
+    }
+
+    /// Build a context by diffing the worktree against `base_commit` with git.
+    ///
+    /// Deleted files are excluded from `touched_files` since no stage can
+    /// analyze them.
+    pub fn from_git(worktree: &Path, base_commit: &str) -> Result<Self, String> {
+        let names = git(
+            worktree,
+            &["diff", "--name-only", "--diff-filter=d", base_commit],
+        )?;
+        let diff = git(worktree, &["diff", base_commit])?;
+        let touched_files = names
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(str::to_string)
+            .collect();
+        Ok(Self {
+            worktree: worktree.to_path_buf(),
+            base_commit: base_commit.to_string(),
+            touched_files,
+            diff,
+            deadline: None,
+        })
+    }
+}
+
+fn git(worktree: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(worktree)
+        .output()
+        .map_err(|e| format!("failed to run git: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Output of a single stage run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageReport {
+    /// Stage outcome recorded in the trace.
+    pub outcome: StageOutcome,
+    /// Issues found by the stage.
+    pub issues: Vec<ReviewIssue>,
+    /// One-line summary recorded in the trace.
+    pub summary: String,
+}
+
+impl StageReport {
+    /// Passing report with no issues.
+    pub fn passed(summary: &str) -> Self {
+        Self {
+            outcome: StageOutcome::Passed,
+            issues: Vec::new(),
+            summary: summary.to_string(),
+        }
+    }
+
+    /// Report whose outcome is derived from its issues: any blocking issue
+    /// fails the stage, other issues warn.
+    pub fn from_issues(issues: Vec<ReviewIssue>, summary: &str) -> Self {
+        let outcome = if issues.iter().any(|i| i.blocking) {
+            StageOutcome::Failed
+        } else if issues.is_empty() {
+            StageOutcome::Passed
+        } else {
+            StageOutcome::Warning
+        };
+        Self {
+            outcome,
+            issues,
+            summary: summary.to_string(),
+        }
+    }
+}
+
+/// A pluggable implementation of one review stage.
+pub trait StageRunner: Send + Sync {
+    /// Run the stage against the review context.
+    fn run(&self, ctx: &ReviewContext) -> StageReport;
+}
+
+/// Result of a full pipeline run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewExecution {
+    /// Audit trace of executed and skipped stages.
+    pub trace: ReviewTrace,
+    /// Aggregated decision.
+    pub decision: ReviewDecisionResult,
+    /// Per-stage reports, in execution order (skipped stages omitted).
+    pub reports: Vec<(ReviewStage, StageReport)>,
+}
+
+/// Drives the reviewer pipeline according to a [`ReviewerPolicy`].
+pub struct ReviewExecutor {
+    policy: ReviewerPolicy,
+    verifier: Box<dyn StageRunner>,
+    ast: Box<dyn StageRunner>,
+    dependency: Box<dyn StageRunner>,
+}
+
+impl ReviewExecutor {
+    /// Create an executor from explicit stage runners.
+    pub fn new(
+        policy: ReviewerPolicy,
+        verifier: Box<dyn StageRunner>,
+        ast: Box<dyn StageRunner>,
+        dependency: Box<dyn StageRunner>,
+    ) -> Self {
+        Self {
+            policy,
+            verifier,
+            ast,
+            dependency,
+        }
+    }
+
+    /// Create an executor with the default cargo, rule-pack and code-graph stages.
+    pub fn with_defaults(policy: ReviewerPolicy) -> Self {
+        Self::new(
+            policy,
+            Box::new(CargoVerifierStage::default()),
+            Box::new(RulePackStage::default()),
+            Box::new(DependencyImpactStage::default()),
+        )
+    }
+
+    /// The policy in use.
+    pub fn policy(&self) -> &ReviewerPolicy {
+        &self.policy
+    }
+
+    /// Diff `worktree` against `base_commit` and run the pipeline.
+    pub fn review(&self, worktree: &Path, base_commit: &str) -> Result<ReviewExecution, String> {
+        let ctx = ReviewContext::from_git(worktree, base_commit)?;
+        Ok(self.execute(&ctx))
+    }
+
+    /// Run the pipeline over a prepared context.
+    pub fn execute(&self, ctx: &ReviewContext) -> ReviewExecution {
+        let mut trace = ReviewTrace::new(&uuid::Uuid::new_v4().to_string());
+        let mut reports: Vec<(ReviewStage, StageReport)> = Vec::new();
+        let mut decided = false;
+
+        while let Some(stage) = self.policy.next_stage(&trace) {
+            let runner = match stage {
+                ReviewStage::VerifierGates => self.verifier.as_ref(),
+                ReviewStage::AstAnalysis if !self.policy.require_ast_analysis => {
+                    trace.record(stage, StageOutcome::Skipped, 0, 0, "not required by policy");
+                    continue;
+                }
+                ReviewStage::AstAnalysis => self.ast.as_ref(),
+                ReviewStage::DependencyCheck if !self.policy.require_dependency_check => {
+                    trace.record(stage, StageOutcome::Skipped, 0, 0, "not required by policy");
+                    continue;
+                }
+                ReviewStage::DependencyCheck => self.dependency.as_ref(),
+                ReviewStage::Decision => {
+                    let start = Instant::now();
+                    let decision = Self::decide(ctx, &reports, false);
+                    let outcome = match decision.verdict {
+                        ReviewVerdict::Pass => StageOutcome::Passed,
+                        ReviewVerdict::Fail => StageOutcome::Failed,
+                        ReviewVerdict::NeedsEscalation => StageOutcome::Warning,
+                    };
+                    trace.record(
+                        stage,
+                        outcome,
+                        start.elapsed().as_millis() as u64,
+                        0,
+                        &format!("{} ({})", decision.verdict, decision.next_action),
+                    );
+                    decided = true;
+                    break;
+                }
+            };
+
+            let start = Instant::now();
+            let report = match self.policy.max_duration_ms {
+                0 => runner.run(ctx),
+                max => {
+                    let remaining = max.saturating_sub(trace.total_duration_ms);
+                    runner.run(&ReviewContext {
+                        deadline: Some(start + Duration::from_millis(remaining)),
+                        ..ctx.clone()
+                    })
+                }
+            };
+            trace.record(
+                stage,
+                report.outcome,
+                start.elapsed().as_millis() as u64,
+                report.issues.len(),
+                &report.summary,
+            );
+            if report.outcome == StageOutcome::Failed && self.policy.should_short_circuit(stage) {
+                trace.mark_short_circuit(stage);
+            }
+            reports.push((stage, report));
+        }
+
+        // Stopping without a short-circuit or decision means the duration
+        // budget ran out before every stage could run.
+        let incomplete = !decided && !trace.short_circuited;
+        let decision = Self::decide(ctx, &reports, incomplete);
+        ReviewExecution {
+            trace,
+            decision,
+            reports,
+        }
+    }
+
+    /// Aggregate stage reports into a decision.
+    fn decide(
+        ctx: &ReviewContext,
+        reports: &[(ReviewStage, StageReport)],
+        incomplete: bool,
+    ) -> ReviewDecisionResult {
+        let issues: Vec<ReviewIssue> = reports
+            .iter()
+            .flat_map(|(_, r)| r.issues.iter().cloned())
+            .collect();
+        let verifier_failed = reports
+            .iter()
+            .any(|(s, r)| *s == ReviewStage::VerifierGates && r.outcome == StageOutcome::Failed);
+
+        if verifier_failed || issues.iter().any(|i| i.blocking) {
+            return ReviewDecisionResult::fail(issues, ctx.touched_files.clone());
+        }
+        if incomplete {
+            let mut result = ReviewDecisionResult::pass(0.5, ctx.touched_files.clone());
+            result.verdict = ReviewVerdict::NeedsEscalation;
+            result.next_action = "review budget exhausted — escalate".to_string();
+            result.issues = issues;
+            return result;
+        }
+
+        // Each advisory issue lowers confidence slightly.
+        let confidence = (1.0 - 0.05 * issues.len() as f64).max(0.5);
+        let mut result = ReviewDecisionResult::pass(confidence, ctx.touched_files.clone());
+        result.issues = issues;
+        result
+    }
+}
+
+// ── Verifier gates ───────────────────────────────────────────────────
+
+/// Runs cargo quality gates in the worktree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CargoVerifierStage {
+    /// Gates to run, in order (`fmt`, `clippy`, `check`, `test`).
+    pub gates: Vec<String>,
+    /// Packages to restrict to (empty = workspace).
+    pub packages: Vec<String>,
+    /// Whether to stop after the first failing gate.
+    pub fail_fast: bool,
+}
+
+impl Default for CargoVerifierStage {
+    fn default() -> Self {
+        Self {
+            gates: ["fmt", "clippy", "check", "test"]
+                .iter()
+                .map(|g| g.to_string())
+                .collect(),
+            packages: Vec::new(),
+            fail_fast: true,
+        }
+    }
+}
+
+impl CargoVerifierStage {
+    /// Cargo arguments for a gate, or `None` for unknown gates.
+    pub fn gate_args(&self, gate: &str) -> Option<Vec<String>> {
+        let mut args: Vec<String> = match gate {
+            "fmt" => vec!["fmt".into()],
+            "clippy" => vec!["clippy".into(), "--all-targets".into()],
+            "check" => vec!["check".into(), "--all-targets".into()],
+            "test" => vec!["test".into()],
+            _ => return None,
+        };
+        if self.packages.is_empty() {
+            if gate == "fmt" {
+                args.push("--all".into());
+            } else {
+                args.push("--workspace".into());
+            }
+        }
+        for package in &self.packages {
+            args.push("-p".into());
+            args.push(package.clone());
+        }
+        match gate {
+            "fmt" => args.extend(["--".into(), "--check".into()]),
+            "clippy" => args.extend(["--".into(), "-D".into(), "warnings".into()]),
+            _ => {}
+        }
+        Some(args)
+    }
+
+    /// Run every configured gate and collect the results.
+    ///
+    /// With a `deadline`, a gate still running when it passes is killed and
+    /// reported as failed, and later gates are not started.
+    pub fn run_gates(
+        &self,
+        worktree: &Path,
+        deadline: Option<Instant>,
+    ) -> (VerifierGateResult, Vec<ReviewIssue>) {
+        let start = Instant::now();
+        let mut gates = Vec::new();
+        let mut issues = Vec::new();
+
+        for gate in &self.gates {
+            let gate_start = Instant::now();
+            let mut timed_out = false;
+            let (passed, errors, warnings, first_error) = match self.gate_args(gate) {
+                None => (false, 1, 0, Some(format!("unknown gate '{gate}'"))),
+                Some(args) => match run_cargo(&args, worktree, deadline) {
+                    GateRun::Failed(e) => (false, 1, 0, Some(e)),
+                    GateRun::TimedOut => {
+                        timed_out = true;
+                        let elapsed = gate_start.elapsed().as_millis();
+                        (false, 1, 0, Some(format!("killed after {elapsed}ms")))
+                    }
+                    GateRun::Finished { success, output } => {
+                        let (errors, warnings, first) = count_diagnostics(gate, &output);
+                        (
+                            success,
+                            if success { 0 } else { errors.max(1) },
+                            warnings,
+                            first,
+                        )
+                    }
+                },
+            };
+
+            if timed_out {
+                issues.push(ReviewIssue {
+                    blocking: true,
+                    file: None,
+                    line: None,
+                    description: format!("verifier gate `{gate}` timed out"),
+                    suggestion: first_error,
+                });
+            } else if !passed {
+                issues.push(ReviewIssue {
+                    blocking: true,
+                    file: None,
+                    line: None,
+                    description: format!("verifier gate `{gate}` failed ({errors} errors)"),
+                    suggestion: first_error,
+                });
+            }
+            gates.push(GateCheckResult {
+                gate: gate.clone(),
+                passed,
+                error_count: errors,
+                warning_count: warnings,
+                dominant_category: None,
+                duration_ms: gate_start.elapsed().as_millis() as u64,
+            });
+            if timed_out || (!passed && self.fail_fast) {
+                break;
+            }
+        }
+
+        let duration_ms = start.elapsed().as_millis() as u64;
+        let gates_passed = gates.iter().filter(|g| g.passed).count();
+        let result = VerifierGateResult {
+            all_passed: gates_passed == gates.len() && gates.len() == self.gates.len(),
+            gates_passed,
+            gates_total: self.gates.len(),
+            total_errors: gates.iter().map(|g| g.error_count).sum(),
+            first_failure: gates.iter().find(|g| !g.passed).map(|g| g.gate.clone()),
+            gates,
+            duration_ms,
+        };
+        (result, issues)
+    }
+}
+
+/// How a cargo gate invocation ended.
+enum GateRun {
+    /// Cargo exited; `output` is stdout followed by stderr.
+    Finished { success: bool, output: String },
+    /// The deadline passed and cargo was killed.
+    TimedOut,
+    /// Cargo could not be started or waited on.
+    Failed(String),
+}
+
+/// Run `cargo <args>` in `worktree`, killing it if `deadline` passes first.
+fn run_cargo(args: &[String], worktree: &Path, deadline: Option<Instant>) -> GateRun {
+    if deadline.is_some_and(|d| Instant::now() >= d) {
+        return GateRun::TimedOut;
+    }
+    let mut child = match Command::new("cargo")
+        .args(args)
+        .current_dir(worktree)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return GateRun::Failed(format!("failed to run cargo: {e}")),
+    };
+
+    // Drain both pipes so a chatty build cannot block on a full pipe
+    let readers = [
+        child
+            .stdout
+            .take()
+            .map(|out| Box::new(out) as Box<dyn Read + Send>),
+        child
+            .stderr
+            .take()
+            .map(|err| Box::new(err) as Box<dyn Read + Send>),
+    ]
+    .map(|pipe| {
+        pipe.map(|mut pipe| {
+            std::thread::spawn(move || {
+                let mut buf = Vec::new();
+                let _ = pipe.read_to_end(&mut buf);
+                buf
+            })
+        })
+    });
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if deadline.is_some_and(|d| Instant::now() >= d) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return GateRun::TimedOut;
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+            Err(e) => {
+                let _ = child.kill();
+                return GateRun::Failed(format!("failed to wait for cargo: {e}"));
+            }
+        }
+    };
+
+    let output = readers
+        .into_iter()
+        .map(|reader| {
+            let bytes = reader.and_then(|h| h.join().ok()).unwrap_or_default();
+            String::from_utf8_lossy(&bytes).into_owned()
+        })
+        .collect();
+    GateRun::Finished {
+        success: status.success(),
+        output,
+    }
+}
+
+/// Count `error`/`warning` diagnostics in cargo output and return the first error line.
+fn count_diagnostics(gate: &str, output: &str) -> (usize, usize, Option<String>) {
+    if gate == "fmt" {
+        let diffs = output.lines().filter(|l| l.starts_with("Diff in")).count();
+        let first = output.lines().find(|l| l.starts_with("Diff in"));
+        return (diffs, 0, first.map(str::to_string));
+    }
+    let mut errors = 0;
+    let mut warnings = 0;
+    let mut first = None;
+    for line in output.lines() {
+        if line.starts_with("error") && !line.starts_with("error: could not compile") {
+            errors += 1;
+            first.get_or_insert_with(|| line.to_string());
+        } else if line.starts_with("warning") && !line.contains("generated") {
+            warnings += 1;
+        }
+    }
+    (errors, warnings, first)
+}
+
+impl StageRunner for CargoVerifierStage {
+    fn run(&self, ctx: &ReviewContext) -> StageReport {
+        let (result, issues) = self.run_gates(&ctx.worktree, ctx.deadline);
+        let summary = format!(
+            "{}/{} gates passed",
+            result.gates_passed, result.gates_total
+        );
+        StageReport::from_issues(issues, &summary)
+    }
+}
+
+// ── AST rule packs ───────────────────────────────────────────────────
+
+/// Runs enabled rule-pack rules over the touched files.
+///
+/// Pattern rules go through [`StructuralSearch`]; rule-file rules (composite
+/// `all`/`any`/`inside` configs) are evaluated in-process with
+/// [`CompiledRule`], resolving the rule file against the worktree. A rule
+/// that cannot run is reported as a non-blocking issue.
+pub struct RulePackStage {
+    registry: RulePackRegistry,
+    config: AstGrepConfig,
+}
+
+impl Default for RulePackStage {
+    fn default() -> Self {
+        Self::new(RulePackRegistry::with_defaults(), AstGrepConfig::default())
+    }
+}
+
+impl RulePackStage {
+    /// Create a stage over a registry; `config.working_dir` is replaced by the worktree.
+    pub fn new(registry: RulePackRegistry, config: AstGrepConfig) -> Self {
+        Self { registry, config }
+    }
+
+    /// Evaluate a rule-file rule in-process over `files` (worktree-relative).
+    fn run_rule_file(
+        rule_id: &str,
+        path: &Path,
+        ctx: &ReviewContext,
+        files: &[String],
+    ) -> Result<Vec<AstGrepMatch>, String> {
+        let docs = load_rule_docs(path).map_err(|e| e.to_string())?;
+        let doc = docs
+            .iter()
+            .find(|d| d.id == rule_id)
+            .ok_or_else(|| format!("no rule `{}` in {}", rule_id, path.display()))?;
+        let compiled = CompiledRule::compile(doc)?;
+        let mut found = Vec::new();
+        for file in files {
+            let full = ctx.worktree.join(file);
+            let source = std::fs::read_to_string(&full)
+                .map_err(|e| format!("Failed to read {}: {}", full.display(), e))?;
+            found.extend(compiled.find(file, &source)?);
+        }
+        Ok(found)
+    }
+}
+
+impl StageRunner for RulePackStage {
+    fn run(&self, ctx: &ReviewContext) -> StageReport {
+        let mut by_language: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for file in &ctx.touched_files {
+            if let Some(language) = language_for_path(Path::new(file)) {
+                by_language.entry(language).or_default().push(file.clone());
+            }
+        }
+        if by_language.is_empty() {
+            return StageReport::passed("no touched files in a supported language");
+        }
+
+        let search = StructuralSearch::new(AstGrepConfig {
+            working_dir: Some(ctx.worktree.display().to_string()),
+            ..self.config.clone()
+        });
+
+        let mut issues = Vec::new();
+        let mut rules_run = 0;
+        let mut matches = 0;
+        let mut failures = Vec::new();
+        for (language, files) in &by_language {
+            for rule in self.registry.all_rules_for_language(language) {
+                rules_run += 1;
+                let found = match (&rule.pattern, &rule.rule_file) {
+                    (Some(pattern), _) => {
+                        let mut query =
+                            AstGrepQuery::pattern(pattern, language).in_paths(files.clone());
+                        query.rule_id = Some(rule.rule_id.clone());
+                        let response = search.search(&query);
+                        response.payload.map(|r| r.matches).ok_or_else(|| {
+                            (
+                                "no search tier could run it".to_string(),
+                                response.warnings.last().cloned(),
+                            )
+                        })
+                    }
+                    (None, Some(path)) => {
+                        Self::run_rule_file(&rule.rule_id, &ctx.worktree.join(path), ctx, files)
+                            .map_err(|e| (format!("rule file {path} failed"), Some(e)))
+                    }
+                    (None, None) => Err(("it has no pattern or rule file".to_string(), None)),
+                };
+                let found = match found {
+                    Ok(found) => found,
+                    Err((reason, detail)) => {
+                        // Surface the gap rather than passing silently
+                        issues.push(ReviewIssue {
+                            blocking: false,
+                            file: None,
+                            line: None,
+                            description: format!(
+                                "[{}] rule could not run: {}",
+                                rule.rule_id, reason
+                            ),
+                            suggestion: detail,
+                        });
+                        failures.push(rule.rule_id.clone());
+                        continue;
+                    }
+                };
+                matches += found.len();
+                for m in found {
+                    issues.push(ReviewIssue {
+                        blocking: rule.severity == RuleSeverity::Error,
+                        file: Some(m.file.clone()),
+                        line: Some(m.line as usize),
+                        description: format!(
+                            "[{}] {}: `{}`",
+                            rule.rule_id, rule.description, m.text
+                        ),
+                        suggestion: None,
+                    });
+                }
+            }
+        }
+
+        let mut summary = format!(
+            "{} rules over {} files, {} matches",
+            rules_run,
+            by_language.values().map(Vec::len).sum::<usize>(),
+            matches
+        );
+        if !failures.is_empty() {
+            summary.push_str(&format!(", {} rules unavailable", failures.len()));
+        }
+        StageReport::from_issues(issues, &summary)
+    }
+}
+
+// ── Dependency impact ────────────────────────────────────────────────
+
+/// Computes reverse dependencies of touched files with the local code graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyImpactStage {
+    /// Graph configuration (backend and root are set per run).
+    pub config: GraphRagConfig,
+    /// Impact level at or above which a (non-blocking) issue is raised.
+    pub flag_at: ImpactLevel,
+}
+
+impl Default for DependencyImpactStage {
+    fn default() -> Self {
+        Self {
+            config: GraphRagConfig::default(),
+            flag_at: ImpactLevel::High,
+        }
+    }
+}
+
+impl DependencyImpactStage {
+    /// Compute the dependency check result for the touched files.
+    pub fn check(&self, ctx: &ReviewContext) -> DependencyCheckResult {
+        self.analyze(ctx).0
+    }
+
+    /// Graph-based reverse dependencies, merged with public API changes
+    /// against `base_commit` when the worktree is a git repository.
+    fn analyze(&self, ctx: &ReviewContext) -> (DependencyCheckResult, ApiDiff) {
+        let start = Instant::now();
+        let runner = GraphRagRunner::with_config(GraphRagConfig {
+            backend: GraphRagBackend::Local,
+            repo_root: Some(ctx.worktree.display().to_string()),
+            ..self.config.clone()
+        });
+
+        let touched: BTreeSet<&str> = ctx.touched_files.iter().map(String::as_str).collect();
+        let mut affected: BTreeMap<String, AffectedFile> = BTreeMap::new();
+        for file in &touched {
+            let Some(language) = language_for_path(Path::new(file)) else {
+                continue;
+            };
+            let query = GraphRagQuery {
+                target: file.to_string(),
+                kind: QueryKind::Dependents,
+                scope: None,
+                max_depth: None,
+                language: language.to_string(),
+            };
+            let result = runner.execute_local(&query);
+            for node in result.nodes {
+                if node.depth == 0 || touched.contains(node.file.as_str()) {
+                    continue;
+                }
+                let direct = node.depth == 1;
+                let entry = affected
+                    .entry(node.file.clone())
+                    .or_insert_with(|| AffectedFile {
+                        file: node.file.clone(),
+                        reason: format!("depends on {file}"),
+                        direct,
+                    });
+                entry.direct |= direct;
+            }
+        }
+
+        let request = DependencyCheckRequest {
+            changed_files: Vec::new(),
+            package: None,
+            include_transitive: false,
+        };
+        let (api, diff) =
+            ApiChangeDetector::new(&ctx.worktree).check_with_diff(&request, &ctx.base_commit, None);
+        if api.success {
+            for file in api.affected_files {
+                if touched.contains(file.file.as_str()) {
+                    continue;
+                }
+                affected
+                    .entry(file.file.clone())
+                    .and_modify(|a| a.direct = true)
+                    .or_insert(file);
+            }
+        }
+
+        let direct_dependents = affected.values().filter(|a| a.direct).count();
+        let transitive_dependents = affected.len() - direct_dependents;
+        let mut result = DependencyCheckResult::no_impact(start.elapsed().as_millis() as u64);
+        result.impact_level = ImpactLevel::from_dependents(affected.len()).max(api.impact_level);
+        result.affected_files = affected.into_values().collect();
+        result.direct_dependents = direct_dependents;
+        result.transitive_dependents = transitive_dependents;
+        result.api_change_detected = api.api_change_detected;
+        (result, diff)
+    }
+}
+
+impl StageRunner for DependencyImpactStage {
+    fn run(&self, ctx: &ReviewContext) -> StageReport {
+        let (result, diff) = self.analyze(ctx);
+        let mut summary = format!(
+            "{} impact: {} direct, {} transitive dependents",
+            result.impact_level, result.direct_dependents, result.transitive_dependents
+        );
+        if !diff.is_empty() {
+            summary.push_str(&format!("; {}", diff.summary_line()));
+        }
+        let mut issues: Vec<ReviewIssue> = diff
+            .breaking()
+            .map(|change| ReviewIssue {
+                blocking: false,
+                file: Some(change.item.file.clone()),
+                line: Some(change.item.line as usize),
+                description: format!("breaking api change: {change}"),
+                suggestion: Some("update dependents or keep a compatible item".to_string()),
+            })
+            .collect();
+        if result.impact_level != ImpactLevel::None && result.impact_level >= self.flag_at {
+            issues.push(ReviewIssue {
+                blocking: false,
+                file: None,
+                line: None,
+                description: format!(
+                    "{} dependency impact: {} files depend on the change",
+                    result.impact_level,
+                    result.affected_files.len()
+                ),
+                suggestion: Some("confirm dependents still build and behave".to_string()),
+            });
+        }
+        StageReport::from_issues(issues, &summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reviewer_tools::{RulePack, RulePackEntry};
+    use crate::test_utils::{git_commit_all, git_init};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    struct Fixed {
+        report: StageReport,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl StageRunner for Fixed {
+        fn run(&self, _ctx: &ReviewContext) -> StageReport {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.report.clone()
+        }
+    }
+
+    fn stage(report: StageReport) -> (Box<dyn StageRunner>, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        (
+            Box::new(Fixed {
+                report,
+                calls: calls.clone(),
+            }),
+            calls,
+        )
+    }
+
+    fn issue(blocking: bool) -> ReviewIssue {
+        ReviewIssue {
+            blocking,
+            file: Some("src/lib.rs".to_string()),
+            line: Some(3),
+            description: "finding".to_string(),
+            suggestion: None,
+        }
+    }
+
+    fn ctx() -> ReviewContext {
+        ReviewContext::new(Path::new("."), "HEAD", vec!["src/lib.rs".to_string()])
+    }
+
+    #[test]
+    fn test_all_stages_pass() {
+        let (v, _) = stage(StageReport::passed("4/4 gates"));
+        let (a, _) = stage(StageReport::passed("clean"));
+        let (d, _) = stage(StageReport::passed("no impact"));
+        let exec = ReviewExecutor::new(ReviewerPolicy::default(), v, a, d).execute(&ctx());
+
+        assert_eq!(exec.decision.verdict, ReviewVerdict::Pass);
+        assert_eq!(exec.decision.confidence, 1.0);
+        assert_eq!(exec.trace.entries.len(), 4);
+        assert_eq!(exec.trace.entries[3].stage, ReviewStage::Decision);
+        assert!(exec.trace.all_passed());
+        assert!(ReviewerPolicy::default()
+            .validate_ordering(&exec.trace)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_verifier_failure_short_circuits() {
+        let (v, _) = stage(StageReport::from_issues(vec![issue(true)], "1/4 gates"));
+        let (a, ast_calls) = stage(StageReport::passed("clean"));
+        let (d, dep_calls) = stage(StageReport::passed("no impact"));
+        let exec = ReviewExecutor::new(ReviewerPolicy::default(), v, a, d).execute(&ctx());
+
+        assert!(exec.trace.short_circuited);
+        assert_eq!(
+            exec.trace.short_circuit_stage,
+            Some(ReviewStage::VerifierGates)
+        );
+        assert_eq!(ast_calls.load(Ordering::SeqCst), 0);
+        assert_eq!(dep_calls.load(Ordering::SeqCst), 0);
+        assert_eq!(exec.decision.verdict, ReviewVerdict::Fail);
+        assert_eq!(exec.decision.blocking_count, 1);
+    }
+
+    #[test]
+    fn test_no_fail_fast_runs_all_stages() {
+        let policy = ReviewerPolicy {
+            fail_fast_on_verifier: false,
+            ..Default::default()
+        };
+        let (v, _) = stage(StageReport::from_issues(vec![issue(true)], "failed"));
+        let (a, ast_calls) = stage(StageReport::from_issues(vec![issue(false)], "1 match"));
+        let (d, _) = stage(StageReport::passed("no impact"));
+        let exec = ReviewExecutor::new(policy, v, a, d).execute(&ctx());
+
+        assert!(!exec.trace.short_circuited);
+        assert_eq!(ast_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(exec.decision.verdict, ReviewVerdict::Fail);
+        assert_eq!(exec.decision.issues.len(), 2);
+        assert_eq!(exec.trace.entries[1].outcome, StageOutcome::Warning);
+    }
+
+    #[test]
+    fn test_optional_stages_skipped() {
+        let policy = ReviewerPolicy {
+            require_ast_analysis: false,
+            require_dependency_check: false,
+            ..Default::default()
+        };
+        let (v, _) = stage(StageReport::passed("ok"));
+        let (a, ast_calls) = stage(StageReport::passed("clean"));
+        let (d, dep_calls) = stage(StageReport::passed("no impact"));
+        let exec = ReviewExecutor::new(policy, v, a, d).execute(&ctx());
+
+        assert_eq!(ast_calls.load(Ordering::SeqCst), 0);
+        assert_eq!(dep_calls.load(Ordering::SeqCst), 0);
+        assert_eq!(exec.trace.entries[1].outcome, StageOutcome::Skipped);
+        assert_eq!(exec.trace.entries[2].outcome, StageOutcome::Skipped);
+        assert_eq!(exec.trace.stages_executed(), 2);
+        assert_eq!(exec.decision.verdict, ReviewVerdict::Pass);
+    }
+
+    #[test]
+    fn test_advisory_issues_lower_confidence() {
+        let (v, _) = stage(StageReport::passed("ok"));
+        let (a, _) = stage(StageReport::from_issues(
+            vec![issue(false), issue(false)],
+            "2 matches",
+        ));
+        let (d, _) = stage(StageReport::passed("no impact"));
+        let exec = ReviewExecutor::new(ReviewerPolicy::default(), v, a, d).execute(&ctx());
+
+        assert_eq!(exec.decision.verdict, ReviewVerdict::Pass);
+        assert!((exec.decision.confidence - 0.9).abs() < 1e-9);
+        assert_eq!(exec.decision.issues.len(), 2);
+    }
+
+    #[test]
+    fn test_duration_budget_escalates() {
+        struct Slow;
+        impl StageRunner for Slow {
+            fn run(&self, _ctx: &ReviewContext) -> StageReport {
+                std::thread::sleep(std::time::Duration::from_millis(5));
+                StageReport::passed("slow")
+            }
+        }
+        let policy = ReviewerPolicy {
+            max_duration_ms: 1,
+            ..Default::default()
+        };
+        let exec = ReviewExecutor::new(policy, Box::new(Slow), Box::new(Slow), Box::new(Slow))
+            .execute(&ctx());
+        assert_eq!(exec.trace.entries.len(), 1);
+        assert_eq!(exec.decision.verdict, ReviewVerdict::NeedsEscalation);
+    }
+
+    #[test]
+    fn test_stages_get_deadline_from_budget() {
+        struct Deadline(Arc<Mutex<Vec<Option<Instant>>>>);
+        impl StageRunner for Deadline {
+            fn run(&self, ctx: &ReviewContext) -> StageReport {
+                self.0.lock().unwrap().push(ctx.deadline);
+                StageReport::passed("seen")
+            }
+        }
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let runner = || Box::new(Deadline(seen.clone()));
+        let policy = ReviewerPolicy {
+            max_duration_ms: 60_000,
+            ..Default::default()
+        };
+        ReviewExecutor::new(policy, runner(), runner(), runner()).execute(&ctx());
+        let unlimited = ReviewerPolicy {
+            max_duration_ms: 0,
+            ..Default::default()
+        };
+        ReviewExecutor::new(unlimited, runner(), runner(), runner()).execute(&ctx());
+
+        let seen = seen.lock().unwrap();
+        let (budgeted, unbudgeted) = seen.split_at(seen.len() / 2);
+        assert!(budgeted.iter().all(Option::is_some));
+        assert!(unbudgeted.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn test_gate_killed_at_deadline() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"slow\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("build.rs"),
+            "fn main() { std::thread::sleep(std::time::Duration::from_secs(30)); }\n",
+        )
+        .unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+        let stage = CargoVerifierStage {
+            gates: vec!["check".to_string(), "test".to_string()],
+            packages: vec!["slow".to_string()],
+            fail_fast: false,
+        };
+
+        let start = Instant::now();
+        let deadline = start + Duration::from_secs(3);
+        let (result, issues) = stage.run_gates(dir.path(), Some(deadline));
+        assert!(start.elapsed() < Duration::from_secs(20));
+        assert!(!result.all_passed);
+        // The timed-out gate stops the run even without fail-fast
+        assert_eq!(result.gates.len(), 1);
+        assert_eq!(result.first_failure.as_deref(), Some("check"));
+        assert_eq!(issues[0].description, "verifier gate `check` timed out");
+
+        let (result, _) = stage.run_gates(dir.path(), Some(start));
+        assert_eq!(result.gates.len(), 1);
+        assert!(!result.gates[0].passed);
+    }
+
+    #[test]
+    fn test_gate_args() {
+        let stage = CargoVerifierStage::default();
+        assert_eq!(
+            stage.gate_args("fmt").unwrap(),
+            vec!["fmt", "--all", "--", "--check"]
+        );
+        assert_eq!(
+            stage.gate_args("clippy").unwrap(),
+            vec![
+                "clippy",
+                "--all-targets",
+                "--workspace",
+                "--",
+                "-D",
+                "warnings"
+            ]
+        );
+        let scoped = CargoVerifierStage {
+            packages: vec!["coordination".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            scoped.gate_args("test").unwrap(),
+            vec!["test", "-p", "coordination"]
+        );
+        assert!(stage.gate_args("miri").is_none());
+    }
+
+    #[test]
+    fn test_count_diagnostics() {
+        let out = "warning: unused variable\nerror[E0308]: mismatched types\nerror: could not compile `x`\nwarning: `x` generated 1 warning\n";
+        let (errors, warnings, first) = count_diagnostics("check", out);
+        assert_eq!(errors, 1);
+        assert_eq!(warnings, 1);
+        assert_eq!(first.as_deref(), Some("error[E0308]: mismatched types"));
+
+        let (errors, _, first) = count_diagnostics("fmt", "Diff in /a.rs at line 1:\n-x\n+y\n");
+        assert_eq!(errors, 1);
+        assert!(first.unwrap().starts_with("Diff in"));
+    }
+
+    fn write_repo(dir: &Path) {
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(
+            dir.join("src/core.rs"),
+            "pub fn target() -> Option<u32> { None }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("src/a.rs"),
+            "pub fn a() -> u32 { target().unwrap() }\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("src/b.rs"), "pub fn b() -> u32 { a() }\n").unwrap();
+    }
+
+    #[test]
+    fn test_rule_pack_stage_on_touched_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write_repo(dir.path());
+        let ctx = ReviewContext::new(
+            dir.path(),
+            "HEAD",
+            vec!["src/a.rs".to_string(), "README.md".to_string()],
+        );
+        let stage = RulePackStage::new(
+            RulePackRegistry::with_defaults(),
+            AstGrepConfig {
+                binary: dir.path().join("no-sg").display().to_string(),
+                ..Default::default()
+            },
+        );
+        let report = stage.run(&ctx);
+        assert_eq!(report.outcome, StageOutcome::Failed);
+        let unwrap = report
+            .issues
+            .iter()
+            .find(|i| i.description.starts_with("[no-unwrap]"))
+            .expect("no-unwrap match");
+        assert!(unwrap.blocking);
+        assert_eq!(unwrap.file.as_deref(), Some("src/a.rs"));
+        assert_eq!(unwrap.line, Some(1));
+
+        let untouched = ReviewContext::new(dir.path(), "HEAD", vec!["README.md".to_string()]);
+        assert_eq!(stage.run(&untouched).outcome, StageOutcome::Passed);
+    }
+
+    #[test]
+    fn test_rule_pack_stage_reports_unavailable_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        write_repo(dir.path());
+        let ctx = ReviewContext::new(dir.path(), "HEAD", vec!["src/a.rs".to_string()]);
+        let mut pack = RulePack::new("broken", "rules no tier can run");
+        pack.add_rule(RulePackEntry::pattern_rule(
+            "bad-query",
+            "invalid node kind",
+            RuleSeverity::Error,
+            "rust",
+            "(no_such_node) @match",
+            "broken",
+        ));
+        let mut registry = RulePackRegistry::new();
+        registry.register(pack);
+        let stage = RulePackStage::new(
+            registry,
+            AstGrepConfig {
+                binary: dir.path().join("no-sg").display().to_string(),
+                ..Default::default()
+            },
+        );
+
+        let report = stage.run(&ctx);
+        assert_eq!(report.outcome, StageOutcome::Warning);
+        assert_eq!(report.issues.len(), 1);
+        let issue = &report.issues[0];
+        assert!(!issue.blocking);
+        assert!(issue.description.starts_with("[bad-query]"));
+        assert!(report.summary.contains("1 rules unavailable"));
+    }
+
+    #[test]
+    fn test_rule_pack_stage_runs_rule_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write_repo(dir.path());
+        std::fs::write(
+            dir.path().join("rules.yml"),
+            "id: unwrap-in-fn\nlanguage: Rust\nrule:\n  pattern: $X.unwrap()\n  \
+             inside:\n    kind: function_item\n    stopBy: end\n",
+        )
+        .unwrap();
+        let ctx = ReviewContext::new(dir.path(), "HEAD", vec!["src/a.rs".to_string()]);
+        let mut pack = RulePack::new("files", "rule-file rules");
+        for (id, file) in [("unwrap-in-fn", "rules.yml"), ("missing", "missing.yml")] {
+            pack.add_rule(RulePackEntry::file_rule(
+                id,
+                "composite rule",
+                RuleSeverity::Warning,
+                "rust",
+                file,
+                "style",
+            ));
+        }
+        let mut registry = RulePackRegistry::new();
+        registry.register(pack);
+
+        let report = RulePackStage::new(registry, AstGrepConfig::default()).run(&ctx);
+        let matched = report
+            .issues
+            .iter()
+            .find(|i| i.description.starts_with("[unwrap-in-fn]"))
+            .expect("rule file match");
+        assert_eq!(matched.file.as_deref(), Some("src/a.rs"));
+        assert_eq!(matched.line, Some(1));
+        let missing = report
+            .issues
+            .iter()
+            .find(|i| i.description.starts_with("[missing]"))
+            .expect("missing rule file reported");
+        assert!(missing.description.contains("could not run"));
+        assert!(report.summary.starts_with("2 rules"), "{}", report.summary);
+        assert!(report.summary.contains("1 rules unavailable"));
+    }
+
+    #[test]
+    fn test_dependency_stage() {
+        let dir = tempfile::tempdir().unwrap();
+        write_repo(dir.path());
+        let ctx = ReviewContext::new(dir.path(), "HEAD", vec!["src/core.rs".to_string()]);
+        let stage = DependencyImpactStage {
+            flag_at: ImpactLevel::Low,
+            ..Default::default()
+        };
+        let result = stage.check(&ctx);
+        assert_eq!(result.impact_level, ImpactLevel::Low);
+        assert_eq!(result.direct_dependents, 1);
+        assert_eq!(result.transitive_dependents, 1);
+        let files: Vec<&str> = result
+            .affected_files
+            .iter()
+            .map(|a| a.file.as_str())
+            .collect();
+        assert_eq!(files, vec!["src/a.rs", "src/b.rs"]);
+
+        let report = stage.run(&ctx);
+        assert_eq!(report.outcome, StageOutcome::Warning);
+        assert!(!report.issues[0].blocking);
+    }
+
+    #[test]
+    fn test_context_from_git() {
+        let dir = tempfile::tempdir().unwrap();
+        git_init(dir.path());
+        write_repo(dir.path());
+        git_commit_all(dir.path(), "base");
+        std::fs::write(dir.path().join("src/b.rs"), "pub fn b() -> u32 { 2 }\n").unwrap();
+        std::fs::remove_file(dir.path().join("src/a.rs")).unwrap();
+
+        let ctx = ReviewContext::from_git(dir.path(), "HEAD").unwrap();
+        assert_eq!(ctx.touched_files, vec!["src/b.rs".to_string()]);
+        assert!(ctx.diff.contains("+pub fn b() -> u32 { 2 }"));
+        assert!(ReviewContext::from_git(dir.path(), "no-such-ref").is_err());
+    }
+
+    #[test]
+    fn test_dependency_stage_flags_api_break() {
+        let dir = tempfile::tempdir().unwrap();
+        git_init(dir.path());
+        write_repo(dir.path());
+        git_commit_all(dir.path(), "base");
+        std::fs::write(
+            dir.path().join("src/core.rs"),
+            "pub fn target(x: u32) -> Option<u32> { Some(x) }\n",
+        )
+        .unwrap();
+
+        let ctx = ReviewContext::from_git(dir.path(), "HEAD").unwrap();
+        let stage = DependencyImpactStage::default();
+        let result = stage.check(&ctx);
+        assert!(result.api_change_detected);
+        assert_eq!(result.impact_level, ImpactLevel::Medium);
+
+        let report = stage.run(&ctx);
+        assert_eq!(report.outcome, StageOutcome::Warning);
+        let issue = &report.issues[0];
+        assert!(!issue.blocking);
+        assert_eq!(issue.file.as_deref(), Some("src/core.rs"));
+        assert!(issue
+            .description
+            .starts_with("breaking api change: signature_changed function `crate::core::target`"));
+    }
+}
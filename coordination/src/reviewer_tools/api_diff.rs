@@ -0,0 +1,1171 @@
+//! Public API change detection — compare the `pub` surface of Rust crates.
+//!
+//! Extracts every publicly reachable item (functions, types, traits, trait
+//! methods, inherent methods, fields, variants, consts and statics) with
+//! tree-sitter, diffs two snapshots, and classifies each change:
+//!
+//! | Change                              | Breaking | Impact   |
+//! |-------------------------------------|----------|----------|
+//! | added item                          | no       | none     |
+//! | added enum variant                  | yes      | medium   |
+//! | added required trait method         | yes      | high     |
+//! | removed / signature-changed item    | yes      | medium   |
+//! | removed / changed trait or its item | yes      | high     |
+//!
+//! Item paths are derived from the file layout (`core/src/store/mod.rs` →
+//! `core::store`), so moving an item between modules shows up as a removal
+//! plus an addition. Only plain `pub` counts as public; `pub(crate)` and
+//! items inside non-`pub` modules are ignored. Dependents are found by
+//! identifier, which over-approximates like the code graph does.
+
+use super::code_graph::{in_scope, language_for_path, ts_language};
+use crate::tool_schema::{
+    AffectedFile, DependencyCheckRequest, DependencyCheckResult, ImpactLevel,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+
+/// Kind of public item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiItemKind {
+    /// Free function.
+    Function,
+    /// Inherent `pub fn` in an `impl` block.
+    Method,
+    /// Struct.
+    Struct,
+    /// Enum.
+    Enum,
+    /// Union.
+    Union,
+    /// Trait.
+    Trait,
+    /// Method, associated type or const declared in a trait.
+    TraitItem,
+    /// Type alias.
+    Type,
+    /// Constant.
+    Const,
+    /// Static.
+    Static,
+    /// Public struct field.
+    Field,
+    /// Enum variant.
+    Variant,
+}
+
+impl ApiItemKind {
+    fn is_trait(self) -> bool {
+        matches!(self, Self::Trait | Self::TraitItem)
+    }
+}
+
+impl std::fmt::Display for ApiItemKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Function => write!(f, "function"),
+            Self::Method => write!(f, "method"),
+            Self::Struct => write!(f, "struct"),
+            Self::Enum => write!(f, "enum"),
+            Self::Union => write!(f, "union"),
+            Self::Trait => write!(f, "trait"),
+            Self::TraitItem => write!(f, "trait_item"),
+            Self::Type => write!(f, "type"),
+            Self::Const => write!(f, "const"),
+            Self::Static => write!(f, "static"),
+            Self::Field => write!(f, "field"),
+            Self::Variant => write!(f, "variant"),
+        }
+    }
+}
+
+/// A publicly reachable item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiItem {
+    /// Module-qualified path (`crate::store::Store::open`, `crate::Config.port`).
+    pub path: String,
+    /// Item kind.
+    pub kind: ApiItemKind,
+    /// File the item is defined in.
+    pub file: String,
+    /// Line number (1-indexed).
+    pub line: u32,
+    /// Declaration without body or value, whitespace-collapsed.
+    pub signature: String,
+    /// Trait item that implementors must provide (no default).
+    pub required: bool,
+}
+
+impl ApiItem {
+    /// Identifier dependents would use to refer to the item.
+    ///
+    /// Tuple fields (`Wrapper.0`) resolve to their parent type name.
+    pub fn name(&self) -> &str {
+        let mut segments = self.path.rsplit(['.', ':']).filter(|s| !s.is_empty());
+        let last = segments.next().unwrap_or(&self.path);
+        if last.chars().all(|c| c.is_ascii_digit()) {
+            segments.next().unwrap_or(last)
+        } else {
+            last
+        }
+    }
+}
+
+/// Module path for a source file (`core/src/store/mod.rs` → `core::store`).
+///
+/// The directory above `src/` names the crate; files outside any `src/`
+/// directory are rooted at `crate`.
+pub fn module_path(file: &str) -> String {
+    let file = file.replace('\\', "/");
+    let parts: Vec<&str> = file.split('/').filter(|p| !p.is_empty()).collect();
+    let (krate, modules) = match parts.iter().rposition(|p| *p == "src") {
+        Some(idx) if idx > 0 => (parts[idx - 1], &parts[idx + 1..]),
+        Some(_) => ("crate", &parts[1..]),
+        None => ("crate", &parts[..]),
+    };
+    let mut path = vec![krate.replace('-', "_")];
+    for (i, part) in modules.iter().enumerate() {
+        let last = i + 1 == modules.len();
+        let stem = if last {
+            part.trim_end_matches(".rs")
+        } else {
+            part
+        };
+        if last && matches!(stem, "lib" | "main" | "mod") {
+            continue;
+        }
+        path.push(stem.replace('-', "_"));
+    }
+    path.join("::")
+}
+
+/// Maximum characters kept for an item signature.
+const MAX_SIGNATURE_CHARS: usize = 400;
+
+/// Text of `node` up to `stop`, collapsed onto one line.
+fn decl_text(
+    node: tree_sitter::Node<'_>,
+    stop: Option<tree_sitter::Node<'_>>,
+    src: &[u8],
+) -> String {
+    let end = stop
+        .map(|s| s.start_byte())
+        .unwrap_or_else(|| node.end_byte());
+    let raw = String::from_utf8_lossy(&src[node.start_byte()..end]);
+    let collapsed = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+    let trimmed = collapsed.trim_end_matches([';', '{', '=', ',', ' ']);
+    match trimmed.char_indices().nth(MAX_SIGNATURE_CHARS) {
+        Some((idx, _)) => format!("{}…", &trimmed[..idx]),
+        None => trimmed.to_string(),
+    }
+}
+
+/// Whether `node` carries a plain `pub` visibility modifier.
+fn is_pub(node: tree_sitter::Node<'_>, src: &[u8]) -> bool {
+    (0..node.named_child_count())
+        .filter_map(|i| node.named_child(i))
+        .any(|c| c.kind() == "visibility_modifier" && c.utf8_text(src) == Ok("pub"))
+}
+
+// ── Surface extraction ───────────────────────────────────────────────
+
+struct SurfaceExtractor<'a> {
+    src: &'a [u8],
+    file: String,
+    items: Vec<ApiItem>,
+}
+
+impl<'a> SurfaceExtractor<'a> {
+    fn text(&self, node: tree_sitter::Node<'_>) -> String {
+        node.utf8_text(self.src).unwrap_or_default().to_string()
+    }
+
+    fn name(&self, node: tree_sitter::Node<'_>) -> Option<String> {
+        node.child_by_field_name("name").map(|n| self.text(n))
+    }
+
+    fn push(
+        &mut self,
+        path: String,
+        kind: ApiItemKind,
+        node: tree_sitter::Node<'_>,
+        signature: String,
+    ) {
+        self.items.push(ApiItem {
+            path,
+            kind,
+            file: self.file.clone(),
+            line: node.start_position().row as u32 + 1,
+            signature,
+            required: false,
+        });
+    }
+
+    /// Last identifier of a type expression (`foo::Bar<T>` → `Bar`).
+    fn type_name(&self, node: tree_sitter::Node<'_>) -> Option<String> {
+        match node.kind() {
+            "type_identifier" | "identifier" => Some(self.text(node)),
+            "scoped_type_identifier" => node
+                .child_by_field_name("name")
+                .and_then(|n| self.type_name(n)),
+            "generic_type" => node
+                .child_by_field_name("type")
+                .and_then(|n| self.type_name(n)),
+            _ => None,
+        }
+    }
+
+    /// Visit the items of a module body; only `pub` items are recorded.
+    fn visit_module(&mut self, node: tree_sitter::Node<'_>, module: &str) {
+        for i in 0..node.named_child_count() {
+            let Some(child) = node.named_child(i) else {
+                continue;
+            };
+            if child.kind() == "impl_item" {
+                self.visit_impl(child, module);
+                continue;
+            }
+            if !is_pub(child, self.src) {
+                continue;
+            }
+            let Some(name) = self.name(child) else {
+                continue;
+            };
+            let path = format!("{module}::{name}");
+            let body = child.child_by_field_name("body");
+            match child.kind() {
+                "function_item" => {
+                    let sig = decl_text(child, body, self.src);
+                    self.push(path, ApiItemKind::Function, child, sig);
+                }
+                "struct_item" | "union_item" => {
+                    let kind = if child.kind() == "struct_item" {
+                        ApiItemKind::Struct
+                    } else {
+                        ApiItemKind::Union
+                    };
+                    let sig = decl_text(child, body, self.src);
+                    self.push(path.clone(), kind, child, sig);
+                    if let Some(body) = body {
+                        self.visit_fields(body, &path);
+                    }
+                }
+                "enum_item" => {
+                    let sig = decl_text(child, body, self.src);
+                    self.push(path.clone(), ApiItemKind::Enum, child, sig);
+                    if let Some(body) = body {
+                        self.visit_variants(body, &path);
+                    }
+                }
+                "trait_item" => {
+                    let sig = decl_text(child, body, self.src);
+                    self.push(path.clone(), ApiItemKind::Trait, child, sig);
+                    if let Some(body) = body {
+                        self.visit_trait(body, &path);
+                    }
+                }
+                "type_item" => {
+                    let sig = decl_text(child, None, self.src);
+                    self.push(path, ApiItemKind::Type, child, sig);
+                }
+                "const_item" | "static_item" => {
+                    let kind = if child.kind() == "const_item" {
+                        ApiItemKind::Const
+                    } else {
+                        ApiItemKind::Static
+                    };
+                    let sig = decl_text(child, child.child_by_field_name("value"), self.src);
+                    self.push(path, kind, child, sig);
+                }
+                "mod_item" => {
+                    if let Some(body) = body {
+                        self.visit_module(body, &path);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn visit_fields(&mut self, body: tree_sitter::Node<'_>, parent: &str) {
+        let mut index = 0;
+        let mut pending_pub = false;
+        for i in 0..body.named_child_count() {
+            let Some(child) = body.named_child(i) else {
+                continue;
+            };
+            match child.kind() {
+                "field_declaration" => {
+                    if let (true, Some(name)) = (is_pub(child, self.src), self.name(child)) {
+                        let sig = decl_text(child, None, self.src);
+                        self.push(format!("{parent}.{name}"), ApiItemKind::Field, child, sig);
+                    }
+                }
+                // Tuple struct: `visibility_modifier? type` pairs.
+                "visibility_modifier" => pending_pub = child.utf8_text(self.src) == Ok("pub"),
+                "attribute_item" | "line_comment" | "block_comment" => {}
+                _ if body.kind() == "ordered_field_declaration_list" => {
+                    if pending_pub {
+                        let sig = format!("pub {}", decl_text(child, None, self.src));
+                        self.push(format!("{parent}.{index}"), ApiItemKind::Field, child, sig);
+                    }
+                    pending_pub = false;
+                    index += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn visit_variants(&mut self, body: tree_sitter::Node<'_>, parent: &str) {
+        for i in 0..body.named_child_count() {
+            let Some(child) = body.named_child(i) else {
+                continue;
+            };
+            if child.kind() != "enum_variant" {
+                continue;
+            }
+            if let Some(name) = self.name(child) {
+                let sig = decl_text(child, None, self.src);
+                self.push(
+                    format!("{parent}::{name}"),
+                    ApiItemKind::Variant,
+                    child,
+                    sig,
+                );
+            }
+        }
+    }
+
+    fn visit_trait(&mut self, body: tree_sitter::Node<'_>, parent: &str) {
+        for i in 0..body.named_child_count() {
+            let Some(child) = body.named_child(i) else {
+                continue;
+            };
+            let required = match child.kind() {
+                "function_signature_item" => true,
+                "function_item" => false,
+                "associated_type" => child.child_by_field_name("default_type").is_none(),
+                "const_item" => child.child_by_field_name("value").is_none(),
+                _ => continue,
+            };
+            let Some(name) = self.name(child) else {
+                continue;
+            };
+            let stop = child
+                .child_by_field_name("body")
+                .or_else(|| child.child_by_field_name("value"));
+            let sig = decl_text(child, stop, self.src);
+            self.push(
+                format!("{parent}::{name}"),
+                ApiItemKind::TraitItem,
+                child,
+                sig,
+            );
+            if let Some(item) = self.items.last_mut() {
+                item.required = required;
+            }
+        }
+    }
+
+    /// Inherent `impl` blocks contribute their `pub fn`s; trait impls do not.
+    fn visit_impl(&mut self, node: tree_sitter::Node<'_>, module: &str) {
+        if node.child_by_field_name("trait").is_some() {
+            return;
+        }
+        let Some(ty) = node
+            .child_by_field_name("type")
+            .and_then(|t| self.type_name(t))
+        else {
+            return;
+        };
+        let Some(body) = node.child_by_field_name("body") else {
+            return;
+        };
+        for i in 0..body.named_child_count() {
+            let Some(child) = body.named_child(i) else {
+                continue;
+            };
+            if !matches!(child.kind(), "function_item" | "const_item") || !is_pub(child, self.src) {
+                continue;
+            }
+            let Some(name) = self.name(child) else {
+                continue;
+            };
+            let (kind, stop) = if child.kind() == "function_item" {
+                (ApiItemKind::Method, child.child_by_field_name("body"))
+            } else {
+                (ApiItemKind::Const, child.child_by_field_name("value"))
+            };
+            let sig = decl_text(child, stop, self.src);
+            self.push(format!("{module}::{ty}::{name}"), kind, child, sig);
+        }
+    }
+}
+
+fn rust_parser() -> Result<tree_sitter::Parser, String> {
+    let mut parser = tree_sitter::Parser::new();
+    let language = ts_language("rust").ok_or("no rust grammar")?;
+    parser
+        .set_language(&language)
+        .map_err(|e| format!("failed to load rust grammar: {e}"))?;
+    Ok(parser)
+}
+
+/// Public items of a set of Rust sources, keyed by path.
+#[derive(Debug, Clone, Default)]
+pub struct ApiSurface {
+    items: BTreeMap<String, ApiItem>,
+}
+
+impl ApiSurface {
+    /// Extract the public surface from in-memory `(path, source)` pairs.
+    ///
+    /// Non-Rust files and files that fail to parse are skipped. When two
+    /// items share a path (e.g. `#[cfg]` alternatives) the first is kept.
+    pub fn from_sources(sources: &[(&str, &str)]) -> Self {
+        let mut surface = Self::default();
+        let Ok(mut parser) = rust_parser() else {
+            return surface;
+        };
+        for (file, source) in sources {
+            if language_for_path(Path::new(file)) != Some("rust") {
+                continue;
+            }
+            let Some(tree) = parser.parse(source, None) else {
+                continue;
+            };
+            let mut extractor = SurfaceExtractor {
+                src: source.as_bytes(),
+                file: file.to_string(),
+                items: Vec::new(),
+            };
+            extractor.visit_module(tree.root_node(), &module_path(file));
+            for item in extractor.items {
+                surface.items.entry(item.path.clone()).or_insert(item);
+            }
+        }
+        surface
+    }
+
+    /// Look up an item by path.
+    pub fn get(&self, path: &str) -> Option<&ApiItem> {
+        self.items.get(path)
+    }
+
+    /// All items, ordered by path.
+    pub fn items(&self) -> impl Iterator<Item = &ApiItem> {
+        self.items.values()
+    }
+
+    /// Number of public items.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether the surface has no public items.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+// ── Diff ─────────────────────────────────────────────────────────────
+
+/// How an item changed between two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiChangeKind {
+    /// Item is new.
+    Added,
+    /// Item no longer exists.
+    Removed,
+    /// Item exists in both snapshots with a different declaration.
+    SignatureChanged,
+}
+
+impl std::fmt::Display for ApiChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Added => write!(f, "added"),
+            Self::Removed => write!(f, "removed"),
+            Self::SignatureChanged => write!(f, "signature_changed"),
+        }
+    }
+}
+
+/// A single change to the public surface.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiChange {
+    /// What happened to the item.
+    pub change: ApiChangeKind,
+    /// The item after the change (before it, for removals).
+    pub item: ApiItem,
+    /// Previous declaration (for removals and signature changes).
+    pub before: Option<String>,
+}
+
+impl ApiChange {
+    /// Whether existing dependents may stop compiling.
+    pub fn is_breaking(&self) -> bool {
+        match self.change {
+            ApiChangeKind::Removed | ApiChangeKind::SignatureChanged => true,
+            ApiChangeKind::Added => {
+                self.item.kind == ApiItemKind::Variant
+                    || (self.item.kind == ApiItemKind::TraitItem && self.item.required)
+            }
+        }
+    }
+
+    /// Impact of this change alone, before counting dependents.
+    pub fn impact(&self) -> ImpactLevel {
+        if !self.is_breaking() {
+            ImpactLevel::None
+        } else if self.item.kind.is_trait() {
+            ImpactLevel::High
+        } else {
+            ImpactLevel::Medium
+        }
+    }
+}
+
+impl std::fmt::Display for ApiChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} `{}`", self.change, self.item.kind, self.item.path)?;
+        if let Some(before) = &self.before {
+            if self.change == ApiChangeKind::SignatureChanged {
+                write!(f, ": `{before}` -> `{}`", self.item.signature)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Changes between two public surfaces.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApiDiff {
+    /// Changes ordered by item path.
+    pub changes: Vec<ApiChange>,
+}
+
+/// Whether `path` is a member (`Parent::x` / `Parent.x`) of any path in `parents`.
+fn is_member_of(path: &str, parents: &BTreeSet<&str>) -> bool {
+    parents.iter().any(|p| {
+        path.strip_prefix(p)
+            .is_some_and(|rest| rest.starts_with("::") || rest.starts_with('.'))
+    })
+}
+
+impl ApiDiff {
+    /// Compare two surfaces.
+    ///
+    /// Members of an item that was itself added or removed (its fields,
+    /// variants, methods) are folded into that item's change.
+    pub fn compare(before: &ApiSurface, after: &ApiSurface) -> Self {
+        let removed: BTreeSet<&str> = before
+            .items
+            .keys()
+            .filter(|k| !after.items.contains_key(*k))
+            .map(String::as_str)
+            .collect();
+        let added: BTreeSet<&str> = after
+            .items
+            .keys()
+            .filter(|k| !before.items.contains_key(*k))
+            .map(String::as_str)
+            .collect();
+
+        let mut changes = Vec::new();
+        for path in &removed {
+            if !is_member_of(path, &removed) {
+                let item = before.items[*path].clone();
+                changes.push(ApiChange {
+                    change: ApiChangeKind::Removed,
+                    before: Some(item.signature.clone()),
+                    item,
+                });
+            }
+        }
+        for path in &added {
+            if !is_member_of(path, &added) {
+                changes.push(ApiChange {
+                    change: ApiChangeKind::Added,
+                    item: after.items[*path].clone(),
+                    before: None,
+                });
+            }
+        }
+        for (path, old) in &before.items {
+            if let Some(new) = after.items.get(path) {
+                if old.signature != new.signature || old.required != new.required {
+                    changes.push(ApiChange {
+                        change: ApiChangeKind::SignatureChanged,
+                        item: new.clone(),
+                        before: Some(old.signature.clone()),
+                    });
+                }
+            }
+        }
+        changes.sort_by(|a, b| a.item.path.cmp(&b.item.path));
+        Self { changes }
+    }
+
+    /// Changes that may break dependents.
+    pub fn breaking(&self) -> impl Iterator<Item = &ApiChange> {
+        self.changes.iter().filter(|c| c.is_breaking())
+    }
+
+    /// Whether any change may break dependents.
+    pub fn has_breaking(&self) -> bool {
+        self.breaking().next().is_some()
+    }
+
+    /// Highest impact across all changes.
+    pub fn impact_level(&self) -> ImpactLevel {
+        self.changes
+            .iter()
+            .map(ApiChange::impact)
+            .max()
+            .unwrap_or(ImpactLevel::None)
+    }
+
+    /// Whether the surface is unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// One-line summary for logs.
+    pub fn summary_line(&self) -> String {
+        let count = |kind| self.changes.iter().filter(|c| c.change == kind).count();
+        format!(
+            "{} api changes ({} added, {} removed, {} changed), {} breaking",
+            self.changes.len(),
+            count(ApiChangeKind::Added),
+            count(ApiChangeKind::Removed),
+            count(ApiChangeKind::SignatureChanged),
+            self.breaking().count()
+        )
+    }
+}
+
+// ── Dependents ───────────────────────────────────────────────────────
+
+/// Identifiers mentioned by each file, for name-based dependent lookup.
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceIndex {
+    names: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl ReferenceIndex {
+    /// Index identifiers in in-memory `(path, source)` pairs.
+    pub fn from_sources(sources: &[(&str, &str)]) -> Self {
+        let mut index = Self::default();
+        let Ok(mut parser) = rust_parser() else {
+            return index;
+        };
+        for (file, source) in sources {
+            if language_for_path(Path::new(file)) != Some("rust") {
+                continue;
+            }
+            let Some(tree) = parser.parse(source, None) else {
+                continue;
+            };
+            let names = index.names.entry(file.to_string()).or_default();
+            let mut stack = vec![tree.root_node()];
+            while let Some(node) = stack.pop() {
+                if matches!(
+                    node.kind(),
+                    "identifier" | "type_identifier" | "field_identifier"
+                ) {
+                    if let Ok(text) = node.utf8_text(source.as_bytes()) {
+                        names.insert(text.to_string());
+                    }
+                }
+                stack.extend((0..node.named_child_count()).filter_map(|i| node.named_child(i)));
+            }
+        }
+        index
+    }
+
+    /// Files that mention `name`, excluding `exclude`.
+    pub fn files_mentioning<'a>(
+        &'a self,
+        name: &'a str,
+        exclude: &'a str,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        self.names
+            .iter()
+            .filter(move |(file, names)| file.as_str() != exclude && names.contains(name))
+            .map(|(file, _)| file.as_str())
+    }
+}
+
+/// Files referencing breaking changes, plus (optionally) their dependents.
+///
+/// Transitive dependents are files that mention a public item defined in an
+/// already-affected file, followed to a fixpoint.
+pub fn affected_files(
+    diff: &ApiDiff,
+    index: &ReferenceIndex,
+    head: &ApiSurface,
+    include_transitive: bool,
+) -> Vec<AffectedFile> {
+    let mut affected: BTreeMap<String, AffectedFile> = BTreeMap::new();
+    let changed_files: BTreeSet<&str> = diff.changes.iter().map(|c| c.item.file.as_str()).collect();
+
+    for change in diff.breaking() {
+        for file in index.files_mentioning(change.item.name(), &change.item.file) {
+            affected
+                .entry(file.to_string())
+                .or_insert_with(|| AffectedFile {
+                    file: file.to_string(),
+                    reason: format!("references {} `{}`", change.change, change.item.path),
+                    direct: true,
+                });
+        }
+    }
+
+    if include_transitive {
+        let mut frontier: Vec<String> = affected.keys().cloned().collect();
+        while let Some(file) = frontier.pop() {
+            let names: BTreeSet<&str> = head
+                .items()
+                .filter(|i| i.file == file && !matches!(i.kind, ApiItemKind::Field))
+                .map(ApiItem::name)
+                .collect();
+            for name in names {
+                for dependent in index.files_mentioning(name, &file) {
+                    if affected.contains_key(dependent) || changed_files.contains(dependent) {
+                        continue;
+                    }
+                    affected.insert(
+                        dependent.to_string(),
+                        AffectedFile {
+                            file: dependent.to_string(),
+                            reason: format!("depends on {file}"),
+                            direct: false,
+                        },
+                    );
+                    frontier.push(dependent.to_string());
+                }
+            }
+        }
+    }
+
+    affected.into_values().collect()
+}
+
+// ── Git-backed detector ──────────────────────────────────────────────
+
+/// Compares the public surface of a repository between two revisions.
+#[derive(Debug, Clone)]
+pub struct ApiChangeDetector {
+    repo_root: PathBuf,
+}
+
+impl ApiChangeDetector {
+    /// Create a detector for the repository at `repo_root`.
+    pub fn new(repo_root: &Path) -> Self {
+        Self {
+            repo_root: repo_root.to_path_buf(),
+        }
+    }
+
+    fn git(&self, args: &[&str]) -> Result<String, String> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(&self.repo_root)
+            .output()
+            .map_err(|e| format!("failed to run git: {e}"))?;
+        if !output.status.success() {
+            return Err(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Contents of `file` at `rev`, or in the worktree when `rev` is `None`.
+    fn read(&self, rev: Option<&str>, file: &str) -> Option<String> {
+        match rev {
+            Some(rev) => self.git(&["show", &format!("{rev}:{file}")]).ok(),
+            None => std::fs::read_to_string(self.repo_root.join(file)).ok(),
+        }
+    }
+
+    /// Rust files at `rev`, or in the worktree (honouring `.gitignore`).
+    fn rust_files(&self, rev: Option<&str>) -> Result<Vec<String>, String> {
+        let mut files: Vec<String> = match rev {
+            Some(rev) => self
+                .git(&["ls-tree", "-r", "--name-only", rev])?
+                .lines()
+                .map(str::to_string)
+                .collect(),
+            None => ignore::WalkBuilder::new(&self.repo_root)
+                .build()
+                .flatten()
+                .filter(|e| e.path().is_file())
+                .filter_map(|e| {
+                    e.path()
+                        .strip_prefix(&self.repo_root)
+                        .ok()
+                        .map(|p| p.to_string_lossy().replace('\\', "/"))
+                })
+                .collect(),
+        };
+        files.retain(|f| language_for_path(Path::new(f)) == Some("rust"));
+        files.sort();
+        Ok(files)
+    }
+
+    fn load(&self, rev: Option<&str>, files: &[String]) -> Vec<(String, String)> {
+        files
+            .iter()
+            .filter_map(|f| self.read(rev, f).map(|c| (f.clone(), c)))
+            .collect()
+    }
+
+    /// Rust files changed between `base` and `head` (the worktree when `None`).
+    pub fn changed_files(&self, base: &str, head: Option<&str>) -> Result<Vec<String>, String> {
+        let mut args = vec!["diff", "--name-only", base];
+        args.extend(head);
+        Ok(self
+            .git(&args)?
+            .lines()
+            .map(str::trim)
+            .filter(|f| language_for_path(Path::new(f)) == Some("rust"))
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Diff the public surface of `files` between `base` and `head`.
+    pub fn diff(&self, base: &str, head: Option<&str>, files: &[String]) -> ApiDiff {
+        let before = self.load(Some(base), files);
+        let after = self.load(head, files);
+        ApiDiff::compare(&surface_of(&before), &surface_of(&after))
+    }
+
+    /// Answer a dependency check by diffing the public surface of the
+    /// request's changed files between `base` and `head`.
+    ///
+    /// An empty `changed_files` list means every Rust file git reports as
+    /// changed, deletions included. `package` restricts both the diffed files
+    /// and the dependents to that crate directory.
+    pub fn check(
+        &self,
+        request: &DependencyCheckRequest,
+        base: &str,
+        head: Option<&str>,
+    ) -> DependencyCheckResult {
+        self.check_with_diff(request, base, head).0
+    }
+
+    /// Like [`check`](Self::check), also returning the underlying diff.
+    ///
+    /// The diff is empty when the check fails.
+    pub fn check_with_diff(
+        &self,
+        request: &DependencyCheckRequest,
+        base: &str,
+        head: Option<&str>,
+    ) -> (DependencyCheckResult, ApiDiff) {
+        let start = Instant::now();
+        match self.try_check(request, base, head) {
+            Ok((mut result, diff)) => {
+                result.duration_ms = start.elapsed().as_millis() as u64;
+                (result, diff)
+            }
+            Err(e) => {
+                let mut result =
+                    DependencyCheckResult::no_impact(start.elapsed().as_millis() as u64);
+                result.success = false;
+                result.error = Some(e);
+                (result, ApiDiff::default())
+            }
+        }
+    }
+
+    fn try_check(
+        &self,
+        request: &DependencyCheckRequest,
+        base: &str,
+        head: Option<&str>,
+    ) -> Result<(DependencyCheckResult, ApiDiff), String> {
+        let scoped = |f: &String| request.package.as_deref().is_none_or(|p| in_scope(f, p));
+        let mut changed = if request.changed_files.is_empty() {
+            self.changed_files(base, head)?
+        } else {
+            request.changed_files.clone()
+        };
+        changed.retain(|f| scoped(f) && language_for_path(Path::new(f)) == Some("rust"));
+
+        let diff = self.diff(base, head, &changed);
+        let mut result = DependencyCheckResult::no_impact(0);
+        result.api_change_detected = !diff.is_empty();
+        if !diff.has_breaking() {
+            return Ok((result, diff));
+        }
+
+        let mut files = self.rust_files(head)?;
+        files.retain(scoped);
+        let sources = self.load(head, &files);
+        let refs: Vec<(&str, &str)> = sources
+            .iter()
+            .map(|(p, c)| (p.as_str(), c.as_str()))
+            .collect();
+        let index = ReferenceIndex::from_sources(&refs);
+        let head_surface = if request.include_transitive {
+            ApiSurface::from_sources(&refs)
+        } else {
+            ApiSurface::default()
+        };
+        let affected = affected_files(&diff, &index, &head_surface, request.include_transitive);
+
+        result.direct_dependents = affected.iter().filter(|a| a.direct).count();
+        result.transitive_dependents = affected.len() - result.direct_dependents;
+        result.impact_level = diff
+            .impact_level()
+            .max(ImpactLevel::from_dependents(affected.len()));
+        result.affected_files = affected;
+        Ok((result, diff))
+    }
+}
+
+fn surface_of(sources: &[(String, String)]) -> ApiSurface {
+    let refs: Vec<(&str, &str)> = sources
+        .iter()
+        .map(|(p, c)| (p.as_str(), c.as_str()))
+        .collect();
+    ApiSurface::from_sources(&refs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{git_commit_all, git_init};
+
+    const BEFORE: &str = r#"
+pub struct Store {
+    pub path: String,
+    cache: Vec<u8>,
+}
+
+pub struct Id(pub u64, u32);
+
+impl Store {
+    pub fn open(path: &str) -> Self { todo!() }
+    fn private(&self) {}
+}
+
+pub enum Mode { Read, Write }
+
+pub trait Backend {
+    fn get(&self, key: &str) -> Option<Vec<u8>>;
+    fn flush(&self) {}
+}
+
+pub(crate) fn internal() {}
+pub const LIMIT: usize = 10;
+
+mod hidden {
+    pub fn not_public() {}
+}
+
+pub mod api {
+    pub fn exported() {}
+}
+"#;
+
+    fn surface(src: &str) -> ApiSurface {
+        ApiSurface::from_sources(&[("core/src/lib.rs", src)])
+    }
+
+    fn diff(after: &str) -> ApiDiff {
+        ApiDiff::compare(&surface(BEFORE), &surface(after))
+    }
+
+    #[test]
+    fn test_module_path() {
+        assert_eq!(module_path("src/lib.rs"), "crate");
+        assert_eq!(module_path("core/src/store/mod.rs"), "core::store");
+        assert_eq!(module_path("my-crate/src/a/b.rs"), "my_crate::a::b");
+        assert_eq!(module_path("build.rs"), "crate::build");
+    }
+
+    #[test]
+    fn test_extracts_public_surface() {
+        let s = surface(BEFORE);
+        let paths: Vec<&str> = s.items().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "core::Backend",
+                "core::Backend::flush",
+                "core::Backend::get",
+                "core::Id",
+                "core::Id.0",
+                "core::LIMIT",
+                "core::Mode",
+                "core::Mode::Read",
+                "core::Mode::Write",
+                "core::Store",
+                "core::Store.path",
+                "core::Store::open",
+                "core::api::exported",
+            ]
+        );
+        assert_eq!(
+            s.get("core::Store::open").unwrap().signature,
+            "pub fn open(path: &str) -> Self"
+        );
+        assert_eq!(
+            s.get("core::LIMIT").unwrap().signature,
+            "pub const LIMIT: usize"
+        );
+        assert!(s.get("core::Backend::get").unwrap().required);
+        assert!(!s.get("core::Backend::flush").unwrap().required);
+        assert_eq!(s.get("core::Id.0").unwrap().name(), "Id");
+    }
+
+    #[test]
+    fn test_unchanged_surface() {
+        let after = BEFORE.replace("todo!()", "unimplemented!()");
+        let d = diff(&after);
+        assert!(d.is_empty());
+        assert_eq!(d.impact_level(), ImpactLevel::None);
+    }
+
+    #[test]
+    fn test_signature_change_and_addition() {
+        let after = BEFORE
+            .replace("pub fn open(path: &str)", "pub fn open(path: &Path)")
+            .replace("pub mod api {", "pub mod api {\n    pub fn added() {}");
+        let d = diff(&after);
+        assert_eq!(d.changes.len(), 2);
+        let added = &d.changes[1];
+        assert_eq!(added.item.path, "core::api::added");
+        assert!(!added.is_breaking());
+        let changed = &d.changes[0];
+        assert_eq!(changed.change, ApiChangeKind::SignatureChanged);
+        assert_eq!(
+            changed.before.as_deref(),
+            Some("pub fn open(path: &str) -> Self")
+        );
+        assert_eq!(changed.impact(), ImpactLevel::Medium);
+        assert_eq!(d.impact_level(), ImpactLevel::Medium);
+        assert!(changed
+            .to_string()
+            .contains("-> `pub fn open(path: &Path) -> Self`"));
+    }
+
+    #[test]
+    fn test_removed_type_folds_members() {
+        let after = BEFORE.replace("pub enum Mode { Read, Write }", "");
+        let d = diff(&after);
+        assert_eq!(d.changes.len(), 1);
+        assert_eq!(d.changes[0].change, ApiChangeKind::Removed);
+        assert_eq!(d.changes[0].item.path, "core::Mode");
+    }
+
+    #[test]
+    fn test_trait_and_variant_additions_are_breaking() {
+        let after = BEFORE
+            .replace("Read, Write", "Read, Write, Append")
+            .replace(
+                "fn flush(&self) {}",
+                "fn flush(&self) {}\n    fn sync(&self);",
+            );
+        let d = diff(&after);
+        assert_eq!(d.breaking().count(), 2);
+        assert_eq!(d.impact_level(), ImpactLevel::High);
+        assert!(d
+            .summary_line()
+            .contains("2 added, 0 removed, 0 changed), 2 breaking"));
+    }
+
+    #[test]
+    fn test_visibility_narrowing_is_removal() {
+        let after = BEFORE.replace("pub path: String", "pub(crate) path: String");
+        let d = diff(&after);
+        assert_eq!(d.changes[0].change, ApiChangeKind::Removed);
+        assert_eq!(d.changes[0].item.path, "core::Store.path");
+    }
+
+    #[test]
+    fn test_affected_files() {
+        let d = diff(&BEFORE.replace("pub fn open(path: &str)", "pub fn open(path: &Path)"));
+        let sources = [
+            ("core/src/lib.rs", BEFORE),
+            (
+                "app/src/main.rs",
+                "use core::Store;\npub fn run() { Store::open(\"x\"); }",
+            ),
+            ("app/src/cli.rs", "fn main() { crate::run(); }"),
+            ("app/src/other.rs", "fn unrelated() {}"),
+        ];
+        let index = ReferenceIndex::from_sources(&sources);
+        let head = ApiSurface::from_sources(&sources);
+
+        let direct = affected_files(&d, &index, &head, false);
+        assert_eq!(direct.len(), 1);
+        assert_eq!(direct[0].file, "app/src/main.rs");
+        assert!(direct[0].reason.contains("core::Store::open"));
+
+        let all = affected_files(&d, &index, &head, true);
+        let files: Vec<(&str, bool)> = all.iter().map(|a| (a.file.as_str(), a.direct)).collect();
+        assert_eq!(
+            files,
+            vec![("app/src/cli.rs", false), ("app/src/main.rs", true)]
+        );
+    }
+
+    #[test]
+    fn test_detector_against_git() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git_init(root);
+        std::fs::create_dir_all(root.join("core/src")).unwrap();
+        std::fs::create_dir_all(root.join("app/src")).unwrap();
+        std::fs::write(root.join("core/src/lib.rs"), BEFORE).unwrap();
+        std::fs::write(
+            root.join("app/src/main.rs"),
+            "fn main() { core::Store::open(\"x\"); }",
+        )
+        .unwrap();
+        git_commit_all(root, "base");
+
+        std::fs::write(
+            root.join("core/src/lib.rs"),
+            BEFORE.replace("pub fn open(path: &str)", "pub fn open(path: &Path)"),
+        )
+        .unwrap();
+
+        let detector = ApiChangeDetector::new(root);
+        let request = DependencyCheckRequest {
+            changed_files: Vec::new(),
+            package: None,
+            include_transitive: false,
+        };
+        let result = detector.check(&request, "HEAD", None);
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.api_change_detected);
+        assert_eq!(result.impact_level, ImpactLevel::Medium);
+        assert_eq!(result.direct_dependents, 1);
+        assert_eq!(result.affected_files[0].file, "app/src/main.rs");
+
+        let scoped = DependencyCheckRequest {
+            package: Some("app".to_string()),
+            ..request
+        };
+        let result = detector.check(&scoped, "HEAD", None);
+        assert!(!result.api_change_detected);
+
+        let bad = detector.check(&scoped, "no-such-rev", None);
+        assert!(!bad.success);
+    }
+}
@@ -4,6 +4,9 @@
 //! and result truncation for safe use by the reviewer agent.
 
 use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
 
 /// Configuration for the ast-grep runner.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -269,6 +272,307 @@ impl AstGrepRunner {
     }
 }
 
+impl AstGrepRunner {
+    /// Run `sg` synchronously for a query.
+    ///
+    /// Spawns the configured binary in `working_dir`, kills it once
+    /// `timeout_ms` elapses, reads at most `max_output_bytes` of stdout, and
+    /// parses the `--json` output. A missing binary, invalid query, or
+    /// non-zero exit without parseable output becomes [`AstGrepResult::err`].
+    pub fn run(&self, query: &AstGrepQuery) -> AstGrepResult {
+        self.run_within(query, self.config.timeout_ms)
+    }
+
+    /// [`Self::run`] with an explicit time budget instead of `timeout_ms`.
+    pub fn run_within(&self, query: &AstGrepQuery, timeout_ms: u64) -> AstGrepResult {
+        let start = Instant::now();
+        if let Err(e) = self.validate_query(query) {
+            return AstGrepResult::err(&e, 0);
+        }
+        match self.exec(&query.to_args(), timeout_ms) {
+            Ok((success, stdout, stderr)) => self.finish(&stdout, &stderr, success, start),
+            Err(result) => result,
+        }
+    }
+
+    /// Whether the configured binary is ast-grep
+    ///
+    /// `sg` is also the name of shadow-utils' switch-group command, so a
+    /// binary on `PATH` is only trusted if `--version` names ast-grep.
+    pub fn is_available(&self) -> bool {
+        self.exec(&["--version".to_string()], self.config.timeout_ms)
+            .is_ok_and(|(success, stdout, _)| success && stdout.starts_with(b"ast-grep"))
+    }
+
+    /// Run `sg test --skip-snapshot-tests` for the project in `working_dir`.
+    ///
+    /// Returns whether every test passed and the combined stdout/stderr,
+    /// or an error if `sg` could not run to completion.
+    pub fn test_project(&self) -> Result<(bool, String), String> {
+        let args = ["test".to_string(), "--skip-snapshot-tests".to_string()];
+        match self.exec(&args, self.config.timeout_ms) {
+            Ok((success, stdout, stderr)) => Ok((
+                success,
+                format!(
+                    "{}{}",
+                    String::from_utf8_lossy(&stdout),
+                    String::from_utf8_lossy(&stderr)
+                ),
+            )),
+            Err(result) => Err(result.error.unwrap_or_default()),
+        }
+    }
+
+    /// Spawn the binary with `args`, returning its exit success, stdout
+    /// (bounded by `max_output_bytes`) and stderr, or an error result if it
+    /// could not be spawned or outlived `timeout_ms`.
+    fn exec(
+        &self,
+        args: &[String],
+        timeout_ms: u64,
+    ) -> Result<(bool, Vec<u8>, Vec<u8>), AstGrepResult> {
+        let start = Instant::now();
+        let mut cmd = Command::new(&self.config.binary);
+        cmd.args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(ref dir) = self.config.working_dir {
+            cmd.current_dir(dir);
+        }
+
+        let mut child = match cmd.spawn() {
+            Ok(c) => c,
+            Err(e) => return Err(self.spawn_error(&e, start)),
+        };
+
+        let limit = self.config.max_output_bytes as u64;
+        let stdout_reader = child.stdout.take().map(|out| {
+            std::thread::spawn(move || {
+                let mut buf = Vec::new();
+                let _ = out.take(limit + 1).read_to_end(&mut buf);
+                buf
+            })
+        });
+        let stderr_reader = child.stderr.take().map(|err| {
+            std::thread::spawn(move || {
+                let mut buf = Vec::new();
+                let _ = err.take(STDERR_LIMIT).read_to_end(&mut buf);
+                buf
+            })
+        });
+
+        let deadline = start + Duration::from_millis(timeout_ms);
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(AstGrepResult::timeout(timeout_ms));
+                }
+                Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+                Err(e) => {
+                    let _ = child.kill();
+                    return Err(AstGrepResult::err(
+                        &format!("failed to wait for {}: {e}", self.config.binary),
+                        start.elapsed().as_millis() as u64,
+                    ));
+                }
+            }
+        };
+
+        let stdout = stdout_reader
+            .and_then(|h| h.join().ok())
+            .unwrap_or_default();
+        let stderr = stderr_reader
+            .and_then(|h| h.join().ok())
+            .unwrap_or_default();
+        Ok((status.success(), stdout, stderr))
+    }
+
+    /// Run `sg` asynchronously for a query.
+    ///
+    /// Same contract as [`Self::run`]; the child is killed when the timeout
+    /// fires or the future is dropped.
+    pub async fn run_async(&self, query: &AstGrepQuery) -> AstGrepResult {
+        use tokio::io::AsyncReadExt;
+
+        let start = Instant::now();
+        if let Err(e) = self.validate_query(query) {
+            return AstGrepResult::err(&e, 0);
+        }
+
+        let mut cmd = tokio::process::Command::new(&self.config.binary);
+        cmd.args(query.to_args())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(ref dir) = self.config.working_dir {
+            cmd.current_dir(dir);
+        }
+
+        let mut child = match cmd.spawn() {
+            Ok(c) => c,
+            Err(e) => return self.spawn_error(&e, start),
+        };
+
+        let limit = self.config.max_output_bytes as u64;
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let io = async {
+            // Drain both pipes concurrently so a chatty stderr cannot block stdout.
+            let read_out = async {
+                let mut buf = Vec::new();
+                if let Some(s) = stdout {
+                    let _ = s.take(limit + 1).read_to_end(&mut buf).await;
+                }
+                buf
+            };
+            let read_err = async {
+                let mut buf = Vec::new();
+                if let Some(s) = stderr {
+                    let _ = s.take(STDERR_LIMIT).read_to_end(&mut buf).await;
+                }
+                buf
+            };
+            let (out, err) = tokio::join!(read_out, read_err);
+            let status = child.wait().await;
+            (out, err, status)
+        };
+
+        let timeout = Duration::from_millis(self.config.timeout_ms);
+        match tokio::time::timeout(timeout, io).await {
+            Ok((out, err, Ok(status))) => self.finish(&out, &err, status.success(), start),
+            Ok((_, _, Err(e))) => AstGrepResult::err(
+                &format!("failed to wait for {}: {e}", self.config.binary),
+                start.elapsed().as_millis() as u64,
+            ),
+            // Dropping the future drops the child, which kills it.
+            Err(_) => AstGrepResult::timeout(self.config.timeout_ms),
+        }
+    }
+
+    fn spawn_error(&self, e: &std::io::Error, start: Instant) -> AstGrepResult {
+        let msg = if e.kind() == std::io::ErrorKind::NotFound {
+            format!("ast-grep binary '{}' not found", self.config.binary)
+        } else {
+            format!("failed to spawn {}: {e}", self.config.binary)
+        };
+        AstGrepResult::err(&msg, start.elapsed().as_millis() as u64)
+    }
+
+    /// Turn captured output into a bounded result.
+    fn finish(&self, stdout: &[u8], stderr: &[u8], success: bool, start: Instant) -> AstGrepResult {
+        let elapsed_ms = start.elapsed().as_millis() as u64;
+        let overflowed = stdout.len() > self.config.max_output_bytes;
+        let stdout = &stdout[..stdout.len().min(self.config.max_output_bytes)];
+        let text = String::from_utf8_lossy(stdout);
+        let (matches, complete) = parse_sg_json(&text);
+
+        if !success && matches.is_empty() {
+            let stderr = String::from_utf8_lossy(stderr);
+            let detail = stderr.trim();
+            let detail = if detail.is_empty() {
+                "no output"
+            } else {
+                detail
+            };
+            return AstGrepResult::err(
+                &format!("{} exited with failure: {detail}", self.config.binary),
+                elapsed_ms,
+            );
+        }
+        if !complete && !overflowed && matches.is_empty() {
+            return AstGrepResult::err("failed to parse sg --json output", elapsed_ms);
+        }
+
+        let mut result = self.apply_bounds(AstGrepResult::ok(matches, elapsed_ms));
+        if overflowed {
+            result.truncated = true;
+        }
+        result
+    }
+}
+
+/// Upper bound on captured stderr (only used for error messages).
+const STDERR_LIMIT: u64 = 8 * 1024;
+
+/// Parse `sg --json` output into matches.
+///
+/// Accepts both the pretty array form (`--json`) and the newline-delimited
+/// stream form (`--json=stream`). Parsing stops at the first incomplete
+/// object, so byte-truncated output still yields its leading matches; the
+/// returned flag is `false` in that case. `sg` reports 0-indexed positions,
+/// which are converted to 1-indexed.
+pub fn parse_sg_json(output: &str) -> (Vec<AstGrepMatch>, bool) {
+    let mut rest = output.trim_start();
+    let in_array = rest.starts_with('[');
+    if in_array {
+        rest = &rest[1..];
+    }
+
+    let mut matches = Vec::new();
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if rest.is_empty() {
+            return (matches, !in_array);
+        }
+        if in_array && rest.starts_with(']') {
+            return (matches, true);
+        }
+        let mut stream = serde_json::Deserializer::from_str(rest).into_iter::<SgJsonMatch>();
+        match stream.next() {
+            Some(Ok(m)) => {
+                matches.push(m.into_match());
+                rest = &rest[stream.byte_offset()..];
+            }
+            _ => return (matches, false),
+        }
+    }
+}
+
+/// Wire format of a single `sg --json` match.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SgJsonMatch {
+    text: String,
+    file: String,
+    range: SgRange,
+    rule_id: Option<String>,
+    severity: Option<String>,
+    message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SgRange {
+    start: SgPosition,
+    end: SgPosition,
+}
+
+#[derive(Debug, Deserialize)]
+struct SgPosition {
+    line: u32,
+    column: u32,
+}
+
+impl SgJsonMatch {
+    fn into_match(self) -> AstGrepMatch {
+        AstGrepMatch {
+            file: self.file,
+            line: self.range.start.line + 1,
+            column: self.range.start.column + 1,
+            end_line: self.range.end.line + 1,
+            end_column: self.range.end.column + 1,
+            text: self.text,
+            rule_id: self.rule_id,
+            severity: self.severity,
+            message: self.message,
+        }
+    }
+}
+
 impl Default for AstGrepRunner {
     fn default() -> Self {
         Self::new()
@@ -448,6 +752,211 @@ mod tests {
         assert_eq!(parsed.timeout_ms, 10_000);
     }
 
+    fn sg_json(file: &str, line: u32, text: &str) -> String {
+        format!(
+            r#"{{"text":"{text}","range":{{"byteOffset":{{"start":0,"end":10}},"start":{{"line":{line},"column":4}},"end":{{"line":{line},"column":14}}}},"file":"{file}","lines":"{text}","language":"Rust"}}"#
+        )
+    }
+
+    #[test]
+    fn test_parse_sg_json_array() {
+        let out = format!(
+            "[\n{},\n{}\n]\n",
+            sg_json("src/a.rs", 9, "x.unwrap()"),
+            sg_json("src/b.rs", 0, "y.unwrap()")
+        );
+        let (matches, complete) = parse_sg_json(&out);
+        assert!(complete);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].file, "src/a.rs");
+        assert_eq!(matches[0].line, 10);
+        assert_eq!(matches[0].column, 5);
+        assert_eq!(matches[0].end_column, 15);
+        assert_eq!(matches[1].text, "y.unwrap()");
+    }
+
+    #[test]
+    fn test_parse_sg_json_stream_and_rule_fields() {
+        let rule = r#"{"text":"panic!()","range":{"start":{"line":1,"column":0},"end":{"line":1,"column":8}},"file":"a.rs","ruleId":"no-panic","severity":"error","message":"Avoid panic"}"#;
+        let out = format!("{}\n{}\n", rule, sg_json("b.rs", 2, "z"));
+        let (matches, complete) = parse_sg_json(&out);
+        assert!(complete);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].rule_id.as_deref(), Some("no-panic"));
+        assert_eq!(matches[0].severity.as_deref(), Some("error"));
+        assert_eq!(matches[0].message.as_deref(), Some("Avoid panic"));
+    }
+
+    #[test]
+    fn test_parse_sg_json_truncated() {
+        let out = format!("[{},{}", sg_json("a.rs", 0, "x"), sg_json("b.rs", 0, "y"));
+        let cut = &out[..out.len() - 20];
+        let (matches, complete) = parse_sg_json(cut);
+        assert!(!complete);
+        assert_eq!(matches.len(), 1);
+
+        let (matches, complete) = parse_sg_json("[]");
+        assert!(complete);
+        assert!(matches.is_empty());
+        let (_, complete) = parse_sg_json("");
+        assert!(complete);
+    }
+
+    #[cfg(unix)]
+    mod fake_sg {
+        use super::*;
+        use std::os::unix::fs::PermissionsExt;
+
+        /// Write an executable shell script standing in for `sg`.
+        fn fake_sg(dir: &std::path::Path, body: &str) -> String {
+            let path = dir.join("sg");
+            std::fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+            path.display().to_string()
+        }
+
+        fn runner(binary: String, dir: &std::path::Path) -> AstGrepRunner {
+            AstGrepRunner::with_config(AstGrepConfig {
+                binary,
+                timeout_ms: 2_000,
+                working_dir: Some(dir.display().to_string()),
+                ..Default::default()
+            })
+        }
+
+        #[test]
+        fn test_run_parses_matches_in_working_dir() {
+            let dir = tempfile::tempdir().unwrap();
+            let bin = fake_sg(
+                dir.path(),
+                r#"printf '[{"text":"x.unwrap()","range":{"start":{"line":3,"column":4},"end":{"line":3,"column":14}},"file":"%s/src/lib.rs"}]' "$(pwd -P)""#,
+            );
+            let result = runner(bin, dir.path()).run(&AstGrepQuery::pattern("$X.unwrap()", "rust"));
+            assert!(result.is_success(), "{:?}", result.error);
+            assert_eq!(result.matches.len(), 1);
+            let expected = dir.path().canonicalize().unwrap().join("src/lib.rs");
+            assert_eq!(result.matches[0].file, expected.display().to_string());
+            assert_eq!(result.matches[0].line, 4);
+            assert_eq!(result.matches[0].column, 5);
+        }
+
+        #[test]
+        fn test_run_passes_query_args() {
+            let dir = tempfile::tempdir().unwrap();
+            let bin = fake_sg(dir.path(), "echo \"$@\" > args.txt; echo '[]'");
+            let query = AstGrepQuery::rule("no-unwrap", "rust").in_paths(vec!["src/".to_string()]);
+            let result = runner(bin, dir.path()).run(&query);
+            assert!(result.is_success());
+            let args = std::fs::read_to_string(dir.path().join("args.txt")).unwrap();
+            assert_eq!(args.trim(), "scan --rule no-unwrap --lang rust --json src/");
+        }
+
+        #[test]
+        fn test_run_caps_matches() {
+            let dir = tempfile::tempdir().unwrap();
+            let items: Vec<String> = (0..20).map(|i| sg_json("a.rs", i, "m")).collect();
+            let bin = fake_sg(dir.path(), &format!("echo '[{}]'", items.join(",")));
+            let mut r = runner(bin, dir.path());
+            r.config.max_matches = 5;
+            let result = r.run(&AstGrepQuery::pattern("$X", "rust"));
+            assert!(result.is_success());
+            assert_eq!(result.matches.len(), 5);
+            assert_eq!(result.total_matches, 20);
+            assert!(result.truncated);
+        }
+
+        #[test]
+        fn test_run_caps_output_bytes() {
+            let dir = tempfile::tempdir().unwrap();
+            let items: Vec<String> = (0..50).map(|i| sg_json("a.rs", i, "m")).collect();
+            let bin = fake_sg(dir.path(), &format!("echo '[{}]'", items.join(",")));
+            let one = sg_json("a.rs", 0, "m").len();
+            let mut r = runner(bin, dir.path());
+            r.config.max_output_bytes = one * 3 + 10;
+            let result = r.run(&AstGrepQuery::pattern("$X", "rust"));
+            assert!(result.is_success());
+            assert!(result.truncated);
+            assert_eq!(result.matches.len(), 3);
+        }
+
+        #[test]
+        fn test_run_bad_pattern_is_error() {
+            let dir = tempfile::tempdir().unwrap();
+            let bin = fake_sg(
+                dir.path(),
+                "echo 'Error: Cannot parse query as a valid pattern.' >&2; exit 1",
+            );
+            let result = runner(bin, dir.path()).run(&AstGrepQuery::pattern("fn (", "rust"));
+            assert!(!result.is_success());
+            assert!(!result.timed_out);
+            assert!(result.error.unwrap().contains("Cannot parse query"));
+        }
+
+        #[test]
+        fn test_run_missing_binary_is_error() {
+            let dir = tempfile::tempdir().unwrap();
+            let missing = dir.path().join("no-such-sg").display().to_string();
+            let result = runner(missing, dir.path()).run(&AstGrepQuery::pattern("$X", "rust"));
+            assert!(!result.is_success());
+            assert!(result.error.unwrap().contains("not found"));
+        }
+
+        #[test]
+        fn test_run_invalid_query_is_error() {
+            let dir = tempfile::tempdir().unwrap();
+            let bin = fake_sg(dir.path(), "echo '[]'");
+            let result = runner(bin, dir.path()).run(&AstGrepQuery::pattern("", "rust"));
+            assert!(!result.is_success());
+        }
+
+        #[test]
+        fn test_run_timeout_kills_process() {
+            let dir = tempfile::tempdir().unwrap();
+            let bin = fake_sg(dir.path(), "sleep 5; echo '[]'");
+            let mut r = runner(bin, dir.path());
+            r.config.timeout_ms = 200;
+            let start = Instant::now();
+            let result = r.run(&AstGrepQuery::pattern("$X", "rust"));
+            assert!(result.timed_out);
+            assert!(start.elapsed() < Duration::from_secs(3));
+        }
+
+        #[tokio::test]
+        async fn test_run_async() {
+            let dir = tempfile::tempdir().unwrap();
+            let json = format!("[{}]", sg_json("a.rs", 0, "x.unwrap()"));
+            let bin = fake_sg(dir.path(), &format!("echo '{json}'"));
+            let result = runner(bin, dir.path())
+                .run_async(&AstGrepQuery::pattern("$X.unwrap()", "rust"))
+                .await;
+            assert!(result.is_success(), "{:?}", result.error);
+            assert_eq!(result.matches.len(), 1);
+            assert_eq!(result.matches[0].line, 1);
+        }
+
+        #[tokio::test]
+        async fn test_run_async_timeout_and_errors() {
+            let dir = tempfile::tempdir().unwrap();
+            let bin = fake_sg(dir.path(), "sleep 5");
+            let mut r = runner(bin, dir.path());
+            r.config.timeout_ms = 200;
+            let result = r.run_async(&AstGrepQuery::pattern("$X", "rust")).await;
+            assert!(result.timed_out);
+
+            let bin = fake_sg(dir.path(), "echo 'bad pattern' >&2; exit 2");
+            let result = runner(bin, dir.path())
+                .run_async(&AstGrepQuery::pattern("$X", "rust"))
+                .await;
+            assert!(result.error.unwrap().contains("bad pattern"));
+
+            let missing = dir.path().join("missing").display().to_string();
+            let result = runner(missing, dir.path())
+                .run_async(&AstGrepQuery::pattern("$X", "rust"))
+                .await;
+            assert!(result.error.unwrap().contains("not found"));
+        }
+    }
+
     #[test]
     fn test_query_serde() {
         let query = AstGrepQuery::pattern("$X.unwrap()", "rust");
@@ -0,0 +1,976 @@
+//! In-process code graph — tree-sitter symbol extraction over petgraph.
+//!
+//! Builds a call / reference / implements graph for a source tree without any
+//! external service, and answers the same `GraphRagQuery` → `GraphRagResult`
+//! contract as the CocoIndex HTTP endpoint. Used by [`GraphRagRunner`] when
+//! the [`GraphRagBackend::Local`] backend is selected, so reviewers work
+//! offline and in CI.
+//!
+//! Only Rust sources are indexed. [`ts_language`] also serves the
+//! structural-search tiers; it withholds grammars built for an ABI the
+//! tree-sitter runtime cannot load (currently the bundled Python and Go
+//! grammars), so those languages report "no grammar" instead of failing
+//! at parse time.
+//!
+//! Name resolution is heuristic (by identifier, narrowed by path qualifier or
+//! `self` receiver when available), so ambiguous calls fan out to every
+//! candidate definition. That over-approximates, which is the safe direction
+//! for impact analysis.
+//!
+//! [`GraphRagRunner`]: super::graph_rag::GraphRagRunner
+//! [`GraphRagBackend::Local`]: super::graph_rag::GraphRagBackend::Local
+
+use super::graph_rag::{GraphEdge, GraphNode, GraphRagQuery, QueryKind};
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::Path;
+
+/// Relationship between two symbols in the code graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    /// Caller → callee.
+    Calls,
+    /// Symbol → type it mentions (signature, body, or field).
+    References,
+    /// `impl Trait for Type` block → trait.
+    Implements,
+}
+
+impl std::fmt::Display for EdgeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EdgeKind::Calls => write!(f, "calls"),
+            EdgeKind::References => write!(f, "uses"),
+            EdgeKind::Implements => write!(f, "implements"),
+        }
+    }
+}
+
+/// A symbol definition extracted from source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolDef {
+    /// Qualified name (`Type::method` for associated items, bare name otherwise).
+    pub qualified: String,
+    /// Bare identifier.
+    pub name: String,
+    /// Enclosing type or trait for associated items.
+    pub parent: Option<String>,
+    /// File path relative to the graph root.
+    pub file: String,
+    /// Line number (1-indexed).
+    pub line: u32,
+    /// Symbol kind (function, method, struct, enum, trait, type, impl).
+    pub kind: String,
+    /// Source language.
+    pub language: String,
+    /// Declaration header without the body, whitespace-collapsed.
+    pub signature: String,
+}
+
+impl SymbolDef {
+    fn is_callable(&self) -> bool {
+        matches!(self.kind.as_str(), "function" | "method")
+    }
+
+    fn is_type(&self) -> bool {
+        matches!(
+            self.kind.as_str(),
+            "struct" | "enum" | "trait" | "type" | "union"
+        )
+    }
+
+    fn to_node(&self, depth: u32) -> GraphNode {
+        GraphNode {
+            symbol: self.qualified.clone(),
+            file: self.file.clone(),
+            line: self.line,
+            symbol_kind: self.kind.clone(),
+            depth,
+        }
+    }
+}
+
+/// An unresolved reference collected during extraction.
+#[derive(Debug, Clone)]
+struct RawRef {
+    /// Index of the enclosing definition in the file's `defs`.
+    from: usize,
+    /// Referenced identifier.
+    name: String,
+    /// Path qualifier (`Foo` in `Foo::bar()`), or `Self` for self receivers.
+    qualifier: Option<String>,
+    kind: EdgeKind,
+}
+
+/// Symbols and references extracted from one file.
+#[derive(Debug, Default)]
+struct FileSymbols {
+    defs: Vec<SymbolDef>,
+    refs: Vec<RawRef>,
+}
+
+/// Map a language name to its tree-sitter grammar.
+///
+/// Returns `None` for languages without a bundled grammar, and for grammars
+/// whose ABI version the linked tree-sitter runtime cannot load.
+pub(crate) fn ts_language(language: &str) -> Option<tree_sitter::Language> {
+    let grammar: tree_sitter::Language = match language {
+        "rust" | "rs" => tree_sitter_rust::LANGUAGE.into(),
+        "typescript" | "ts" => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+        "tsx" => tree_sitter_typescript::LANGUAGE_TSX.into(),
+        "python" | "py" => tree_sitter_python::LANGUAGE.into(),
+        "go" => tree_sitter_go::LANGUAGE.into(),
+        _ => return None,
+    };
+    let supported = tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION..=tree_sitter::LANGUAGE_VERSION;
+    supported.contains(&grammar.version()).then_some(grammar)
+}
+
+/// Infer a language name from a file extension.
+pub(crate) fn language_for_path(path: &Path) -> Option<&'static str> {
+    match path.extension().and_then(|e| e.to_str())? {
+        "rs" => Some("rust"),
+        "ts" => Some("typescript"),
+        "tsx" => Some("tsx"),
+        "py" => Some("python"),
+        "go" => Some("go"),
+        _ => None,
+    }
+}
+
+/// Split a qualified symbol into path segments (`a::b::C` → `[a, b, C]`).
+fn segments(symbol: &str) -> Vec<&str> {
+    symbol.split("::").filter(|s| !s.is_empty()).collect()
+}
+
+/// Whether a definition matches a query target.
+///
+/// Single-segment targets match by bare name; multi-segment targets match
+/// when the definition's qualified name is a suffix of the target path, so
+/// `crate::store::Store::open` matches `Store::open`.
+fn matches_target(def: &SymbolDef, target: &str) -> bool {
+    let target_segs = segments(target);
+    match target_segs.len() {
+        0 => false,
+        1 => def.name == target_segs[0],
+        n => {
+            let def_segs = segments(&def.qualified);
+            if def_segs.len() > 1 {
+                return target_segs.ends_with(&def_segs);
+            }
+            // `module::func` — a lowercase qualifier is a module path, not a type.
+            let qualifier = target_segs[n - 2];
+            target_segs[n - 1] == def.name && !qualifier.starts_with(char::is_uppercase)
+        }
+    }
+}
+
+/// Maximum characters kept for a symbol signature.
+const MAX_SIGNATURE_CHARS: usize = 200;
+
+/// Declaration text of `node` up to its body, collapsed onto one line.
+fn signature_text(node: tree_sitter::Node<'_>, source: &[u8]) -> String {
+    let end = node
+        .child_by_field_name("body")
+        .map(|b| b.start_byte())
+        .unwrap_or_else(|| node.end_byte());
+    let raw = String::from_utf8_lossy(&source[node.start_byte()..end]);
+    let collapsed = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+    let trimmed = collapsed.trim_end_matches([';', '{', ' ']);
+    match trimmed.char_indices().nth(MAX_SIGNATURE_CHARS) {
+        Some((idx, _)) => format!("{}…", &trimmed[..idx]),
+        None => trimmed.to_string(),
+    }
+}
+
+/// Whether a file path falls under a scope (path prefix or crate directory).
+pub(crate) fn in_scope(file: &str, scope: &str) -> bool {
+    let scope = scope.trim_end_matches('/');
+    file == scope || file.starts_with(&format!("{scope}/")) || file.contains(&format!("/{scope}/"))
+}
+
+// ── Rust extraction ──────────────────────────────────────────────────
+
+struct RustExtractor<'a> {
+    source: &'a [u8],
+    file: String,
+    out: FileSymbols,
+}
+
+impl<'a> RustExtractor<'a> {
+    fn text(&self, node: tree_sitter::Node<'_>) -> String {
+        node.utf8_text(self.source).unwrap_or_default().to_string()
+    }
+
+    fn push_def(
+        &mut self,
+        name: String,
+        parent: Option<String>,
+        kind: &str,
+        node: tree_sitter::Node<'_>,
+    ) -> usize {
+        let qualified = match &parent {
+            Some(p) if kind != "impl" => format!("{p}::{name}"),
+            _ => name.clone(),
+        };
+        self.out.defs.push(SymbolDef {
+            qualified,
+            name,
+            parent,
+            file: self.file.clone(),
+            line: node.start_position().row as u32 + 1,
+            kind: kind.to_string(),
+            language: "rust".to_string(),
+            signature: signature_text(node, self.source),
+        });
+        self.out.defs.len() - 1
+    }
+
+    fn push_ref(
+        &mut self,
+        from: Option<usize>,
+        name: String,
+        qualifier: Option<String>,
+        kind: EdgeKind,
+    ) {
+        if let Some(from) = from {
+            self.out.refs.push(RawRef {
+                from,
+                name,
+                qualifier,
+                kind,
+            });
+        }
+    }
+
+    /// Last identifier of a type expression (`foo::Bar<T>` → `Bar`).
+    fn type_name(&self, node: tree_sitter::Node<'_>) -> Option<String> {
+        match node.kind() {
+            "type_identifier" | "identifier" => Some(self.text(node)),
+            "scoped_type_identifier" | "scoped_identifier" => node
+                .child_by_field_name("name")
+                .and_then(|n| self.type_name(n)),
+            "generic_type" => node
+                .child_by_field_name("type")
+                .and_then(|n| self.type_name(n)),
+            "reference_type" | "pointer_type" => node
+                .child_by_field_name("type")
+                .and_then(|n| self.type_name(n)),
+            _ => None,
+        }
+    }
+
+    /// Record the callee of a `call_expression`.
+    fn record_call(&mut self, function: tree_sitter::Node<'_>, current: Option<usize>) {
+        match function.kind() {
+            "identifier" => {
+                let name = self.text(function);
+                self.push_ref(current, name, None, EdgeKind::Calls);
+            }
+            "scoped_identifier" => {
+                let name = function
+                    .child_by_field_name("name")
+                    .map(|n| self.text(n))
+                    .unwrap_or_default();
+                let qualifier = function
+                    .child_by_field_name("path")
+                    .and_then(|p| self.type_name(p).or_else(|| Some(self.text(p))))
+                    .map(|q| segments(&q).last().map(|s| s.to_string()).unwrap_or(q));
+                self.push_ref(current, name, qualifier, EdgeKind::Calls);
+            }
+            "field_expression" => {
+                let name = function
+                    .child_by_field_name("field")
+                    .map(|n| self.text(n))
+                    .unwrap_or_default();
+                let qualifier = function
+                    .child_by_field_name("value")
+                    .filter(|v| v.kind() == "self")
+                    .map(|_| "Self".to_string());
+                self.push_ref(current, name, qualifier, EdgeKind::Calls);
+            }
+            "generic_function" => {
+                if let Some(inner) = function.child_by_field_name("function") {
+                    self.record_call(inner, current);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit(&mut self, node: tree_sitter::Node<'_>, parent: Option<&str>, current: Option<usize>) {
+        let mut parent_owned: Option<String> = parent.map(str::to_string);
+        let mut current = current;
+
+        match node.kind() {
+            "function_item" | "function_signature_item" => {
+                if let Some(name) = node.child_by_field_name("name").map(|n| self.text(n)) {
+                    let kind = if parent.is_some() {
+                        "method"
+                    } else {
+                        "function"
+                    };
+                    current = Some(self.push_def(name, parent_owned.clone(), kind, node));
+                }
+            }
+            "struct_item" | "enum_item" | "trait_item" | "type_item" | "union_item" => {
+                if let Some(name) = node.child_by_field_name("name").map(|n| self.text(n)) {
+                    let kind = node.kind().trim_end_matches("_item");
+                    current = Some(self.push_def(name.clone(), None, kind, node));
+                    if node.kind() == "trait_item" {
+                        parent_owned = Some(name);
+                    }
+                }
+            }
+            "impl_item" => {
+                let ty = node
+                    .child_by_field_name("type")
+                    .and_then(|t| self.type_name(t));
+                let tr = node
+                    .child_by_field_name("trait")
+                    .and_then(|t| self.type_name(t));
+                if let Some(ty) = ty {
+                    current = match tr {
+                        Some(tr) => {
+                            let idx = self.push_def(ty.clone(), Some(tr.clone()), "impl", node);
+                            self.push_ref(Some(idx), tr, None, EdgeKind::Implements);
+                            Some(idx)
+                        }
+                        None => None,
+                    };
+                    parent_owned = Some(ty);
+                }
+            }
+            "call_expression" => {
+                if let Some(function) = node.child_by_field_name("function") {
+                    self.record_call(function, current);
+                }
+            }
+            "type_identifier" => {
+                let name = self.text(node);
+                self.push_ref(current, name, None, EdgeKind::References);
+            }
+            _ => {}
+        }
+
+        let child_parent = parent_owned.as_deref();
+        for i in 0..node.named_child_count() {
+            if let Some(child) = node.named_child(i) {
+                // The impl/trait name itself is not a reference from the block.
+                if matches!(node.kind(), "struct_item" | "enum_item" | "trait_item")
+                    && node.child_by_field_name("name") == Some(child)
+                {
+                    continue;
+                }
+                self.visit(child, child_parent, current);
+            }
+        }
+    }
+}
+
+fn extract_rust(file: &str, source: &str) -> Result<FileSymbols, String> {
+    let mut parser = tree_sitter::Parser::new();
+    let language = ts_language("rust").ok_or("no rust grammar")?;
+    parser
+        .set_language(&language)
+        .map_err(|e| format!("failed to load rust grammar: {e}"))?;
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| format!("failed to parse {file}"))?;
+
+    let mut extractor = RustExtractor {
+        source: source.as_bytes(),
+        file: file.to_string(),
+        out: FileSymbols::default(),
+    };
+    extractor.visit(tree.root_node(), None, None);
+    Ok(extractor.out)
+}
+
+// ── Graph ────────────────────────────────────────────────────────────
+
+/// In-process symbol graph built from tree-sitter parses.
+#[derive(Debug, Default)]
+pub struct CodeGraph {
+    graph: DiGraph<SymbolDef, EdgeKind>,
+    by_name: HashMap<String, Vec<NodeIndex>>,
+    files: BTreeSet<String>,
+}
+
+impl CodeGraph {
+    /// Build a graph from every supported source file under `root`.
+    ///
+    /// Honours `.gitignore` via the `ignore` walker. File paths in the graph
+    /// are relative to `root`. Files that fail to read or parse are skipped.
+    pub fn build(root: &Path) -> Result<Self, String> {
+        if !root.is_dir() {
+            return Err(format!("{} is not a directory", root.display()));
+        }
+
+        let mut sources = Vec::new();
+        for entry in ignore::WalkBuilder::new(root).build().flatten() {
+            let path = entry.path();
+            if language_for_path(path) != Some("rust") || !path.is_file() {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            let rel = path
+                .strip_prefix(root)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            sources.push((rel, content));
+        }
+        sources.sort();
+
+        let refs: Vec<(&str, &str)> = sources
+            .iter()
+            .map(|(p, c)| (p.as_str(), c.as_str()))
+            .collect();
+        Ok(Self::from_sources(&refs))
+    }
+
+    /// Build a graph from in-memory `(path, source)` pairs.
+    ///
+    /// Files whose language is unsupported or fail to parse are skipped.
+    pub fn from_sources(sources: &[(&str, &str)]) -> Self {
+        let mut parsed = Vec::new();
+        for (path, source) in sources {
+            if language_for_path(Path::new(path)) != Some("rust") {
+                continue;
+            }
+            if let Ok(symbols) = extract_rust(path, source) {
+                parsed.push(symbols);
+            }
+        }
+        Self::link(parsed)
+    }
+
+    /// Insert all definitions, then resolve raw references into edges.
+    fn link(parsed: Vec<FileSymbols>) -> Self {
+        let mut cg = CodeGraph::default();
+        let mut file_indices: Vec<Vec<NodeIndex>> = Vec::with_capacity(parsed.len());
+
+        for symbols in &parsed {
+            let mut indices = Vec::with_capacity(symbols.defs.len());
+            for def in &symbols.defs {
+                cg.files.insert(def.file.clone());
+                let name = def.name.clone();
+                let is_impl = def.kind == "impl";
+                let idx = cg.graph.add_node(def.clone());
+                if !is_impl {
+                    cg.by_name.entry(name).or_default().push(idx);
+                }
+                indices.push(idx);
+            }
+            file_indices.push(indices);
+        }
+
+        let mut seen: HashSet<(NodeIndex, NodeIndex, EdgeKind)> = HashSet::new();
+        for (symbols, indices) in parsed.iter().zip(&file_indices) {
+            for raw in &symbols.refs {
+                let from = indices[raw.from];
+                for to in cg.resolve(from, raw) {
+                    if from != to && seen.insert((from, to, raw.kind)) {
+                        cg.graph.add_edge(from, to, raw.kind);
+                    }
+                }
+            }
+        }
+        cg
+    }
+
+    /// Resolve a raw reference to candidate definitions.
+    fn resolve(&self, from: NodeIndex, raw: &RawRef) -> Vec<NodeIndex> {
+        let Some(candidates) = self.by_name.get(&raw.name) else {
+            return Vec::new();
+        };
+        let source = &self.graph[from];
+
+        let kind_ok: Vec<NodeIndex> = candidates
+            .iter()
+            .copied()
+            .filter(|&c| {
+                let def = &self.graph[c];
+                def.language == source.language
+                    && match raw.kind {
+                        EdgeKind::Calls => def.is_callable(),
+                        EdgeKind::References => def.is_type(),
+                        EdgeKind::Implements => def.kind == "trait",
+                    }
+            })
+            .collect();
+
+        let qualifier = match raw.qualifier.as_deref() {
+            Some("Self") | Some("self") => source.parent.as_deref(),
+            other => other,
+        };
+        if let Some(q) = qualifier {
+            let narrowed: Vec<NodeIndex> = kind_ok
+                .iter()
+                .copied()
+                .filter(|&c| self.graph[c].parent.as_deref() == Some(q))
+                .collect();
+            if !narrowed.is_empty() {
+                return narrowed;
+            }
+        }
+        kind_ok
+    }
+
+    /// Underlying petgraph graph (symbols as nodes, relations as edges).
+    pub(crate) fn graph(&self) -> &DiGraph<SymbolDef, EdgeKind> {
+        &self.graph
+    }
+
+    /// Number of symbol definitions in the graph.
+    pub fn symbol_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    /// Number of resolved edges in the graph.
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    /// Number of distinct files that contributed symbols.
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// All definitions matching a target symbol (impl blocks excluded).
+    pub fn find(&self, target: &str) -> Vec<&SymbolDef> {
+        self.find_nodes(target)
+            .into_iter()
+            .map(|i| &self.graph[i])
+            .collect()
+    }
+
+    /// Node indices of definitions matching a target symbol.
+    pub(crate) fn find_nodes(&self, target: &str) -> Vec<NodeIndex> {
+        self.targets(target, None)
+    }
+
+    fn targets(&self, target: &str, language: Option<&str>) -> Vec<NodeIndex> {
+        let Some(last) = segments(target).last().map(|s| s.to_string()) else {
+            return Vec::new();
+        };
+        let mut found: Vec<NodeIndex> = self
+            .by_name
+            .get(&last)
+            .map(|v| {
+                v.iter()
+                    .copied()
+                    .filter(|&i| {
+                        let def = &self.graph[i];
+                        matches_target(def, target) && language.is_none_or(|l| def.language == l)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        found.sort_by_key(|&i| self.sort_key(i));
+        found
+    }
+
+    fn sort_key(&self, idx: NodeIndex) -> (String, u32, String) {
+        let def = &self.graph[idx];
+        (def.file.clone(), def.line, def.qualified.clone())
+    }
+
+    /// Answer a graph query.
+    ///
+    /// Returns `Err` when the target cannot be resolved. Traversal depth is
+    /// `query.max_depth` or `default_depth`; depth 1 is a direct neighbour.
+    /// Nodes outside `query.scope` are dropped after traversal so transitive
+    /// paths through out-of-scope code are still followed.
+    pub fn query(
+        &self,
+        query: &GraphRagQuery,
+        default_depth: u32,
+    ) -> Result<(Vec<GraphNode>, Vec<GraphEdge>), String> {
+        let max_depth = query.effective_depth(default_depth);
+        let (mut nodes, edges) = match query.kind {
+            QueryKind::Dependencies => {
+                self.file_traversal(query, max_depth, Direction::Outgoing)?
+            }
+            QueryKind::Dependents => self.file_traversal(query, max_depth, Direction::Incoming)?,
+            _ => self.symbol_traversal(query, max_depth)?,
+        };
+
+        if let Some(scope) = &query.scope {
+            nodes.retain(|n| in_scope(&n.file, scope));
+        }
+        Ok((nodes, edges))
+    }
+
+    fn symbol_traversal(
+        &self,
+        query: &GraphRagQuery,
+        max_depth: u32,
+    ) -> Result<(Vec<GraphNode>, Vec<GraphEdge>), String> {
+        let starts = self.targets(&query.target, Some(query.language.as_str()));
+        if starts.is_empty() {
+            return Err(format!(
+                "symbol '{}' not found in local code graph",
+                query.target
+            ));
+        }
+
+        let (direction, kinds): (Direction, &[EdgeKind]) = match query.kind {
+            QueryKind::Callers => (Direction::Incoming, &[EdgeKind::Calls]),
+            QueryKind::Callees => (Direction::Outgoing, &[EdgeKind::Calls]),
+            QueryKind::Implementors => (Direction::Incoming, &[EdgeKind::Implements]),
+            QueryKind::TypeUsages => (Direction::Incoming, &[EdgeKind::References]),
+            _ => (
+                Direction::Incoming,
+                &[EdgeKind::Calls, EdgeKind::References, EdgeKind::Implements],
+            ),
+        };
+
+        let mut depth_of: HashMap<NodeIndex, u32> = starts.iter().map(|&s| (s, 0)).collect();
+        let mut queue: VecDeque<NodeIndex> = starts.iter().copied().collect();
+        let mut order: Vec<NodeIndex> = Vec::new();
+        let mut edges: Vec<GraphEdge> = Vec::new();
+
+        while let Some(current) = queue.pop_front() {
+            let depth = depth_of[&current];
+            if depth >= max_depth {
+                continue;
+            }
+            let mut neighbours: Vec<(NodeIndex, EdgeKind)> = self
+                .graph
+                .edges_directed(current, direction)
+                .filter(|e| kinds.contains(e.weight()))
+                .map(|e| {
+                    let other = match direction {
+                        Direction::Incoming => e.source(),
+                        Direction::Outgoing => e.target(),
+                    };
+                    (other, *e.weight())
+                })
+                .collect();
+            neighbours.sort_by_key(|(n, _)| self.sort_key(*n));
+
+            for (next, kind) in neighbours {
+                let (from, to) = match direction {
+                    Direction::Incoming => (next, current),
+                    Direction::Outgoing => (current, next),
+                };
+                edges.push(GraphEdge {
+                    from: self.graph[from].qualified.clone(),
+                    to: self.graph[to].qualified.clone(),
+                    relation: kind.to_string(),
+                });
+                if let std::collections::hash_map::Entry::Vacant(slot) = depth_of.entry(next) {
+                    slot.insert(depth + 1);
+                    order.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let nodes = order
+            .into_iter()
+            .map(|i| self.graph[i].to_node(depth_of[&i]))
+            .collect();
+        Ok((nodes, edges))
+    }
+
+    /// File-level traversal: file A depends on file B when a symbol in A
+    /// has any edge to a symbol in B.
+    fn file_traversal(
+        &self,
+        query: &GraphRagQuery,
+        max_depth: u32,
+        direction: Direction,
+    ) -> Result<(Vec<GraphNode>, Vec<GraphEdge>), String> {
+        let target = query.target.trim_start_matches("./");
+        let starts: Vec<&String> = self
+            .files
+            .iter()
+            .filter(|f| f.as_str() == target || f.ends_with(&format!("/{target}")))
+            .collect();
+        if starts.is_empty() {
+            return Err(format!("file '{}' not found in local code graph", target));
+        }
+
+        let mut file_edges: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+        for edge in self.graph.edge_references() {
+            let a = self.graph[edge.source()].file.as_str();
+            let b = self.graph[edge.target()].file.as_str();
+            if a == b {
+                continue;
+            }
+            let (from, to) = match direction {
+                Direction::Outgoing => (a, b),
+                Direction::Incoming => (b, a),
+            };
+            file_edges.entry(from).or_default().insert(to);
+        }
+
+        let mut depth_of: HashMap<&str, u32> = starts.iter().map(|s| (s.as_str(), 0)).collect();
+        let mut queue: VecDeque<&str> = starts.iter().map(|s| s.as_str()).collect();
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+
+        while let Some(current) = queue.pop_front() {
+            let depth = depth_of[current];
+            if depth >= max_depth {
+                continue;
+            }
+            for &next in file_edges.get(current).into_iter().flatten() {
+                let (from, to) = match direction {
+                    Direction::Outgoing => (current, next),
+                    Direction::Incoming => (next, current),
+                };
+                edges.push(GraphEdge {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    relation: "depends_on".to_string(),
+                });
+                if !depth_of.contains_key(next) {
+                    depth_of.insert(next, depth + 1);
+                    queue.push_back(next);
+                    nodes.push(GraphNode {
+                        symbol: next.to_string(),
+                        file: next.to_string(),
+                        line: 1,
+                        symbol_kind: "file".to_string(),
+                        depth: depth + 1,
+                    });
+                }
+            }
+        }
+        Ok((nodes, edges))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STORE: &str = r#"
+pub trait Storage {
+    fn put(&mut self, key: &str);
+}
+
+pub struct Store {
+    items: Vec<String>,
+}
+
+impl Store {
+    pub fn open() -> Self {
+        Store { items: Vec::new() }
+    }
+
+    pub fn insert(&mut self, key: &str) {
+        self.validate(key);
+        self.items.push(key.to_string());
+    }
+
+    fn validate(&self, _key: &str) {}
+}
+
+impl Storage for Store {
+    fn put(&mut self, key: &str) {
+        self.insert(key);
+    }
+}
+"#;
+
+    const APP: &str = r#"
+use crate::store::Store;
+
+pub fn run() {
+    let mut store = Store::open();
+    ingest(&mut store);
+}
+
+fn ingest(store: &mut Store) {
+    store.insert("a");
+}
+"#;
+
+    const TEST: &str = r#"
+#[test]
+fn test_ingest() {
+    crate::app::run();
+}
+"#;
+
+    fn graph() -> CodeGraph {
+        CodeGraph::from_sources(&[
+            ("src/store.rs", STORE),
+            ("src/app.rs", APP),
+            ("tests/app_test.rs", TEST),
+            ("README.md", "# not rust"),
+        ])
+    }
+
+    fn symbols(nodes: &[GraphNode]) -> Vec<&str> {
+        nodes.iter().map(|n| n.symbol.as_str()).collect()
+    }
+
+    #[test]
+    fn test_ts_language_only_returns_loadable_grammars() {
+        for language in ["rust", "typescript", "tsx", "python", "go", "cobol"] {
+            if let Some(grammar) = ts_language(language) {
+                let mut parser = tree_sitter::Parser::new();
+                assert!(parser.set_language(&grammar).is_ok(), "{language}");
+            }
+        }
+        assert!(ts_language("rust").is_some());
+        assert!(ts_language("cobol").is_none());
+    }
+
+    #[test]
+    fn test_extracts_definitions() {
+        let g = graph();
+        assert_eq!(g.file_count(), 3);
+        assert_eq!(g.find("Store::insert").len(), 1);
+        assert_eq!(g.find("insert")[0].kind, "method");
+        assert_eq!(g.find("run")[0].kind, "function");
+        assert_eq!(g.find("Storage")[0].kind, "trait");
+        assert_eq!(g.find("Store")[0].line, 6);
+        assert_eq!(g.find("Store")[0].signature, "pub struct Store");
+        assert_eq!(
+            g.find("Store::insert")[0].signature,
+            "pub fn insert(&mut self, key: &str)"
+        );
+        assert_eq!(
+            g.find("Storage::put")[0].signature,
+            "fn put(&mut self, key: &str)"
+        );
+        assert!(g.find("crate::store::Store::open").len() == 1);
+        assert!(g.find("missing").is_empty());
+    }
+
+    #[test]
+    fn test_callers_with_depth() {
+        let g = graph();
+        let (nodes, edges) = g
+            .query(&GraphRagQuery::callers("Store::insert", "rust"), 5)
+            .unwrap();
+        let syms = symbols(&nodes);
+        assert!(syms.contains(&"ingest"));
+        assert!(syms.contains(&"Store::put"));
+        assert!(syms.contains(&"run"));
+        assert!(syms.contains(&"test_ingest"));
+        assert!(edges
+            .iter()
+            .any(|e| e.from == "ingest" && e.to == "Store::insert" && e.relation == "calls"));
+
+        let direct = nodes.iter().find(|n| n.symbol == "ingest").unwrap();
+        assert_eq!(direct.depth, 1);
+        let transitive = nodes.iter().find(|n| n.symbol == "run").unwrap();
+        assert_eq!(transitive.depth, 2);
+
+        let (shallow, _) = g
+            .query(
+                &GraphRagQuery::callers("Store::insert", "rust").with_depth(1),
+                5,
+            )
+            .unwrap();
+        assert!(!symbols(&shallow).contains(&"run"));
+    }
+
+    #[test]
+    fn test_callees_resolve_self_receiver() {
+        let g = graph();
+        let (nodes, _) = g
+            .query(
+                &GraphRagQuery::callees("Store::insert", "rust").with_depth(1),
+                5,
+            )
+            .unwrap();
+        assert_eq!(symbols(&nodes), vec!["Store::validate"]);
+    }
+
+    #[test]
+    fn test_implementors() {
+        let g = graph();
+        let (nodes, edges) = g
+            .query(&GraphRagQuery::implementors("Storage", "rust"), 5)
+            .unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].symbol, "Store");
+        assert_eq!(nodes[0].symbol_kind, "impl");
+        assert_eq!(nodes[0].file, "src/store.rs");
+        assert_eq!(edges[0].relation, "implements");
+    }
+
+    #[test]
+    fn test_impact_follows_all_edge_kinds() {
+        let g = graph();
+        let (nodes, _) = g.query(&GraphRagQuery::impact("Store", "rust"), 5).unwrap();
+        let syms = symbols(&nodes);
+        // Type references in signatures and bodies.
+        assert!(syms.contains(&"ingest"));
+        assert!(syms.contains(&"Store::open"));
+        // Transitive callers of referencing functions.
+        assert!(syms.contains(&"run"));
+    }
+
+    #[test]
+    fn test_scope_filter() {
+        let g = graph();
+        let (nodes, _) = g
+            .query(
+                &GraphRagQuery::callers("Store::insert", "rust").in_scope("src"),
+                5,
+            )
+            .unwrap();
+        assert!(nodes.iter().all(|n| n.file.starts_with("src/")));
+        assert!(!symbols(&nodes).contains(&"test_ingest"));
+    }
+
+    #[test]
+    fn test_file_dependencies() {
+        let g = graph();
+        let query = GraphRagQuery {
+            target: "src/app.rs".to_string(),
+            kind: QueryKind::Dependencies,
+            scope: None,
+            max_depth: None,
+            language: "rust".to_string(),
+        };
+        let (nodes, edges) = g.query(&query, 5).unwrap();
+        assert_eq!(symbols(&nodes), vec!["src/store.rs"]);
+        assert_eq!(edges[0].relation, "depends_on");
+
+        let query = GraphRagQuery {
+            kind: QueryKind::Dependents,
+            target: "store.rs".to_string(),
+            ..query
+        };
+        let (nodes, _) = g.query(&query, 5).unwrap();
+        let syms = symbols(&nodes);
+        assert!(syms.contains(&"src/app.rs"));
+        assert!(syms.contains(&"tests/app_test.rs"));
+    }
+
+    #[test]
+    fn test_unknown_target_is_error() {
+        let g = graph();
+        assert!(g.query(&GraphRagQuery::callers("nope", "rust"), 5).is_err());
+        assert!(g
+            .query(&GraphRagQuery::callers("run", "python"), 5)
+            .is_err());
+    }
+
+    #[test]
+    fn test_build_from_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/store.rs"), STORE).unwrap();
+        std::fs::write(dir.path().join("src/app.rs"), APP).unwrap();
+        let g = CodeGraph::build(dir.path()).unwrap();
+        assert_eq!(g.file_count(), 2);
+        assert!(g.edge_count() > 0);
+        assert!(CodeGraph::build(&dir.path().join("missing")).is_err());
+    }
+}
@@ -2,10 +2,27 @@
 //!
 //! Wraps a graph-based code index (e.g., CocoIndex) with structured input/output,
 //! timeout enforcement, and result truncation for safe use by the reviewer agent.
+//!
+//! Two backends answer the same query contract: the CocoIndex HTTP service
+//! (default) and an in-process [`CodeGraph`] built with tree-sitter.
 
+use super::code_graph::CodeGraph;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+/// Which index answers GraphRAG queries.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphRagBackend {
+    /// CocoIndex HTTP service at `endpoint_url`.
+    #[default]
+    CocoIndex,
+    /// In-process code graph built from `repo_root` (no external service).
+    Local,
+}
+
 /// Configuration for the GraphRAG runner.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphRagConfig {
@@ -19,6 +36,12 @@ pub struct GraphRagConfig {
     pub max_depth: u32,
     /// Whether to include test files in results.
     pub include_tests: bool,
+    /// Backend that answers queries.
+    #[serde(default)]
+    pub backend: GraphRagBackend,
+    /// Repository root indexed by the local backend (default: current dir).
+    #[serde(default)]
+    pub repo_root: Option<String>,
 }
 
 impl Default for GraphRagConfig {
@@ -29,6 +52,8 @@ impl Default for GraphRagConfig {
             max_results: 50,
             max_depth: 5,
             include_tests: false,
+            backend: GraphRagBackend::CocoIndex,
+            repo_root: None,
         }
     }
 }
@@ -297,21 +322,36 @@ impl GraphRagResult {
 }
 
 /// Runner that manages GraphRAG query execution.
+///
+/// Clones share the local code graph cache.
+#[derive(Clone)]
 pub struct GraphRagRunner {
     config: GraphRagConfig,
+    /// Lazily built code graph for the local backend. Only successful builds
+    /// are cached; a failed build is retried on the next query.
+    code_graph: Arc<Mutex<Option<Arc<CodeGraph>>>>,
 }
 
 impl GraphRagRunner {
     /// Create a new runner with default config.
     pub fn new() -> Self {
-        Self {
-            config: GraphRagConfig::default(),
-        }
+        Self::with_config(GraphRagConfig::default())
     }
 
     /// Create with custom config.
     pub fn with_config(config: GraphRagConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            code_graph: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Create a local-backend runner over a pre-built code graph.
+    pub fn with_code_graph(mut config: GraphRagConfig, graph: CodeGraph) -> Self {
+        config.backend = GraphRagBackend::Local;
+        let runner = Self::with_config(config);
+        *runner.code_graph.lock().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(graph));
+        runner
     }
 
     /// Get the configuration.
@@ -427,11 +467,75 @@ impl GraphRagEnvBridge {
 }
 
 impl GraphRagRunner {
-    /// Execute a query against the CocoIndex HTTP endpoint.
+    /// Get the local code graph, building it from `repo_root` on first use.
+    ///
+    /// Build failures are returned but not cached.
+    pub fn code_graph(&self) -> Result<Arc<CodeGraph>, String> {
+        let mut cached = self.code_graph.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(graph) = cached.as_ref() {
+            return Ok(Arc::clone(graph));
+        }
+        let graph = self.build_code_graph()?;
+        *cached = Some(Arc::clone(&graph));
+        Ok(graph)
+    }
+
+    /// Rebuild the local code graph from `repo_root`, replacing the cached one.
+    ///
+    /// On failure the previously cached graph (if any) is kept.
+    pub fn rebuild(&self) -> Result<Arc<CodeGraph>, String> {
+        let graph = self.build_code_graph()?;
+        *self.code_graph.lock().unwrap_or_else(|e| e.into_inner()) = Some(Arc::clone(&graph));
+        Ok(graph)
+    }
+
+    /// Drop the cached code graph so the next query rebuilds it.
+    pub fn invalidate(&self) {
+        *self.code_graph.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    fn build_code_graph(&self) -> Result<Arc<CodeGraph>, String> {
+        let root = self.config.repo_root.as_deref().unwrap_or(".");
+        CodeGraph::build(Path::new(root)).map(Arc::new)
+    }
+
+    /// Execute a query against the in-process code graph.
+    ///
+    /// Applies test filtering, then truncation to `max_results`.
+    pub fn execute_local(&self, query: &GraphRagQuery) -> GraphRagResult {
+        let start = std::time::Instant::now();
+
+        if let Err(e) = self.validate_query(query) {
+            return GraphRagResult::err(&e, 0);
+        }
+        let graph = match self.code_graph() {
+            Ok(g) => g,
+            Err(e) => {
+                return GraphRagResult::err(
+                    &format!("failed to build code graph: {e}"),
+                    start.elapsed().as_millis() as u64,
+                );
+            }
+        };
+
+        match graph.query(query, self.config.max_depth) {
+            Ok((nodes, edges)) => {
+                let result = GraphRagResult::ok(nodes, edges, start.elapsed().as_millis() as u64);
+                let result = self.filter_tests(result);
+                self.apply_bounds(result)
+            }
+            Err(e) => GraphRagResult::err(&e, start.elapsed().as_millis() as u64),
+        }
+    }
+
+    /// Execute a query against the configured backend.
     ///
-    /// Sends a POST to `<endpoint_url>/query` with a JSON body of the form
+    /// For [`GraphRagBackend::CocoIndex`], sends a POST to
+    /// `<endpoint_url>/query` with a JSON body of the form
     /// `{"query": "<target>", "kind": "<kind>", "top_k": <max_results>}` and
-    /// deserializes the response into a `GraphRagResult`.
+    /// deserializes the response into a `GraphRagResult`. For
+    /// [`GraphRagBackend::Local`], runs [`Self::execute_local`] on the
+    /// blocking pool, since building the graph parses the whole worktree.
     ///
     /// Applies configured timeout, then bounds (truncation + test filtering).
     pub async fn execute(&self, query: &GraphRagQuery) -> GraphRagResult {
@@ -439,6 +543,21 @@ impl GraphRagRunner {
 
         let start = Instant::now();
         let timeout = Duration::from_millis(self.config.timeout_ms);
+
+        if self.config.backend == GraphRagBackend::Local {
+            let runner = self.clone();
+            let query = query.clone();
+            let task = tokio::task::spawn_blocking(move || runner.execute_local(&query));
+            return match tokio::time::timeout(timeout, task).await {
+                Ok(Ok(result)) => result,
+                Ok(Err(e)) => GraphRagResult::err(
+                    &format!("local graph query panicked: {e}"),
+                    start.elapsed().as_millis() as u64,
+                ),
+                Err(_) => GraphRagResult::timeout(self.config.timeout_ms),
+            };
+        }
+
         let url = format!("{}/query", self.config.endpoint_url.trim_end_matches('/'));
 
         let body = serde_json::json!({
@@ -884,6 +1003,141 @@ mod tests {
         std::env::remove_var("COCOINDEX_PYTHON");
     }
 
+    #[tokio::test]
+    async fn test_execute_local_backend() {
+        let graph = CodeGraph::from_sources(&[
+            (
+                "src/lib.rs",
+                "pub fn target() {}\nfn caller() { target(); }\nfn outer() { caller(); }\n",
+            ),
+            ("tests/it.rs", "#[test]\nfn it_works() { target(); }\n"),
+        ]);
+        let runner = GraphRagRunner::with_code_graph(GraphRagConfig::default(), graph);
+        assert_eq!(runner.config().backend, GraphRagBackend::Local);
+
+        let result = runner
+            .execute(&GraphRagQuery::callers("target", "rust"))
+            .await;
+        assert!(result.is_success());
+        let symbols: Vec<&str> = result.nodes.iter().map(|n| n.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["caller", "outer"]);
+        assert!(result.truncated, "test caller should be filtered");
+        assert_eq!(result.depth_reached, 2);
+
+        let result = runner.execute_local(&GraphRagQuery::callers("target", "rust").with_depth(1));
+        assert_eq!(result.nodes.len(), 1);
+
+        let result = runner.execute_local(&GraphRagQuery::callers("missing", "rust"));
+        assert!(!result.is_success());
+        assert!(result.summary_line().contains("not found"));
+    }
+
+    #[test]
+    fn test_local_backend_builds_from_repo_root() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("lib.rs"),
+            "trait Greet {}\nstruct A;\nimpl Greet for A {}\n",
+        )
+        .unwrap();
+        let runner = GraphRagRunner::with_config(GraphRagConfig {
+            backend: GraphRagBackend::Local,
+            repo_root: Some(dir.path().display().to_string()),
+            ..GraphRagConfig::default()
+        });
+        let result = runner.execute_local(&GraphRagQuery::implementors("Greet", "rust"));
+        assert!(result.is_success());
+        assert_eq!(result.nodes.len(), 1);
+        assert_eq!(result.nodes[0].symbol, "A");
+    }
+
+    #[test]
+    fn test_local_backend_does_not_cache_build_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("repo");
+        let runner = GraphRagRunner::with_config(GraphRagConfig {
+            backend: GraphRagBackend::Local,
+            repo_root: Some(root.display().to_string()),
+            ..GraphRagConfig::default()
+        });
+        let result = runner.execute_local(&GraphRagQuery::implementors("Greet", "rust"));
+        assert!(!result.is_success());
+
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(
+            root.join("lib.rs"),
+            "trait Greet {}\nstruct A;\nimpl Greet for A {}\n",
+        )
+        .unwrap();
+        let result = runner.execute_local(&GraphRagQuery::implementors("Greet", "rust"));
+        assert!(result.is_success());
+        assert_eq!(result.nodes.len(), 1);
+    }
+
+    #[test]
+    fn test_local_backend_rebuild_and_invalidate() {
+        let dir = tempfile::tempdir().unwrap();
+        let lib = dir.path().join("lib.rs");
+        std::fs::write(&lib, "trait Greet {}\nstruct A;\nimpl Greet for A {}\n").unwrap();
+        let runner = GraphRagRunner::with_config(GraphRagConfig {
+            backend: GraphRagBackend::Local,
+            repo_root: Some(dir.path().display().to_string()),
+            ..GraphRagConfig::default()
+        });
+        let query = GraphRagQuery::implementors("Greet", "rust");
+        assert_eq!(runner.execute_local(&query).nodes.len(), 1);
+
+        std::fs::write(
+            &lib,
+            "trait Greet {}\nstruct A;\nimpl Greet for A {}\nstruct B;\nimpl Greet for B {}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            runner.execute_local(&query).nodes.len(),
+            1,
+            "graph is cached"
+        );
+        runner.rebuild().unwrap();
+        assert_eq!(runner.execute_local(&query).nodes.len(), 2);
+
+        std::fs::write(&lib, "trait Greet {}\n").unwrap();
+        runner.invalidate();
+        assert!(runner.execute_local(&query).nodes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_local_backend_respects_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..50 {
+            std::fs::write(
+                dir.path().join(format!("m{i}.rs")),
+                "trait Greet {}\nstruct A;\nimpl Greet for A {}\n",
+            )
+            .unwrap();
+        }
+        let runner = GraphRagRunner::with_config(GraphRagConfig {
+            backend: GraphRagBackend::Local,
+            repo_root: Some(dir.path().display().to_string()),
+            timeout_ms: 0,
+            ..GraphRagConfig::default()
+        });
+        let result = runner
+            .execute(&GraphRagQuery::implementors("Greet", "rust"))
+            .await;
+        assert!(result.timed_out);
+    }
+
+    #[test]
+    fn test_backend_serde_default() {
+        let parsed: GraphRagConfig = serde_json::from_str(
+            r#"{"endpoint_url":"http://x","timeout_ms":1,"max_results":1,"max_depth":1,"include_tests":false}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.backend, GraphRagBackend::CocoIndex);
+        let json = serde_json::to_string(&GraphRagBackend::Local).unwrap();
+        assert_eq!(json, "\"local\"");
+    }
+
     #[tokio::test]
     async fn test_execute_returns_error_when_endpoint_unreachable() {
         let config = GraphRagConfig {
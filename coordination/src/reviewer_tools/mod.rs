@@ -5,19 +5,40 @@
 //!
 //! # Modules
 //!
+//! - [`api_diff`] — Public API surface diffing between commits
 //! - [`ast_grep`] — ast-grep (sg) wrapper with bounded output
+//! - [`code_graph`] — In-process tree-sitter/petgraph code graph
 //! - [`graph_rag`] — GraphRAG/CocoIndex wrapper for dependency queries
+//! - [`rule_config`] — ast-grep rule file loading and in-process evaluation
 //! - [`rule_pack`] — Rule pack mapping to sgconfig
+//! - [`rule_test`] — Fixture-based rule pattern tests
+//! - [`sgconfig`] — sgconfig/rule file export and drift checks
+//! - [`structural_search`] — tree-sitter/regex fallback tiers when `sg` is unavailable
 
+pub mod api_diff;
 pub mod ast_grep;
+pub mod code_graph;
 pub mod graph_rag;
+pub mod rule_config;
 pub mod rule_pack;
+pub mod rule_test;
+pub mod sgconfig;
+pub mod structural_search;
 
+pub use api_diff::{
+    ApiChange, ApiChangeDetector, ApiChangeKind, ApiDiff, ApiItem, ApiItemKind, ApiSurface,
+};
 pub use ast_grep::{AstGrepConfig, AstGrepMatch, AstGrepQuery, AstGrepRunner};
+pub use code_graph::{CodeGraph, EdgeKind, SymbolDef};
 pub use graph_rag::{
-    GraphRagConfig, GraphRagEnvBridge, GraphRagQuery, GraphRagResult, GraphRagRunner,
+    GraphRagBackend, GraphRagConfig, GraphRagEnvBridge, GraphRagQuery, GraphRagResult,
+    GraphRagRunner,
 };
+pub use rule_config::{AstGrepRuleDoc, CompiledRule};
 pub use rule_pack::{
-    IngestionErrorKind, IngestionSummary, RuleIngestionError, RulePack, RulePackEntry,
-    RulePackRegistry, RuleSeverity,
+    IngestionErrorKind, IngestionSummary, RuleFixtures, RuleIngestionError, RulePack,
+    RulePackEntry, RulePackRegistry, RuleSeverity,
 };
+pub use rule_test::{RuleTestFailure, RuleTestFailureKind, RuleTestReport, RuleTestRunner};
+pub use sgconfig::{ExportFilter, ExportSummary, RuleDrift, SgConfigExporter};
+pub use structural_search::{RegexSearcher, StructuralSearch, TreeSitterSearcher};
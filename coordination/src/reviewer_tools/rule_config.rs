@@ -0,0 +1,612 @@
+//! ast-grep rule configs — load `id`/`rule` YAML documents and run them in-process.
+//!
+//! Files under `rules/ast-grep/rules` hold one or more rule documents in
+//! ast-grep's config format. [`CompiledRule`] evaluates the `rule` object
+//! (plus `constraints`) over a tree-sitter parse, so rule files can be tested
+//! without the `sg` binary. Supported keys:
+//!
+//! - atomic: `pattern`, `kind`, `regex` (searched anywhere in the node text)
+//! - composite: `all`, `any`, `not`
+//! - relational: `inside`, `has`, `precedes`, `follows`, with
+//!   `stopBy: neighbor | end` and (for `has`) `field`
+//!
+//! Anything else is a compile error rather than a rule that silently never
+//! matches.
+
+use super::ast_grep::AstGrepMatch;
+use super::code_graph::ts_language;
+use super::rule_pack::{IngestionErrorKind, RuleIngestionError};
+use super::structural_search::translate_pattern;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use streaming_iterator::StreamingIterator;
+
+/// One rule document from an ast-grep rule file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AstGrepRuleDoc {
+    /// Rule ID.
+    pub id: String,
+    /// Target language (ast-grep accepts `Rust` or `rust`).
+    #[serde(default)]
+    pub language: String,
+    /// ast-grep severity string (`error`, `warning`, `info`, `hint`).
+    #[serde(default)]
+    pub severity: Option<String>,
+    /// Message shown on match.
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Rule object.
+    #[serde(default)]
+    pub rule: Option<Value>,
+    /// Extra rules applied to captured metavariables.
+    #[serde(default)]
+    pub constraints: Option<BTreeMap<String, Value>>,
+}
+
+impl AstGrepRuleDoc {
+    /// The pattern, when the rule is nothing but a single `pattern`.
+    pub fn simple_pattern(&self) -> Option<&str> {
+        if self.constraints.as_ref().is_some_and(|c| !c.is_empty()) {
+            return None;
+        }
+        let map = self.rule.as_ref()?.as_mapping()?;
+        if map.len() != 1 {
+            return None;
+        }
+        map.get("pattern")?.as_str()
+    }
+}
+
+/// Read every rule document in a (possibly multi-document) rule file.
+pub fn load_rule_docs(path: &Path) -> Result<Vec<AstGrepRuleDoc>, RuleIngestionError> {
+    let content = std::fs::read_to_string(path).map_err(|e| RuleIngestionError {
+        source_path: Some(path.display().to_string()),
+        kind: IngestionErrorKind::IoError,
+        detail: format!("Failed to read file: {}", e),
+    })?;
+    serde_yaml::Deserializer::from_str(&content)
+        .map(|document| {
+            AstGrepRuleDoc::deserialize(document).map_err(|e| RuleIngestionError {
+                source_path: Some(path.display().to_string()),
+                kind: IngestionErrorKind::ParseError,
+                detail: format!("YAML parse error: {}", e),
+            })
+        })
+        .collect()
+}
+
+// ── Compilation ──────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Relation {
+    Inside,
+    Has,
+    Precedes,
+    Follows,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StopBy {
+    Neighbor,
+    End,
+}
+
+#[derive(Debug)]
+enum Matcher {
+    /// Index into [`CompiledRule::patterns`].
+    Pattern(usize),
+    Kind(String),
+    Regex(Regex),
+    All(Vec<Matcher>),
+    Any(Vec<Matcher>),
+    Not(Box<Matcher>),
+    Relational {
+        relation: Relation,
+        stop_by: StopBy,
+        field: Option<String>,
+        inner: Box<Matcher>,
+    },
+}
+
+struct Pattern {
+    query: tree_sitter::Query,
+    /// Whether `constraints` apply (false for patterns inside constraints).
+    constrained: bool,
+}
+
+/// A rule document compiled for in-process evaluation.
+pub struct CompiledRule {
+    id: String,
+    severity: Option<String>,
+    message: Option<String>,
+    language: tree_sitter::Language,
+    matcher: Matcher,
+    constraints: Vec<(String, Matcher)>,
+    patterns: Vec<Pattern>,
+}
+
+struct Compiler {
+    language_name: String,
+    language: tree_sitter::Language,
+    patterns: Vec<Pattern>,
+    constrained: bool,
+}
+
+impl Compiler {
+    fn rule(&mut self, value: &Value) -> Result<Matcher, String> {
+        let map = value
+            .as_mapping()
+            .ok_or_else(|| "rule must be a mapping".to_string())?;
+        let mut parts = Vec::new();
+        for (key, value) in map {
+            let key = key.as_str().unwrap_or_default();
+            parts.push(match key {
+                "pattern" => self.pattern(value)?,
+                "kind" => self.kind(value)?,
+                "regex" => {
+                    let source = value.as_str().ok_or("`regex` must be a string")?;
+                    Matcher::Regex(Regex::new(source).map_err(|e| format!("invalid regex: {e}"))?)
+                }
+                "all" => Matcher::All(self.rules(key, value)?),
+                "any" => Matcher::Any(self.rules(key, value)?),
+                "not" => Matcher::Not(Box::new(self.rule(value)?)),
+                "inside" => self.relational(Relation::Inside, value)?,
+                "has" => self.relational(Relation::Has, value)?,
+                "precedes" => self.relational(Relation::Precedes, value)?,
+                "follows" => self.relational(Relation::Follows, value)?,
+                other => return Err(format!("unsupported rule key `{other}`")),
+            });
+        }
+        match parts.len() {
+            0 => Err("empty rule".to_string()),
+            1 => Ok(parts.remove(0)),
+            _ => Ok(Matcher::All(parts)),
+        }
+    }
+
+    fn rules(&mut self, key: &str, value: &Value) -> Result<Vec<Matcher>, String> {
+        value
+            .as_sequence()
+            .ok_or_else(|| format!("`{key}` must be a list of rules"))?
+            .iter()
+            .map(|rule| self.rule(rule))
+            .collect()
+    }
+
+    fn pattern(&mut self, value: &Value) -> Result<Matcher, String> {
+        let pattern = value.as_str().ok_or("only string patterns are supported")?;
+        let source = translate_pattern(pattern, &self.language_name)?;
+        let query = tree_sitter::Query::new(&self.language, &source)
+            .map_err(|e| format!("invalid tree-sitter query for `{pattern}`: {e}"))?;
+        self.patterns.push(Pattern {
+            query,
+            constrained: self.constrained,
+        });
+        Ok(Matcher::Pattern(self.patterns.len() - 1))
+    }
+
+    fn kind(&self, value: &Value) -> Result<Matcher, String> {
+        let kind = value.as_str().ok_or("`kind` must be a string")?;
+        if self.language.id_for_node_kind(kind, true) == 0 {
+            return Err(format!("unknown {} node kind `{kind}`", self.language_name));
+        }
+        Ok(Matcher::Kind(kind.to_string()))
+    }
+
+    fn relational(&mut self, relation: Relation, value: &Value) -> Result<Matcher, String> {
+        let mut map = value
+            .as_mapping()
+            .cloned()
+            .ok_or_else(|| "relational rule must be a mapping".to_string())?;
+        let stop_by = match map.remove("stopBy") {
+            None => StopBy::Neighbor,
+            Some(v) => match v.as_str() {
+                Some("neighbor") => StopBy::Neighbor,
+                Some("end") => StopBy::End,
+                _ => return Err("only `stopBy: neighbor | end` is supported".to_string()),
+            },
+        };
+        let field = match map.remove("field") {
+            None => None,
+            Some(_) if relation != Relation::Has => {
+                return Err("`field` is only supported on `has`".to_string())
+            }
+            Some(v) => Some(v.as_str().ok_or("`field` must be a string")?.to_string()),
+        };
+        Ok(Matcher::Relational {
+            relation,
+            stop_by,
+            field,
+            inner: Box::new(self.rule(&Value::Mapping(map))?),
+        })
+    }
+}
+
+impl CompiledRule {
+    /// Compile a rule document for its language's tree-sitter grammar.
+    pub fn compile(doc: &AstGrepRuleDoc) -> Result<Self, String> {
+        let language_name = doc.language.to_lowercase();
+        let language = ts_language(&language_name)
+            .ok_or_else(|| format!("no tree-sitter grammar for '{}'", doc.language))?;
+        let rule = doc
+            .rule
+            .as_ref()
+            .ok_or_else(|| format!("rule `{}` has no `rule` object", doc.id))?;
+
+        let mut compiler = Compiler {
+            language_name,
+            language: language.clone(),
+            patterns: Vec::new(),
+            constrained: false,
+        };
+        let mut constraints = Vec::new();
+        for (name, value) in doc.constraints.iter().flatten() {
+            constraints.push((name.clone(), compiler.rule(value)?));
+        }
+        compiler.constrained = true;
+        let matcher = compiler.rule(rule)?;
+
+        Ok(Self {
+            id: doc.id.clone(),
+            severity: doc.severity.clone(),
+            message: doc.message.clone(),
+            language,
+            matcher,
+            constraints,
+            patterns: compiler.patterns,
+        })
+    }
+
+    /// Rule ID.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Every named node in `source` the rule matches, in document order.
+    pub fn find(&self, file: &str, source: &str) -> Result<Vec<AstGrepMatch>, String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&self.language)
+            .map_err(|e| format!("failed to load grammar: {e}"))?;
+        let tree = parser
+            .parse(source, None)
+            .ok_or_else(|| format!("failed to parse {file}"))?;
+
+        let mut eval = Evaluator {
+            source: source.as_bytes(),
+            hits: vec![HashSet::new(); self.patterns.len()],
+        };
+        // Constraint patterns first: constrained hits are filtered by them.
+        for constrained in [false, true] {
+            for (i, pattern) in self.patterns.iter().enumerate() {
+                if pattern.constrained == constrained {
+                    eval.hits[i] = self.pattern_hits(&eval, pattern, tree.root_node());
+                }
+            }
+        }
+
+        let mut matches = Vec::new();
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            if node.is_named() && eval.matches(&self.matcher, node) {
+                matches.push(self.to_match(file, source, node));
+            }
+            let mut cursor = node.walk();
+            let children: Vec<_> = node.children(&mut cursor).collect();
+            stack.extend(children.into_iter().rev());
+        }
+        Ok(matches)
+    }
+
+    fn pattern_hits(
+        &self,
+        eval: &Evaluator<'_>,
+        pattern: &Pattern,
+        root: tree_sitter::Node<'_>,
+    ) -> HashSet<usize> {
+        let query = &pattern.query;
+        let match_capture = query.capture_index_for_name("match").unwrap_or(0);
+        let names = query.capture_names();
+        let mut hits = HashSet::new();
+        let mut cursor = tree_sitter::QueryCursor::new();
+        let mut it = cursor.matches(query, root, eval.source);
+        while let Some(m) = it.next() {
+            let satisfied = !pattern.constrained
+                || m.captures.iter().all(|c| {
+                    let name = names[c.index as usize];
+                    let name = name.split_once("__").map_or(name, |(base, _)| base);
+                    self.constraints
+                        .iter()
+                        .filter(|(constrained, _)| constrained == name)
+                        .all(|(_, rule)| eval.matches(rule, c.node))
+                });
+            if !satisfied {
+                continue;
+            }
+            if let Some(c) = m.captures.iter().find(|c| c.index == match_capture) {
+                hits.insert(c.node.id());
+            }
+        }
+        hits
+    }
+
+    fn to_match(&self, file: &str, source: &str, node: tree_sitter::Node<'_>) -> AstGrepMatch {
+        let (s, e) = (node.start_position(), node.end_position());
+        AstGrepMatch {
+            file: file.to_string(),
+            line: s.row as u32 + 1,
+            column: s.column as u32 + 1,
+            end_line: e.row as u32 + 1,
+            end_column: e.column as u32 + 1,
+            text: node
+                .utf8_text(source.as_bytes())
+                .unwrap_or_default()
+                .to_string(),
+            rule_id: Some(self.id.clone()),
+            severity: self.severity.clone(),
+            message: self.message.clone(),
+        }
+    }
+}
+
+// ── Evaluation ───────────────────────────────────────────────────────
+
+struct Evaluator<'s> {
+    source: &'s [u8],
+    /// Node ids each pattern matched, indexed like [`CompiledRule::patterns`].
+    hits: Vec<HashSet<usize>>,
+}
+
+impl Evaluator<'_> {
+    fn matches(&self, matcher: &Matcher, node: tree_sitter::Node<'_>) -> bool {
+        match matcher {
+            Matcher::Pattern(i) => self.hits[*i].contains(&node.id()),
+            Matcher::Kind(kind) => node.kind() == kind,
+            Matcher::Regex(regex) => {
+                regex.is_match(node.utf8_text(self.source).unwrap_or_default())
+            }
+            Matcher::All(rules) => rules.iter().all(|r| self.matches(r, node)),
+            Matcher::Any(rules) => rules.iter().any(|r| self.matches(r, node)),
+            Matcher::Not(rule) => !self.matches(rule, node),
+            Matcher::Relational {
+                relation,
+                stop_by,
+                field,
+                inner,
+            } => related(node, *relation, *stop_by, field.as_deref())
+                .into_iter()
+                .any(|n| self.matches(inner, n)),
+        }
+    }
+}
+
+/// Nodes a relational rule checks, nearest first.
+fn related<'t>(
+    node: tree_sitter::Node<'t>,
+    relation: Relation,
+    stop_by: StopBy,
+    field: Option<&str>,
+) -> Vec<tree_sitter::Node<'t>> {
+    let end = stop_by == StopBy::End;
+    let mut out = Vec::new();
+    match relation {
+        Relation::Inside => {
+            let mut current = node.parent();
+            while let Some(parent) = current {
+                out.push(parent);
+                if !end {
+                    break;
+                }
+                current = parent.parent();
+            }
+        }
+        Relation::Has => {
+            let mut cursor = node.walk();
+            let children: Vec<_> = node
+                .children(&mut cursor)
+                .enumerate()
+                .filter(|(i, _)| field.is_none() || node.field_name_for_child(*i as u32) == field)
+                .map(|(_, child)| child)
+                .collect();
+            for child in children {
+                out.push(child);
+                if end {
+                    out.extend(descendants(child));
+                }
+            }
+        }
+        Relation::Precedes | Relation::Follows => {
+            let step = |n: tree_sitter::Node<'t>| {
+                if relation == Relation::Precedes {
+                    n.next_named_sibling()
+                } else {
+                    n.prev_named_sibling()
+                }
+            };
+            let mut current = step(node);
+            while let Some(sibling) = current {
+                current = step(sibling);
+                if sibling.is_extra() {
+                    continue;
+                }
+                out.push(sibling);
+                if !end {
+                    break;
+                }
+            }
+        }
+    }
+    out
+}
+
+fn descendants(node: tree_sitter::Node<'_>) -> Vec<tree_sitter::Node<'_>> {
+    let mut out = Vec::new();
+    let mut stack = vec![node];
+    while let Some(n) = stack.pop() {
+        let mut cursor = n.walk();
+        for child in n.children(&mut cursor) {
+            out.push(child);
+            stack.push(child);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(yaml: &str) -> Result<CompiledRule, String> {
+        CompiledRule::compile(&serde_yaml::from_str(yaml).unwrap())
+    }
+
+    fn texts(rule: &CompiledRule, source: &str) -> Vec<String> {
+        rule.find("test.rs", source)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_simple_pattern() {
+        let doc: AstGrepRuleDoc =
+            serde_yaml::from_str("id: a\nlanguage: Rust\nrule:\n  pattern: $X.unwrap()\n").unwrap();
+        assert_eq!(doc.simple_pattern(), Some("$X.unwrap()"));
+
+        let doc: AstGrepRuleDoc = serde_yaml::from_str(
+            "id: a\nlanguage: Rust\nrule:\n  pattern: $X.unwrap()\n  inside:\n    kind: block\n",
+        )
+        .unwrap();
+        assert_eq!(doc.simple_pattern(), None);
+    }
+
+    #[test]
+    fn test_relational_and_composite() {
+        let rule = compile(
+            r#"
+id: unwrap-in-async
+language: Rust
+severity: warning
+rule:
+  pattern: $E.unwrap()
+  inside:
+    kind: function_item
+    has:
+      kind: function_modifiers
+      regex: "^async$"
+    stopBy: end
+"#,
+        )
+        .unwrap();
+        let source = "async fn a() { x.unwrap(); }\nfn b() { y.unwrap(); }\n";
+        assert_eq!(texts(&rule, source), vec!["x.unwrap()"]);
+        let m = &rule.find("f.rs", source).unwrap()[0];
+        assert_eq!((m.line, m.column), (1, 16));
+        assert_eq!(m.severity.as_deref(), Some("warning"));
+
+        let rule = compile(
+            r##"
+id: attr-before-fn
+language: rust
+rule:
+  any:
+    - pattern: "#[cfg(test)]"
+    - kind: line_comment
+  precedes:
+    kind: function_item
+  not:
+    regex: skip
+"##,
+        )
+        .unwrap();
+        let source = "#[cfg(test)]\nfn a() {}\n#[cfg(test)]\nmod t {}\n// skip\nfn b() {}\n// c\nfn c() {}\n";
+        assert_eq!(texts(&rule, source), vec!["#[cfg(test)]", "// c"]);
+    }
+
+    #[test]
+    fn test_constraints_and_fields() {
+        let rule = compile(
+            r#"
+id: bare-expect
+language: rust
+rule:
+  pattern: $E.expect($MSG)
+constraints:
+  MSG:
+    regex: '^"(failed|error)"$'
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            texts(
+                &rule,
+                r#"fn f() { a.expect("failed"); b.expect("config loaded"); }"#
+            ),
+            vec![r#"a.expect("failed")"#]
+        );
+
+        let rule = compile(
+            "id: lhs-literal\nlanguage: rust\nrule:\n  kind: binary_expression\n  has:\n    field: left\n    kind: string_literal\n",
+        )
+        .unwrap();
+        assert_eq!(
+            texts(&rule, r#"fn f() { let a = "x" + y; let b = y + "x"; }"#),
+            vec![r#""x" + y"#]
+        );
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        for (yaml, expected) in [
+            ("id: a\nlanguage: rust\n", "no `rule`"),
+            (
+                "id: a\nlanguage: cobol\nrule:\n  kind: x\n",
+                "no tree-sitter grammar",
+            ),
+            (
+                "id: a\nlanguage: rust\nrule:\n  kind: no_such_kind\n",
+                "unknown rust node kind",
+            ),
+            (
+                "id: a\nlanguage: rust\nrule:\n  matches: util\n",
+                "unsupported rule key `matches`",
+            ),
+            (
+                "id: a\nlanguage: rust\nrule:\n  regex: '('\n",
+                "invalid regex",
+            ),
+            (
+                "id: a\nlanguage: rust\nrule:\n  inside:\n    kind: block\n    field: body\n",
+                "only supported on `has`",
+            ),
+            ("id: a\nlanguage: rust\nrule: {}\n", "empty rule"),
+        ] {
+            let err = compile(yaml).err().unwrap_or_default();
+            assert!(err.contains(expected), "{yaml}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_load_rule_docs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rules.yml");
+        std::fs::write(
+            &path,
+            "id: a\nlanguage: Rust\nrule:\n  pattern: x\n---\nid: b\nlanguage: Rust\nseverity: hint\nrule:\n  kind: block\n",
+        )
+        .unwrap();
+        let docs = load_rule_docs(&path).unwrap();
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[1].severity.as_deref(), Some("hint"));
+
+        std::fs::write(&path, "id: [unterminated\n").unwrap();
+        let err = load_rule_docs(&path).unwrap_err();
+        assert_eq!(err.kind, IngestionErrorKind::ParseError);
+        let err = load_rule_docs(&dir.path().join("missing.yml")).unwrap_err();
+        assert_eq!(err.kind, IngestionErrorKind::IoError);
+    }
+}
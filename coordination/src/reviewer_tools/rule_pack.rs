@@ -46,6 +46,27 @@ pub struct RulePackEntry {
     pub category: String,
     /// Whether this rule is enabled by default.
     pub enabled: bool,
+    /// Code snippets the rule must (invalid) and must not (valid) match.
+    #[serde(default, skip_serializing_if = "RuleFixtures::is_empty")]
+    pub fixtures: RuleFixtures,
+}
+
+/// Test fixtures for a rule, in the shape of ast-grep's `valid`/`invalid` tests.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleFixtures {
+    /// Snippets the rule must not match.
+    #[serde(default)]
+    pub valid: Vec<String>,
+    /// Snippets the rule must match at least once each.
+    #[serde(default)]
+    pub invalid: Vec<String>,
+}
+
+impl RuleFixtures {
+    /// Whether no fixtures are defined.
+    pub fn is_empty(&self) -> bool {
+        self.valid.is_empty() && self.invalid.is_empty()
+    }
 }
 
 impl RulePackEntry {
@@ -67,6 +88,7 @@ impl RulePackEntry {
             rule_file: None,
             category: category.to_string(),
             enabled: true,
+            fixtures: RuleFixtures::default(),
         }
     }
 
@@ -88,6 +110,7 @@ impl RulePackEntry {
             rule_file: Some(rule_file.to_string()),
             category: category.to_string(),
             enabled: true,
+            fixtures: RuleFixtures::default(),
         }
     }
 
@@ -96,6 +119,15 @@ impl RulePackEntry {
         self.enabled = enabled;
         self
     }
+
+    /// Attach valid/invalid code fixtures.
+    pub fn with_fixtures(mut self, valid: &[&str], invalid: &[&str]) -> Self {
+        self.fixtures = RuleFixtures {
+            valid: valid.iter().map(|s| s.to_string()).collect(),
+            invalid: invalid.iter().map(|s| s.to_string()).collect(),
+        };
+        self
+    }
 }
 
 /// A named collection of rules for a specific purpose.
@@ -222,106 +254,175 @@ impl RulePackRegistry {
     /// Default Rust safety rule pack.
     fn rust_safety_pack() -> RulePack {
         let mut pack = RulePack::new("rust-safety", "Rust safety and correctness checks");
-        pack.add_rule(RulePackEntry::pattern_rule(
-            "no-unwrap",
-            "Avoid .unwrap() — use ? or explicit error handling",
-            RuleSeverity::Error,
-            "rust",
-            "$EXPR.unwrap()",
-            "safety",
-        ));
-        pack.add_rule(RulePackEntry::pattern_rule(
-            "no-expect",
-            "Avoid .expect() in library code — use ? or explicit error handling",
-            RuleSeverity::Warning,
-            "rust",
-            "$EXPR.expect($MSG)",
-            "safety",
-        ));
-        pack.add_rule(RulePackEntry::pattern_rule(
-            "no-unsafe",
-            "Unsafe blocks require justification comment",
-            RuleSeverity::Error,
-            "rust",
-            "unsafe { $$$BODY }",
-            "safety",
-        ));
-        pack.add_rule(RulePackEntry::pattern_rule(
-            "no-panic",
-            "Avoid panic!() — use Result return types",
-            RuleSeverity::Error,
-            "rust",
-            "panic!($$$ARGS)",
-            "safety",
-        ));
-        pack.add_rule(RulePackEntry::pattern_rule(
-            "no-todo",
-            "Remove TODO macros before merge",
-            RuleSeverity::Warning,
-            "rust",
-            "todo!($$$ARGS)",
-            "safety",
-        ));
-        pack.add_rule(RulePackEntry::pattern_rule(
-            "no-unimplemented",
-            "Remove unimplemented!() before merge",
-            RuleSeverity::Error,
-            "rust",
-            "unimplemented!($$$ARGS)",
-            "safety",
-        ));
+        pack.add_rule(
+            RulePackEntry::pattern_rule(
+                "no-unwrap",
+                "Avoid .unwrap() — use ? or explicit error handling",
+                RuleSeverity::Error,
+                "rust",
+                "$EXPR.unwrap()",
+                "safety",
+            )
+            .with_fixtures(
+                &["let v = opt.unwrap_or(0);", "let v = opt?;"],
+                &[
+                    "let v = opt.unwrap();",
+                    "let n = s.parse::<u32>().unwrap();",
+                ],
+            ),
+        );
+        pack.add_rule(
+            RulePackEntry::pattern_rule(
+                "no-expect",
+                "Avoid .expect() in library code — use ? or explicit error handling",
+                RuleSeverity::Warning,
+                "rust",
+                "$EXPR.expect($MSG)",
+                "safety",
+            )
+            .with_fixtures(
+                &["let v = res?;", "let v = res.expect_err();"],
+                &["let v = res.expect(\"config present\");"],
+            ),
+        );
+        pack.add_rule(
+            RulePackEntry::pattern_rule(
+                "no-unsafe",
+                "Unsafe blocks require justification comment",
+                RuleSeverity::Error,
+                "rust",
+                "unsafe { $$$BODY }",
+                "safety",
+            )
+            .with_fixtures(
+                &["fn f() { let x = 1; }"],
+                &["fn f() { unsafe { ptr.read() } }"],
+            ),
+        );
+        pack.add_rule(
+            RulePackEntry::pattern_rule(
+                "no-panic",
+                "Avoid panic!() — use Result return types",
+                RuleSeverity::Error,
+                "rust",
+                "panic!($$$ARGS)",
+                "safety",
+            )
+            .with_fixtures(
+                &["fn f() -> Result<(), E> { Err(e) }"],
+                &[
+                    "fn f() { panic!(\"boom: {}\", x); }",
+                    "fn f() { panic!(); }",
+                ],
+            ),
+        );
+        pack.add_rule(
+            RulePackEntry::pattern_rule(
+                "no-todo",
+                "Remove TODO macros before merge",
+                RuleSeverity::Warning,
+                "rust",
+                "todo!($$$ARGS)",
+                "safety",
+            )
+            .with_fixtures(&["fn f() { todo_list.push(1); }"], &["fn f() { todo!() }"]),
+        );
+        pack.add_rule(
+            RulePackEntry::pattern_rule(
+                "no-unimplemented",
+                "Remove unimplemented!() before merge",
+                RuleSeverity::Error,
+                "rust",
+                "unimplemented!($$$ARGS)",
+                "safety",
+            )
+            .with_fixtures(
+                &["fn f() { todo!() }"],
+                &["fn f() { unimplemented!(\"later\") }"],
+            ),
+        );
         pack
     }
 
     /// Default Rust performance rule pack.
     fn rust_performance_pack() -> RulePack {
         let mut pack = RulePack::new("rust-performance", "Rust performance anti-patterns");
-        pack.add_rule(RulePackEntry::pattern_rule(
-            "no-clone-in-loop",
-            "Avoid .clone() inside loops — consider borrowing",
-            RuleSeverity::Warning,
-            "rust",
-            "$EXPR.clone()",
-            "performance",
-        ));
-        pack.add_rule(RulePackEntry::pattern_rule(
-            "no-collect-iter",
-            "Avoid .collect::<Vec<_>>() followed by .iter() — chain iterators instead",
-            RuleSeverity::Info,
-            "rust",
-            "$EXPR.collect::<Vec<$T>>()",
-            "performance",
-        ));
-        pack.add_rule(RulePackEntry::pattern_rule(
-            "no-format-in-loop",
-            "Avoid format!() in hot loops — preallocate or use write!",
-            RuleSeverity::Info,
-            "rust",
-            "format!($$$ARGS)",
-            "performance",
-        ));
+        pack.add_rule(
+            RulePackEntry::pattern_rule(
+                "no-clone-in-loop",
+                "Avoid .clone() inside loops — consider borrowing",
+                RuleSeverity::Warning,
+                "rust",
+                "$EXPR.clone()",
+                "performance",
+            )
+            .with_fixtures(
+                &["for x in &xs { v.push(x); }"],
+                &["for x in xs { v.push(x.clone()); }"],
+            ),
+        );
+        pack.add_rule(
+            RulePackEntry::pattern_rule(
+                "no-collect-iter",
+                "Avoid .collect::<Vec<_>>() followed by .iter() — chain iterators instead",
+                RuleSeverity::Info,
+                "rust",
+                "$EXPR.collect::<Vec<$T>>()",
+                "performance",
+            )
+            .with_fixtures(
+                &["let s = it.collect::<String>();"],
+                &["let v = it.collect::<Vec<u32>>();"],
+            ),
+        );
+        pack.add_rule(
+            RulePackEntry::pattern_rule(
+                "no-format-in-loop",
+                "Avoid format!() in hot loops — preallocate or use write!",
+                RuleSeverity::Info,
+                "rust",
+                "format!($$$ARGS)",
+                "performance",
+            )
+            .with_fixtures(
+                &["let s = x.to_string();"],
+                &["let s = format!(\"{}\", x);"],
+            ),
+        );
         pack
     }
 
     /// Default Rust style rule pack.
     fn rust_style_pack() -> RulePack {
         let mut pack = RulePack::new("rust-style", "Rust style and idiomatic patterns");
-        pack.add_rule(RulePackEntry::pattern_rule(
-            "use-if-let",
-            "Prefer if let over match with single arm and wildcard",
-            RuleSeverity::Info,
-            "rust",
-            "match $EXPR { $PAT => $BODY, _ => {} }",
-            "style",
-        ));
-        pack.add_rule(RulePackEntry::pattern_rule(
-            "no-string-to-string",
-            "Use .to_owned() or String::from() instead of .to_string() for &str",
-            RuleSeverity::Info,
-            "rust",
-            "$EXPR.to_string()",
-            "style",
-        ));
+        pack.add_rule(
+            RulePackEntry::pattern_rule(
+                "use-if-let",
+                "Prefer if let over match with single arm and wildcard",
+                RuleSeverity::Info,
+                "rust",
+                "match $EXPR { $PAT => $BODY, _ => {} }",
+                "style",
+            )
+            .with_fixtures(
+                &["if let Some(x) = opt { run(x) }"],
+                &["match opt { Some(x) => run(x), _ => {} }"],
+            ),
+        );
+        pack.add_rule(
+            RulePackEntry::pattern_rule(
+                "no-string-to-string",
+                "Use .to_owned() or String::from() instead of .to_string() for &str",
+                RuleSeverity::Info,
+                "rust",
+                "$EXPR.to_string()",
+                "style",
+            )
+            .with_fixtures(
+                &["let s = String::from(\"a\");"],
+                &["let s = \"a\".to_string();"],
+            ),
+        );
         pack
     }
 }
@@ -435,6 +536,74 @@ impl RulePackRegistry {
         Ok(summary)
     }
 
+    /// Load an ast-grep rule file (one or more `id`/`rule` documents) as a pack.
+    ///
+    /// The pack is named after the file stem, which also becomes each rule's
+    /// category. Every entry points back at the file; rules that are a single
+    /// `pattern` also carry it inline.
+    pub fn load_ast_grep_file(path: &std::path::Path) -> Result<RulePack, RuleIngestionError> {
+        let docs = super::rule_config::load_rule_docs(path)?;
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("ast-grep");
+        let mut pack = RulePack::new(stem, &format!("ast-grep rules from {}", path.display()));
+
+        for doc in docs {
+            let raw = doc.severity.as_deref().unwrap_or("hint");
+            let severity =
+                super::sgconfig::parse_sg_severity(raw).ok_or_else(|| RuleIngestionError {
+                    source_path: Some(path.display().to_string()),
+                    kind: IngestionErrorKind::ParseError,
+                    detail: format!("rule `{}` has unknown severity `{}`", doc.id, raw),
+                })?;
+            let description = doc
+                .message
+                .as_deref()
+                .and_then(|m| m.lines().map(str::trim).find(|l| !l.is_empty()))
+                .unwrap_or(&doc.id);
+            let mut rule = RulePackEntry::file_rule(
+                &doc.id,
+                description,
+                severity,
+                &doc.language.to_lowercase(),
+                &path.display().to_string(),
+                stem,
+            );
+            rule.pattern = doc.simple_pattern().map(str::to_string);
+            pack.add_rule(rule);
+        }
+        Ok(pack)
+    }
+
+    /// Load every ast-grep rule file in a directory, one pack per file.
+    ///
+    /// Files that fail to load are reported in the summary; the rest are
+    /// registered.
+    pub fn load_ast_grep_directory(
+        &mut self,
+        dir: &std::path::Path,
+    ) -> Result<IngestionSummary, RuleIngestionError> {
+        let paths = yaml_files(dir).map_err(|e| RuleIngestionError {
+            source_path: Some(dir.display().to_string()),
+            kind: IngestionErrorKind::IoError,
+            detail: format!("Failed to read directory: {}", e),
+        })?;
+
+        let mut summary = IngestionSummary::default();
+        for path in paths {
+            match Self::load_ast_grep_file(&path) {
+                Ok(pack) => {
+                    summary.loaded_packs.push(pack.name.clone());
+                    summary.total_rules += pack.enabled_count();
+                    self.register(pack);
+                }
+                Err(e) => summary.errors.push(e),
+            }
+        }
+        Ok(summary)
+    }
+
     /// Create a registry with defaults, then overlay packs from a directory.
     pub fn with_defaults_and_directory(
         dir: &std::path::Path,
@@ -532,6 +701,22 @@ impl IngestionSummary {
     }
 }
 
+/// `*.yml` / `*.yaml` files directly under `dir`, sorted by path.
+pub fn yaml_files(dir: &std::path::Path) -> std::io::Result<Vec<std::path::PathBuf>> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            matches!(
+                p.extension().and_then(|e| e.to_str()),
+                Some("yml") | Some("yaml")
+            )
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
 impl Default for RulePackRegistry {
     fn default() -> Self {
         Self::with_defaults()
@@ -753,4 +938,47 @@ mod tests {
         assert_eq!(parsed.rule_id, "test");
         assert_eq!(parsed.severity, RuleSeverity::Error);
     }
+
+    #[test]
+    fn test_load_ast_grep_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("async-safety.yml"),
+            "id: no-sleep\nlanguage: Rust\nseverity: error\nmessage: |\n  Blocks the runtime\n  more\nrule:\n  pattern: std::thread::sleep($D)\n\
+             ---\nid: no-lock\nlanguage: Rust\nrule:\n  pattern: $M.lock()\n  inside:\n    kind: block\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("bad.yml"),
+            "id: x\nlanguage: Rust\nseverity: fatal\nrule:\n  kind: block\n",
+        )
+        .unwrap();
+
+        let mut registry = RulePackRegistry::new();
+        let summary = registry.load_ast_grep_directory(dir.path()).unwrap();
+        assert_eq!(summary.loaded_packs, vec!["async-safety"]);
+        assert_eq!(summary.total_rules, 2);
+        assert_eq!(summary.errors.len(), 1);
+        assert!(summary.errors[0]
+            .detail
+            .contains("unknown severity `fatal`"));
+
+        let pack = registry.get("async-safety").unwrap();
+        let sleep = &pack.rules[0];
+        assert_eq!(sleep.description, "Blocks the runtime");
+        assert_eq!(sleep.severity, RuleSeverity::Error);
+        assert_eq!(sleep.language, "rust");
+        assert_eq!(sleep.category, "async-safety");
+        assert_eq!(sleep.pattern.as_deref(), Some("std::thread::sleep($D)"));
+        assert!(sleep
+            .rule_file
+            .as_ref()
+            .unwrap()
+            .ends_with("async-safety.yml"));
+
+        let lock = &pack.rules[1];
+        assert_eq!(lock.severity, RuleSeverity::Info);
+        assert_eq!(lock.description, "no-lock");
+        assert!(lock.pattern.is_none());
+    }
 }
@@ -0,0 +1,852 @@
+//! Fixture-based rule testing — check rule patterns against code snippets.
+//!
+//! Each [`RulePackEntry`] can carry `valid`/`invalid` snippets (inline via
+//! [`RuleFixtures`] or in sidecar files). The runner checks that every
+//! enabled rule matches each invalid snippet and none of the valid ones.
+//! When ast-grep is installed the rules and fixtures are written to a
+//! scratch project and checked with `sg test`, so results match what `sg`
+//! will report. Without it they are checked in-process: inline patterns
+//! run through the tree-sitter tier, and rules backed by an ast-grep rule
+//! file are compiled with [`CompiledRule`].
+//!
+//! Sidecar files use ast-grep's rule test layout (as in
+//! `rules/ast-grep/rule-tests`), one rule per document:
+//!
+//! ```yaml
+//! id: no-unwrap
+//! valid:
+//!   - "let v = opt?;"
+//! invalid:
+//!   - "let v = opt.unwrap();"
+//! ```
+
+use super::ast_grep::{AstGrepConfig, AstGrepMatch, AstGrepQuery, AstGrepRunner};
+use super::rule_config::{load_rule_docs, AstGrepRuleDoc, CompiledRule};
+use super::rule_pack::{
+    yaml_files, RuleFixtures, RuleIngestionError, RulePackEntry, RulePackRegistry,
+};
+use super::structural_search::TreeSitterSearcher;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Kind of rule test failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RuleTestFailureKind {
+    /// An invalid snippet produced no match.
+    MissedInvalid,
+    /// A valid snippet produced a match.
+    MatchedValid,
+    /// The rule pattern could not be compiled or run.
+    PatternError,
+    /// A sidecar fixture file could not be read or applied.
+    FixtureError,
+}
+
+impl std::fmt::Display for RuleTestFailureKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissedInvalid => write!(f, "missed_invalid"),
+            Self::MatchedValid => write!(f, "matched_valid"),
+            Self::PatternError => write!(f, "pattern_error"),
+            Self::FixtureError => write!(f, "fixture_error"),
+        }
+    }
+}
+
+/// A single failed rule test case.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleTestFailure {
+    /// Rule under test (or the sidecar path for fixture errors).
+    pub rule_id: String,
+    /// Failure kind.
+    pub kind: RuleTestFailureKind,
+    /// Snippet that failed (if the failure is per-snippet).
+    pub snippet: Option<String>,
+    /// Human-readable detail.
+    pub detail: String,
+}
+
+impl std::fmt::Display for RuleTestFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}: {}", self.rule_id, self.kind, self.detail)
+    }
+}
+
+/// Summary of a rule test run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleTestReport {
+    /// Rules whose fixtures all passed.
+    pub passed: Vec<String>,
+    /// Enabled rules with no fixtures, or with neither a pattern nor a rule file.
+    pub untested: Vec<String>,
+    /// Total snippets checked.
+    pub total_cases: usize,
+    /// Failures encountered.
+    pub failures: Vec<RuleTestFailure>,
+}
+
+impl RuleTestReport {
+    /// Whether every tested rule passed.
+    pub fn all_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// One-line summary for logs and CI output.
+    pub fn summary_line(&self) -> String {
+        format!(
+            "{} rules passed, {} failures, {} untested ({} cases)",
+            self.passed.len(),
+            self.failures.len(),
+            self.untested.len(),
+            self.total_cases
+        )
+    }
+}
+
+/// Sidecar fixture document.
+#[derive(Debug, Deserialize)]
+struct FixtureFile {
+    id: String,
+    #[serde(default)]
+    valid: Vec<String>,
+    #[serde(default)]
+    invalid: Vec<String>,
+}
+
+/// Runs rule fixtures through `sg test`, or in-process without ast-grep.
+pub struct RuleTestRunner {
+    searcher: TreeSitterSearcher,
+    sg: AstGrepConfig,
+    fixture_dir: Option<PathBuf>,
+}
+
+impl RuleTestRunner {
+    /// Create a runner using inline fixtures only.
+    pub fn new() -> Self {
+        Self {
+            searcher: TreeSitterSearcher::new(AstGrepConfig::default()),
+            sg: AstGrepConfig::default(),
+            fixture_dir: None,
+        }
+    }
+
+    /// Use this ast-grep configuration (binary, timeout) for `sg test`.
+    pub fn with_ast_grep(mut self, config: AstGrepConfig) -> Self {
+        self.sg = config;
+        self
+    }
+
+    /// Also load sidecar fixtures (`*.yml` / `*.yaml`) from a directory.
+    pub fn with_fixture_dir(mut self, dir: &Path) -> Self {
+        self.fixture_dir = Some(dir.to_path_buf());
+        self
+    }
+
+    /// Test every enabled rule in the registry.
+    ///
+    /// When the configured `sg` binary is ast-grep, all rules with fixtures
+    /// go through a single `sg test` run; otherwise (or if that run fails)
+    /// they are checked in-process.
+    pub fn run(&self, registry: &RulePackRegistry) -> RuleTestReport {
+        let mut report = RuleTestReport::default();
+        let mut sidecars = match &self.fixture_dir {
+            Some(dir) => Self::load_sidecars(dir, &mut report.failures),
+            None => BTreeMap::new(),
+        };
+
+        let mut rule_files = HashMap::new();
+        let mut cases = Vec::new();
+        for name in registry.pack_names() {
+            let Some(pack) = registry.get(name) else {
+                continue;
+            };
+            for rule in pack.enabled_rules() {
+                let mut fixtures = rule.fixtures.clone();
+                if let Some(extra) = sidecars.remove(&rule.rule_id) {
+                    fixtures.valid.extend(extra.valid);
+                    fixtures.invalid.extend(extra.invalid);
+                }
+                if fixtures.is_empty() || (rule.pattern.is_none() && rule.rule_file.is_none()) {
+                    report.untested.push(rule.rule_id.clone());
+                    continue;
+                }
+                if let (None, Some(path)) = (&rule.pattern, &rule.rule_file) {
+                    rule_files
+                        .entry(path.clone())
+                        .or_insert_with(|| load_rule_docs(Path::new(path)));
+                }
+                cases.push((rule, fixtures));
+            }
+        }
+
+        let mut sg_results = self.sg_test(&cases, &rule_files).unwrap_or_default();
+        for (rule, fixtures) in &cases {
+            let failures = match sg_results.remove(&rule.rule_id) {
+                Some(failures) => failures,
+                None => match (&rule.pattern, &rule.rule_file) {
+                    (Some(_), _) => self.test_rule(rule, fixtures),
+                    (None, Some(path)) => {
+                        Self::test_rule_file(rule, path, &rule_files[path], fixtures)
+                    }
+                    (None, None) => continue,
+                },
+            };
+            report.total_cases += fixtures.valid.len() + fixtures.invalid.len();
+            if failures.is_empty() {
+                report.passed.push(rule.rule_id.clone());
+            } else {
+                report.failures.extend(failures);
+            }
+        }
+
+        for rule_id in sidecars.into_keys() {
+            report.failures.push(RuleTestFailure {
+                rule_id,
+                kind: RuleTestFailureKind::FixtureError,
+                snippet: None,
+                detail: "fixtures reference no enabled rule".to_string(),
+            });
+        }
+        report
+    }
+
+    /// Run every case through `sg test` in a scratch project.
+    ///
+    /// Returns failures keyed by rule ID, covering each case whose rule
+    /// could be written out (the first of any duplicate ID), or `None` if
+    /// ast-grep is unavailable or did not report on every rule.
+    fn sg_test(
+        &self,
+        cases: &[(&RulePackEntry, RuleFixtures)],
+        rule_files: &HashMap<String, Result<Vec<AstGrepRuleDoc>, RuleIngestionError>>,
+    ) -> Option<BTreeMap<String, Vec<RuleTestFailure>>> {
+        if cases.is_empty() || !AstGrepRunner::with_config(self.sg.clone()).is_available() {
+            return None;
+        }
+
+        let mut project = Vec::new();
+        let mut ids = HashSet::new();
+        for (rule, fixtures) in cases {
+            let doc = match (&rule.pattern, &rule.rule_file) {
+                (Some(pattern), _) => sg_rule_doc(&AstGrepRuleDoc {
+                    id: rule.rule_id.clone(),
+                    language: rule.language.clone(),
+                    severity: None,
+                    message: None,
+                    rule: Some(Value::Mapping(
+                        [("pattern".into(), pattern.as_str().into())]
+                            .into_iter()
+                            .collect(),
+                    )),
+                    constraints: None,
+                }),
+                (None, Some(path)) => match &rule_files[path] {
+                    Ok(docs) => match docs.iter().find(|d| d.id == rule.rule_id) {
+                        Some(doc) => sg_rule_doc(doc),
+                        None => continue,
+                    },
+                    Err(_) => continue,
+                },
+                (None, None) => continue,
+            };
+            if ids.insert(rule.rule_id.as_str()) {
+                project.push((rule.rule_id.as_str(), doc, fixtures));
+            }
+        }
+
+        let dir = std::env::temp_dir().join(format!("rule-test-{}", uuid::Uuid::new_v4()));
+        let output = write_sg_project(&dir, &project).and_then(|()| {
+            AstGrepRunner::with_config(AstGrepConfig {
+                working_dir: Some(dir.display().to_string()),
+                ..self.sg.clone()
+            })
+            .test_project()
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+        let (_, output) = output.ok()?;
+
+        let cases: Vec<(&str, &RuleFixtures)> =
+            project.iter().map(|(id, _, f)| (*id, *f)).collect();
+        parse_sg_test(&output, &cases)
+    }
+
+    /// Check one rule against its fixtures, returning any failures.
+    pub fn test_rule(&self, rule: &RulePackEntry, fixtures: &RuleFixtures) -> Vec<RuleTestFailure> {
+        let Some(pattern) = rule.pattern.as_deref() else {
+            return Vec::new();
+        };
+        let mut query = AstGrepQuery::pattern(pattern, &rule.language);
+        query.rule_id = Some(rule.rule_id.clone());
+
+        check_fixtures(
+            &rule.rule_id,
+            &format!("pattern `{pattern}`"),
+            fixtures,
+            |file, snippet| {
+                let result = self.searcher.search_source(&query, file, snippet);
+                match result.error {
+                    Some(error) => Err(error),
+                    None => Ok(result.matches),
+                }
+            },
+        )
+    }
+
+    /// Check a rule defined in an ast-grep rule file against its fixtures.
+    fn test_rule_file(
+        rule: &RulePackEntry,
+        path: &str,
+        docs: &Result<Vec<AstGrepRuleDoc>, RuleIngestionError>,
+        fixtures: &RuleFixtures,
+    ) -> Vec<RuleTestFailure> {
+        let compiled = match docs {
+            Ok(docs) => match docs.iter().find(|d| d.id == rule.rule_id) {
+                Some(doc) => CompiledRule::compile(doc),
+                None => Err(format!("no rule `{}` in {}", rule.rule_id, path)),
+            },
+            Err(e) => Err(e.to_string()),
+        };
+        let compiled = match compiled {
+            Ok(compiled) => compiled,
+            Err(detail) => {
+                return vec![RuleTestFailure {
+                    rule_id: rule.rule_id.clone(),
+                    kind: RuleTestFailureKind::PatternError,
+                    snippet: None,
+                    detail,
+                }]
+            }
+        };
+        check_fixtures(
+            &rule.rule_id,
+            &format!("rule in {path}"),
+            fixtures,
+            |file, snippet| compiled.find(file, snippet),
+        )
+    }
+
+    /// Load sidecar fixtures keyed by rule ID, merging repeated IDs.
+    fn load_sidecars(
+        dir: &Path,
+        failures: &mut Vec<RuleTestFailure>,
+    ) -> BTreeMap<String, RuleFixtures> {
+        let mut fixtures: BTreeMap<String, RuleFixtures> = BTreeMap::new();
+        let mut fixture_error = |path: &Path, detail: String| {
+            failures.push(RuleTestFailure {
+                rule_id: path.display().to_string(),
+                kind: RuleTestFailureKind::FixtureError,
+                snippet: None,
+                detail,
+            })
+        };
+
+        let paths = match yaml_files(dir) {
+            Ok(paths) => paths,
+            Err(e) => {
+                fixture_error(dir, format!("Failed to read directory: {}", e));
+                return fixtures;
+            }
+        };
+
+        for path in paths {
+            let content = match std::fs::read_to_string(&path) {
+                Ok(c) => c,
+                Err(e) => {
+                    fixture_error(&path, format!("Failed to read file: {}", e));
+                    continue;
+                }
+            };
+            for document in serde_yaml::Deserializer::from_str(&content) {
+                match FixtureFile::deserialize(document) {
+                    Ok(file) => {
+                        let entry = fixtures.entry(file.id).or_default();
+                        entry.valid.extend(file.valid);
+                        entry.invalid.extend(file.invalid);
+                    }
+                    Err(e) => {
+                        fixture_error(&path, format!("YAML parse error: {}", e));
+                        break;
+                    }
+                }
+            }
+        }
+        fixtures
+    }
+}
+
+/// A rule document as ast-grep reads it, leaving out unset keys.
+fn sg_rule_doc(doc: &AstGrepRuleDoc) -> Value {
+    let mut map = Mapping::new();
+    map.insert("id".into(), doc.id.as_str().into());
+    map.insert("language".into(), doc.language.as_str().into());
+    if let Some(severity) = &doc.severity {
+        map.insert("severity".into(), severity.as_str().into());
+    }
+    if let Some(message) = &doc.message {
+        map.insert("message".into(), message.as_str().into());
+    }
+    if let Some(rule) = &doc.rule {
+        map.insert("rule".into(), rule.clone());
+    }
+    if let Some(constraints) = &doc.constraints {
+        let constraints = constraints
+            .iter()
+            .map(|(k, v)| (k.as_str().into(), v.clone()))
+            .collect();
+        map.insert("constraints".into(), Value::Mapping(constraints));
+    }
+    Value::Mapping(map)
+}
+
+/// Lay out an `sg test` project: one rule file and one test file per rule.
+fn write_sg_project(dir: &Path, project: &[(&str, Value, &RuleFixtures)]) -> Result<(), String> {
+    let write = |path: PathBuf, value: &Value| {
+        let yaml = serde_yaml::to_string(value).map_err(|e| e.to_string())?;
+        std::fs::write(&path, yaml).map_err(|e| format!("{}: {}", path.display(), e))
+    };
+    for sub in ["rules", "rule-tests"] {
+        std::fs::create_dir_all(dir.join(sub)).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(
+        dir.join("sgconfig.yml"),
+        "ruleDirs:\n  - rules\ntestConfigs:\n  - testDir: rule-tests\n",
+    )
+    .map_err(|e| e.to_string())?;
+    for (i, (id, doc, fixtures)) in project.iter().enumerate() {
+        write(dir.join(format!("rules/rule-{i}.yml")), doc)?;
+        let mut test = Mapping::new();
+        test.insert("id".into(), (*id).into());
+        test.insert("valid".into(), fixtures.valid.clone().into());
+        test.insert("invalid".into(), fixtures.invalid.clone().into());
+        write(
+            dir.join(format!("rule-tests/rule-{i}-test.yml")),
+            &Value::Mapping(test),
+        )?;
+    }
+    Ok(())
+}
+
+/// Turn `sg test` output into failures keyed by rule ID.
+///
+/// Each rule gets a `PASS <id> <cases>` or `FAIL <id> <cases>` line, with
+/// one status character per case, valid cases first: `.` passed, `N` a
+/// valid case matched (noisy), `M` an invalid case was missed, `E` an
+/// error. Returns `None` unless every rule in `cases` was reported.
+fn parse_sg_test(
+    output: &str,
+    cases: &[(&str, &RuleFixtures)],
+) -> Option<BTreeMap<String, Vec<RuleTestFailure>>> {
+    let mut statuses: HashMap<&str, (bool, &str)> = HashMap::new();
+    for line in output.lines() {
+        let mut parts = line.split_whitespace();
+        let passed = match parts.next() {
+            Some("PASS") => true,
+            Some("FAIL") => false,
+            _ => continue,
+        };
+        if let Some(id) = parts.next() {
+            statuses.insert(id, (passed, parts.next().unwrap_or("")));
+        }
+    }
+
+    let mut results = BTreeMap::new();
+    for (id, fixtures) in cases {
+        let (passed, status) = *statuses.get(id)?;
+        let mut failures = Vec::new();
+        let failure = |kind, snippet: Option<&String>, detail: &str| RuleTestFailure {
+            rule_id: id.to_string(),
+            kind,
+            snippet: snippet.cloned(),
+            detail: format!("sg test: {detail}"),
+        };
+        let snippets = fixtures.valid.iter().chain(&fixtures.invalid);
+        for (c, snippet) in status.chars().zip(snippets) {
+            match c {
+                'N' => failures.push(failure(
+                    RuleTestFailureKind::MatchedValid,
+                    Some(snippet),
+                    "rule matched valid snippet",
+                )),
+                'M' => failures.push(failure(
+                    RuleTestFailureKind::MissedInvalid,
+                    Some(snippet),
+                    "rule did not match invalid snippet",
+                )),
+                'E' => failures.push(failure(
+                    RuleTestFailureKind::PatternError,
+                    Some(snippet),
+                    "rule failed to run",
+                )),
+                _ => {}
+            }
+        }
+        if !passed && failures.is_empty() {
+            failures.push(failure(
+                RuleTestFailureKind::PatternError,
+                None,
+                &format!("FAIL {id} {status}"),
+            ));
+        }
+        results.insert(id.to_string(), failures);
+    }
+    Some(results)
+}
+
+/// Run `search` over each fixture snippet and collect the cases that fail.
+///
+/// `search` returns the matches for `(file label, snippet)`, or an error if
+/// the rule could not run at all, which stops the check.
+fn check_fixtures(
+    rule_id: &str,
+    describe: &str,
+    fixtures: &RuleFixtures,
+    search: impl Fn(&str, &str) -> Result<Vec<AstGrepMatch>, String>,
+) -> Vec<RuleTestFailure> {
+    let failure = |kind, snippet: &str, detail: String| RuleTestFailure {
+        rule_id: rule_id.to_string(),
+        kind,
+        snippet: Some(snippet.to_string()),
+        detail,
+    };
+
+    let mut failures = Vec::new();
+    for snippet in &fixtures.invalid {
+        match search("invalid", snippet) {
+            Err(error) => {
+                failures.push(failure(RuleTestFailureKind::PatternError, snippet, error));
+                return failures;
+            }
+            Ok(matches) if matches.is_empty() => failures.push(failure(
+                RuleTestFailureKind::MissedInvalid,
+                snippet,
+                format!("{describe} did not match invalid snippet"),
+            )),
+            Ok(_) => {}
+        }
+    }
+    for snippet in &fixtures.valid {
+        match search("valid", snippet) {
+            Err(error) => {
+                failures.push(failure(RuleTestFailureKind::PatternError, snippet, error));
+                return failures;
+            }
+            Ok(matches) => {
+                if let Some(m) = matches.first() {
+                    failures.push(failure(
+                        RuleTestFailureKind::MatchedValid,
+                        snippet,
+                        format!("{describe} matched valid snippet at `{}`", m.text),
+                    ));
+                }
+            }
+        }
+    }
+    failures
+}
+
+impl Default for RuleTestRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reviewer_tools::rule_pack::{RulePack, RuleSeverity};
+
+    fn registry_with(rule: RulePackEntry) -> RulePackRegistry {
+        let mut registry = RulePackRegistry::new();
+        let mut pack = RulePack::new("test", "Test pack");
+        pack.add_rule(rule);
+        registry.register(pack);
+        registry
+    }
+
+    /// Runner pinned to the in-process checks, whatever is on `PATH`
+    fn in_process() -> RuleTestRunner {
+        RuleTestRunner::new().with_ast_grep(AstGrepConfig {
+            binary: "/nonexistent/sg".to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn unwrap_rule() -> RulePackEntry {
+        RulePackEntry::pattern_rule(
+            "no-unwrap",
+            "Avoid unwrap",
+            RuleSeverity::Error,
+            "rust",
+            "$EXPR.unwrap()",
+            "safety",
+        )
+    }
+
+    #[test]
+    fn test_default_rule_fixtures_pass() {
+        let registry = RulePackRegistry::with_defaults();
+        let report = RuleTestRunner::new().run(&registry);
+        assert!(
+            report.all_ok(),
+            "{}",
+            report
+                .failures
+                .iter()
+                .map(|f| f.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+        assert_eq!(report.passed.len(), registry.total_enabled());
+        assert!(report.untested.is_empty());
+    }
+
+    #[test]
+    fn test_bad_pattern_reports_missed_invalid() {
+        let rule = RulePackEntry::pattern_rule(
+            "typo",
+            "Misspelled method",
+            RuleSeverity::Error,
+            "rust",
+            "$EXPR.unwarp()",
+            "safety",
+        )
+        .with_fixtures(&[], &["let v = opt.unwrap();"]);
+        let report = in_process().run(&registry_with(rule));
+        assert!(!report.all_ok());
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].kind, RuleTestFailureKind::MissedInvalid);
+        assert_eq!(
+            report.failures[0].snippet.as_deref(),
+            Some("let v = opt.unwrap();")
+        );
+    }
+
+    #[test]
+    fn test_matched_valid_and_pattern_error() {
+        let rule = unwrap_rule().with_fixtures(&["let v = a.unwrap();"], &[]);
+        let report = in_process().run(&registry_with(rule));
+        assert_eq!(report.failures[0].kind, RuleTestFailureKind::MatchedValid);
+        assert!(report.failures[0].detail.contains("a.unwrap()"));
+
+        let rule = RulePackEntry::pattern_rule(
+            "cobol",
+            "Unsupported language",
+            RuleSeverity::Info,
+            "cobol",
+            "$X",
+            "misc",
+        )
+        .with_fixtures(&["a"], &["b"]);
+        let report = in_process().run(&registry_with(rule));
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].kind, RuleTestFailureKind::PatternError);
+    }
+
+    #[test]
+    fn test_untested_rules() {
+        let mut registry = registry_with(unwrap_rule());
+        let mut pack = RulePack::new("files", "File rules");
+        pack.add_rule(RulePackEntry::file_rule(
+            "custom",
+            "Custom",
+            RuleSeverity::Warning,
+            "rust",
+            "rules/custom.yml",
+            "custom",
+        ));
+        registry.register(pack);
+        let report = RuleTestRunner::new().run(&registry);
+        assert!(report.all_ok());
+        assert_eq!(report.untested, vec!["custom", "no-unwrap"]);
+        assert_eq!(report.total_cases, 0);
+    }
+
+    #[test]
+    fn test_rule_file_fixtures() {
+        let dir = tempfile::tempdir().unwrap();
+        let rules = dir.path().join("async.yml");
+        std::fs::write(
+            &rules,
+            "id: lock-in-async\nlanguage: Rust\nrule:\n  pattern: $M.lock()\n  inside:\n    kind: function_item\n    regex: '^async'\n    stopBy: end\n\
+             ---\nid: broken\nlanguage: Rust\nrule:\n  kind: not_a_kind\n",
+        )
+        .unwrap();
+        let mut registry = RulePackRegistry::new();
+        registry.load_ast_grep_directory(dir.path()).unwrap();
+        let mut pack = RulePack::new("extra", "Extra rules");
+        pack.add_rule(
+            RulePackEntry::file_rule(
+                "missing",
+                "Missing file",
+                RuleSeverity::Info,
+                "rust",
+                &dir.path().join("missing.yml").display().to_string(),
+                "custom",
+            )
+            .with_fixtures(&[], &["x"]),
+        );
+        registry.register(pack);
+
+        let tests = dir.path().join("rule-tests");
+        std::fs::create_dir(&tests).unwrap();
+        std::fs::write(
+            tests.join("async-test.yml"),
+            "id: lock-in-async\nvalid:\n  - \"fn f() { m.lock(); }\"\ninvalid:\n  - \"async fn f() { m.lock(); }\"\n\
+             ---\nid: broken\ninvalid:\n  - \"fn f() {}\"\n",
+        )
+        .unwrap();
+        let report = in_process().with_fixture_dir(&tests).run(&registry);
+
+        assert_eq!(report.passed, vec!["lock-in-async"]);
+        assert!(report.untested.is_empty());
+        let kinds: Vec<_> = report
+            .failures
+            .iter()
+            .map(|f| (f.rule_id.as_str(), f.kind))
+            .collect();
+        assert!(kinds.contains(&("broken", RuleTestFailureKind::PatternError)));
+        assert!(kinds.contains(&("missing", RuleTestFailureKind::PatternError)));
+    }
+
+    #[test]
+    fn test_repo_ast_grep_rules() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../rules/ast-grep");
+        let mut registry = RulePackRegistry::new();
+        let summary = registry
+            .load_ast_grep_directory(&root.join("rules"))
+            .unwrap();
+        assert!(summary.all_ok(), "{:?}", summary.errors);
+
+        let report = in_process()
+            .with_fixture_dir(&root.join("rule-tests"))
+            .run(&registry);
+        assert!(
+            report.all_ok(),
+            "{}",
+            report
+                .failures
+                .iter()
+                .map(|f| format!("{f} ({:?})", f.snippet))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+        assert!(
+            report.untested.is_empty(),
+            "untested: {:?}",
+            report.untested
+        );
+        assert_eq!(report.passed.len(), registry.total_enabled());
+    }
+
+    #[test]
+    fn test_sidecar_fixtures() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("no-unwrap-test.yml"),
+            "id: no-unwrap\nvalid:\n  - \"let v = opt?;\"\ninvalid:\n  - \"let v = opt.unwrap();\"\n\
+             ---\nid: ghost\ninvalid:\n  - \"x\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("broken.yml"), "id: [unterminated\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let report = in_process()
+            .with_fixture_dir(dir.path())
+            .run(&registry_with(unwrap_rule()));
+        assert_eq!(report.passed, vec!["no-unwrap"]);
+        assert_eq!(report.total_cases, 2);
+        assert_eq!(report.failures.len(), 2);
+        assert!(report
+            .failures
+            .iter()
+            .all(|f| f.kind == RuleTestFailureKind::FixtureError));
+        assert!(report.failures.iter().any(|f| f.rule_id == "ghost"));
+        assert!(report
+            .summary_line()
+            .starts_with("1 rules passed, 2 failures"));
+    }
+
+    #[test]
+    fn test_fixtures_serde_round_trip() {
+        let rule = unwrap_rule().with_fixtures(&["a?"], &["a.unwrap()"]);
+        let yaml = serde_yaml::to_string(&rule).unwrap();
+        let parsed: RulePackEntry = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed.fixtures, rule.fixtures);
+
+        let bare = serde_json::to_string(&unwrap_rule()).unwrap();
+        assert!(!bare.contains("fixtures"));
+        let parsed: RulePackEntry = serde_json::from_str(&bare).unwrap();
+        assert!(parsed.fixtures.is_empty());
+    }
+
+    #[test]
+    fn test_parse_sg_test_output() {
+        let fixtures = RuleFixtures {
+            valid: vec!["ok()".to_string(), "fine()".to_string()],
+            invalid: vec!["bad()".to_string()],
+        };
+        let output = "Running 2 tests\n\
+                      PASS no-unwrap ...\n\
+                      FAIL no-expect .NM\n\
+                      ----------- Failure Details -----------\n";
+        let cases = [("no-unwrap", &fixtures), ("no-expect", &fixtures)];
+        let results = parse_sg_test(output, &cases).unwrap();
+        assert!(results["no-unwrap"].is_empty());
+        let failures = &results["no-expect"];
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].kind, RuleTestFailureKind::MatchedValid);
+        assert_eq!(failures[0].snippet.as_deref(), Some("fine()"));
+        assert_eq!(failures[1].kind, RuleTestFailureKind::MissedInvalid);
+        assert_eq!(failures[1].snippet.as_deref(), Some("bad()"));
+
+        // A rule sg did not report on means the run cannot be trusted
+        assert!(parse_sg_test("PASS no-unwrap ...\n", &cases).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_runs_sg_test_when_ast_grep_is_installed() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let fake = |name: &str, body: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+            AstGrepConfig {
+                binary: path.display().to_string(),
+                timeout_ms: 5_000,
+                ..Default::default()
+            }
+        };
+        let rule = unwrap_rule().with_fixtures(&["let v = opt?;"], &["let v = opt.unwrap();"]);
+
+        // Reports the noisy valid case only if it was given the project layout
+        let ast_grep = fake(
+            "sg",
+            r#"case "$1" in
+  --version) echo "ast-grep 0.39.0" ;;
+  test) grep -q 'id: no-unwrap' rule-tests/rule-0-test.yml \
+          && grep -q 'pattern: \$EXPR.unwrap()' rules/rule-0.yml \
+          && echo "FAIL no-unwrap N." ;;
+esac"#,
+        );
+        let report = RuleTestRunner::new()
+            .with_ast_grep(ast_grep)
+            .run(&registry_with(rule.clone()));
+        assert_eq!(report.failures.len(), 1, "{:?}", report.failures);
+        assert_eq!(report.failures[0].kind, RuleTestFailureKind::MatchedValid);
+        assert!(report.failures[0].detail.starts_with("sg test:"));
+        assert_eq!(report.total_cases, 2);
+
+        // shadow-utils `sg` is not ast-grep: fall back to in-process checks
+        let switch_group = fake("sg-group", "echo 'Usage: sg group [[-c] command]'");
+        let report = RuleTestRunner::new()
+            .with_ast_grep(switch_group)
+            .run(&registry_with(rule));
+        assert!(report.all_ok(), "{:?}", report.failures);
+        assert_eq!(report.passed, vec!["no-unwrap"]);
+    }
+}
@@ -0,0 +1,849 @@
+//! sgconfig export — generate ast-grep project config from the rule registry.
+//!
+//! Keeps `sgconfig.yml` and the per-rule YAML files under its `ruleDirs` in
+//! sync with [`RulePackRegistry`]. The exporter writes one rule file per
+//! pattern-based entry; the drift checker compares the registry against the
+//! rule files currently on disk and reports what disagrees.
+
+use super::rule_config::load_rule_docs;
+use super::rule_pack::{
+    yaml_files, IngestionErrorKind, RuleIngestionError, RulePackEntry, RulePackRegistry,
+    RuleSeverity,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// File name of the ast-grep project config.
+pub const SGCONFIG_FILE: &str = "sgconfig.yml";
+
+/// Default rule directory, relative to the project root.
+pub const DEFAULT_RULES_DIR: &str = "rules/ast-grep/rules";
+
+/// Which registry rules to export or compare.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportFilter {
+    /// Languages to include (empty = all). Compared case-insensitively.
+    pub languages: Vec<String>,
+    /// Least severe level to include (`Warning` keeps errors and warnings).
+    pub min_severity: Option<RuleSeverity>,
+}
+
+impl ExportFilter {
+    /// Restrict to the given languages.
+    pub fn with_languages(mut self, languages: &[&str]) -> Self {
+        self.languages = languages.iter().map(|l| l.to_lowercase()).collect();
+        self
+    }
+
+    /// Drop rules less severe than `severity`.
+    pub fn with_min_severity(mut self, severity: RuleSeverity) -> Self {
+        self.min_severity = Some(severity);
+        self
+    }
+
+    fn matches_language(&self, language: &str) -> bool {
+        self.languages.is_empty()
+            || self
+                .languages
+                .iter()
+                .any(|l| l.eq_ignore_ascii_case(language))
+    }
+
+    fn matches_severity(&self, severity: RuleSeverity) -> bool {
+        self.min_severity.is_none_or(|min| severity <= min)
+    }
+
+    /// Whether an enabled registry entry passes this filter.
+    pub fn matches(&self, rule: &RulePackEntry) -> bool {
+        rule.enabled
+            && self.matches_language(&rule.language)
+            && self.matches_severity(rule.severity)
+    }
+}
+
+/// Top-level `sgconfig.yml` structure.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SgConfigFile {
+    /// Directories scanned for rule files, relative to the config.
+    #[serde(rename = "ruleDirs", default)]
+    pub rule_dirs: Vec<String>,
+    /// Keys the exporter does not manage (`testConfigs`, `utilDirs`, ...),
+    /// kept so that re-exporting over a hand-written config preserves them.
+    #[serde(flatten)]
+    pub extra: serde_yaml::Mapping,
+}
+
+/// Rule matcher for a generated rule file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SgRuleMatcher {
+    /// ast-grep pattern.
+    pub pattern: String,
+}
+
+/// A generated ast-grep rule file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SgRuleFile {
+    /// Rule ID (matches [`RulePackEntry::rule_id`]).
+    pub id: String,
+    /// Target language.
+    pub language: String,
+    /// ast-grep severity (`error`, `warning`, `info`).
+    pub severity: String,
+    /// Message shown on match.
+    pub message: String,
+    /// Pack and category the rule came from.
+    pub metadata: BTreeMap<String, String>,
+    /// Matcher body.
+    pub rule: SgRuleMatcher,
+}
+
+/// A rule not exported because another rule file already declares its ID.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdConflict {
+    /// Rule ID.
+    pub rule_id: String,
+    /// Existing rule file declaring the ID.
+    pub source_path: String,
+}
+
+/// Result of writing the config and rule files.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportSummary {
+    /// Paths written, relative to the export root.
+    pub written: Vec<String>,
+    /// Rules skipped because they have no inline pattern (rule-file-based).
+    pub skipped: Vec<String>,
+    /// Rules skipped because a different rule file on disk already has the ID.
+    pub conflicts: Vec<IdConflict>,
+    /// Rules rejected (IDs that are not plain file names) and rule files
+    /// that could not be indexed.
+    pub errors: Vec<RuleIngestionError>,
+    /// [`RulePackRegistry::version_hash`] at export time.
+    pub version_hash: u64,
+}
+
+/// A rule whose severity differs between the registry and disk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeverityMismatch {
+    /// Rule ID.
+    pub rule_id: String,
+    /// Severity in the registry.
+    pub registry: RuleSeverity,
+    /// Raw severity string in the rule file.
+    pub on_disk: String,
+    /// Rule file the on-disk severity came from.
+    pub source_path: String,
+}
+
+/// Differences between the registry and the rule files on disk.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleDrift {
+    /// [`RulePackRegistry::version_hash`] the report was computed against.
+    pub version_hash: u64,
+    /// Rule IDs in the registry with no rule file on disk.
+    pub only_in_registry: Vec<String>,
+    /// Rule IDs on disk that the registry does not know.
+    pub only_on_disk: Vec<String>,
+    /// Rules present in both places with different severities.
+    pub severity_mismatches: Vec<SeverityMismatch>,
+    /// Rule files that could not be read or parsed.
+    pub errors: Vec<RuleIngestionError>,
+}
+
+impl RuleDrift {
+    /// Whether the registry and disk agree completely.
+    pub fn is_clean(&self) -> bool {
+        self.only_in_registry.is_empty()
+            && self.only_on_disk.is_empty()
+            && self.severity_mismatches.is_empty()
+            && self.errors.is_empty()
+    }
+
+    /// One-line summary for logs and reviewer output.
+    pub fn summary_line(&self) -> String {
+        if self.is_clean() {
+            return format!("rules in sync (registry {:016x})", self.version_hash);
+        }
+        format!(
+            "rule drift (registry {:016x}): {} registry-only, {} disk-only, {} severity mismatches, {} errors",
+            self.version_hash,
+            self.only_in_registry.len(),
+            self.only_on_disk.len(),
+            self.severity_mismatches.len(),
+            self.errors.len()
+        )
+    }
+}
+
+/// Map a registry severity to the ast-grep severity string.
+pub fn sg_severity(severity: RuleSeverity) -> &'static str {
+    match severity {
+        RuleSeverity::Error => "error",
+        RuleSeverity::Warning => "warning",
+        RuleSeverity::Info => "info",
+    }
+}
+
+/// Parse an ast-grep severity string (`hint` is treated as `info`).
+pub fn parse_sg_severity(severity: &str) -> Option<RuleSeverity> {
+    match severity.to_ascii_lowercase().as_str() {
+        "error" => Some(RuleSeverity::Error),
+        "warning" => Some(RuleSeverity::Warning),
+        "info" | "hint" => Some(RuleSeverity::Info),
+        _ => None,
+    }
+}
+
+/// Whether a rule ID can be used as a file name inside the rule directory.
+fn is_file_name_safe(rule_id: &str) -> bool {
+    !rule_id.is_empty()
+        && !rule_id.contains("..")
+        && !rule_id.contains(['/', '\\'])
+        && !rule_id.chars().any(std::path::is_separator)
+}
+
+/// Minimal view of an on-disk rule document used for drift checks.
+#[derive(Debug, Deserialize)]
+struct DiskRule {
+    id: String,
+    #[serde(default)]
+    language: String,
+    #[serde(default)]
+    severity: Option<String>,
+}
+
+fn io_error(path: &Path, detail: String) -> RuleIngestionError {
+    RuleIngestionError {
+        source_path: Some(path.display().to_string()),
+        kind: IngestionErrorKind::IoError,
+        detail,
+    }
+}
+
+fn serialize_error(detail: String) -> RuleIngestionError {
+    RuleIngestionError {
+        source_path: None,
+        kind: IngestionErrorKind::SerializeError,
+        detail,
+    }
+}
+
+/// Generates `sgconfig.yml` and rule files, and checks them for drift.
+pub struct SgConfigExporter {
+    rules_dir: String,
+    filter: ExportFilter,
+}
+
+impl SgConfigExporter {
+    /// Create an exporter writing to [`DEFAULT_RULES_DIR`] with no filter.
+    pub fn new() -> Self {
+        Self {
+            rules_dir: DEFAULT_RULES_DIR.to_string(),
+            filter: ExportFilter::default(),
+        }
+    }
+
+    /// Set the rule directory (relative to the export root).
+    pub fn with_rules_dir(mut self, rules_dir: &str) -> Self {
+        self.rules_dir = rules_dir.to_string();
+        self
+    }
+
+    /// Set the language/severity filter.
+    pub fn with_filter(mut self, filter: ExportFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Registry rules selected by the filter, ordered by pack then rule.
+    ///
+    /// When several packs define the same rule ID, the first pack (by name)
+    /// wins.
+    pub fn selected_rules<'a>(
+        &self,
+        registry: &'a RulePackRegistry,
+    ) -> Vec<(&'a str, &'a RulePackEntry)> {
+        let mut seen = HashSet::new();
+        let mut rules = Vec::new();
+        for name in registry.pack_names() {
+            let Some(pack) = registry.get(name) else {
+                continue;
+            };
+            for rule in &pack.rules {
+                if self.filter.matches(rule) && seen.insert(rule.rule_id.as_str()) {
+                    rules.push((pack.name.as_str(), rule));
+                }
+            }
+        }
+        rules
+    }
+
+    fn header(version_hash: u64) -> String {
+        format!(
+            "# Generated from RulePackRegistry (version {version_hash:016x}).\n\
+             # Do not edit by hand — re-run the exporter instead.\n"
+        )
+    }
+
+    /// Render `sgconfig.yml`.
+    pub fn render_sgconfig(
+        &self,
+        registry: &RulePackRegistry,
+    ) -> Result<String, RuleIngestionError> {
+        let config = SgConfigFile {
+            rule_dirs: vec![self.rules_dir.clone()],
+            ..Default::default()
+        };
+        let body = serde_yaml::to_string(&config)
+            .map_err(|e| serialize_error(format!("YAML serialize error: {}", e)))?;
+        Ok(Self::header(registry.version_hash()) + &body)
+    }
+
+    /// Content to write to an existing `sgconfig.yml` at `path`, merged with
+    /// what is already there (`None` if the file needs no change).
+    ///
+    /// Unknown keys and a leading hand-written comment block are kept; a
+    /// config the exporter generated itself gets a fresh header.
+    fn merged_sgconfig(
+        &self,
+        registry: &RulePackRegistry,
+        path: &Path,
+    ) -> Result<Option<String>, RuleIngestionError> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return self.render_sgconfig(registry).map(Some)
+            }
+            Err(e) => return Err(io_error(path, format!("Failed to read file: {}", e))),
+        };
+        let mut config: SgConfigFile = if content.trim().is_empty() {
+            SgConfigFile::default()
+        } else {
+            serde_yaml::from_str(&content).map_err(|e| RuleIngestionError {
+                source_path: Some(path.display().to_string()),
+                kind: IngestionErrorKind::ParseError,
+                detail: format!("YAML parse error: {}", e),
+            })?
+        };
+
+        let comments: String = content
+            .lines()
+            .take_while(|line| line.starts_with('#'))
+            .map(|line| format!("{line}\n"))
+            .collect();
+        let generated = comments.starts_with("# Generated from RulePackRegistry");
+        let wanted = self.rules_dir.trim_end_matches('/');
+        let listed = config
+            .rule_dirs
+            .iter()
+            .any(|dir| dir.trim_end_matches('/') == wanted);
+        if listed && !generated {
+            return Ok(None);
+        }
+        if !listed {
+            config.rule_dirs.push(self.rules_dir.clone());
+        }
+
+        let body = serde_yaml::to_string(&config)
+            .map_err(|e| serialize_error(format!("YAML serialize error: {}", e)))?;
+        let header = if generated {
+            Self::header(registry.version_hash())
+        } else {
+            comments
+        };
+        Ok(Some(header + &body))
+    }
+
+    /// Build the rule file for a pattern-based entry (`None` for file rules).
+    pub fn rule_file(pack: &str, rule: &RulePackEntry) -> Option<SgRuleFile> {
+        let pattern = rule.pattern.as_ref()?;
+        let mut metadata = BTreeMap::new();
+        metadata.insert("pack".to_string(), pack.to_string());
+        metadata.insert("category".to_string(), rule.category.clone());
+        Some(SgRuleFile {
+            id: rule.rule_id.clone(),
+            language: rule.language.clone(),
+            severity: sg_severity(rule.severity).to_string(),
+            message: rule.description.clone(),
+            metadata,
+            rule: SgRuleMatcher {
+                pattern: pattern.clone(),
+            },
+        })
+    }
+
+    /// Render a rule file to YAML with the generated-file header.
+    pub fn render_rule(
+        &self,
+        registry: &RulePackRegistry,
+        rule: &SgRuleFile,
+    ) -> Result<String, RuleIngestionError> {
+        let body = serde_yaml::to_string(rule)
+            .map_err(|e| serialize_error(format!("YAML serialize error: {}", e)))?;
+        Ok(Self::header(registry.version_hash()) + &body)
+    }
+
+    /// Write `sgconfig.yml` and one `<rule_id>.yml` per selected rule under `root`.
+    ///
+    /// An existing `sgconfig.yml` is merged rather than replaced: the rule
+    /// directory is added to its `ruleDirs` and its other keys are kept.
+    /// Existing rule files for other IDs are left alone; use
+    /// [`check_drift`](Self::check_drift) to find them. A rule whose ID is
+    /// already declared by another file in the rule directory (including
+    /// later documents of a multi-document file) is reported in
+    /// [`ExportSummary::conflicts`] instead of being written twice, and IDs
+    /// that are not plain file names are rejected.
+    pub fn export(
+        &self,
+        registry: &RulePackRegistry,
+        root: &Path,
+    ) -> Result<ExportSummary, RuleIngestionError> {
+        let rules_dir = root.join(&self.rules_dir);
+        std::fs::create_dir_all(&rules_dir)
+            .map_err(|e| io_error(&rules_dir, format!("Failed to create directory: {}", e)))?;
+
+        let mut summary = ExportSummary {
+            version_hash: registry.version_hash(),
+            ..Default::default()
+        };
+
+        let config_path = root.join(SGCONFIG_FILE);
+        if let Some(config) = self.merged_sgconfig(registry, &config_path)? {
+            std::fs::write(&config_path, config)
+                .map_err(|e| io_error(&config_path, format!("Failed to write file: {}", e)))?;
+            summary.written.push(SGCONFIG_FILE.to_string());
+        }
+
+        let on_disk = Self::index_ids(&rules_dir, &mut summary.errors);
+
+        for (pack, rule) in self.selected_rules(registry) {
+            let Some(file) = Self::rule_file(pack, rule) else {
+                summary.skipped.push(rule.rule_id.clone());
+                continue;
+            };
+            if !is_file_name_safe(&rule.rule_id) {
+                summary.errors.push(RuleIngestionError {
+                    source_path: None,
+                    kind: IngestionErrorKind::ParseError,
+                    detail: format!("rule ID `{}` is not a valid file name", rule.rule_id),
+                });
+                continue;
+            }
+            let file_name = format!("{}.yml", rule.rule_id);
+            let path = rules_dir.join(&file_name);
+            let existing = on_disk.get(&rule.rule_id).into_iter().flatten();
+            if let Some(other) = existing.into_iter().find(|p| **p != path) {
+                summary.conflicts.push(IdConflict {
+                    rule_id: rule.rule_id.clone(),
+                    source_path: other.display().to_string(),
+                });
+                continue;
+            }
+            std::fs::write(&path, self.render_rule(registry, &file)?)
+                .map_err(|e| io_error(&path, format!("Failed to write file: {}", e)))?;
+            summary.written.push(format!(
+                "{}/{}",
+                self.rules_dir.trim_end_matches('/'),
+                file_name
+            ));
+        }
+
+        Ok(summary)
+    }
+
+    /// Compare the registry against the rule files referenced by `root/sgconfig.yml`.
+    ///
+    /// Only rules passing the exporter's filter are compared on either side.
+    /// Multi-document rule files are supported; unreadable files are
+    /// collected in [`RuleDrift::errors`] rather than aborting the check.
+    pub fn check_drift(
+        &self,
+        registry: &RulePackRegistry,
+        root: &Path,
+    ) -> Result<RuleDrift, RuleIngestionError> {
+        let config_path = root.join(SGCONFIG_FILE);
+        let content = std::fs::read_to_string(&config_path)
+            .map_err(|e| io_error(&config_path, format!("Failed to read file: {}", e)))?;
+        let config: SgConfigFile =
+            serde_yaml::from_str(&content).map_err(|e| RuleIngestionError {
+                source_path: Some(config_path.display().to_string()),
+                kind: IngestionErrorKind::ParseError,
+                detail: format!("YAML parse error: {}", e),
+            })?;
+
+        let mut drift = RuleDrift {
+            version_hash: registry.version_hash(),
+            ..Default::default()
+        };
+
+        let mut on_disk: BTreeMap<String, (Option<String>, String)> = BTreeMap::new();
+        for dir in &config.rule_dirs {
+            for path in Self::rule_paths(&root.join(dir), &mut drift.errors) {
+                Self::read_disk_rules(&path, &self.filter, &mut on_disk, &mut drift.errors);
+            }
+        }
+
+        let mut registry_ids = HashSet::new();
+        for (_, rule) in self.selected_rules(registry) {
+            registry_ids.insert(rule.rule_id.clone());
+            match on_disk.get(&rule.rule_id) {
+                None => drift.only_in_registry.push(rule.rule_id.clone()),
+                Some((severity, source_path)) => {
+                    let raw = severity.clone().unwrap_or_default();
+                    if parse_sg_severity(&raw) != Some(rule.severity) {
+                        drift.severity_mismatches.push(SeverityMismatch {
+                            rule_id: rule.rule_id.clone(),
+                            registry: rule.severity,
+                            on_disk: raw,
+                            source_path: source_path.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        drift.only_on_disk = on_disk
+            .keys()
+            .filter(|id| !registry_ids.contains(*id))
+            .cloned()
+            .collect();
+        drift.only_in_registry.sort();
+
+        Ok(drift)
+    }
+
+    /// Every rule ID declared in a rule directory, with the files declaring it.
+    fn index_ids(
+        dir: &Path,
+        errors: &mut Vec<RuleIngestionError>,
+    ) -> BTreeMap<String, Vec<PathBuf>> {
+        let mut ids: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+        for path in Self::rule_paths(dir, errors) {
+            match load_rule_docs(&path) {
+                Ok(docs) => {
+                    for doc in docs {
+                        ids.entry(doc.id).or_default().push(path.clone());
+                    }
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+        ids
+    }
+
+    /// `*.yml` / `*.yaml` files in a rule directory, sorted.
+    fn rule_paths(dir: &Path, errors: &mut Vec<RuleIngestionError>) -> Vec<PathBuf> {
+        yaml_files(dir).unwrap_or_else(|e| {
+            errors.push(io_error(dir, format!("Failed to read directory: {}", e)));
+            Vec::new()
+        })
+    }
+
+    fn read_disk_rules(
+        path: &Path,
+        filter: &ExportFilter,
+        on_disk: &mut BTreeMap<String, (Option<String>, String)>,
+        errors: &mut Vec<RuleIngestionError>,
+    ) {
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => {
+                errors.push(io_error(path, format!("Failed to read file: {}", e)));
+                return;
+            }
+        };
+        for document in serde_yaml::Deserializer::from_str(&content) {
+            match DiskRule::deserialize(document) {
+                Ok(rule) => {
+                    if !filter.matches_language(&rule.language) {
+                        continue;
+                    }
+                    let severity = rule.severity.as_deref().and_then(parse_sg_severity);
+                    if severity.is_some_and(|s| !filter.matches_severity(s)) {
+                        continue;
+                    }
+                    on_disk
+                        .entry(rule.id)
+                        .or_insert((rule.severity, path.display().to_string()));
+                }
+                Err(e) => {
+                    errors.push(RuleIngestionError {
+                        source_path: Some(path.display().to_string()),
+                        kind: IngestionErrorKind::ParseError,
+                        detail: format!("YAML parse error: {}", e),
+                    });
+                    // The document stream cannot resync after a syntax error.
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl Default for SgConfigExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reviewer_tools::rule_pack::RulePack;
+
+    fn small_registry() -> RulePackRegistry {
+        let mut registry = RulePackRegistry::new();
+        let mut pack = RulePack::new("mixed", "Mixed rules");
+        pack.add_rule(RulePackEntry::pattern_rule(
+            "no-unwrap",
+            "Avoid unwrap",
+            RuleSeverity::Error,
+            "rust",
+            "$EXPR.unwrap()",
+            "safety",
+        ));
+        pack.add_rule(RulePackEntry::pattern_rule(
+            "no-print",
+            "Use logging",
+            RuleSeverity::Info,
+            "python",
+            "print($$$ARGS)",
+            "style",
+        ));
+        pack.add_rule(RulePackEntry::file_rule(
+            "custom",
+            "Custom rule file",
+            RuleSeverity::Warning,
+            "rust",
+            "rules/custom.yml",
+            "custom",
+        ));
+        registry.register(pack);
+        registry
+    }
+
+    #[test]
+    fn test_severity_round_trip() {
+        for s in [
+            RuleSeverity::Error,
+            RuleSeverity::Warning,
+            RuleSeverity::Info,
+        ] {
+            assert_eq!(parse_sg_severity(sg_severity(s)), Some(s));
+        }
+        assert_eq!(parse_sg_severity("hint"), Some(RuleSeverity::Info));
+        assert_eq!(parse_sg_severity("off"), None);
+    }
+
+    #[test]
+    fn test_filter() {
+        let registry = RulePackRegistry::with_defaults();
+        let all = SgConfigExporter::new().selected_rules(&registry);
+        assert_eq!(all.len(), registry.total_enabled());
+
+        let errors = SgConfigExporter::new()
+            .with_filter(ExportFilter::default().with_min_severity(RuleSeverity::Error))
+            .selected_rules(&registry);
+        assert_eq!(errors.len(), registry.blocking_rules().len());
+
+        let python = SgConfigExporter::new()
+            .with_filter(ExportFilter::default().with_languages(&["Python"]))
+            .selected_rules(&registry);
+        assert!(python.is_empty());
+    }
+
+    #[test]
+    fn test_render_rule() {
+        let registry = small_registry();
+        let exporter = SgConfigExporter::new();
+        let (pack, entry) = exporter.selected_rules(&registry)[0];
+        let file = SgConfigExporter::rule_file(pack, entry).unwrap();
+        let yaml = exporter.render_rule(&registry, &file).unwrap();
+        assert!(yaml.starts_with("# Generated from RulePackRegistry"));
+        assert!(yaml.contains(&format!("{:016x}", registry.version_hash())));
+        let parsed: SgRuleFile = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed.id, "no-unwrap");
+        assert_eq!(parsed.severity, "error");
+        assert_eq!(parsed.rule.pattern, "$EXPR.unwrap()");
+        assert_eq!(parsed.metadata["pack"], "mixed");
+    }
+
+    #[test]
+    fn test_export_writes_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = small_registry();
+        let summary = SgConfigExporter::new()
+            .with_filter(ExportFilter::default().with_languages(&["rust"]))
+            .export(&registry, dir.path())
+            .unwrap();
+
+        assert_eq!(
+            summary.written,
+            vec![
+                "sgconfig.yml".to_string(),
+                "rules/ast-grep/rules/no-unwrap.yml".to_string()
+            ]
+        );
+        assert_eq!(summary.skipped, vec!["custom".to_string()]);
+        assert_eq!(summary.version_hash, registry.version_hash());
+
+        let config: SgConfigFile =
+            serde_yaml::from_str(&std::fs::read_to_string(dir.path().join(SGCONFIG_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(config.rule_dirs, vec![DEFAULT_RULES_DIR.to_string()]);
+        assert!(dir
+            .path()
+            .join("rules/ast-grep/rules/no-unwrap.yml")
+            .exists());
+    }
+
+    #[test]
+    fn test_export_merges_existing_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join(SGCONFIG_FILE);
+        let original = "# Project ast-grep config\n\
+                        ruleDirs:\n  - rules/ast-grep/rules/\n\
+                        testConfigs:\n  - testDir: rules/ast-grep/rule-tests\n";
+        std::fs::write(&config_path, original).unwrap();
+        let registry = small_registry();
+
+        // Rule directory already listed: the config is left untouched
+        let summary = SgConfigExporter::new()
+            .export(&registry, dir.path())
+            .unwrap();
+        assert!(!summary.written.contains(&SGCONFIG_FILE.to_string()));
+        assert_eq!(std::fs::read_to_string(&config_path).unwrap(), original);
+
+        // New rule directory: appended, other keys and comments kept
+        let summary = SgConfigExporter::new()
+            .with_rules_dir("generated")
+            .export(&registry, dir.path())
+            .unwrap();
+        assert_eq!(summary.written[0], SGCONFIG_FILE);
+        let merged = std::fs::read_to_string(&config_path).unwrap();
+        assert!(merged.starts_with("# Project ast-grep config\n"));
+        let config: SgConfigFile = serde_yaml::from_str(&merged).unwrap();
+        assert_eq!(config.rule_dirs, vec!["rules/ast-grep/rules/", "generated"]);
+        assert_eq!(
+            config.extra["testConfigs"][0]["testDir"],
+            serde_yaml::Value::from("rules/ast-grep/rule-tests")
+        );
+    }
+
+    #[test]
+    fn test_export_skips_ids_declared_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let rules = dir.path().join("rules");
+        std::fs::create_dir_all(&rules).unwrap();
+        std::fs::write(
+            rules.join("no-panic-in-prod.yml"),
+            "id: no-panic-in-prod\nlanguage: Rust\nrule:\n  pattern: panic!($$$A)\n\
+             ---\nid: no-unimplemented\nlanguage: Rust\nrule:\n  pattern: unimplemented!($$$A)\n",
+        )
+        .unwrap();
+        let registry = RulePackRegistry::with_defaults();
+        let exporter = SgConfigExporter::new().with_rules_dir("rules");
+
+        for _ in 0..2 {
+            let summary = exporter.export(&registry, dir.path()).unwrap();
+            assert_eq!(
+                summary.conflicts,
+                vec![IdConflict {
+                    rule_id: "no-unimplemented".to_string(),
+                    source_path: rules.join("no-panic-in-prod.yml").display().to_string(),
+                }]
+            );
+            assert!(summary.errors.is_empty(), "{:?}", summary.errors);
+            // Re-exporting overwrites the exporter's own files without conflict
+            assert_eq!(summary.written.len(), registry.total_enabled());
+        }
+        assert!(!rules.join("no-unimplemented.yml").exists());
+        assert!(rules.join("no-unwrap.yml").exists());
+    }
+
+    #[test]
+    fn test_export_rejects_path_like_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut registry = RulePackRegistry::new();
+        let mut pack = RulePack::new("user", "User rules");
+        for id in ["../escape", "nested/rule", "ok-rule"] {
+            pack.add_rule(RulePackEntry::pattern_rule(
+                id,
+                "User rule",
+                RuleSeverity::Warning,
+                "rust",
+                "$X.unwrap()",
+                "safety",
+            ));
+        }
+        registry.register(pack);
+
+        let summary = SgConfigExporter::new()
+            .with_rules_dir("rules")
+            .export(&registry, dir.path())
+            .unwrap();
+        assert_eq!(summary.written, vec!["sgconfig.yml", "rules/ok-rule.yml"]);
+        assert_eq!(summary.errors.len(), 2);
+        assert!(summary.errors[0].detail.contains("`../escape`"));
+        assert!(!dir.path().join("escape.yml").exists());
+        assert!(!dir.path().join("rules/nested").exists());
+    }
+
+    #[test]
+    fn test_drift_clean_after_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = RulePackRegistry::with_defaults();
+        let exporter = SgConfigExporter::new().with_rules_dir("rules");
+        exporter.export(&registry, dir.path()).unwrap();
+        let drift = exporter.check_drift(&registry, dir.path()).unwrap();
+        assert!(drift.is_clean(), "{}", drift.summary_line());
+        assert_eq!(drift.version_hash, registry.version_hash());
+    }
+
+    #[test]
+    fn test_drift_reports_differences() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = small_registry();
+        let exporter = SgConfigExporter::new().with_rules_dir("rules");
+        exporter.export(&registry, dir.path()).unwrap();
+
+        let rules = dir.path().join("rules");
+        std::fs::write(
+            rules.join("no-unwrap.yml"),
+            "id: no-unwrap\nlanguage: Rust\nseverity: hint\nrule:\n  pattern: $X.unwrap()\n",
+        )
+        .unwrap();
+        std::fs::remove_file(rules.join("no-print.yml")).unwrap();
+        std::fs::write(
+            rules.join("extra.yml"),
+            "id: extra-a\nlanguage: rust\nseverity: warning\n---\nid: extra-b\nlanguage: rust\n",
+        )
+        .unwrap();
+        std::fs::write(rules.join("broken.yml"), "id: [unterminated\n").unwrap();
+
+        let drift = exporter.check_drift(&registry, dir.path()).unwrap();
+        assert!(!drift.is_clean());
+        assert_eq!(
+            drift.only_in_registry,
+            vec!["custom".to_string(), "no-print".to_string()]
+        );
+        assert_eq!(
+            drift.only_on_disk,
+            vec!["extra-a".to_string(), "extra-b".to_string()]
+        );
+        assert_eq!(drift.severity_mismatches.len(), 1);
+        assert_eq!(drift.severity_mismatches[0].rule_id, "no-unwrap");
+        assert_eq!(drift.severity_mismatches[0].on_disk, "hint");
+        assert_eq!(drift.errors.len(), 1);
+        assert!(drift.summary_line().contains("1 severity mismatches"));
+    }
+
+    #[test]
+    fn test_drift_missing_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = SgConfigExporter::new()
+            .check_drift(&RulePackRegistry::new(), dir.path())
+            .unwrap_err();
+        assert_eq!(err.kind, IngestionErrorKind::IoError);
+    }
+}
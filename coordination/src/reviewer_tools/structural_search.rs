@@ -0,0 +1,844 @@
+//! Structural search fallback tiers — native tree-sitter and regex search.
+//!
+//! Implements the `ast_grep → tree_sitter → regex_grep` chain sketched in
+//! [`crate::resilience`], so structural checks keep working when the `sg`
+//! binary is missing, times out, or rejects a pattern. Every tier returns
+//! the same [`AstGrepResult`] payload; the [`DegradedResponse`] wrapper
+//! reports which tier served it and with what confidence.
+//!
+//! # Patterns
+//!
+//! The tree-sitter tier accepts either a raw S-expression query
+//! (`(call_expression) @match`) or a simple ast-grep metavariable pattern
+//! (`$EXPR.unwrap()`), which is translated into an S-expression by parsing
+//! the pattern and turning `$X` into wildcards and `$$$X` into omitted
+//! children. The regex tier translates metavariable patterns into line
+//! regexes and cannot run S-expressions.
+
+use super::ast_grep::{AstGrepConfig, AstGrepMatch, AstGrepQuery, AstGrepResult, AstGrepRunner};
+use super::code_graph::{language_for_path, ts_language};
+use crate::resilience::{DegradedResponse, FallbackChain};
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Instant;
+use streaming_iterator::StreamingIterator;
+
+/// Tier names used in the fallback chain.
+pub const TIER_AST_GREP: &str = "ast_grep";
+/// Native tree-sitter query tier.
+pub const TIER_TREE_SITTER: &str = "tree_sitter";
+/// Line-regex tier.
+pub const TIER_REGEX: &str = "regex_grep";
+
+/// Normalize a language name to the name used by [`language_for_path`].
+fn canonical_language(language: &str) -> &str {
+    match language {
+        "rs" => "rust",
+        "ts" => "typescript",
+        "py" => "python",
+        other => other,
+    }
+}
+
+/// Whether a pattern is a raw tree-sitter S-expression.
+fn is_sexpr(pattern: &str) -> bool {
+    pattern.trim_start().starts_with('(')
+}
+
+/// Walk the search roots and yield `(relative_path, absolute_path)` for
+/// files in the query language.
+fn source_files(root: &Path, query: &AstGrepQuery) -> Vec<(String, PathBuf)> {
+    let language = canonical_language(&query.language);
+    let roots: Vec<PathBuf> = if query.paths.is_empty() {
+        vec![root.to_path_buf()]
+    } else {
+        query.paths.iter().map(|p| root.join(p)).collect()
+    };
+
+    let mut files = Vec::new();
+    for start in roots {
+        for entry in ignore::WalkBuilder::new(&start).build().flatten() {
+            let path = entry.path();
+            if !path.is_file() || language_for_path(path) != Some(language) {
+                continue;
+            }
+            let rel = path
+                .strip_prefix(root)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            files.push((rel, path.to_path_buf()));
+        }
+    }
+    files.sort();
+    files.dedup();
+    files
+}
+
+// ── Pattern translation ──────────────────────────────────────────────
+
+/// Prefix for single-node metavariable placeholders.
+const SINGLE_PLACEHOLDER: &str = "__MV_";
+/// Prefix for multi-node (`$$$`) metavariable placeholders.
+const MULTI_PLACEHOLDER: &str = "__MVM_";
+
+fn metavar_regex() -> &'static Regex {
+    static METAVAR: OnceLock<Regex> = OnceLock::new();
+    METAVAR.get_or_init(|| {
+        Regex::new(r"\$\$\$([A-Z_][A-Z0-9_]*)?|\$([A-Z_][A-Z0-9_]*)").expect("static regex")
+    })
+}
+
+/// Replace `$X` / `$$$X` with identifier placeholders that parse as code.
+///
+/// With `multi_as_comment`, `$$$X` becomes a block comment instead, for
+/// positions where an identifier does not parse (e.g. an `impl` body).
+fn substitute_metavars(pattern: &str, multi_as_comment: bool) -> String {
+    metavar_regex()
+        .replace_all(pattern, |caps: &regex::Captures<'_>| {
+            let multi = caps.get(1).map(|m| m.as_str()).unwrap_or("");
+            match caps.get(2) {
+                Some(name) => format!("{SINGLE_PLACEHOLDER}{}", name.as_str()),
+                None if multi_as_comment => format!("/*{MULTI_PLACEHOLDER}{multi}*/"),
+                None => format!("{MULTI_PLACEHOLDER}{multi}"),
+            }
+        })
+        .into_owned()
+}
+
+fn has_errors(node: tree_sitter::Node<'_>) -> bool {
+    node.has_error() || node.is_missing()
+}
+
+fn escape_query_string(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+struct QueryBuilder<'a> {
+    source: &'a [u8],
+    predicates: Vec<String>,
+    metavars: HashMap<String, usize>,
+    literals: usize,
+}
+
+impl QueryBuilder<'_> {
+    fn text(&self, node: tree_sitter::Node<'_>) -> &str {
+        node.utf8_text(self.source).unwrap_or_default()
+    }
+
+    fn emit(&mut self, node: tree_sitter::Node<'_>) -> Option<String> {
+        let text = self.text(node).to_string();
+
+        if node.is_named() {
+            if let Some(name) = text.strip_prefix(SINGLE_PLACEHOLDER) {
+                if is_plain_ident(name) {
+                    return Some(self.metavar_capture(name));
+                }
+            }
+            if text.starts_with(MULTI_PLACEHOLDER) && is_plain_ident(&text) {
+                return None;
+            }
+        } else {
+            return Some(format!("\"{}\"", escape_query_string(node.kind())));
+        }
+
+        if node.child_count() == 0 {
+            self.literals += 1;
+            let capture = format!("_lit{}", self.literals);
+            self.predicates.push(format!(
+                "(#eq? @{capture} \"{}\")",
+                escape_query_string(&text)
+            ));
+            return Some(format!("({}) @{capture}", node.kind()));
+        }
+
+        let mut parts = vec![node.kind().to_string()];
+        let mut omitted = false;
+        let mut named = false;
+        let mut tokens = Vec::new();
+        let mut cursor = node.walk();
+        for (i, child) in node.children(&mut cursor).enumerate() {
+            if child.is_extra() {
+                omitted |= self
+                    .text(child)
+                    .starts_with(&format!("/*{MULTI_PLACEHOLDER}"));
+                continue;
+            }
+            let Some(child_query) = self.emit(child) else {
+                omitted = true;
+                continue;
+            };
+            if child.is_named() {
+                named = true;
+            } else {
+                tokens.push(regex::escape(self.text(child)));
+            }
+            match node.field_name_for_child(i as u32) {
+                Some(field) => parts.push(format!("{field}: {child_query}")),
+                None => parts.push(child_query),
+            }
+        }
+        if named || omitted || tokens.is_empty() {
+            return Some(format!("({})", parts.join(" ")));
+        }
+
+        // Only punctuation (`()`, `{}`, `""`): child patterns are
+        // non-exhaustive, so pin the text to keep `{}` from matching
+        // `{ x }`.
+        self.literals += 1;
+        let capture = format!("_tok{}", self.literals);
+        let predicate = if node.kind().ends_with("literal") {
+            format!("(#eq? @{capture} \"{}\")", escape_query_string(&text))
+        } else {
+            let regex = format!("^{}$", tokens.join(r"\s*"));
+            format!("(#match? @{capture} \"{}\")", escape_query_string(&regex))
+        };
+        self.predicates.push(predicate);
+        Some(format!("({}) @{capture}", parts.join(" ")))
+    }
+
+    /// Capture for a single metavariable; repeats must match the same text.
+    fn metavar_capture(&mut self, name: &str) -> String {
+        let count = self.metavars.entry(name.to_string()).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            format!("(_) @{name}")
+        } else {
+            let capture = format!("{name}__{count}");
+            self.predicates.push(format!("(#eq? @{capture} @{name})"));
+            format!("(_) @{capture}")
+        }
+    }
+}
+
+fn is_plain_ident(s: &str) -> bool {
+    s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Translate an ast-grep metavariable pattern into a tree-sitter query.
+///
+/// The outermost pattern node is captured as `@match`. Literal leaves and
+/// punctuation-only nodes are pinned with predicates, `$X` becomes a named
+/// wildcard capture, `$$$X` is omitted (tree-sitter child patterns are
+/// non-exhaustive), and repeated metavariables must capture identical text.
+pub fn translate_pattern(pattern: &str, language: &str) -> Result<String, String> {
+    let ts_lang = ts_language(canonical_language(language))
+        .ok_or_else(|| format!("no tree-sitter grammar for '{language}'"))?;
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&ts_lang)
+        .map_err(|e| format!("failed to load {language} grammar: {e}"))?;
+
+    let wrappers: &[(&str, &str)] = match canonical_language(language) {
+        "rust" => &[("", ""), ("fn __sg_pattern() { ", "; }")],
+        _ => &[("", ""), ("", ";")],
+    };
+    let attempts = [false, true].into_iter().flat_map(|multi_as_comment| {
+        let substituted = substitute_metavars(pattern.trim(), multi_as_comment);
+        wrappers.iter().map(move |w| (w, substituted.clone()))
+    });
+
+    for ((prefix, suffix), substituted) in attempts {
+        let source = format!("{prefix}{substituted}{suffix}");
+        let Some(tree) = parser.parse(&source, None) else {
+            continue;
+        };
+        if has_errors(tree.root_node()) {
+            continue;
+        }
+        let start = prefix.len();
+        let end = start + substituted.len();
+        let Some(node) = tree.root_node().descendant_for_byte_range(start, end) else {
+            continue;
+        };
+        let mut builder = QueryBuilder {
+            source: source.as_bytes(),
+            predicates: Vec::new(),
+            metavars: HashMap::new(),
+            literals: 0,
+        };
+        // A node may carry several captures, so `@match` is added alongside
+        // any metavariable or literal capture already on the outermost node.
+        let body = builder.emit(node).unwrap_or_else(|| "(_)".to_string());
+        let mut query = format!("({body} @match");
+        for predicate in &builder.predicates {
+            query.push(' ');
+            query.push_str(predicate);
+        }
+        query.push(')');
+        return Ok(query);
+    }
+
+    Err(format!("cannot parse pattern as {language}: {pattern}"))
+}
+
+// ── Tree-sitter tier ─────────────────────────────────────────────────
+
+/// Native structural search over tree-sitter queries.
+pub struct TreeSitterSearcher {
+    config: AstGrepConfig,
+    root: PathBuf,
+}
+
+impl TreeSitterSearcher {
+    /// Create a searcher rooted at `config.working_dir` (default: current dir).
+    pub fn new(config: AstGrepConfig) -> Self {
+        let root = PathBuf::from(config.working_dir.as_deref().unwrap_or("."));
+        Self { config, root }
+    }
+
+    /// Compile the query pattern (S-expression or metavariable pattern).
+    fn compile(
+        &self,
+        query: &AstGrepQuery,
+    ) -> Result<(tree_sitter::Language, tree_sitter::Query), String> {
+        if query.pattern.is_empty() {
+            return Err("tree-sitter search requires a pattern".to_string());
+        }
+        let language = canonical_language(&query.language);
+        let ts_lang = ts_language(language)
+            .ok_or_else(|| format!("no tree-sitter grammar for '{language}'"))?;
+
+        let source = if is_sexpr(&query.pattern) {
+            if query.pattern.contains('@') {
+                query.pattern.clone()
+            } else {
+                format!("{} @match", query.pattern.trim())
+            }
+        } else {
+            translate_pattern(&query.pattern, language)?
+        };
+        let ts_query = tree_sitter::Query::new(&ts_lang, &source)
+            .map_err(|e| format!("invalid tree-sitter query: {e}"))?;
+        Ok((ts_lang, ts_query))
+    }
+
+    /// Run a pattern or S-expression query over matching source files.
+    pub fn search(&self, query: &AstGrepQuery) -> AstGrepResult {
+        self.search_within(query, self.config.timeout_ms)
+    }
+
+    /// [`Self::search`] with an explicit time budget instead of `timeout_ms`.
+    pub fn search_within(&self, query: &AstGrepQuery, timeout_ms: u64) -> AstGrepResult {
+        let start = Instant::now();
+        let (ts_lang, ts_query) = match self.compile(query) {
+            Ok(compiled) => compiled,
+            Err(e) => return AstGrepResult::err(&e, start.elapsed().as_millis() as u64),
+        };
+
+        let mut parser = tree_sitter::Parser::new();
+        if let Err(e) = parser.set_language(&ts_lang) {
+            return AstGrepResult::err(&format!("failed to load grammar: {e}"), 0);
+        }
+
+        let mut matches = Vec::new();
+        for (rel, path) in source_files(&self.root, query) {
+            if start.elapsed().as_millis() as u64 > timeout_ms {
+                return AstGrepResult::timeout(timeout_ms);
+            }
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            collect_matches(&mut parser, &ts_query, query, &rel, &content, &mut matches);
+        }
+
+        let mut result = AstGrepResult::ok(matches, start.elapsed().as_millis() as u64);
+        result.truncate_to(self.config.max_matches);
+        result
+    }
+
+    /// Run a query over an in-memory source string reported as `file`.
+    pub fn search_source(&self, query: &AstGrepQuery, file: &str, content: &str) -> AstGrepResult {
+        let start = Instant::now();
+        let (ts_lang, ts_query) = match self.compile(query) {
+            Ok(compiled) => compiled,
+            Err(e) => return AstGrepResult::err(&e, start.elapsed().as_millis() as u64),
+        };
+        let mut parser = tree_sitter::Parser::new();
+        if let Err(e) = parser.set_language(&ts_lang) {
+            return AstGrepResult::err(&format!("failed to load grammar: {e}"), 0);
+        }
+
+        let mut matches = Vec::new();
+        collect_matches(&mut parser, &ts_query, query, file, content, &mut matches);
+        let mut result = AstGrepResult::ok(matches, start.elapsed().as_millis() as u64);
+        result.truncate_to(self.config.max_matches);
+        result
+    }
+}
+
+/// Parse `content` and append every query match as an [`AstGrepMatch`].
+fn collect_matches(
+    parser: &mut tree_sitter::Parser,
+    ts_query: &tree_sitter::Query,
+    query: &AstGrepQuery,
+    file: &str,
+    content: &str,
+    matches: &mut Vec<AstGrepMatch>,
+) {
+    let Some(tree) = parser.parse(content, None) else {
+        return;
+    };
+    let match_capture = ts_query.capture_index_for_name("match").unwrap_or(0);
+    let mut cursor = tree_sitter::QueryCursor::new();
+    let mut it = cursor.matches(ts_query, tree.root_node(), content.as_bytes());
+    while let Some(m) = it.next() {
+        let Some(capture) = m
+            .captures
+            .iter()
+            .find(|c| c.index == match_capture)
+            .or_else(|| m.captures.first())
+        else {
+            continue;
+        };
+        let node = capture.node;
+        let (s, e) = (node.start_position(), node.end_position());
+        matches.push(AstGrepMatch {
+            file: file.to_string(),
+            line: s.row as u32 + 1,
+            column: s.column as u32 + 1,
+            end_line: e.row as u32 + 1,
+            end_column: e.column as u32 + 1,
+            text: node
+                .utf8_text(content.as_bytes())
+                .unwrap_or_default()
+                .to_string(),
+            rule_id: query.rule_id.clone(),
+            severity: None,
+            message: None,
+        });
+    }
+}
+
+// ── Regex tier ───────────────────────────────────────────────────────
+
+/// Translate a metavariable pattern into a single-line regex.
+///
+/// `$X` matches a run of non-whitespace, `$$$X` matches anything (lazily),
+/// and whitespace in the pattern matches optional whitespace.
+pub fn pattern_to_regex(pattern: &str) -> Result<Regex, String> {
+    if is_sexpr(pattern) {
+        return Err("regex search cannot run S-expression queries".to_string());
+    }
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return Err("regex search requires a pattern".to_string());
+    }
+
+    let literal = |s: &str| -> String {
+        s.split_whitespace()
+            .map(regex::escape)
+            .collect::<Vec<_>>()
+            .join(r"\s*")
+            + if s.ends_with(char::is_whitespace) && !s.trim().is_empty() {
+                r"\s*"
+            } else {
+                ""
+            }
+    };
+
+    let mut out = String::new();
+    let mut last = 0;
+    for caps in metavar_regex().captures_iter(pattern) {
+        let whole = caps.get(0).expect("group 0 always present");
+        let before = &pattern[last..whole.start()];
+        if before.starts_with(char::is_whitespace) && !out.is_empty() {
+            out.push_str(r"\s*");
+        }
+        out.push_str(&literal(before));
+        out.push_str(if caps.get(2).is_some() {
+            r"\S+?"
+        } else {
+            ".*?"
+        });
+        last = whole.end();
+    }
+    let tail = &pattern[last..];
+    if tail.starts_with(char::is_whitespace) && !out.is_empty() {
+        out.push_str(r"\s*");
+    }
+    out.push_str(&literal(tail));
+
+    Regex::new(&out).map_err(|e| format!("invalid regex from pattern: {e}"))
+}
+
+/// Line-oriented regex search over `ignore`-walked files.
+pub struct RegexSearcher {
+    config: AstGrepConfig,
+    root: PathBuf,
+}
+
+impl RegexSearcher {
+    /// Create a searcher rooted at `config.working_dir` (default: current dir).
+    pub fn new(config: AstGrepConfig) -> Self {
+        let root = PathBuf::from(config.working_dir.as_deref().unwrap_or("."));
+        Self { config, root }
+    }
+
+    /// Run a metavariable pattern as a per-line regex.
+    pub fn search(&self, query: &AstGrepQuery) -> AstGrepResult {
+        self.search_within(query, self.config.timeout_ms)
+    }
+
+    /// [`Self::search`] with an explicit time budget instead of `timeout_ms`.
+    pub fn search_within(&self, query: &AstGrepQuery, timeout_ms: u64) -> AstGrepResult {
+        let start = Instant::now();
+        let re = match pattern_to_regex(&query.pattern) {
+            Ok(re) => re,
+            Err(e) => return AstGrepResult::err(&e, 0),
+        };
+
+        let mut matches = Vec::new();
+        for (rel, path) in source_files(&self.root, query) {
+            if start.elapsed().as_millis() as u64 > timeout_ms {
+                return AstGrepResult::timeout(timeout_ms);
+            }
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            for (i, line) in content.lines().enumerate() {
+                for m in re.find_iter(line) {
+                    matches.push(AstGrepMatch {
+                        file: rel.clone(),
+                        line: i as u32 + 1,
+                        column: line[..m.start()].chars().count() as u32 + 1,
+                        end_line: i as u32 + 1,
+                        end_column: line[..m.end()].chars().count() as u32 + 1,
+                        text: m.as_str().to_string(),
+                        rule_id: query.rule_id.clone(),
+                        severity: None,
+                        message: None,
+                    });
+                }
+            }
+        }
+
+        let mut result = AstGrepResult::ok(matches, start.elapsed().as_millis() as u64);
+        result.truncate_to(self.config.max_matches);
+        result
+    }
+}
+
+// ── Fallback chain ───────────────────────────────────────────────────
+
+/// Structural search with graceful degradation across three tiers.
+///
+/// | Tier          | Confidence | Backend                          |
+/// |---------------|------------|----------------------------------|
+/// | `ast_grep`    | 1.0        | `sg` subprocess                  |
+/// | `tree_sitter` | 0.85       | in-process tree-sitter queries   |
+/// | `regex_grep`  | 0.5        | per-line regex over source files |
+pub struct StructuralSearch {
+    ast_grep: AstGrepRunner,
+    tree_sitter: TreeSitterSearcher,
+    regex: RegexSearcher,
+    chain: FallbackChain,
+    /// Rule ID → pattern, used when `sg` is unavailable for rule scans.
+    rule_patterns: HashMap<String, String>,
+    /// Budget for a whole search, shared by the tiers it falls through.
+    timeout_ms: u64,
+}
+
+impl StructuralSearch {
+    /// Create a search chain sharing one ast-grep configuration.
+    pub fn new(config: AstGrepConfig) -> Self {
+        Self {
+            ast_grep: AstGrepRunner::with_config(config.clone()),
+            tree_sitter: TreeSitterSearcher::new(config.clone()),
+            timeout_ms: config.timeout_ms,
+            regex: RegexSearcher::new(config),
+            chain: Self::default_chain(),
+            rule_patterns: HashMap::new(),
+        }
+    }
+
+    /// The default `ast_grep → tree_sitter → regex_grep` chain.
+    pub fn default_chain() -> FallbackChain {
+        FallbackChain::new("ast_analysis")
+            .add_tier(TIER_AST_GREP, 1.0)
+            .add_tier(TIER_TREE_SITTER, 0.85)
+            .add_tier(TIER_REGEX, 0.5)
+    }
+
+    /// Replace the fallback chain (e.g. to drop or reweight tiers).
+    pub fn with_chain(mut self, chain: FallbackChain) -> Self {
+        self.chain = chain;
+        self
+    }
+
+    /// Register rule patterns so rule scans can run without `sg`.
+    pub fn with_rule_patterns(mut self, patterns: HashMap<String, String>) -> Self {
+        self.rule_patterns = patterns;
+        self
+    }
+
+    /// The fallback chain in use.
+    pub fn chain(&self) -> &FallbackChain {
+        &self.chain
+    }
+
+    /// Run a query through the chain, returning the first successful tier.
+    ///
+    /// `timeout_ms` bounds the whole search: each tier gets whatever budget
+    /// the tiers before it left over.
+    pub fn search(&self, query: &AstGrepQuery) -> DegradedResponse<Option<AstGrepResult>> {
+        let start = Instant::now();
+        let fallback_query = self.resolve_rule(query);
+        self.chain.execute(|tier| {
+            let remaining = self
+                .timeout_ms
+                .saturating_sub(start.elapsed().as_millis() as u64);
+            if remaining == 0 {
+                return Err(format!("timed out after {}ms", self.timeout_ms));
+            }
+            let result = match tier {
+                TIER_AST_GREP => self.ast_grep.run_within(query, remaining),
+                TIER_TREE_SITTER => self.tree_sitter.search_within(&fallback_query, remaining),
+                TIER_REGEX => self.regex.search_within(&fallback_query, remaining),
+                other => return Err(format!("unknown tier '{other}'")),
+            };
+            if result.is_success() {
+                Ok(result)
+            } else {
+                Err(result.error.unwrap_or_else(|| "failed".to_string()))
+            }
+        })
+    }
+
+    /// Fill in the pattern of a rule query from the registered rule patterns.
+    fn resolve_rule(&self, query: &AstGrepQuery) -> AstGrepQuery {
+        let mut resolved = query.clone();
+        if resolved.pattern.is_empty() {
+            if let Some(pattern) = query
+                .rule_id
+                .as_ref()
+                .and_then(|id| self.rule_patterns.get(id))
+            {
+                resolved.pattern = pattern.clone();
+            }
+        }
+        resolved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resilience::DegradationLevel;
+
+    const LIB: &str = r#"pub fn load(path: &str) -> String {
+    let raw = std::fs::read_to_string(path).unwrap();
+    let cfg = parse(&raw).expect("valid config");
+    if cfg.is_empty() {
+        panic!("empty config: {}", path);
+    }
+    cfg
+}
+
+fn parse(s: &str) -> Option<String> {
+    let x = s.to_string();
+    Some(x.clone() + &x)
+}
+"#;
+
+    fn repo() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), LIB).unwrap();
+        std::fs::write(dir.path().join("src/other.py"), "x.unwrap()\n").unwrap();
+        dir
+    }
+
+    fn config(dir: &Path) -> AstGrepConfig {
+        AstGrepConfig {
+            binary: dir.join("missing-sg").display().to_string(),
+            working_dir: Some(dir.display().to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_translate_pattern() {
+        let q = translate_pattern("$EXPR.unwrap()", "rust").unwrap();
+        assert!(q.contains("call_expression"));
+        assert!(q.contains("(_) @EXPR"));
+        assert!(q.contains("(#eq? @_lit1 \"unwrap\")"));
+        assert!(q.contains("@match"));
+
+        let q = translate_pattern("panic!($$$ARGS)", "rust").unwrap();
+        assert!(q.contains("macro_invocation"));
+        assert!(!q.contains("ARGS"));
+
+        assert!(translate_pattern("fn (", "rust").is_err());
+        assert!(translate_pattern("$X", "cobol").is_err());
+    }
+
+    #[test]
+    fn test_tree_sitter_metavar_pattern() {
+        let dir = repo();
+        let searcher = TreeSitterSearcher::new(config(dir.path()));
+
+        let result = searcher.search(&AstGrepQuery::pattern("$EXPR.unwrap()", "rust"));
+        assert!(result.is_success(), "{:?}", result.error);
+        assert_eq!(result.matches.len(), 1);
+        let m = &result.matches[0];
+        assert_eq!(m.file, "src/lib.rs");
+        assert_eq!(m.line, 2);
+        assert_eq!(m.column, 15);
+        assert_eq!(m.text, "std::fs::read_to_string(path).unwrap()");
+
+        let result = searcher.search(&AstGrepQuery::pattern("$X.expect($MSG)", "rust"));
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches[0].line, 3);
+
+        let result = searcher.search(&AstGrepQuery::pattern("panic!($$$ARGS)", "rust"));
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches[0].line, 5);
+    }
+
+    #[test]
+    fn test_tree_sitter_repeated_metavar() {
+        let dir = repo();
+        let searcher = TreeSitterSearcher::new(config(dir.path()));
+        let result = searcher.search(&AstGrepQuery::pattern("$A.clone() + &$A", "rust"));
+        assert!(result.is_success(), "{:?}", result.error);
+        assert_eq!(result.matches.len(), 1);
+        let result = searcher.search(&AstGrepQuery::pattern("$A.clone() + &s", "rust"));
+        assert!(result.matches.is_empty());
+    }
+
+    #[test]
+    fn test_tree_sitter_sexpr_and_bounds() {
+        let dir = repo();
+        let mut cfg = config(dir.path());
+        cfg.max_matches = 1;
+        let searcher = TreeSitterSearcher::new(cfg);
+        let result = searcher.search(&AstGrepQuery::pattern("(function_item)", "rust"));
+        assert!(result.is_success(), "{:?}", result.error);
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.total_matches, 2);
+        assert!(result.truncated);
+
+        let result = searcher.search(&AstGrepQuery::pattern("(not_a_node)", "rust"));
+        assert!(!result.is_success());
+    }
+
+    #[test]
+    fn test_tree_sitter_respects_paths() {
+        let dir = repo();
+        std::fs::create_dir_all(dir.path().join("vendor")).unwrap();
+        std::fs::write(dir.path().join("vendor/v.rs"), "fn f() { a.unwrap(); }").unwrap();
+        let searcher = TreeSitterSearcher::new(config(dir.path()));
+        let all = searcher.search(&AstGrepQuery::pattern("$E.unwrap()", "rust"));
+        assert_eq!(all.matches.len(), 2);
+        let scoped = searcher.search(
+            &AstGrepQuery::pattern("$E.unwrap()", "rust").in_paths(vec!["vendor".to_string()]),
+        );
+        assert_eq!(scoped.matches.len(), 1);
+        assert_eq!(scoped.matches[0].file, "vendor/v.rs");
+    }
+
+    #[test]
+    fn test_pattern_to_regex() {
+        let re = pattern_to_regex("$EXPR.unwrap()").unwrap();
+        assert!(re.is_match("let x = foo(a).unwrap();"));
+        assert!(!re.is_match("foo.unwrap_or(1)"));
+        let re = pattern_to_regex("panic!($$$ARGS)").unwrap();
+        assert!(re.is_match(r#"panic!("boom {}", x)"#));
+        let re = pattern_to_regex("unsafe { $$$BODY }").unwrap();
+        assert!(re.is_match("unsafe {ptr.read()}"));
+        assert!(pattern_to_regex("(call_expression)").is_err());
+        assert!(pattern_to_regex("").is_err());
+    }
+
+    #[test]
+    fn test_regex_searcher() {
+        let dir = repo();
+        let searcher = RegexSearcher::new(config(dir.path()));
+        let result = searcher.search(&AstGrepQuery::pattern("$EXPR.unwrap()", "rust"));
+        assert!(result.is_success());
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches[0].line, 2);
+        assert!(result.matches[0].text.ends_with(".unwrap()"));
+    }
+
+    #[test]
+    fn test_chain_falls_back_to_tree_sitter() {
+        let dir = repo();
+        let search = StructuralSearch::new(config(dir.path()));
+        let resp = search.search(&AstGrepQuery::pattern("$EXPR.unwrap()", "rust"));
+        assert_eq!(resp.level, DegradationLevel::Partial);
+        assert_eq!(resp.served_by, TIER_TREE_SITTER);
+        assert_eq!(resp.confidence, 0.85);
+        assert!(resp.warnings.iter().any(|w| w.contains("not found")));
+        assert_eq!(resp.payload.unwrap().matches.len(), 1);
+    }
+
+    #[test]
+    fn test_chain_falls_back_to_regex() {
+        let dir = repo();
+        let search = StructuralSearch::new(config(dir.path()));
+        // Not parseable as Rust, so only the regex tier can serve it.
+        let resp = search.search(&AstGrepQuery::pattern("read_to_string(path", "rust"));
+        assert_eq!(resp.served_by, TIER_REGEX);
+        assert_eq!(resp.confidence, 0.5);
+        assert_eq!(resp.payload.unwrap().matches.len(), 1);
+    }
+
+    #[test]
+    fn test_chain_rule_patterns_and_exhaustion() {
+        let dir = repo();
+        let mut patterns = HashMap::new();
+        patterns.insert("no-panic".to_string(), "panic!($$$ARGS)".to_string());
+        let search = StructuralSearch::new(config(dir.path())).with_rule_patterns(patterns);
+
+        let resp = search.search(&AstGrepQuery::rule("no-panic", "rust"));
+        assert_eq!(resp.served_by, TIER_TREE_SITTER);
+        let payload = resp.payload.unwrap();
+        assert_eq!(payload.matches[0].rule_id.as_deref(), Some("no-panic"));
+
+        let resp = search.search(&AstGrepQuery::rule("unknown-rule", "rust"));
+        assert_eq!(resp.level, DegradationLevel::Unavailable);
+        assert!(resp.payload.is_none());
+        assert_eq!(resp.warnings.len(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_chain_uses_ast_grep_when_available() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = repo();
+        let bin = dir.path().join("sg");
+        std::fs::write(&bin, "#!/bin/sh\necho '[]'\n").unwrap();
+        std::fs::set_permissions(&bin, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let search = StructuralSearch::new(AstGrepConfig {
+            binary: bin.display().to_string(),
+            ..config(dir.path())
+        });
+        let resp = search.search(&AstGrepQuery::pattern("$X", "rust"));
+        assert!(resp.is_full());
+        assert_eq!(resp.served_by, TIER_AST_GREP);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_chain_shares_one_timeout_budget() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = repo();
+        let bin = dir.path().join("sg");
+        std::fs::write(&bin, "#!/bin/sh\nsleep 5\n").unwrap();
+        std::fs::set_permissions(&bin, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let search = StructuralSearch::new(AstGrepConfig {
+            binary: bin.display().to_string(),
+            timeout_ms: 200,
+            ..config(dir.path())
+        });
+
+        let resp = search.search(&AstGrepQuery::pattern("$EXPR.unwrap()", "rust"));
+        assert!(resp.payload.is_none(), "sg used up the whole budget");
+        assert_eq!(resp.warnings.len(), 3);
+        assert!(resp.warnings.iter().all(|w| w.contains("timed out")));
+    }
+}
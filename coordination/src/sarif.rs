@@ -0,0 +1,755 @@
+//! SARIF 2.1.0 Export — review, rule-pack and compiler findings.
+//!
+//! Serializes reviewer output into the Static Analysis Results Interchange
+//! Format so findings can be viewed in editors and code-scanning UIs. Each
+//! source gets its own run (one tool per run, as SARIF expects):
+//!
+//! ```text
+//! ast-grep  ← AstGrepResult matches, rule metadata from RulePackRegistry
+//! rustc     ← CompilerDiagnostics from cargo JSON output
+//! reviewer  ← ReviewDecisionResult issues
+//! ```
+//!
+//! Locations are emitted relative to the repository root under the
+//! `SRCROOT` base id.
+
+use crate::diagnostics::{CompilerDiagnostic, DiagnosticLevel};
+use crate::reviewer_tools::ast_grep::AstGrepResult;
+use crate::reviewer_tools::{RulePackEntry, RulePackRegistry, RuleSeverity};
+use crate::tool_schema::ReviewDecisionResult;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// SARIF schema URI.
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+/// SARIF version emitted.
+pub const SARIF_VERSION: &str = "2.1.0";
+/// Base id that all artifact URIs are relative to.
+pub const SRCROOT: &str = "SRCROOT";
+
+/// Top-level SARIF log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SarifLog {
+    /// Schema URI.
+    #[serde(rename = "$schema")]
+    pub schema: String,
+    /// Format version (always `2.1.0`).
+    pub version: String,
+    /// One run per tool.
+    pub runs: Vec<SarifRun>,
+}
+
+/// A single tool run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRun {
+    /// Tool that produced the results.
+    pub tool: SarifTool,
+    /// Base URIs that artifact locations are relative to.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub original_uri_base_ids: BTreeMap<String, SarifArtifactLocation>,
+    /// Findings.
+    pub results: Vec<SarifResult>,
+}
+
+/// Tool wrapper.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SarifTool {
+    /// Tool driver component.
+    pub driver: SarifDriver,
+}
+
+/// Tool driver with rule metadata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifDriver {
+    /// Tool name.
+    pub name: String,
+    /// Tool homepage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub information_uri: Option<String>,
+    /// Rules referenced by results.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<SarifRule>,
+}
+
+/// Reporting descriptor for a rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRule {
+    /// Rule ID.
+    pub id: String,
+    /// Short description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub short_description: Option<SarifMessage>,
+    /// Default level for results of this rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_configuration: Option<SarifConfiguration>,
+    /// Extra metadata (category, pattern, tags).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, serde_json::Value>,
+}
+
+/// Default rule configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SarifConfiguration {
+    /// `error`, `warning`, `note` or `none`.
+    pub level: String,
+}
+
+/// Plain-text message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SarifMessage {
+    /// Message text.
+    pub text: String,
+}
+
+/// A single finding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    /// Rule ID.
+    pub rule_id: String,
+    /// Index into the driver's `rules` (when the rule is described).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_index: Option<usize>,
+    /// `error`, `warning`, `note` or `none`.
+    pub level: String,
+    /// Finding message.
+    pub message: SarifMessage,
+    /// Where the finding applies (empty for repository-wide findings).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<SarifLocation>,
+}
+
+/// Location wrapper.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLocation {
+    /// Physical file location.
+    pub physical_location: SarifPhysicalLocation,
+}
+
+/// File and region.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifPhysicalLocation {
+    /// File reference.
+    pub artifact_location: SarifArtifactLocation,
+    /// Region within the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<SarifRegion>,
+}
+
+/// File reference, relative to a base id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifArtifactLocation {
+    /// URI (relative when `uri_base_id` is set).
+    pub uri: String,
+    /// Base id the URI is relative to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri_base_id: Option<String>,
+}
+
+/// Line/column region (1-indexed).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRegion {
+    /// Start line.
+    pub start_line: u32,
+    /// Start column.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_column: Option<u32>,
+    /// End line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
+    /// End column (exclusive).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<u32>,
+}
+
+/// Map a rule severity to a SARIF level.
+pub fn sarif_level(severity: RuleSeverity) -> &'static str {
+    match severity {
+        RuleSeverity::Error => "error",
+        RuleSeverity::Warning => "warning",
+        RuleSeverity::Info => "note",
+    }
+}
+
+/// Map a compiler diagnostic level to a SARIF level.
+pub fn diagnostic_level(level: DiagnosticLevel) -> &'static str {
+    match level {
+        DiagnosticLevel::Error => "error",
+        DiagnosticLevel::Warning => "warning",
+        DiagnosticLevel::Note | DiagnosticLevel::Help => "note",
+    }
+}
+
+/// Map an ast-grep severity string (`error`, `warning`, `info`, `hint`) to a SARIF level.
+fn sg_level(severity: &str) -> &'static str {
+    match severity.to_ascii_lowercase().as_str() {
+        "error" => "error",
+        "warning" => "warning",
+        "off" => "none",
+        _ => "note",
+    }
+}
+
+/// Build the SARIF rule descriptor for a rule-pack entry.
+pub fn rule_descriptor(rule: &RulePackEntry) -> SarifRule {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "category".to_string(),
+        serde_json::Value::String(rule.category.clone()),
+    );
+    properties.insert(
+        "tags".to_string(),
+        serde_json::json!([rule.category, rule.language]),
+    );
+    if let Some(pattern) = &rule.pattern {
+        properties.insert(
+            "pattern".to_string(),
+            serde_json::Value::String(pattern.clone()),
+        );
+    }
+    SarifRule {
+        id: rule.rule_id.clone(),
+        short_description: Some(SarifMessage {
+            text: rule.description.clone(),
+        }),
+        default_configuration: Some(SarifConfiguration {
+            level: sarif_level(rule.severity).to_string(),
+        }),
+        properties,
+    }
+}
+
+/// Index every rule in the registry by ID.
+///
+/// When several packs define the same rule ID, the first pack (by name)
+/// wins, as in `SgConfigExporter::selected_rules`.
+pub(crate) fn rules_by_id(registry: &RulePackRegistry) -> BTreeMap<&str, &RulePackEntry> {
+    let mut rules = BTreeMap::new();
+    for pack in registry
+        .pack_names()
+        .into_iter()
+        .filter_map(|name| registry.get(name))
+    {
+        for rule in &pack.rules {
+            rules.entry(rule.rule_id.as_str()).or_insert(rule);
+        }
+    }
+    rules
+}
+
+/// Rule ID from a `[rule-id] ...` issue description (as the rule-pack stage writes them).
+pub(crate) fn tagged_rule_id(description: &str) -> Option<&str> {
+    description
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+        .map(|(id, _)| id)
+}
+
+/// Normalize a path to forward slashes, relative to `root` when under it.
+pub(crate) fn repo_relative(root: &Path, file: &str) -> String {
+    let path = Path::new(file);
+    let rel = path.strip_prefix(root).unwrap_or(path);
+    let uri = rel.to_string_lossy().replace('\\', "/");
+    uri.trim_start_matches("./").to_string()
+}
+
+/// Percent-encode a forward-slash path for use in a URI.
+///
+/// Unreserved characters, `/` and `:` (drive letters) are kept; every other
+/// byte of the UTF-8 encoding becomes `%XX`.
+fn encode_uri_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/:".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+
+/// Collects findings from several sources into a [`SarifLog`].
+pub struct SarifBuilder {
+    repo_root: PathBuf,
+    /// Canonical form of `repo_root`; this is what `SRCROOT` points at.
+    canonical_root: PathBuf,
+    runs: Vec<SarifRun>,
+}
+
+impl SarifBuilder {
+    /// Create a builder; locations are made relative to `repo_root`.
+    pub fn new(repo_root: &Path) -> Self {
+        Self {
+            repo_root: repo_root.to_path_buf(),
+            canonical_root: std::fs::canonicalize(repo_root)
+                .unwrap_or_else(|_| repo_root.to_path_buf()),
+            runs: Vec::new(),
+        }
+    }
+
+    /// Normalize a path to a percent-encoded, forward-slash URI relative to
+    /// the repo root.
+    ///
+    /// Absolute paths may be spelled under either the root as given or its
+    /// canonical form (e.g. through a symlinked checkout).
+    pub fn relative_uri(&self, file: &str) -> String {
+        let root = if Path::new(file).starts_with(&self.canonical_root) {
+            &self.canonical_root
+        } else {
+            &self.repo_root
+        };
+        encode_uri_path(&repo_relative(root, file))
+    }
+
+    fn location(&self, file: &str, region: Option<SarifRegion>) -> SarifLocation {
+        SarifLocation {
+            physical_location: SarifPhysicalLocation {
+                artifact_location: SarifArtifactLocation {
+                    uri: self.relative_uri(file),
+                    uri_base_id: Some(SRCROOT.to_string()),
+                },
+                region,
+            },
+        }
+    }
+
+    fn run(&self, name: &str, information_uri: Option<&str>) -> SarifRun {
+        let mut bases = BTreeMap::new();
+        let mut root_path = self.canonical_root.to_string_lossy().replace('\\', "/");
+        if !root_path.starts_with('/') {
+            root_path.insert(0, '/');
+        }
+        let mut root_uri = format!("file://{}", encode_uri_path(&root_path));
+        if !root_uri.ends_with('/') {
+            root_uri.push('/');
+        }
+        bases.insert(
+            SRCROOT.to_string(),
+            SarifArtifactLocation {
+                uri: root_uri,
+                uri_base_id: None,
+            },
+        );
+        SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: name.to_string(),
+                    information_uri: information_uri.map(str::to_string),
+                    rules: Vec::new(),
+                },
+            },
+            original_uri_base_ids: bases,
+            results: Vec::new(),
+        }
+    }
+
+    /// Add a run for ast-grep matches, describing each rule found in `registry`.
+    pub fn add_ast_grep(mut self, result: &AstGrepResult, registry: &RulePackRegistry) -> Self {
+        let mut run = self.run("ast-grep", Some("https://ast-grep.github.io"));
+        let known = rules_by_id(registry);
+        let mut indices: BTreeMap<String, usize> = BTreeMap::new();
+
+        for m in &result.matches {
+            let rule_id = m.rule_id.clone().unwrap_or_else(|| "pattern".to_string());
+            let entry = known.get(rule_id.as_str()).copied();
+            let rule_index = entry.map(|rule| {
+                *indices.entry(rule_id.clone()).or_insert_with(|| {
+                    run.tool.driver.rules.push(rule_descriptor(rule));
+                    run.tool.driver.rules.len() - 1
+                })
+            });
+            let level = match (entry, m.severity.as_deref()) {
+                (Some(rule), _) => sarif_level(rule.severity),
+                (None, Some(severity)) => sg_level(severity),
+                (None, None) => "warning",
+            };
+            let text = m
+                .message
+                .clone()
+                .or_else(|| entry.map(|r| r.description.clone()))
+                .unwrap_or_else(|| format!("matched `{}`", m.text));
+            let region = SarifRegion {
+                start_line: m.line,
+                start_column: Some(m.column),
+                end_line: Some(m.end_line),
+                end_column: Some(m.end_column),
+            };
+            run.results.push(SarifResult {
+                rule_id,
+                rule_index,
+                level: level.to_string(),
+                message: SarifMessage { text },
+                locations: vec![self.location(&m.file, Some(region))],
+            });
+        }
+
+        self.runs.push(run);
+        self
+    }
+
+    /// Add a run for compiler diagnostics (rustc/clippy).
+    pub fn add_diagnostics(mut self, diagnostics: &[CompilerDiagnostic]) -> Self {
+        let mut run = self.run("rustc", Some("https://doc.rust-lang.org/rustc/"));
+        let mut indices: BTreeMap<String, usize> = BTreeMap::new();
+
+        for d in diagnostics {
+            let rule_id = d.code.clone().unwrap_or_else(|| "rustc".to_string());
+            let rule_index = d.code.as_ref().map(|code| {
+                *indices.entry(code.clone()).or_insert_with(|| {
+                    run.tool.driver.rules.push(SarifRule {
+                        id: code.clone(),
+                        short_description: None,
+                        default_configuration: None,
+                        properties: BTreeMap::new(),
+                    });
+                    run.tool.driver.rules.len() - 1
+                })
+            });
+            let region = SarifRegion {
+                start_line: d.line,
+                start_column: Some(d.column),
+                end_line: Some(d.end_line),
+                end_column: Some(d.end_column),
+            };
+            run.results.push(SarifResult {
+                rule_id,
+                rule_index,
+                level: diagnostic_level(d.level).to_string(),
+                message: SarifMessage {
+                    text: d.message.clone(),
+                },
+                locations: vec![self.location(&d.file, Some(region))],
+            });
+        }
+
+        self.runs.push(run);
+        self
+    }
+
+    /// Add a run for reviewer decision issues.
+    ///
+    /// Issues whose description starts with `[rule-id]` (as produced by the
+    /// rule-pack stage) keep that rule ID and its registry metadata; others
+    /// are reported as `review/blocking` or `review/advisory`.
+    pub fn add_review(
+        mut self,
+        decision: &ReviewDecisionResult,
+        registry: &RulePackRegistry,
+    ) -> Self {
+        let mut run = self.run("reviewer", None);
+        let known = rules_by_id(registry);
+        let mut indices: BTreeMap<String, usize> = BTreeMap::new();
+
+        for issue in &decision.issues {
+            let tagged = tagged_rule_id(&issue.description).map(str::to_string);
+            let rule = tagged.as_deref().and_then(|id| known.get(id).copied());
+            let rule_id = tagged.unwrap_or_else(|| {
+                if issue.blocking {
+                    "review/blocking".to_string()
+                } else {
+                    "review/advisory".to_string()
+                }
+            });
+            let rule_index = rule.map(|r| {
+                *indices.entry(rule_id.clone()).or_insert_with(|| {
+                    run.tool.driver.rules.push(rule_descriptor(r));
+                    run.tool.driver.rules.len() - 1
+                })
+            });
+            let level = if issue.blocking { "error" } else { "warning" };
+            let mut text = issue.description.clone();
+            if let Some(suggestion) = &issue.suggestion {
+                text.push_str(&format!("\nSuggestion: {suggestion}"));
+            }
+            let locations = issue
+                .file
+                .as_deref()
+                .map(|file| {
+                    // SARIF lines are 1-based; a zero line means "unknown".
+                    let region = issue.line.filter(|&line| line > 0).map(|line| SarifRegion {
+                        start_line: line as u32,
+                        start_column: None,
+                        end_line: None,
+                        end_column: None,
+                    });
+                    vec![self.location(file, region)]
+                })
+                .unwrap_or_default();
+            run.results.push(SarifResult {
+                rule_id,
+                rule_index,
+                level: level.to_string(),
+                message: SarifMessage { text },
+                locations,
+            });
+        }
+
+        self.runs.push(run);
+        self
+    }
+
+    /// Finish the log.
+    pub fn build(self) -> SarifLog {
+        SarifLog {
+            schema: SARIF_SCHEMA.to_string(),
+            version: SARIF_VERSION.to_string(),
+            runs: self.runs,
+        }
+    }
+
+    /// Finish the log and serialize it as pretty-printed JSON.
+    pub fn to_json(self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&self.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::parse_cargo_json;
+    use crate::reviewer_tools::{AstGrepMatch, RulePack};
+    use crate::tool_schema::ReviewIssue;
+
+    fn m(file: &str, rule_id: Option<&str>, severity: Option<&str>) -> AstGrepMatch {
+        AstGrepMatch {
+            file: file.to_string(),
+            line: 4,
+            column: 9,
+            end_line: 4,
+            end_column: 21,
+            text: "opt.unwrap()".to_string(),
+            rule_id: rule_id.map(str::to_string),
+            severity: severity.map(str::to_string),
+            message: None,
+        }
+    }
+
+    #[test]
+    fn test_levels() {
+        assert_eq!(sarif_level(RuleSeverity::Error), "error");
+        assert_eq!(sarif_level(RuleSeverity::Info), "note");
+        assert_eq!(diagnostic_level(DiagnosticLevel::Help), "note");
+        assert_eq!(sg_level("hint"), "note");
+    }
+
+    #[test]
+    fn test_rules_by_id_first_pack_wins() {
+        let mut registry = RulePackRegistry::new();
+        for (name, severity) in [
+            ("b-pack", RuleSeverity::Info),
+            ("a-pack", RuleSeverity::Error),
+        ] {
+            let mut pack = RulePack::new(name, "Pack");
+            pack.add_rule(RulePackEntry::pattern_rule(
+                "dup",
+                "Duplicate rule",
+                severity,
+                "rust",
+                "$X.unwrap()",
+                "safety",
+            ));
+            registry.register(pack);
+        }
+        assert_eq!(rules_by_id(&registry)["dup"].severity, RuleSeverity::Error);
+    }
+
+    #[test]
+    fn test_relative_uri() {
+        let b = SarifBuilder::new(Path::new("/repo"));
+        assert_eq!(b.relative_uri("/repo/src/lib.rs"), "src/lib.rs");
+        assert_eq!(b.relative_uri("./src/lib.rs"), "src/lib.rs");
+        assert_eq!(b.relative_uri("src\\win.rs"), "src/win.rs");
+        assert_eq!(
+            b.relative_uri("/repo/src/my file#1.rs"),
+            "src/my%20file%231.rs"
+        );
+        assert_eq!(b.relative_uri("docs/café.md"), "docs/caf%C3%A9.md");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_relative_uri_through_symlinked_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("real");
+        std::fs::create_dir(&real).unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+        let canonical = std::fs::canonicalize(&real).unwrap();
+
+        let b = SarifBuilder::new(&link);
+        let via_link = link.join("src/lib.rs");
+        let via_real = canonical.join("src/lib.rs");
+        assert_eq!(b.relative_uri(&via_link.to_string_lossy()), "src/lib.rs");
+        assert_eq!(b.relative_uri(&via_real.to_string_lossy()), "src/lib.rs");
+    }
+
+    #[test]
+    fn test_root_uri_is_percent_encoded() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("my repo");
+        std::fs::create_dir(&root).unwrap();
+        let root = std::fs::canonicalize(&root).unwrap();
+
+        let run = SarifBuilder::new(&root).run("test", None);
+        let uri = &run.original_uri_base_ids[SRCROOT].uri;
+        assert!(uri.starts_with("file:///"), "{uri}");
+        assert!(uri.ends_with("/my%20repo/"), "{uri}");
+        assert!(!uri.contains(' '));
+    }
+
+    #[test]
+    fn test_ast_grep_run() {
+        let registry = RulePackRegistry::with_defaults();
+        let result = AstGrepResult::ok(
+            vec![
+                m("/repo/src/a.rs", Some("no-unwrap"), None),
+                m("/repo/src/b.rs", Some("no-unwrap"), None),
+                m("src/c.rs", Some("external-rule"), Some("hint")),
+            ],
+            5,
+        );
+        let log = SarifBuilder::new(Path::new("/repo"))
+            .add_ast_grep(&result, &registry)
+            .build();
+
+        let run = &log.runs[0];
+        assert_eq!(run.tool.driver.name, "ast-grep");
+        assert_eq!(run.tool.driver.rules.len(), 1);
+        let rule = &run.tool.driver.rules[0];
+        assert_eq!(rule.id, "no-unwrap");
+        assert_eq!(rule.default_configuration.as_ref().unwrap().level, "error");
+        assert_eq!(rule.properties["category"], "safety");
+
+        assert_eq!(run.results.len(), 3);
+        assert_eq!(run.results[1].rule_index, Some(0));
+        assert_eq!(run.results[0].level, "error");
+        assert_eq!(run.results[2].level, "note");
+        assert!(run.results[2].rule_index.is_none());
+        let loc = &run.results[0].locations[0].physical_location;
+        assert_eq!(loc.artifact_location.uri, "src/a.rs");
+        assert_eq!(loc.artifact_location.uri_base_id.as_deref(), Some(SRCROOT));
+        assert_eq!(loc.region.as_ref().unwrap().start_column, Some(9));
+    }
+
+    #[test]
+    fn test_diagnostics_and_review_runs() {
+        let diags = parse_cargo_json(
+            r#"{"reason":"compiler-message","message":{"message":"mismatched types","code":{"code":"E0308"},"level":"error","spans":[{"file_name":"src/main.rs","line_start":2,"line_end":2,"column_start":9,"column_end":12,"is_primary":true}],"children":[]}}"#,
+        );
+        let decision = ReviewDecisionResult::fail(
+            vec![
+                ReviewIssue {
+                    blocking: true,
+                    file: Some("src/a.rs".to_string()),
+                    line: Some(7),
+                    description: "[no-panic] Avoid panic!(): `panic!()`".to_string(),
+                    suggestion: None,
+                },
+                ReviewIssue {
+                    blocking: false,
+                    file: None,
+                    line: None,
+                    description: "high dependency impact".to_string(),
+                    suggestion: Some("check dependents".to_string()),
+                },
+            ],
+            vec!["src/a.rs".to_string()],
+        );
+        let log = SarifBuilder::new(Path::new("/repo"))
+            .add_diagnostics(&diags)
+            .add_review(&decision, &RulePackRegistry::with_defaults())
+            .build();
+
+        assert_eq!(log.runs.len(), 2);
+        let rustc = &log.runs[0];
+        assert_eq!(rustc.results[0].rule_id, "E0308");
+        assert_eq!(rustc.results[0].level, "error");
+        assert_eq!(rustc.tool.driver.rules[0].id, "E0308");
+
+        let review = &log.runs[1];
+        assert_eq!(review.results[0].rule_id, "no-panic");
+        assert_eq!(review.results[0].rule_index, Some(0));
+        assert_eq!(
+            review.results[0].locations[0]
+                .physical_location
+                .region
+                .as_ref()
+                .unwrap()
+                .start_line,
+            7
+        );
+        assert_eq!(review.results[1].rule_id, "review/advisory");
+        assert_eq!(review.results[1].level, "warning");
+        assert!(review.results[1].locations.is_empty());
+        assert!(review.results[1].message.text.contains("check dependents"));
+    }
+
+    #[test]
+    fn test_review_issue_on_line_zero_has_no_region() {
+        let decision = ReviewDecisionResult::fail(
+            vec![ReviewIssue {
+                blocking: true,
+                file: Some("src/a.rs".to_string()),
+                line: Some(0),
+                description: "file-level finding".to_string(),
+                suggestion: None,
+            }],
+            vec!["src/a.rs".to_string()],
+        );
+        let log = SarifBuilder::new(Path::new("/repo"))
+            .add_review(&decision, &RulePackRegistry::new())
+            .build();
+
+        let location = &log.runs[0].results[0].locations[0].physical_location;
+        assert_eq!(location.artifact_location.uri, "src/a.rs");
+        assert!(location.region.is_none());
+    }
+
+    #[test]
+    fn test_json_shape() {
+        let json = SarifBuilder::new(Path::new("/repo"))
+            .add_ast_grep(
+                &AstGrepResult::ok(vec![m("src/a.rs", None, None)], 1),
+                &RulePackRegistry::new(),
+            )
+            .to_json()
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["version"], "2.1.0");
+        assert_eq!(value["$schema"], SARIF_SCHEMA);
+        let run = &value["runs"][0];
+        assert_eq!(run["originalUriBaseIds"]["SRCROOT"]["uri"], "file:///repo/");
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], "pattern");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["artifactLocation"]["uriBaseId"],
+            "SRCROOT"
+        );
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["region"]["startLine"],
+            4
+        );
+        assert!(result.get("ruleIndex").is_none());
+        assert!(run["tool"]["driver"].get("rules").is_none());
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let log = SarifBuilder::new(Path::new("/repo"))
+            .add_diagnostics(&[])
+            .build();
+        let json = serde_json::to_string(&log).unwrap();
+        let parsed: SarifLog = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.runs.len(), 1);
+        assert!(parsed.runs[0].results.is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+//! Command execution for SLURM tooling
+//!
+//! [`SlurmInferenceManager`](super::SlurmInferenceManager) drives the cluster
+//! entirely through CLI tools (`sbatch`, `squeue`, `sacct`, `scancel`, and
+//! `ls`/`cat` on the shared endpoint directory). It runs them through a
+//! [`CommandExecutor`], so the lifecycle logic can be exercised off-cluster
+//! against [`FakeSlurm`](super::fake::FakeSlurm).
+
+use std::process::Command;
+
+/// Captured result of one command
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    /// Exit status was zero
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CommandOutput {
+    /// Successful output with the given stdout
+    pub fn ok(stdout: impl Into<String>) -> Self {
+        Self {
+            success: true,
+            stdout: stdout.into(),
+            stderr: String::new(),
+        }
+    }
+
+    /// Failed output with the given stderr
+    pub fn failed(stderr: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            stdout: String::new(),
+            stderr: stderr.into(),
+        }
+    }
+}
+
+/// Runs commands where the SLURM tools live
+///
+/// `Err` means the command could not be started at all; a command that ran
+/// and exited non-zero is `Ok` with `success == false`.
+pub trait CommandExecutor: Send + Sync {
+    fn execute(&self, program: &str, args: &[&str]) -> std::io::Result<CommandOutput>;
+}
+
+/// Runs commands as local processes, or over SSH on the SLURM controller
+#[derive(Debug, Clone, Default)]
+pub struct ProcessExecutor {
+    ssh_host: Option<String>,
+}
+
+impl ProcessExecutor {
+    /// Run commands on this machine
+    pub fn local() -> Self {
+        Self { ssh_host: None }
+    }
+
+    /// Run commands on `host` via `ssh`
+    ///
+    /// Arguments are individually shell-escaped to prevent command injection
+    /// via metacharacters.
+    pub fn ssh(host: impl Into<String>) -> Self {
+        Self {
+            ssh_host: Some(host.into()),
+        }
+    }
+
+    /// SSH host, if commands run remotely
+    pub fn ssh_host(&self) -> Option<&str> {
+        self.ssh_host.as_deref()
+    }
+}
+
+impl CommandExecutor for ProcessExecutor {
+    fn execute(&self, program: &str, args: &[&str]) -> std::io::Result<CommandOutput> {
+        let output = if let Some(ref host) = self.ssh_host {
+            let full_cmd = crate::shell_safety::build_ssh_command(program, args);
+            Command::new("ssh")
+                .args([host.as_str(), &full_cmd])
+                .output()?
+        } else {
+            Command::new(program).args(args).output()?
+        };
+
+        Ok(CommandOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        })
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_process_output() {
+        let exec = ProcessExecutor::local();
+        assert_eq!(exec.ssh_host(), None);
+
+        let out = exec
+            .execute("sh", &["-c", "echo out; echo err >&2; exit 3"])
+            .unwrap();
+        assert!(!out.success);
+        assert_eq!(out.stdout, "out\n");
+        assert_eq!(out.stderr, "err\n");
+
+        assert_eq!(
+            exec.execute("echo", &["a b"]).unwrap(),
+            CommandOutput::ok("a b\n")
+        );
+        assert!(exec.execute("definitely-not-a-command-xyz", &[]).is_err());
+    }
+}
@@ -0,0 +1,582 @@
+//! In-memory SLURM cluster for off-cluster testing
+//!
+//! [`FakeSlurm`] answers the exact commands [`SlurmInferenceManager`] runs —
+//! `sbatch --parsable`, `squeue -j/-n`, `sacct -j -P`, `scancel`, and
+//! `ls -1t` / `cat` on the endpoint directory — from a simulated queue and
+//! shared filesystem. Tests drive the cluster side by hand:
+//!
+//! ```text
+//! submit ──► PENDING ──start_job──► RUNNING ──preempt_job──► PREEMPTED
+//!               ▲                      │                        │
+//!               └────── requeue_job ───┼────────────────────────┘
+//!                                      └──complete_job──► COMPLETED
+//! ```
+//!
+//! Starting a job writes its endpoint file, as the job script does on the
+//! cluster; the file outlives the job, so stale endpoints are reproducible.
+//! [`fail_next`](FakeSlurm::fail_next) and
+//! [`set_unreachable`](FakeSlurm::set_unreachable) inject command failures.
+//!
+//! [`SlurmInferenceManager`]: super::SlurmInferenceManager
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::executor::{CommandExecutor, CommandOutput};
+use super::{EndpointInfo, JobState};
+
+/// Job name `sbatch` gives submitted jobs (set by `run-qwen35.slurm`)
+pub const DEFAULT_JOB_NAME: &str = "llama-qwen35";
+
+/// First job id handed out
+const FIRST_JOB_ID: u32 = 1000;
+
+/// Error text for an unreachable controller
+const UNREACHABLE: &str = "ssh: connect to host slurm-ctl port 22: Connection refused";
+
+/// A job in the simulated queue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FakeJob {
+    pub job_id: u32,
+    pub name: String,
+    pub script: String,
+    pub state: JobState,
+    pub node: Option<String>,
+}
+
+impl FakeJob {
+    /// Whether `squeue` still lists the job
+    pub fn in_queue(&self) -> bool {
+        matches!(
+            self.state,
+            JobState::Pending | JobState::Running | JobState::Completing | JobState::Suspended
+        )
+    }
+
+    fn row(&self) -> String {
+        format!(
+            "{}|{}|{}|{}|gpu|10:00|UNLIMITED",
+            self.job_id,
+            self.name,
+            state_name(&self.state),
+            self.node.as_deref().unwrap_or("")
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Cluster {
+    next_job_id: u32,
+    jobs: BTreeMap<u32, FakeJob>,
+    /// Path → (write sequence, content); the sequence stands in for mtime
+    files: BTreeMap<String, (u64, String)>,
+    file_seq: u64,
+    job_name: String,
+    auto_start: Option<(String, u16)>,
+    failures: HashMap<String, VecDeque<String>>,
+    unreachable: bool,
+    calls: Vec<String>,
+}
+
+/// Simulated SLURM controller and shared endpoint directory
+#[derive(Debug)]
+pub struct FakeSlurm {
+    endpoints_path: PathBuf,
+    cluster: Mutex<Cluster>,
+}
+
+impl FakeSlurm {
+    /// Empty cluster whose jobs publish endpoints under `endpoints_path`
+    pub fn new(endpoints_path: impl Into<PathBuf>) -> Self {
+        Self {
+            endpoints_path: endpoints_path.into(),
+            cluster: Mutex::new(Cluster {
+                next_job_id: FIRST_JOB_ID,
+                jobs: BTreeMap::new(),
+                files: BTreeMap::new(),
+                file_seq: 0,
+                job_name: DEFAULT_JOB_NAME.to_string(),
+                auto_start: None,
+                failures: HashMap::new(),
+                unreachable: false,
+                calls: Vec::new(),
+            }),
+        }
+    }
+
+    /// Name `sbatch` gives new jobs (default [`DEFAULT_JOB_NAME`])
+    pub fn with_job_name(self, name: impl Into<String>) -> Self {
+        self.lock().job_name = name.into();
+        self
+    }
+
+    /// Start every submitted job at once on `node`, serving on `port`
+    pub fn with_auto_start(self, node: impl Into<String>, port: u16) -> Self {
+        self.lock().auto_start = Some((node.into(), port));
+        self
+    }
+
+    // ── Cluster side ──
+
+    /// Queue a job as if submitted by someone else, returning its id
+    pub fn submit(&self, name: &str) -> u32 {
+        let mut cluster = self.lock();
+        let job_id = cluster.next_job_id;
+        cluster.next_job_id += 1;
+        cluster.jobs.insert(
+            job_id,
+            FakeJob {
+                job_id,
+                name: name.to_string(),
+                script: String::new(),
+                state: JobState::Pending,
+                node: None,
+            },
+        );
+        job_id
+    }
+
+    /// Schedule a job on `node` and publish its endpoint on `127.0.0.1:port`
+    ///
+    /// Jobs on `vasp-02` publish implementer endpoints; all others architect.
+    pub fn start_job(&self, job_id: u32, node: &str, port: u16) {
+        let mut cluster = self.lock();
+        start(&mut cluster, &self.endpoints_path, job_id, node, port);
+    }
+
+    /// Preempt a job; its endpoint file stays behind
+    pub fn preempt_job(&self, job_id: u32) {
+        self.set_state(job_id, JobState::Preempted);
+    }
+
+    /// Put a preempted job back in the queue under the same id
+    pub fn requeue_job(&self, job_id: u32) {
+        let mut cluster = self.lock();
+        let job = job_mut(&mut cluster, job_id);
+        job.state = JobState::Pending;
+        job.node = None;
+    }
+
+    /// Finish a job normally
+    pub fn complete_job(&self, job_id: u32) {
+        self.set_state(job_id, JobState::Completed);
+    }
+
+    /// Force any state transition
+    pub fn set_state(&self, job_id: u32, state: JobState) {
+        job_mut(&mut self.lock(), job_id).state = state;
+    }
+
+    /// Snapshot of a job
+    pub fn job(&self, job_id: u32) -> Option<FakeJob> {
+        self.lock().jobs.get(&job_id).cloned()
+    }
+
+    /// Jobs `squeue` would list
+    pub fn queued_jobs(&self) -> Vec<FakeJob> {
+        self.lock()
+            .jobs
+            .values()
+            .filter(|j| j.in_queue())
+            .cloned()
+            .collect()
+    }
+
+    /// Write a file on the shared filesystem (newest mtime)
+    pub fn write_file(&self, path: impl AsRef<Path>, content: impl Into<String>) {
+        let mut cluster = self.lock();
+        write(&mut cluster, path.as_ref(), content.into());
+    }
+
+    /// Delete a file from the shared filesystem
+    pub fn remove_file(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref().to_string_lossy().to_string();
+        self.lock().files.remove(&path);
+    }
+
+    /// Path of the endpoint file a job on `node` publishes
+    pub fn endpoint_file(&self, job_id: u32, node: &str) -> PathBuf {
+        endpoint_file(&self.endpoints_path, job_id, node)
+    }
+
+    // ── Failure injection ──
+
+    /// Make the next run of `program` exit non-zero with `stderr`
+    pub fn fail_next(&self, program: &str, stderr: impl Into<String>) {
+        self.lock()
+            .failures
+            .entry(program.to_string())
+            .or_default()
+            .push_back(stderr.into());
+    }
+
+    /// Fail every command as if the controller were down
+    pub fn set_unreachable(&self, unreachable: bool) {
+        self.lock().unreachable = unreachable;
+    }
+
+    // ── Inspection ──
+
+    /// Every command run so far, as `program arg…`
+    pub fn calls(&self) -> Vec<String> {
+        self.lock().calls.clone()
+    }
+
+    /// How many times `program` ran
+    pub fn call_count(&self, program: &str) -> usize {
+        self.lock()
+            .calls
+            .iter()
+            .filter(|c| c.split(' ').next() == Some(program))
+            .count()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Cluster> {
+        self.cluster.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl CommandExecutor for FakeSlurm {
+    fn execute(&self, program: &str, args: &[&str]) -> std::io::Result<CommandOutput> {
+        let mut cluster = self.lock();
+        cluster.calls.push(
+            std::iter::once(program)
+                .chain(args.iter().copied())
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+
+        if cluster.unreachable {
+            return Ok(CommandOutput::failed(UNREACHABLE));
+        }
+        if let Some(stderr) = cluster
+            .failures
+            .get_mut(program)
+            .and_then(|queue| queue.pop_front())
+        {
+            return Ok(CommandOutput::failed(stderr));
+        }
+
+        Ok(match program {
+            "sbatch" => self.sbatch(&mut cluster, args),
+            "squeue" => squeue(&cluster, args),
+            "sacct" => sacct(&cluster, args),
+            "scancel" => scancel(&mut cluster, args),
+            "ls" => ls(&cluster, args),
+            "cat" => cat(&cluster, args),
+            other => CommandOutput::failed(format!("bash: {}: command not found", other)),
+        })
+    }
+}
+
+impl FakeSlurm {
+    fn sbatch(&self, cluster: &mut Cluster, args: &[&str]) -> CommandOutput {
+        let Some(script) = args.iter().rev().find(|a| !a.starts_with('-')) else {
+            return CommandOutput::failed("sbatch: error: no batch script specified");
+        };
+        let job_id = cluster.next_job_id;
+        cluster.next_job_id += 1;
+        cluster.jobs.insert(
+            job_id,
+            FakeJob {
+                job_id,
+                name: cluster.job_name.clone(),
+                script: script.to_string(),
+                state: JobState::Pending,
+                node: None,
+            },
+        );
+        if let Some((node, port)) = cluster.auto_start.clone() {
+            start(cluster, &self.endpoints_path, job_id, &node, port);
+        }
+        CommandOutput::ok(format!("{}\n", job_id))
+    }
+}
+
+// ── Command handlers ────────────────────────────────────────────────
+
+fn squeue(cluster: &Cluster, args: &[&str]) -> CommandOutput {
+    let job_filter = flag(args, "-j").and_then(|id| id.parse::<u32>().ok());
+    let names: Option<Vec<&str>> = flag(args, "-n").map(|n| n.split(',').collect());
+
+    let rows: Vec<String> = cluster
+        .jobs
+        .values()
+        .filter(|j| j.in_queue())
+        .filter(|j| job_filter.is_none_or(|id| j.job_id == id))
+        .filter(|j| names.as_ref().is_none_or(|n| n.contains(&j.name.as_str())))
+        .map(FakeJob::row)
+        .collect();
+    CommandOutput::ok(lines(rows))
+}
+
+fn sacct(cluster: &Cluster, args: &[&str]) -> CommandOutput {
+    let Some(job) = flag(args, "-j")
+        .and_then(|id| id.parse::<u32>().ok())
+        .and_then(|id| cluster.jobs.get(&id))
+    else {
+        return CommandOutput::ok("");
+    };
+    // Accounting also lists the batch step, which callers must skip
+    let step = format!(
+        "{}.batch|batch|{}|{}|gpu|10:00|",
+        job.job_id,
+        state_name(&job.state),
+        job.node.as_deref().unwrap_or("")
+    );
+    CommandOutput::ok(lines(vec![job.row(), step]))
+}
+
+fn scancel(cluster: &mut Cluster, args: &[&str]) -> CommandOutput {
+    let id = args.first().and_then(|id| id.parse::<u32>().ok());
+    match id.and_then(|id| cluster.jobs.get_mut(&id)) {
+        Some(job) => {
+            if job.in_queue() {
+                job.state = JobState::Cancelled;
+            }
+            CommandOutput::ok("")
+        }
+        None => CommandOutput::failed(format!(
+            "scancel: error: Kill job error on job id {}: Invalid job id specified",
+            args.first().unwrap_or(&"")
+        )),
+    }
+}
+
+fn ls(cluster: &Cluster, args: &[&str]) -> CommandOutput {
+    let Some(pattern) = args.iter().find(|a| !a.starts_with('-')) else {
+        return CommandOutput::failed("ls: missing operand");
+    };
+    let pattern = Path::new(pattern);
+    let dir = pattern.parent().unwrap_or(Path::new(""));
+    let glob = pattern
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut matches: Vec<(u64, &String)> = cluster
+        .files
+        .iter()
+        .filter(|(path, _)| {
+            let path = Path::new(path.as_str());
+            path.parent() == Some(dir)
+                && path
+                    .file_name()
+                    .is_some_and(|n| glob_match(&glob, &n.to_string_lossy()))
+        })
+        .map(|(path, (seq, _))| (*seq, path))
+        .collect();
+    if matches.is_empty() {
+        return CommandOutput::failed(format!(
+            "ls: cannot access '{}': No such file or directory",
+            pattern.display()
+        ));
+    }
+    // -t: newest first
+    matches.sort_by_key(|m| std::cmp::Reverse(m.0));
+    CommandOutput::ok(lines(matches.into_iter().map(|(_, p)| p.clone()).collect()))
+}
+
+fn cat(cluster: &Cluster, args: &[&str]) -> CommandOutput {
+    let path = args.first().copied().unwrap_or("");
+    match cluster.files.get(path) {
+        Some((_, content)) => CommandOutput::ok(content.clone()),
+        None => CommandOutput::failed(format!("cat: {}: No such file or directory", path)),
+    }
+}
+
+// ── Helpers ─────────────────────────────────────────────────────────
+
+fn start(cluster: &mut Cluster, endpoints_path: &Path, job_id: u32, node: &str, port: u16) {
+    let job = job_mut(cluster, job_id);
+    job.state = JobState::Running;
+    job.node = Some(node.to_string());
+
+    let tier = if node.contains("vasp-02") {
+        "implementer"
+    } else {
+        "architect"
+    };
+    let endpoint = EndpointInfo {
+        job_id,
+        model: "Qwen3.5-397B-A17B".to_string(),
+        tier: tier.to_string(),
+        node: node.to_string(),
+        host: "127.0.0.1".to_string(),
+        port,
+        endpoint: format!("http://127.0.0.1:{}/v1", port),
+        started_at: chrono::Utc::now().to_rfc3339(),
+        head_node: None,
+        rpc_workers: None,
+    };
+    let content = serde_json::to_string(&endpoint).expect("EndpointInfo serializes");
+    write(
+        cluster,
+        &endpoint_file(endpoints_path, job_id, node),
+        content,
+    );
+}
+
+fn endpoint_file(endpoints_path: &Path, job_id: u32, node: &str) -> PathBuf {
+    let suffix = if node.contains("vasp-02") {
+        "qwen35-impl"
+    } else {
+        "qwen35"
+    };
+    endpoints_path.join(format!("{}-{}.json", job_id, suffix))
+}
+
+fn write(cluster: &mut Cluster, path: &Path, content: String) {
+    cluster.file_seq += 1;
+    let seq = cluster.file_seq;
+    cluster
+        .files
+        .insert(path.to_string_lossy().to_string(), (seq, content));
+}
+
+fn job_mut(cluster: &mut Cluster, job_id: u32) -> &mut FakeJob {
+    cluster
+        .jobs
+        .get_mut(&job_id)
+        .unwrap_or_else(|| panic!("FakeSlurm: unknown job {}", job_id))
+}
+
+/// Value following `name` in `args`
+fn flag<'a>(args: &[&'a str], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| *a == name)
+        .and_then(|i| args.get(i + 1))
+        .copied()
+}
+
+fn lines(rows: Vec<String>) -> String {
+    rows.into_iter().map(|r| r + "\n").collect()
+}
+
+fn state_name(state: &JobState) -> String {
+    serde_json::to_value(state)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| "UNKNOWN".to_string())
+}
+
+/// Shell-style match supporting `*` wildcards
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if name.len() < first.len() + last.len() || !name.starts_with(first) || !name.ends_with(last) {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(slurm: &FakeSlurm, program: &str, args: &[&str]) -> CommandOutput {
+        slurm.execute(program, args).unwrap()
+    }
+
+    #[test]
+    fn test_job_lifecycle_is_visible_to_squeue_and_sacct() {
+        let slurm = FakeSlurm::new("/eps");
+        let out = run(
+            &slurm,
+            "sbatch",
+            &["--parsable", "/scripts/run-qwen35.slurm"],
+        );
+        let job_id: u32 = out.stdout.trim().parse().unwrap();
+        let job_arg = job_id.to_string();
+        let squeue_job = ["-j", job_arg.as_str(), "-o", "%i|%j|%T|%N|%P|%M|%l"];
+
+        let row = run(&slurm, "squeue", &squeue_job).stdout;
+        assert!(row.contains("|llama-qwen35|PENDING||"), "{}", row);
+
+        slurm.start_job(job_id, "vasp-01", 8081);
+        assert!(run(&slurm, "squeue", &squeue_job)
+            .stdout
+            .contains("RUNNING|vasp-01"));
+        let listed = run(&slurm, "squeue", &["-n", "llama-worker,llama-qwen35"]).stdout;
+        assert_eq!(listed.lines().count(), 1);
+
+        slurm.preempt_job(job_id);
+        assert!(run(&slurm, "squeue", &squeue_job).stdout.is_empty());
+        let acct = run(&slurm, "sacct", &["-j", &job_arg, "-P"]).stdout;
+        assert!(acct.lines().next().unwrap().contains("|PREEMPTED|"));
+        assert_eq!(acct.lines().count(), 2, "job and batch step");
+
+        slurm.requeue_job(job_id);
+        assert!(run(&slurm, "squeue", &squeue_job)
+            .stdout
+            .contains("PENDING"));
+        slurm.start_job(job_id, "vasp-01", 8081);
+        slurm.complete_job(job_id);
+        assert_eq!(slurm.job(job_id).unwrap().state, JobState::Completed);
+        assert!(run(&slurm, "sacct", &["-j", "999"]).stdout.is_empty());
+    }
+
+    #[test]
+    fn test_endpoint_files_outlive_jobs_and_sort_newest_first() {
+        let slurm = FakeSlurm::new("/eps");
+        let first = slurm.submit(DEFAULT_JOB_NAME);
+        let second = slurm.submit(DEFAULT_JOB_NAME);
+        let impl_job = slurm.submit(DEFAULT_JOB_NAME);
+        slurm.start_job(first, "vasp-01", 1);
+        slurm.start_job(impl_job, "vasp-02", 3);
+        slurm.start_job(second, "vasp-01", 2);
+        slurm.complete_job(first);
+
+        let listed = run(&slurm, "ls", &["-1t", "/eps/*-qwen35.json"]).stdout;
+        let files: Vec<&str> = listed.lines().collect();
+        assert_eq!(
+            files,
+            vec!["/eps/1001-qwen35.json", "/eps/1000-qwen35.json"]
+        );
+
+        let endpoint: EndpointInfo =
+            serde_json::from_str(&run(&slurm, "cat", &[files[0]]).stdout).unwrap();
+        assert_eq!((endpoint.job_id, endpoint.port), (second, 2));
+
+        let impl_files = run(&slurm, "ls", &["-1t", "/eps/*-qwen35-impl.json"]).stdout;
+        assert_eq!(impl_files.trim(), "/eps/1002-qwen35-impl.json");
+        let missing = run(&slurm, "ls", &["-1t", "/other/*.json"]);
+        assert!(!missing.success && missing.stderr.contains("cannot access"));
+    }
+
+    #[test]
+    fn test_failure_injection() {
+        let slurm = FakeSlurm::new("/eps");
+        slurm.fail_next("sbatch", "sbatch: error: QOSMaxSubmitJobPerUserLimit");
+        assert!(!run(&slurm, "sbatch", &["x.slurm"]).success);
+        assert!(run(&slurm, "sbatch", &["x.slurm"]).success, "one-shot");
+
+        slurm.set_unreachable(true);
+        let out = run(&slurm, "squeue", &[]);
+        assert!(!out.success && out.stderr.starts_with("ssh:"));
+        slurm.set_unreachable(false);
+        assert!(!run(&slurm, "scancel", &["4242"]).success);
+        assert_eq!(slurm.call_count("sbatch"), 2);
+        assert_eq!(slurm.calls().len(), 4);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*-qwen35.json", "12-qwen35.json"));
+        assert!(!glob_match("*-qwen35.json", "12-qwen35-impl.json"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("a*b*c", "ac"));
+        assert!(glob_match("exact", "exact"));
+    }
+}
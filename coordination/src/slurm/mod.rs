@@ -2,16 +2,25 @@
 //!
 //! Manages llama.cpp inference server jobs on the beefcake2 cluster.
 //! Handles job submission, health checking, and preemption recovery.
+//!
+//! All cluster access goes through a [`CommandExecutor`]: the default
+//! [`ProcessExecutor`] shells out (over SSH when `slurm_host` is set), and
+//! [`FakeSlurm`] simulates the queue and endpoint directory in memory.
+
+pub mod executor;
+pub mod fake;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::process::Command;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
 
+pub use executor::{CommandExecutor, CommandOutput, ProcessExecutor};
+pub use fake::FakeSlurm;
+
 /// Inference tier for model selection.
 ///
 /// Both tiers run Qwen3.5-397B-A17B on independent single-node instances.
@@ -388,6 +397,8 @@ pub struct SlurmInferenceManager {
     recovery_state: HashMap<InferenceTier, RecoveryState>,
     /// Health check metrics
     health_metrics: Arc<HealthCheckMetrics>,
+    /// Runs SLURM and filesystem commands
+    executor: Arc<dyn CommandExecutor>,
 }
 
 impl SlurmInferenceManager {
@@ -396,6 +407,18 @@ impl SlurmInferenceManager {
     /// Returns an error if the HTTP client cannot be created.
     /// Performs startup job reconciliation to prevent duplicate submissions.
     pub fn new(config: SlurmConfig) -> Result<Self, SlurmError> {
+        let executor = match config.slurm_host {
+            Some(ref host) => ProcessExecutor::ssh(host.clone()),
+            None => ProcessExecutor::local(),
+        };
+        Self::with_executor(config, Arc::new(executor))
+    }
+
+    /// Create a manager that runs cluster commands through `executor`
+    pub fn with_executor(
+        config: SlurmConfig,
+        executor: Arc<dyn CommandExecutor>,
+    ) -> Result<Self, SlurmError> {
         let http_client = reqwest::Client::builder()
             .connect_timeout(config.health_check.connect_timeout)
             .build()
@@ -410,6 +433,7 @@ impl SlurmInferenceManager {
             http_client,
             recovery_state: HashMap::new(),
             health_metrics: Arc::new(HealthCheckMetrics::default()),
+            executor,
         };
 
         // Startup job reconciliation: discover existing inference jobs to prevent duplicates
@@ -474,26 +498,14 @@ impl SlurmInferenceManager {
         Self::new(SlurmConfig::default())
     }
 
-    /// Run a SLURM command, optionally via SSH to slurm-ctl.
-    ///
-    /// When running over SSH, arguments are individually shell-escaped
-    /// to prevent command injection via metacharacters.
+    /// Run a SLURM command through the executor (SSH to slurm-ctl by default).
     fn run_slurm_cmd(&self, cmd: &str, args: &[&str]) -> Result<String, SlurmError> {
-        let output = if let Some(ref host) = self.config.slurm_host {
-            let full_cmd = crate::shell_safety::build_ssh_command(cmd, args);
-            Command::new("ssh")
-                .args([host.as_str(), &full_cmd])
-                .output()?
-        } else {
-            Command::new(cmd).args(args).output()?
-        };
+        let output = self.executor.execute(cmd, args)?;
 
-        if output.status.success() {
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        if output.success {
+            Ok(output.stdout)
         } else {
-            Err(SlurmError::CommandFailed(
-                String::from_utf8_lossy(&output.stderr).to_string(),
-            ))
+            Err(SlurmError::CommandFailed(output.stderr))
         }
     }
 
@@ -1352,4 +1364,360 @@ mod tests {
         assert!(!config.scripts_path.as_os_str().is_empty());
         assert!(!config.endpoints_path.as_os_str().is_empty());
     }
+
+    // ── Lifecycle against FakeSlurm ──
+
+    use std::sync::atomic::AtomicBool;
+
+    const ENDPOINTS: &str = "/fake/endpoints";
+
+    /// Minimal `/health` server answering 200 or 503 depending on the flag
+    fn health_server(healthy: Arc<AtomicBool>) -> u16 {
+        let (url, _) = crate::test_utils::http_server(move |_| {
+            let status = if healthy.load(Ordering::SeqCst) {
+                200
+            } else {
+                503
+            };
+            Some((status, "ok".to_string()))
+        });
+        url.rsplit(':').next().unwrap().parse().unwrap()
+    }
+
+    fn fake_config() -> SlurmConfig {
+        SlurmConfig {
+            scripts_path: PathBuf::from("/fake/scripts"),
+            endpoints_path: PathBuf::from(ENDPOINTS),
+            slurm_host: None,
+            health_check_interval: Duration::from_millis(10),
+            endpoint_timeout: Duration::from_secs(1),
+            health_check: HealthCheckConfig {
+                connect_timeout: Duration::from_millis(500),
+                response_timeout: Duration::from_secs(2),
+                max_retries: 0,
+                retry_backoff: Duration::from_millis(1),
+                min_rpc_workers_healthy: 1,
+                max_consecutive_degraded: 3,
+                max_recovery_attempts: 3,
+            },
+        }
+    }
+
+    fn manager(slurm: &Arc<FakeSlurm>) -> SlurmInferenceManager {
+        SlurmInferenceManager::with_executor(fake_config(), slurm.clone()).unwrap()
+    }
+
+    /// A healthy server plus a cluster that starts submitted jobs on vasp-01
+    fn auto_cluster() -> (Arc<FakeSlurm>, Arc<AtomicBool>, u16) {
+        let healthy = Arc::new(AtomicBool::new(true));
+        let port = health_server(healthy.clone());
+        let slurm = Arc::new(FakeSlurm::new(ENDPOINTS).with_auto_start("vasp-01", port));
+        (slurm, healthy, port)
+    }
+
+    fn is_waiting(result: Result<EndpointInfo, SlurmError>) -> bool {
+        matches!(result, Err(SlurmError::EndpointTimeout(_)))
+    }
+
+    #[tokio::test]
+    async fn test_cold_start_submits_once_and_caches() {
+        let (slurm, _healthy, port) = auto_cluster();
+        let mut mgr = manager(&slurm);
+
+        let endpoint = mgr.ensure_running(InferenceTier::Architect).await.unwrap();
+        assert_eq!(endpoint.port, port);
+        assert_eq!(mgr.active_jobs[&InferenceTier::Architect], endpoint.job_id);
+        assert!(slurm
+            .calls()
+            .contains(&"sbatch --parsable /fake/scripts/run-qwen35.slurm".to_string()));
+
+        let again = mgr.ensure_running(InferenceTier::Architect).await.unwrap();
+        assert_eq!(again.job_id, endpoint.job_id);
+        assert_eq!(slurm.call_count("sbatch"), 1);
+        assert_eq!(
+            mgr.get_recovery_state(InferenceTier::Architect),
+            RecoveryState::Stable
+        );
+        assert_eq!(mgr.health_metrics_snapshot().healthy, 2);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_tracks_active_jobs_by_node() {
+        let slurm = Arc::new(FakeSlurm::new(ENDPOINTS));
+        let architect = slurm.submit("llama-qwen35");
+        let implementer = slurm.submit("llama-qwen35");
+        let finished = slurm.submit("llama-qwen35");
+        let foreign = slurm.submit("llama-worker");
+        slurm.start_job(architect, "vasp-01", 1);
+        slurm.start_job(implementer, "vasp-02", 2);
+        slurm.start_job(finished, "vasp-03", 3);
+        slurm.complete_job(finished);
+
+        let mgr = manager(&slurm);
+        assert_eq!(mgr.active_jobs.len(), 2);
+        assert_eq!(mgr.active_jobs[&InferenceTier::Architect], architect);
+        assert_eq!(mgr.active_jobs[&InferenceTier::Implementer], implementer);
+        assert!(!mgr.active_jobs.values().any(|id| *id == foreign));
+    }
+
+    #[tokio::test]
+    async fn test_pending_job_is_not_duplicated() {
+        let healthy = Arc::new(AtomicBool::new(true));
+        let port = health_server(healthy);
+        let slurm = Arc::new(FakeSlurm::new(ENDPOINTS));
+        let job = slurm.submit("llama-qwen35");
+        let mut mgr = manager(&slurm);
+
+        for _ in 0..3 {
+            assert!(is_waiting(
+                mgr.ensure_running(InferenceTier::Architect).await
+            ));
+        }
+        assert_eq!(slurm.call_count("sbatch"), 0);
+
+        slurm.start_job(job, "vasp-01", port);
+        let endpoint = mgr.ensure_running(InferenceTier::Architect).await.unwrap();
+        assert_eq!(endpoint.job_id, job);
+        assert_eq!(slurm.call_count("sbatch"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_preempted_cached_endpoint_is_replaced() {
+        let (slurm, _healthy, _port) = auto_cluster();
+        let mut mgr = manager(&slurm);
+        let first = mgr.ensure_running(InferenceTier::Architect).await.unwrap();
+
+        slurm.preempt_job(first.job_id);
+        let second = mgr.ensure_running(InferenceTier::Architect).await.unwrap();
+
+        assert_ne!(second.job_id, first.job_id);
+        let queued: Vec<u32> = slurm.queued_jobs().iter().map(|j| j.job_id).collect();
+        assert_eq!(queued, vec![second.job_id], "exactly one live job");
+        assert_eq!(mgr.active_jobs[&InferenceTier::Architect], second.job_id);
+        assert_eq!(
+            mgr.get_recovery_state(InferenceTier::Architect),
+            RecoveryState::Stable
+        );
+        assert!(mgr.health_metrics_snapshot().stale >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_requeued_job_is_waited_on_after_restart() {
+        let healthy = Arc::new(AtomicBool::new(true));
+        let port = health_server(healthy);
+        let slurm = Arc::new(FakeSlurm::new(ENDPOINTS));
+        let job = slurm.submit("llama-qwen35");
+        slurm.start_job(job, "vasp-01", port);
+        slurm.preempt_job(job);
+        slurm.requeue_job(job);
+
+        // A fresh manager (e.g. after a coordinator restart) finds the requeued job
+        let mut mgr = manager(&slurm);
+        assert_eq!(mgr.active_jobs[&InferenceTier::Architect], job);
+        assert!(is_waiting(
+            mgr.ensure_running(InferenceTier::Architect).await
+        ));
+
+        // The old endpoint file is still there but must not be trusted
+        assert!(mgr
+            .discover_endpoint(InferenceTier::Architect)
+            .unwrap()
+            .is_none());
+
+        slurm.start_job(job, "vasp-01", port);
+        let endpoint = mgr.ensure_running(InferenceTier::Architect).await.unwrap();
+        assert_eq!(endpoint.job_id, job);
+        assert_eq!(slurm.call_count("sbatch"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_preempted_tracked_job_is_resubmitted() {
+        let (slurm, _healthy, port) = auto_cluster();
+        let job = slurm.submit("llama-qwen35");
+        slurm.start_job(job, "vasp-01", port);
+        let mut mgr = manager(&slurm);
+        assert_eq!(mgr.active_jobs[&InferenceTier::Architect], job);
+
+        // Preempted without requeue: SLURM will not bring it back
+        slurm.preempt_job(job);
+        let endpoint = mgr.ensure_running(InferenceTier::Architect).await.unwrap();
+
+        assert_ne!(endpoint.job_id, job);
+        assert_eq!(slurm.call_count("sbatch"), 1);
+        assert_eq!(slurm.queued_jobs().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unhealthy_endpoint_escalates_to_forced_restart() {
+        let (slurm, healthy, _port) = auto_cluster();
+        let mut mgr = manager(&slurm);
+        let first = mgr.ensure_running(InferenceTier::Architect).await.unwrap();
+
+        healthy.store(false, Ordering::SeqCst);
+        // Cached endpoint and rediscovered endpoint both fail: two attempts
+        assert!(is_waiting(
+            mgr.ensure_running(InferenceTier::Architect).await
+        ));
+        assert_eq!(
+            mgr.get_recovery_state(InferenceTier::Architect),
+            RecoveryState::Recovering { attempts: 2 }
+        );
+        assert!(mgr.get_cached_endpoint(InferenceTier::Architect).is_none());
+        assert_eq!(slurm.job(first.job_id).unwrap().state, JobState::Running);
+
+        // Third failure hits max_recovery_attempts: cancel and reset
+        assert!(is_waiting(
+            mgr.ensure_running(InferenceTier::Architect).await
+        ));
+        assert_eq!(slurm.job(first.job_id).unwrap().state, JobState::Cancelled);
+        assert_eq!(slurm.call_count("scancel"), 1);
+        assert_eq!(
+            mgr.get_recovery_state(InferenceTier::Architect),
+            RecoveryState::Stable
+        );
+        assert!(!mgr.active_jobs.contains_key(&InferenceTier::Architect));
+
+        healthy.store(true, Ordering::SeqCst);
+        let second = mgr.ensure_running(InferenceTier::Architect).await.unwrap();
+        assert_ne!(second.job_id, first.job_id);
+        assert_eq!(slurm.call_count("sbatch"), 2);
+        assert!(mgr.health_metrics_snapshot().unhealthy >= 3);
+    }
+
+    #[tokio::test]
+    async fn test_untrustworthy_endpoint_files_are_ignored() {
+        let slurm = Arc::new(FakeSlurm::new(ENDPOINTS));
+        let mut mgr = manager(&slurm);
+
+        // Left behind by a finished job
+        let finished = slurm.submit("llama-qwen35");
+        slurm.start_job(finished, "vasp-01", 1);
+        slurm.complete_job(finished);
+        assert!(mgr
+            .discover_endpoint(InferenceTier::Architect)
+            .unwrap()
+            .is_none());
+
+        // Points at a job SLURM has never heard of
+        let mut orphan: EndpointInfo = serde_json::from_str(
+            &slurm
+                .execute(
+                    "cat",
+                    &[&slurm.endpoint_file(finished, "vasp-01").to_string_lossy()],
+                )
+                .unwrap()
+                .stdout,
+        )
+        .unwrap();
+        orphan.job_id = 4242;
+        slurm.write_file(
+            format!("{}/4242-qwen35.json", ENDPOINTS),
+            serde_json::to_string(&orphan).unwrap(),
+        );
+        assert!(mgr
+            .discover_endpoint(InferenceTier::Architect)
+            .unwrap()
+            .is_none());
+
+        // Half-written by a job that is still starting up
+        slurm.write_file(format!("{}/9999-qwen35.json", ENDPOINTS), "{\"job_id\":");
+        assert!(mgr
+            .discover_endpoint(InferenceTier::Architect)
+            .unwrap()
+            .is_none());
+
+        // Implementer files never satisfy the architect tier
+        let implementer = slurm.submit("llama-qwen35");
+        slurm.start_job(implementer, "vasp-02", 2);
+        assert!(mgr
+            .discover_endpoint(InferenceTier::Architect)
+            .unwrap()
+            .is_none());
+        let found = mgr.discover_endpoint(InferenceTier::Implementer).unwrap();
+        assert_eq!(found.unwrap().job_id, implementer);
+        assert!(mgr.get_cached_endpoint(InferenceTier::Architect).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_discovery_errors_block_submission() {
+        let (slurm, _healthy, _port) = auto_cluster();
+        let mut mgr = manager(&slurm);
+
+        slurm.fail_next(
+            "ls",
+            "ls: cannot open directory '/fake/endpoints': Permission denied",
+        );
+        let err = mgr
+            .ensure_running(InferenceTier::Architect)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SlurmError::CommandFailed(ref m) if m.contains("Permission denied")));
+        assert_eq!(slurm.call_count("sbatch"), 0);
+
+        // Once the share is readable again the tier comes up normally
+        assert!(mgr.ensure_running(InferenceTier::Architect).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_controller() {
+        let (slurm, _healthy, _port) = auto_cluster();
+        slurm.set_unreachable(true);
+
+        // Startup reconciliation failure is not fatal
+        let mut mgr = manager(&slurm);
+        assert!(mgr.active_jobs.is_empty());
+
+        let err = mgr
+            .ensure_running(InferenceTier::Architect)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SlurmError::CommandFailed(ref m) if m.contains("ssh:")));
+        assert_eq!(slurm.call_count("sbatch"), 0);
+
+        slurm.set_unreachable(false);
+        mgr.reconcile_active_jobs().unwrap();
+        assert!(mgr.ensure_running(InferenceTier::Architect).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rejected_submission_is_not_tracked() {
+        let (slurm, _healthy, _port) = auto_cluster();
+        let mut mgr = manager(&slurm);
+
+        slurm.fail_next("sbatch", "sbatch: error: QOSMaxSubmitJobPerUserLimit");
+        let err = mgr
+            .ensure_running(InferenceTier::Architect)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SlurmError::CommandFailed(ref m) if m.contains("QOS")));
+        assert!(mgr.active_jobs.is_empty());
+        assert!(slurm.queued_jobs().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_job_that_never_starts_times_out_without_resubmitting() {
+        let slurm = Arc::new(FakeSlurm::new(ENDPOINTS));
+        let config = SlurmConfig {
+            endpoint_timeout: Duration::ZERO,
+            ..fake_config()
+        };
+        let mut mgr = SlurmInferenceManager::with_executor(config, slurm.clone()).unwrap();
+
+        let err = mgr
+            .ensure_running(InferenceTier::Architect)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SlurmError::EndpointTimeout(d) if d.is_zero()));
+        let job = mgr.active_jobs[&InferenceTier::Architect];
+        assert_eq!(slurm.job(job).unwrap().state, JobState::Pending);
+
+        assert!(is_waiting(
+            mgr.ensure_running(InferenceTier::Architect).await
+        ));
+        assert_eq!(slurm.call_count("sbatch"), 1);
+
+        mgr.cancel_job(job).unwrap();
+        assert_eq!(slurm.job(job).unwrap().state, JobState::Cancelled);
+        assert!(mgr.active_jobs.is_empty());
+    }
 }
@@ -0,0 +1,379 @@
+//! Storage-agnostic interface for ensemble state
+//!
+//! [`StateBackend`] is the contract every state store implements: sessions,
+//! tasks, results, votes, shared context and the time-ordered event log.
+//! Consumers (the event bus, history replay) hold a [`SharedStateStore`] and
+//! never see which backend is underneath.
+//!
+//! Backends:
+//! - `StateStore` (`heavy-state`): RocksDB column families, bincode values.
+//! - [`FileStateStore`](super::FileStateStore) (`full`): in-memory tables,
+//!   optionally persisted to an append-only JSON-lines log.
+//!
+//! Event payloads cross the trait as JSON bytes so the trait stays object
+//! safe; [`StateBackendExt`] adds the typed `put_event` / `get_events_range`.
+
+use std::sync::Arc;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::types::*;
+
+/// Error type for state store operations
+#[derive(Debug, thiserror::Error)]
+pub enum StoreError {
+    #[cfg(feature = "heavy-state")]
+    #[error("RocksDB error: {0}")]
+    RocksDb(#[from] rocksdb::Error),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+
+    #[error("Deserialization error: {0}")]
+    Deserialization(String),
+
+    #[error("Key not found: {0}")]
+    NotFound(String),
+
+    #[error("Lock poisoned")]
+    LockPoisoned,
+
+    #[error("Column family not found: {0}")]
+    ColumnFamilyNotFound(String),
+
+    #[error("Schema version {found} is newer than supported version {supported}")]
+    SchemaTooNew { found: u32, supported: u32 },
+
+    #[error("Migration to schema v{version} failed: {reason}")]
+    Migration { version: u32, reason: String },
+}
+
+/// Result type for state store operations
+pub type StoreResult<T> = Result<T, StoreError>;
+
+/// Shared reference to a state backend
+pub type SharedStateStore = Arc<dyn StateBackend>;
+
+/// Persistent ensemble state, independent of the storage engine
+///
+/// Event ranges are inclusive on both ends and returned oldest first;
+/// `prune_events_before` removes events strictly older than the timestamp.
+pub trait StateBackend: Send + Sync {
+    /// Short backend name for logs ("rocksdb", "file", "memory")
+    fn backend_name(&self) -> &'static str;
+
+    // ── Sessions ──
+
+    /// Store an ensemble session
+    fn put_session(&self, session: &EnsembleSession) -> StoreResult<()>;
+
+    /// Get an ensemble session by ID
+    fn get_session(&self, session_id: &str) -> StoreResult<Option<EnsembleSession>>;
+
+    /// List all sessions, newest first
+    fn list_sessions(&self) -> StoreResult<Vec<EnsembleSession>>;
+
+    /// Get the most recently updated active session
+    fn get_active_session(&self) -> StoreResult<Option<EnsembleSession>> {
+        Ok(self
+            .list_sessions()?
+            .into_iter()
+            .filter(|s| s.active)
+            .max_by(|a, b| a.updated_at.cmp(&b.updated_at)))
+    }
+
+    // ── Tasks ──
+
+    /// Store an ensemble task
+    fn put_task(&self, task: &EnsembleTask) -> StoreResult<()>;
+
+    /// Get a task by ID
+    fn get_task(&self, task_id: &str) -> StoreResult<Option<EnsembleTask>>;
+
+    /// Get tasks for a session
+    fn get_session_tasks(&self, session_id: &str) -> StoreResult<Vec<EnsembleTask>>;
+
+    /// List every task in key order
+    fn list_tasks(&self) -> StoreResult<Vec<EnsembleTask>>;
+
+    /// Get pending tasks for a session
+    fn get_pending_tasks(&self, session_id: &str) -> StoreResult<Vec<EnsembleTask>> {
+        Ok(self
+            .get_session_tasks(session_id)?
+            .into_iter()
+            .filter(|t| matches!(t.status, TaskStatus::Pending | TaskStatus::InProgress))
+            .collect())
+    }
+
+    // ── Results ──
+
+    /// Store a model result
+    fn put_result(&self, result: &ModelResult) -> StoreResult<()>;
+
+    /// Get a specific model's result for a task
+    fn get_result(&self, task_id: &str, model_id: &ModelId) -> StoreResult<Option<ModelResult>>;
+
+    /// Get all results for a task
+    fn get_task_results(&self, task_id: &str) -> StoreResult<Vec<ModelResult>>;
+
+    /// List every result in key order
+    fn list_results(&self) -> StoreResult<Vec<ModelResult>>;
+
+    // ── Votes ──
+
+    /// Store a vote record
+    fn put_vote(&self, vote: &VoteRecord) -> StoreResult<()>;
+
+    /// Get a vote record for a task
+    fn get_vote(&self, task_id: &str) -> StoreResult<Option<VoteRecord>>;
+
+    /// List every vote record in key order
+    fn list_votes(&self) -> StoreResult<Vec<VoteRecord>>;
+
+    // ── Context ──
+
+    /// Store shared context
+    fn put_context(&self, context: &SharedContext) -> StoreResult<()>;
+
+    /// Get shared context for a session
+    fn get_context(&self, session_id: &str) -> StoreResult<Option<SharedContext>>;
+
+    /// List every shared context in key order
+    fn list_contexts(&self) -> StoreResult<Vec<SharedContext>>;
+
+    /// Get or create context for a session
+    fn get_or_create_context(&self, session_id: &str) -> StoreResult<SharedContext> {
+        match self.get_context(session_id)? {
+            Some(ctx) => Ok(ctx),
+            None => {
+                let ctx = SharedContext::new(session_id.to_string());
+                self.put_context(&ctx)?;
+                Ok(ctx)
+            }
+        }
+    }
+
+    // ── Events ──
+
+    /// Store a JSON-encoded event
+    fn put_event_raw(&self, timestamp_nanos: i64, event_id: &str, json: &[u8]) -> StoreResult<()>;
+
+    /// Get JSON-encoded events with `start <= timestamp <= end`
+    fn get_events_range_raw(
+        &self,
+        start_nanos: i64,
+        end_nanos: i64,
+    ) -> StoreResult<Vec<(i64, Vec<u8>)>>;
+
+    /// Delete events older than a timestamp, returning how many were removed
+    fn prune_events_before(&self, timestamp_nanos: i64) -> StoreResult<usize>;
+}
+
+/// Typed event helpers for any [`StateBackend`]
+pub trait StateBackendExt {
+    /// Store an event (serialized as JSON for debuggability)
+    fn put_event(
+        &self,
+        timestamp_nanos: i64,
+        event_id: &str,
+        event: &impl Serialize,
+    ) -> StoreResult<()>;
+
+    /// Get events in a time range
+    fn get_events_range<T: DeserializeOwned>(
+        &self,
+        start_nanos: i64,
+        end_nanos: i64,
+    ) -> StoreResult<Vec<(i64, T)>>;
+}
+
+impl<B: StateBackend + ?Sized> StateBackendExt for B {
+    fn put_event(
+        &self,
+        timestamp_nanos: i64,
+        event_id: &str,
+        event: &impl Serialize,
+    ) -> StoreResult<()> {
+        let bytes =
+            serde_json::to_vec(event).map_err(|e| StoreError::Serialization(e.to_string()))?;
+        self.put_event_raw(timestamp_nanos, event_id, &bytes)
+    }
+
+    fn get_events_range<T: DeserializeOwned>(
+        &self,
+        start_nanos: i64,
+        end_nanos: i64,
+    ) -> StoreResult<Vec<(i64, T)>> {
+        self.get_events_range_raw(start_nanos, end_nanos)?
+            .into_iter()
+            .map(|(ts, bytes)| {
+                serde_json::from_slice(&bytes)
+                    .map(|event| (ts, event))
+                    .map_err(|e| StoreError::Deserialization(e.to_string()))
+            })
+            .collect()
+    }
+}
+
+/// Behavioural checks every backend must pass
+#[cfg(test)]
+pub(crate) mod conformance {
+    use super::*;
+
+    /// Run the whole suite against a fresh, empty backend
+    pub fn run(store: &dyn StateBackend) {
+        sessions(store);
+        tasks(store);
+        results(store);
+        votes(store);
+        contexts(store);
+        events(store);
+    }
+
+    fn sessions(store: &dyn StateBackend) {
+        assert!(store.get_session("missing").unwrap().is_none());
+        assert!(store.get_active_session().unwrap().is_none());
+
+        let first = EnsembleSession::new();
+        store.put_session(&first).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let mut second = EnsembleSession::new().with_harness("harness-1".to_string());
+        store.put_session(&second).unwrap();
+
+        let listed = store.list_sessions().unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].id, second.id, "newest first");
+        assert_eq!(store.get_active_session().unwrap().unwrap().id, second.id);
+
+        // Overwrite: deactivating the newest exposes the older one
+        second.active = false;
+        store.put_session(&second).unwrap();
+        assert_eq!(store.list_sessions().unwrap().len(), 2);
+        assert_eq!(store.get_active_session().unwrap().unwrap().id, first.id);
+        let fetched = store.get_session(&second.id).unwrap().unwrap();
+        assert!(!fetched.active);
+        assert_eq!(fetched.harness_session_id.as_deref(), Some("harness-1"));
+    }
+
+    fn tasks(store: &dyn StateBackend) {
+        let mut done = EnsembleTask::new("sess-a".to_string(), "first".to_string(), true);
+        done.status = TaskStatus::Completed;
+        let pending = EnsembleTask::new("sess-a".to_string(), "second".to_string(), false);
+        let other = EnsembleTask::new("sess-b".to_string(), "third".to_string(), false);
+        for task in [&done, &pending, &other] {
+            store.put_task(task).unwrap();
+        }
+
+        assert_eq!(
+            store.get_task(&pending.id).unwrap().unwrap().prompt,
+            "second"
+        );
+        assert!(store.get_task("missing").unwrap().is_none());
+        assert_eq!(store.get_session_tasks("sess-a").unwrap().len(), 2);
+        let pending_ids: Vec<String> = store
+            .get_pending_tasks("sess-a")
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(pending_ids, vec![pending.id.clone()]);
+        assert_eq!(store.list_tasks().unwrap().len(), 3);
+    }
+
+    fn results(store: &dyn StateBackend) {
+        let opus = ModelResult::new(
+            "task-r".to_string(),
+            ModelId::OPUS_45,
+            "opus answer".to_string(),
+            100,
+            500,
+        )
+        .with_confidence(0.9);
+        let qwen = ModelResult::new(
+            "task-r".to_string(),
+            ModelId::QWEN35,
+            "qwen answer".to_string(),
+            80,
+            900,
+        );
+        // A task id sharing a prefix must not leak into the results
+        let other = ModelResult::new(
+            "task-r2".to_string(),
+            ModelId::OPUS_45,
+            "other".to_string(),
+            1,
+            1,
+        );
+        for result in [&opus, &qwen, &other] {
+            store.put_result(result).unwrap();
+        }
+
+        let fetched = store
+            .get_result("task-r", &ModelId::OPUS_45)
+            .unwrap()
+            .unwrap();
+        assert_eq!(fetched.response, "opus answer");
+        assert!((fetched.confidence - 0.9).abs() < f32::EPSILON);
+        assert!(store
+            .get_result("task-r", &ModelId::HYDRA_CODER)
+            .unwrap()
+            .is_none());
+        assert_eq!(store.get_task_results("task-r").unwrap().len(), 2);
+        assert_eq!(store.list_results().unwrap().len(), 3);
+    }
+
+    fn votes(store: &dyn StateBackend) {
+        let mut vote = VoteRecord::new("task-v".to_string(), VotingStrategy::Weighted);
+        vote.add_vote(ModelId::OPUS_45, ModelId::QWEN35, 0.9);
+        vote.set_winner(ModelId::QWEN35);
+        store.put_vote(&vote).unwrap();
+
+        let fetched = store.get_vote("task-v").unwrap().unwrap();
+        assert_eq!(fetched.strategy, VotingStrategy::Weighted);
+        assert_eq!(fetched.winner, Some(ModelId::QWEN35));
+        assert_eq!(fetched.votes[&ModelId::OPUS_45], (ModelId::QWEN35, 0.9));
+        assert!(store.get_vote("missing").unwrap().is_none());
+        assert_eq!(store.list_votes().unwrap().len(), 1);
+    }
+
+    fn contexts(store: &dyn StateBackend) {
+        let created = store.get_or_create_context("sess-c").unwrap();
+        assert_eq!(created.version, 0);
+
+        let mut ctx = created;
+        ctx.update_summary("summary".to_string());
+        ctx.add_file_reference("src/lib.rs".to_string());
+        store.put_context(&ctx).unwrap();
+
+        let fetched = store.get_or_create_context("sess-c").unwrap();
+        assert_eq!(fetched.version, 2);
+        assert_eq!(fetched.summary, "summary");
+        assert_eq!(fetched.file_references, vec!["src/lib.rs".to_string()]);
+        assert_eq!(store.list_contexts().unwrap().len(), 1);
+    }
+
+    fn events(store: &dyn StateBackend) {
+        for (ts, id) in [(300, "c"), (100, "a"), (200, "b"), (200, "b2")] {
+            store
+                .put_event(ts, id, &serde_json::json!({ "id": id }))
+                .unwrap();
+        }
+
+        let all: Vec<(i64, serde_json::Value)> = store.get_events_range(0, i64::MAX).unwrap();
+        let order: Vec<i64> = all.iter().map(|(ts, _)| *ts).collect();
+        assert_eq!(order, vec![100, 200, 200, 300]);
+
+        // Both bounds are inclusive
+        let window: Vec<(i64, serde_json::Value)> = store.get_events_range(200, 300).unwrap();
+        assert_eq!(window.len(), 3);
+        assert_eq!(window[2].1["id"], "c");
+
+        assert_eq!(store.prune_events_before(200).unwrap(), 1);
+        assert_eq!(store.prune_events_before(200).unwrap(), 0);
+        let left: Vec<(i64, serde_json::Value)> = store.get_events_range(0, i64::MAX).unwrap();
+        assert_eq!(left.len(), 3);
+    }
+}
@@ -0,0 +1,442 @@
+//! Model catalog: which models take part and how they behave
+//!
+//! The catalog is data, not code. It is read from TOML or YAML at startup
+//! (see `config/models.toml`) and lists each model's kind, API name,
+//! endpoint, context window, voting weight, costs and capabilities.
+//! [`ModelId`] methods such as `weight()` and `kind()` read from the
+//! installed catalog, so swapping a model is a config edit.
+//!
+//! ```ignore
+//! let catalog = ModelCatalog::load("config/models.toml")?;
+//! catalog.install(); // once, at startup
+//! let strategist = ModelCatalog::active().resolve("qwen35")?;
+//! ```
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use super::types::{ModelId, ModelKind};
+
+/// Catalog compiled into the binary, used until another is installed
+pub const BUILTIN_CATALOG_TOML: &str = include_str!("../../../config/models.toml");
+
+/// Error type for catalog loading and lookup
+#[derive(Debug, thiserror::Error)]
+pub enum CatalogError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse catalog: {0}")]
+    Parse(String),
+
+    #[error("Invalid catalog: {0}")]
+    Invalid(String),
+
+    #[error("Unknown model: {0}")]
+    UnknownModel(String),
+}
+
+/// Result type for catalog operations
+pub type CatalogResult<T> = Result<T, CatalogError>;
+
+/// Price per million tokens in USD (0 when untracked)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelCosts {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
+
+impl ModelCosts {
+    /// Cost of one call in USD
+    pub fn cost(&self, input_tokens: u64, output_tokens: u64) -> f64 {
+        (input_tokens as f64 * self.input_per_mtok + output_tokens as f64 * self.output_per_mtok)
+            / 1_000_000.0
+    }
+}
+
+/// Features a model supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelCapabilities {
+    pub streaming: bool,
+    pub tool_calls: bool,
+    pub reasoning: bool,
+}
+
+impl Default for ModelCapabilities {
+    fn default() -> Self {
+        Self {
+            streaming: true,
+            tool_calls: false,
+            reasoning: false,
+        }
+    }
+}
+
+fn default_max_output_tokens() -> u32 {
+    4_096
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+/// One catalog entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelSpec {
+    /// Stable id used in persisted state and events
+    pub id: String,
+    pub kind: ModelKind,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Model name sent in API requests
+    pub api_name: String,
+    /// Base URL, when not the provider default
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Runs on our own hardware (vs cloud API)
+    #[serde(default)]
+    pub local: bool,
+    pub context_window: u32,
+    #[serde(default = "default_max_output_tokens")]
+    pub max_output_tokens: u32,
+    /// Voting weight
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    #[serde(default)]
+    pub costs: ModelCosts,
+    #[serde(default)]
+    pub capabilities: ModelCapabilities,
+    /// Approximate generation speed (local models)
+    #[serde(default)]
+    pub tokens_per_sec: Option<u32>,
+    #[serde(default)]
+    pub specialty: String,
+    /// Other ids that resolve to this model
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+impl ModelSpec {
+    /// The model's id
+    pub fn model_id(&self) -> ModelId {
+        ModelId::intern(&self.id)
+    }
+
+    /// Display name, falling back to the id
+    pub fn name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.id)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CatalogFile {
+    #[serde(default)]
+    default_model: Option<String>,
+    models: Vec<ModelSpec>,
+}
+
+/// Validated set of models
+#[derive(Debug, Clone)]
+pub struct ModelCatalog {
+    models: Vec<ModelSpec>,
+    default_model: ModelId,
+    /// Ids and aliases → position in `models`
+    index: HashMap<String, usize>,
+}
+
+static ACTIVE: OnceLock<RwLock<Arc<ModelCatalog>>> = OnceLock::new();
+
+fn active_slot() -> &'static RwLock<Arc<ModelCatalog>> {
+    ACTIVE.get_or_init(|| RwLock::new(Arc::new(ModelCatalog::builtin())))
+}
+
+impl ModelCatalog {
+    /// Build a catalog from specs; the first manager is the default model
+    pub fn new(models: Vec<ModelSpec>) -> CatalogResult<Self> {
+        Self::build(models, None)
+    }
+
+    /// The catalog compiled into the binary
+    pub fn builtin() -> Self {
+        Self::from_toml_str(BUILTIN_CATALOG_TOML).expect("built-in model catalog is valid")
+    }
+
+    /// Parse a TOML catalog
+    pub fn from_toml_str(source: &str) -> CatalogResult<Self> {
+        let file: CatalogFile =
+            toml::from_str(source).map_err(|e| CatalogError::Parse(e.to_string()))?;
+        Self::build(file.models, file.default_model)
+    }
+
+    /// Parse a YAML catalog
+    pub fn from_yaml_str(source: &str) -> CatalogResult<Self> {
+        let file: CatalogFile =
+            serde_yaml::from_str(source).map_err(|e| CatalogError::Parse(e.to_string()))?;
+        Self::build(file.models, file.default_model)
+    }
+
+    /// Load a catalog file; `.yaml`/`.yml` are YAML, anything else TOML
+    pub fn load(path: impl AsRef<Path>) -> CatalogResult<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml" | "yml") => Self::from_yaml_str(&source),
+            _ => Self::from_toml_str(&source),
+        }
+    }
+
+    fn build(models: Vec<ModelSpec>, default_model: Option<String>) -> CatalogResult<Self> {
+        if models.is_empty() {
+            return Err(CatalogError::Invalid("catalog lists no models".to_string()));
+        }
+
+        let mut index = HashMap::new();
+        for (position, spec) in models.iter().enumerate() {
+            for name in std::iter::once(&spec.id).chain(&spec.aliases) {
+                ModelId::validate(name).map_err(|e| CatalogError::Invalid(e.to_string()))?;
+                if index.insert(name.clone(), position).is_some() {
+                    return Err(CatalogError::Invalid(format!(
+                        "'{}' is used by more than one model",
+                        name
+                    )));
+                }
+            }
+            if !(spec.weight.is_finite() && spec.weight > 0.0) {
+                return Err(CatalogError::Invalid(format!(
+                    "{}: weight must be positive, got {}",
+                    spec.id, spec.weight
+                )));
+            }
+            if spec.context_window == 0 {
+                return Err(CatalogError::Invalid(format!(
+                    "{}: context_window must be positive",
+                    spec.id
+                )));
+            }
+            if spec.api_name.trim().is_empty() {
+                return Err(CatalogError::Invalid(format!(
+                    "{}: api_name is empty",
+                    spec.id
+                )));
+            }
+        }
+
+        let default_model = match default_model {
+            Some(name) => {
+                let position = *index.get(&name).ok_or_else(|| {
+                    CatalogError::Invalid(format!("default_model '{}' is not in the catalog", name))
+                })?;
+                models[position].model_id()
+            }
+            None => models
+                .iter()
+                .find(|m| m.kind == ModelKind::Manager)
+                .unwrap_or(&models[0])
+                .model_id(),
+        };
+
+        Ok(Self {
+            models,
+            default_model,
+            index,
+        })
+    }
+
+    /// The installed catalog (the built-in one until [`install`](Self::install))
+    pub fn active() -> Arc<ModelCatalog> {
+        Arc::clone(&active_slot().read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Make this the catalog behind [`ModelId`] lookups
+    ///
+    /// Returns the previously installed catalog.
+    pub fn install(self) -> Arc<ModelCatalog> {
+        let mut slot = active_slot().write().unwrap_or_else(|e| e.into_inner());
+        std::mem::replace(&mut *slot, Arc::new(self))
+    }
+
+    /// Entry for a model
+    pub fn get(&self, model: ModelId) -> Option<&ModelSpec> {
+        self.index.get(model.as_str()).map(|&i| &self.models[i])
+    }
+
+    /// Resolve an id or alias to a catalogued model
+    pub fn resolve(&self, name: &str) -> CatalogResult<ModelId> {
+        ModelId::validate(name).map_err(|e| CatalogError::UnknownModel(e.to_string()))?;
+        let id = ModelId::legacy(name).unwrap_or(name);
+        self.index
+            .get(id)
+            .map(|&i| self.models[i].model_id())
+            .ok_or_else(|| CatalogError::UnknownModel(name.to_string()))
+    }
+
+    /// Canonical id for an alias, if this catalog defines it
+    pub(crate) fn canonical(&self, name: &str) -> Option<&str> {
+        self.index.get(name).map(|&i| self.models[i].id.as_str())
+    }
+
+    /// Whether the model is catalogued
+    pub fn contains(&self, model: ModelId) -> bool {
+        self.index.contains_key(model.as_str())
+    }
+
+    /// Model used when a task does not ask for consensus
+    pub fn default_model(&self) -> ModelId {
+        self.default_model
+    }
+
+    /// All entries, in catalog order
+    pub fn specs(&self) -> &[ModelSpec] {
+        &self.models
+    }
+
+    /// All model ids, in catalog order
+    pub fn ids(&self) -> Vec<ModelId> {
+        self.models.iter().map(ModelSpec::model_id).collect()
+    }
+
+    /// Model ids of one kind, in catalog order
+    pub fn ids_of_kind(&self, kind: ModelKind) -> Vec<ModelId> {
+        self.models
+            .iter()
+            .filter(|m| m.kind == kind)
+            .map(ModelSpec::model_id)
+            .collect()
+    }
+
+    /// Position of a model in catalog order
+    pub fn position(&self, model: ModelId) -> Option<usize> {
+        self.index.get(model.as_str()).copied()
+    }
+
+    /// Number of models
+    pub fn len(&self) -> usize {
+        self.models.len()
+    }
+
+    /// Whether the catalog is empty (never true for a built catalog)
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+}
+
+impl Default for ModelCatalog {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"
+default_model: small
+models:
+  - id: small
+    kind: worker
+    api_name: small-7b.gguf
+    endpoint: http://vasp-02:8081/v1
+    local: true
+    context_window: 8192
+    weight: 0.5
+    costs: { input_per_mtok: 0.1 }
+  - id: big
+    kind: manager
+    api_name: big-cloud
+    context_window: 128000
+    aliases: [big_v1]
+"#;
+
+    #[test]
+    fn test_builtin_matches_legacy_models() {
+        let catalog = ModelCatalog::builtin();
+        assert_eq!(catalog.len(), 4);
+        assert_eq!(catalog.default_model(), ModelId::OPUS_45);
+        assert_eq!(
+            catalog.ids_of_kind(ModelKind::Manager),
+            vec![ModelId::OPUS_45, ModelId::GEMINI_3_PRO, ModelId::QWEN35]
+        );
+        let hydra = catalog.get(ModelId::HYDRA_CODER).unwrap();
+        assert_eq!(hydra.api_name, "HydraCoder-Q6_K.gguf");
+        assert!(hydra.local);
+        assert_eq!(hydra.weight, 0.85);
+    }
+
+    #[test]
+    fn test_yaml_catalog_with_defaults_and_aliases() {
+        let catalog = ModelCatalog::from_yaml_str(YAML).unwrap();
+        let big = catalog.resolve("big_v1").unwrap();
+        assert_eq!(big.as_str(), "big");
+
+        let spec = catalog.get(big).unwrap();
+        assert_eq!(spec.weight, 1.0);
+        assert_eq!(spec.max_output_tokens, 4_096);
+        assert!(spec.capabilities.streaming);
+
+        let small = catalog.get(catalog.default_model()).unwrap();
+        assert_eq!(small.endpoint.as_deref(), Some("http://vasp-02:8081/v1"));
+        assert!((small.costs.cost(2_000_000, 0) - 0.2).abs() < 1e-9);
+
+        assert!(matches!(
+            catalog.resolve("opus_45"),
+            Err(CatalogError::UnknownModel(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_dispatches_on_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = dir.path().join("models.yaml");
+        std::fs::write(&yaml, YAML).unwrap();
+        assert_eq!(ModelCatalog::load(&yaml).unwrap().len(), 2);
+
+        let toml = dir.path().join("models.toml");
+        std::fs::write(&toml, BUILTIN_CATALOG_TOML).unwrap();
+        assert_eq!(ModelCatalog::load(&toml).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_validation_rejects_bad_catalogs() {
+        let spec = |id: &str| ModelSpec {
+            id: id.to_string(),
+            kind: ModelKind::Worker,
+            display_name: None,
+            api_name: "m".to_string(),
+            endpoint: None,
+            local: true,
+            context_window: 1024,
+            max_output_tokens: 256,
+            weight: 1.0,
+            costs: ModelCosts::default(),
+            capabilities: ModelCapabilities::default(),
+            tokens_per_sec: None,
+            specialty: String::new(),
+            aliases: Vec::new(),
+        };
+
+        assert!(ModelCatalog::new(vec![]).is_err());
+        assert!(ModelCatalog::new(vec![spec("a"), spec("a")]).is_err());
+        assert!(ModelCatalog::new(vec![spec("Has Spaces")]).is_err());
+        assert!(ModelCatalog::new(vec![ModelSpec {
+            weight: 0.0,
+            ..spec("a")
+        }])
+        .is_err());
+        assert!(ModelCatalog::from_toml_str("default_model = \"x\"\n[[models]]\nid = \"a\"\nkind = \"worker\"\napi_name = \"a\"\ncontext_window = 1").is_err());
+
+        // Without a manager the first model is the default
+        assert_eq!(
+            ModelCatalog::new(vec![spec("a"), spec("b")])
+                .unwrap()
+                .default_model()
+                .as_str(),
+            "a"
+        );
+    }
+}
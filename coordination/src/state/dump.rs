@@ -0,0 +1,242 @@
+//! Whole-store JSON export and import, for debugging
+//!
+//! A [`StateDump`] is every record of a [`StateBackend`] in one
+//! human-readable document, independent of the engine: export a RocksDB
+//! store, read it in an editor or `jq`, or load it into a `FileStateStore`
+//! to reproduce a problem without the native build.
+//!
+//! ```ignore
+//! let dump = StateDump::export(&store)?;
+//! dump.write_json("state-dump.json")?;
+//!
+//! let copy = FileStateStore::in_memory();
+//! StateDump::read_json("state-dump.json")?.import(&copy)?;
+//! ```
+//!
+//! Event ids are not part of the backend interface, so imported events get
+//! ids derived from their timestamp and content: importing the same dump
+//! twice leaves one copy of each event.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::backend::{StateBackend, StoreError, StoreResult};
+use super::migration::CURRENT_SCHEMA_VERSION;
+use super::types::*;
+
+/// Dump document format version
+pub const DUMP_FORMAT: u32 = 1;
+
+/// One event from the log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DumpedEvent {
+    pub timestamp_nanos: i64,
+    pub event: Value,
+}
+
+/// Every record of a state store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateDump {
+    /// [`DUMP_FORMAT`] at export time
+    pub format: u32,
+    /// Schema version the records were exported with
+    pub schema_version: u32,
+    pub exported_at: DateTime<Utc>,
+    /// Backend the dump came from ("rocksdb", "file", "memory")
+    pub backend: String,
+    pub sessions: Vec<EnsembleSession>,
+    pub tasks: Vec<EnsembleTask>,
+    pub results: Vec<ModelResult>,
+    pub votes: Vec<VoteRecord>,
+    pub contexts: Vec<SharedContext>,
+    pub events: Vec<DumpedEvent>,
+}
+
+impl StateDump {
+    /// Read every record from a store
+    pub fn export(store: &dyn StateBackend) -> StoreResult<Self> {
+        let events = store
+            .get_events_range_raw(0, i64::MAX)?
+            .into_iter()
+            .map(|(timestamp_nanos, bytes)| {
+                serde_json::from_slice(&bytes)
+                    .map(|event| DumpedEvent {
+                        timestamp_nanos,
+                        event,
+                    })
+                    .map_err(|e| StoreError::Deserialization(e.to_string()))
+            })
+            .collect::<StoreResult<_>>()?;
+
+        Ok(Self {
+            format: DUMP_FORMAT,
+            schema_version: CURRENT_SCHEMA_VERSION,
+            exported_at: Utc::now(),
+            backend: store.backend_name().to_string(),
+            sessions: store.list_sessions()?,
+            tasks: store.list_tasks()?,
+            results: store.list_results()?,
+            votes: store.list_votes()?,
+            contexts: store.list_contexts()?,
+            events,
+        })
+    }
+
+    /// Write every record into a store, returning the record count
+    ///
+    /// Existing records with the same keys are overwritten.
+    pub fn import(&self, store: &dyn StateBackend) -> StoreResult<usize> {
+        if self.schema_version > CURRENT_SCHEMA_VERSION {
+            return Err(StoreError::SchemaTooNew {
+                found: self.schema_version,
+                supported: CURRENT_SCHEMA_VERSION,
+            });
+        }
+
+        for session in &self.sessions {
+            store.put_session(session)?;
+        }
+        for task in &self.tasks {
+            store.put_task(task)?;
+        }
+        for result in &self.results {
+            store.put_result(result)?;
+        }
+        for vote in &self.votes {
+            store.put_vote(vote)?;
+        }
+        for context in &self.contexts {
+            store.put_context(context)?;
+        }
+        for event in &self.events {
+            let bytes = serde_json::to_vec(&event.event)
+                .map_err(|e| StoreError::Serialization(e.to_string()))?;
+            let id = format!("import-{:016x}", fnv1a(&bytes));
+            store.put_event_raw(event.timestamp_nanos, &id, &bytes)?;
+        }
+        Ok(self.record_count())
+    }
+
+    /// Total records across all tables
+    pub fn record_count(&self) -> usize {
+        self.sessions.len()
+            + self.tasks.len()
+            + self.results.len()
+            + self.votes.len()
+            + self.contexts.len()
+            + self.events.len()
+    }
+
+    /// Write the dump as pretty-printed JSON
+    ///
+    /// Written beside `path` and renamed over it, so a failed export never
+    /// leaves a truncated file.
+    pub fn write_json(&self, path: impl AsRef<Path>) -> StoreResult<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_vec_pretty(self)
+            .map_err(|e| StoreError::Serialization(e.to_string()))?;
+        let tmp = path.with_extension("tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(&json)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Read a dump written by [`write_json`](Self::write_json)
+    pub fn read_json(path: impl AsRef<Path>) -> StoreResult<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| StoreError::Deserialization(format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// 64-bit FNV-1a, stable across builds (unlike `DefaultHasher`)
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::backend::StateBackendExt;
+    use crate::state::FileStateStore;
+    use tempfile::tempdir;
+
+    fn populated() -> FileStateStore {
+        let store = FileStateStore::in_memory();
+        let session = EnsembleSession::new();
+        store.put_session(&session).unwrap();
+        let task = EnsembleTask::new(session.id.clone(), "prompt".to_string(), true);
+        store.put_task(&task).unwrap();
+        store
+            .put_result(&ModelResult::new(
+                task.id.clone(),
+                ModelId::QWEN35,
+                "answer".to_string(),
+                5,
+                50,
+            ))
+            .unwrap();
+        let mut vote = VoteRecord::new(task.id.clone(), VotingStrategy::Majority);
+        vote.add_vote(ModelId::OPUS_45, ModelId::QWEN35, 1.0);
+        store.put_vote(&vote).unwrap();
+        store
+            .put_context(&SharedContext::new(session.id.clone()))
+            .unwrap();
+        store
+            .put_event(10, "e1", &serde_json::json!({ "kind": "started" }))
+            .unwrap();
+        store
+            .put_event(20, "e2", &serde_json::json!({ "kind": "done" }))
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn test_round_trip_through_file() {
+        let source = populated();
+        let dump = StateDump::export(&source).unwrap();
+        assert_eq!(dump.backend, "memory");
+        assert_eq!(dump.record_count(), 7);
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("dump.json");
+        dump.write_json(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.contains("\"qwen35\""), "readable model ids");
+
+        let target = FileStateStore::in_memory();
+        let loaded = StateDump::read_json(&path).unwrap();
+        assert_eq!(loaded.import(&target).unwrap(), 7);
+        // Re-importing overwrites instead of duplicating
+        loaded.import(&target).unwrap();
+
+        let copy = StateDump::export(&target).unwrap();
+        assert_eq!(copy.record_count(), 7);
+        assert_eq!(copy.tasks[0].id, dump.tasks[0].id);
+        assert_eq!(copy.votes[0].votes, dump.votes[0].votes);
+        let kinds: Vec<&Value> = copy.events.iter().map(|e| &e.event["kind"]).collect();
+        assert_eq!(kinds, vec!["started", "done"]);
+    }
+
+    #[test]
+    fn test_newer_dump_is_refused() {
+        let mut dump = StateDump::export(&populated()).unwrap();
+        dump.schema_version = CURRENT_SCHEMA_VERSION + 1;
+        let target = FileStateStore::in_memory();
+        assert!(matches!(
+            dump.import(&target),
+            Err(StoreError::SchemaTooNew { .. })
+        ));
+        assert!(target.list_sessions().unwrap().is_empty());
+    }
+}
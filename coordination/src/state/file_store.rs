@@ -0,0 +1,499 @@
+//! Pure-Rust state backend: in-memory tables with an optional JSON-lines log
+//!
+//! Each column family from [`schema`] is a `BTreeMap` keyed by the same
+//! compound keys RocksDB uses, so prefix scans and event ordering behave
+//! identically. When opened on a path, every mutation is appended to the log
+//! as one JSON object per line and replayed on the next open:
+//!
+//! ```text
+//! {"op":"put","cf":"sessions","key":"sess:…","value":{…}}
+//! {"op":"delete","cf":"events","key":"evt:…"}
+//! ```
+//!
+//! A torn final line (crash mid-write) is truncated on open; damage anywhere
+//! else is reported as [`StoreError::Deserialization`]. The log only grows,
+//! so long-lived stores should call [`FileStateStore::compact`] now and then.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use super::backend::{SharedStateStore, StateBackend, StoreError, StoreResult};
+use super::jsonl;
+use super::schema::{self, ALL_CFS};
+use super::types::*;
+
+/// One mutation in the on-disk log
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum LogRecord {
+    Put {
+        cf: String,
+        key: String,
+        value: Value,
+    },
+    Delete {
+        cf: String,
+        key: String,
+    },
+}
+
+type Table = BTreeMap<String, Value>;
+
+/// State backend without native dependencies
+pub struct FileStateStore {
+    tables: RwLock<HashMap<&'static str, Table>>,
+    log: Option<Mutex<File>>,
+    path: Option<PathBuf>,
+}
+
+impl FileStateStore {
+    /// Create a store that lives only in memory
+    pub fn in_memory() -> Self {
+        Self {
+            tables: RwLock::new(empty_tables()),
+            log: None,
+            path: None,
+        }
+    }
+
+    /// Open or create a store persisted to the log file at `path`
+    pub fn open(path: impl Into<PathBuf>) -> StoreResult<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut tables = empty_tables();
+        if path.exists() {
+            let bytes = std::fs::read(&path)?;
+            let (records, good) = jsonl::parse(&bytes)
+                .map_err(|e| StoreError::Deserialization(format!("{}: {}", path.display(), e)))?;
+            jsonl::repair_tail(&path, &bytes, good)?;
+            for record in records {
+                apply(&mut tables, record);
+            }
+        }
+
+        let log = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            tables: RwLock::new(tables),
+            log: Some(Mutex::new(log)),
+            path: Some(path),
+        })
+    }
+
+    /// Create a shared reference to this store
+    pub fn shared(self) -> SharedStateStore {
+        Arc::new(self)
+    }
+
+    /// Log file path (`None` for in-memory stores)
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Flush the log to stable storage
+    pub fn sync(&self) -> StoreResult<()> {
+        if let Some(log) = &self.log {
+            log.lock()
+                .map_err(|_| StoreError::LockPoisoned)?
+                .sync_data()?;
+        }
+        Ok(())
+    }
+
+    /// Rewrite the log to hold only live values, returning the record count
+    ///
+    /// The new log is written beside the old one and renamed over it, so a
+    /// crash mid-compaction leaves the previous log intact.
+    pub fn compact(&self) -> StoreResult<usize> {
+        let (Some(log), Some(path)) = (&self.log, &self.path) else {
+            return Ok(0);
+        };
+        let tables = self.tables.read().map_err(|_| StoreError::LockPoisoned)?;
+        let mut log = log.lock().map_err(|_| StoreError::LockPoisoned)?;
+
+        let tmp = path.with_extension("compact.tmp");
+        let mut out = Vec::new();
+        let mut count = 0;
+        for cf in ALL_CFS {
+            for (key, value) in &tables[cf] {
+                let record = LogRecord::Put {
+                    cf: cf.to_string(),
+                    key: key.clone(),
+                    value: value.clone(),
+                };
+                out.extend(encode(&record)?);
+                count += 1;
+            }
+        }
+        let mut file = File::create(&tmp)?;
+        file.write_all(&out)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)?;
+
+        *log = OpenOptions::new().append(true).open(path)?;
+        Ok(count)
+    }
+
+    // =========================================================================
+    // Generic operations
+    // =========================================================================
+
+    /// Apply a mutation to the tables and append it to the log
+    fn write(&self, record: LogRecord) -> StoreResult<()> {
+        let mut tables = self.tables.write().map_err(|_| StoreError::LockPoisoned)?;
+        if let Some(log) = &self.log {
+            let line = encode(&record)?;
+            log.lock()
+                .map_err(|_| StoreError::LockPoisoned)?
+                .write_all(&line)?;
+        }
+        apply(&mut tables, record);
+        Ok(())
+    }
+
+    /// Store a value in a column family
+    fn put<T: Serialize>(&self, cf_name: &str, key: &str, value: &T) -> StoreResult<()> {
+        let value =
+            serde_json::to_value(value).map_err(|e| StoreError::Serialization(e.to_string()))?;
+        self.write(LogRecord::Put {
+            cf: cf_name.to_string(),
+            key: key.to_string(),
+            value,
+        })
+    }
+
+    /// Get a value from a column family
+    fn get<T: DeserializeOwned>(&self, cf_name: &str, key: &str) -> StoreResult<Option<T>> {
+        let tables = self.tables.read().map_err(|_| StoreError::LockPoisoned)?;
+        let table = tables
+            .get(cf_name)
+            .ok_or_else(|| StoreError::ColumnFamilyNotFound(cf_name.to_string()))?;
+        table.get(key).map(|v| decode(v.clone())).transpose()
+    }
+
+    /// Decode every value whose key starts with `prefix`
+    fn scan<T: DeserializeOwned>(&self, cf_name: &str, prefix: &str) -> StoreResult<Vec<T>> {
+        let tables = self.tables.read().map_err(|_| StoreError::LockPoisoned)?;
+        let table = tables
+            .get(cf_name)
+            .ok_or_else(|| StoreError::ColumnFamilyNotFound(cf_name.to_string()))?;
+        table
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(_, v)| decode(v.clone()))
+            .collect()
+    }
+}
+
+impl Default for FileStateStore {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+impl StateBackend for FileStateStore {
+    fn backend_name(&self) -> &'static str {
+        if self.log.is_some() {
+            "file"
+        } else {
+            "memory"
+        }
+    }
+
+    fn put_session(&self, session: &EnsembleSession) -> StoreResult<()> {
+        self.put(
+            schema::CF_SESSIONS,
+            &schema::keys::session(&session.id),
+            session,
+        )
+    }
+
+    fn get_session(&self, session_id: &str) -> StoreResult<Option<EnsembleSession>> {
+        self.get(schema::CF_SESSIONS, &schema::keys::session(session_id))
+    }
+
+    fn list_sessions(&self) -> StoreResult<Vec<EnsembleSession>> {
+        let mut sessions: Vec<EnsembleSession> = self.scan(schema::CF_SESSIONS, "sess:")?;
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+        Ok(sessions)
+    }
+
+    fn put_task(&self, task: &EnsembleTask) -> StoreResult<()> {
+        self.put(schema::CF_TASKS, &schema::keys::task(&task.id), task)
+    }
+
+    fn get_task(&self, task_id: &str) -> StoreResult<Option<EnsembleTask>> {
+        self.get(schema::CF_TASKS, &schema::keys::task(task_id))
+    }
+
+    fn get_session_tasks(&self, session_id: &str) -> StoreResult<Vec<EnsembleTask>> {
+        Ok(self
+            .scan::<EnsembleTask>(schema::CF_TASKS, "task:")?
+            .into_iter()
+            .filter(|t| t.session_id == session_id)
+            .collect())
+    }
+
+    fn list_tasks(&self) -> StoreResult<Vec<EnsembleTask>> {
+        self.scan(schema::CF_TASKS, "task:")
+    }
+
+    fn put_result(&self, result: &ModelResult) -> StoreResult<()> {
+        let key = schema::keys::result(&result.task_id, &result.model_id.to_string());
+        self.put(schema::CF_RESULTS, &key, result)
+    }
+
+    fn get_result(&self, task_id: &str, model_id: &ModelId) -> StoreResult<Option<ModelResult>> {
+        let key = schema::keys::result(task_id, &model_id.to_string());
+        self.get(schema::CF_RESULTS, &key)
+    }
+
+    fn get_task_results(&self, task_id: &str) -> StoreResult<Vec<ModelResult>> {
+        self.scan(schema::CF_RESULTS, &format!("result:{}:", task_id))
+    }
+
+    fn list_results(&self) -> StoreResult<Vec<ModelResult>> {
+        self.scan(schema::CF_RESULTS, "result:")
+    }
+
+    fn put_vote(&self, vote: &VoteRecord) -> StoreResult<()> {
+        self.put(schema::CF_VOTING, &schema::keys::vote(&vote.task_id), vote)
+    }
+
+    fn get_vote(&self, task_id: &str) -> StoreResult<Option<VoteRecord>> {
+        self.get(schema::CF_VOTING, &schema::keys::vote(task_id))
+    }
+
+    fn list_votes(&self) -> StoreResult<Vec<VoteRecord>> {
+        self.scan(schema::CF_VOTING, "vote:")
+    }
+
+    fn put_context(&self, context: &SharedContext) -> StoreResult<()> {
+        let key = schema::keys::context(&context.session_id);
+        self.put(schema::CF_CONTEXT, &key, context)
+    }
+
+    fn get_context(&self, session_id: &str) -> StoreResult<Option<SharedContext>> {
+        self.get(schema::CF_CONTEXT, &schema::keys::context(session_id))
+    }
+
+    fn list_contexts(&self) -> StoreResult<Vec<SharedContext>> {
+        self.scan(schema::CF_CONTEXT, "ctx:")
+    }
+
+    fn put_event_raw(&self, timestamp_nanos: i64, event_id: &str, json: &[u8]) -> StoreResult<()> {
+        let value: Value =
+            serde_json::from_slice(json).map_err(|e| StoreError::Serialization(e.to_string()))?;
+        self.write(LogRecord::Put {
+            cf: schema::CF_EVENTS.to_string(),
+            key: schema::keys::event(timestamp_nanos, event_id),
+            value,
+        })
+    }
+
+    fn get_events_range_raw(
+        &self,
+        start_nanos: i64,
+        end_nanos: i64,
+    ) -> StoreResult<Vec<(i64, Vec<u8>)>> {
+        let tables = self.tables.read().map_err(|_| StoreError::LockPoisoned)?;
+        let mut events = Vec::new();
+        for (key, value) in tables[schema::CF_EVENTS].range(schema::keys::event(start_nanos, "")..)
+        {
+            let Some(ts) = schema::keys::parse_event_timestamp(key) else {
+                continue;
+            };
+            if ts > end_nanos {
+                break;
+            }
+            let bytes =
+                serde_json::to_vec(value).map_err(|e| StoreError::Serialization(e.to_string()))?;
+            events.push((ts, bytes));
+        }
+        Ok(events)
+    }
+
+    fn prune_events_before(&self, timestamp_nanos: i64) -> StoreResult<usize> {
+        let end_key = schema::keys::event(timestamp_nanos, "");
+        let stale: Vec<String> = {
+            let tables = self.tables.read().map_err(|_| StoreError::LockPoisoned)?;
+            tables[schema::CF_EVENTS]
+                .range(..end_key)
+                .map(|(key, _)| key.clone())
+                .collect()
+        };
+        for key in &stale {
+            self.write(LogRecord::Delete {
+                cf: schema::CF_EVENTS.to_string(),
+                key: key.clone(),
+            })?;
+        }
+        Ok(stale.len())
+    }
+}
+
+// ── Helpers ─────────────────────────────────────────────────────────
+
+fn empty_tables() -> HashMap<&'static str, Table> {
+    ALL_CFS.iter().map(|cf| (*cf, Table::new())).collect()
+}
+
+fn apply(tables: &mut HashMap<&'static str, Table>, record: LogRecord) {
+    match record {
+        LogRecord::Put { cf, key, value } => {
+            if let Some(table) = tables.get_mut(cf.as_str()) {
+                table.insert(key, value);
+            }
+        }
+        LogRecord::Delete { cf, key } => {
+            if let Some(table) = tables.get_mut(cf.as_str()) {
+                table.remove(&key);
+            }
+        }
+    }
+}
+
+fn encode(record: &LogRecord) -> StoreResult<Vec<u8>> {
+    let mut line =
+        serde_json::to_vec(record).map_err(|e| StoreError::Serialization(e.to_string()))?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+fn decode<T: DeserializeOwned>(value: Value) -> StoreResult<T> {
+    serde_json::from_value(value).map_err(|e| StoreError::Deserialization(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::backend::conformance;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_in_memory_conformance() {
+        let store = FileStateStore::in_memory();
+        assert_eq!(store.backend_name(), "memory");
+        conformance::run(&store);
+    }
+
+    #[test]
+    fn test_file_conformance() {
+        let dir = tempdir().unwrap();
+        let store = FileStateStore::open(dir.path().join("state.jsonl")).unwrap();
+        assert_eq!(store.backend_name(), "file");
+        conformance::run(&store);
+    }
+
+    #[test]
+    fn test_state_survives_reopen() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("state.jsonl");
+        let session = EnsembleSession::new();
+        {
+            let store = FileStateStore::open(&path).unwrap();
+            store.put_session(&session).unwrap();
+            store.put_event_raw(10, "e1", br#"{"n":1}"#).unwrap();
+            store.put_event_raw(20, "e2", br#"{"n":2}"#).unwrap();
+            assert_eq!(store.prune_events_before(15).unwrap(), 1);
+            store.sync().unwrap();
+        }
+
+        let store = FileStateStore::open(&path).unwrap();
+        assert_eq!(
+            store.get_session(&session.id).unwrap().unwrap().id,
+            session.id
+        );
+        let events = store.get_events_range_raw(0, i64::MAX).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, 20);
+    }
+
+    #[test]
+    fn test_torn_tail_is_truncated() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("state.jsonl");
+        let store = FileStateStore::open(&path).unwrap();
+        store
+            .put_vote(&VoteRecord::new("t1".into(), VotingStrategy::Majority))
+            .unwrap();
+        drop(store);
+
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"op":"put","cf":"voting","ke"#).unwrap();
+        drop(file);
+
+        let store = FileStateStore::open(&path).unwrap();
+        assert!(store.get_vote("t1").unwrap().is_some());
+        store
+            .put_vote(&VoteRecord::new("t2".into(), VotingStrategy::Majority))
+            .unwrap();
+        drop(store);
+
+        let store = FileStateStore::open(&path).unwrap();
+        assert!(store.get_vote("t2").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_corruption_before_tail_is_an_error() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("state.jsonl");
+        std::fs::write(
+            &path,
+            "not json\n{\"op\":\"delete\",\"cf\":\"tasks\",\"key\":\"task:x\"}\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            FileStateStore::open(&path),
+            Err(StoreError::Deserialization(_))
+        ));
+    }
+
+    #[test]
+    fn test_compact_drops_dead_records() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("state.jsonl");
+        let store = FileStateStore::open(&path).unwrap();
+        let mut ctx = SharedContext::new("s1".to_string());
+        for i in 0..5 {
+            ctx.update_summary(format!("summary {}", i));
+            store.put_context(&ctx).unwrap();
+        }
+        store.put_event_raw(1, "old", b"{}").unwrap();
+        store.prune_events_before(2).unwrap();
+
+        let before = std::fs::read_to_string(&path).unwrap().lines().count();
+        assert_eq!(store.compact().unwrap(), 1);
+        let after = std::fs::read_to_string(&path).unwrap().lines().count();
+        assert!(after < before);
+        assert_eq!(after, 1);
+
+        // Appends after compaction land in the new log
+        store
+            .put_context(&SharedContext::new("s2".to_string()))
+            .unwrap();
+        drop(store);
+        let store = FileStateStore::open(&path).unwrap();
+        assert_eq!(
+            store.get_context("s1").unwrap().unwrap().summary,
+            "summary 4"
+        );
+        assert!(store.get_context("s2").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_shared_handle_is_backend_agnostic() {
+        let shared: SharedStateStore = FileStateStore::in_memory().shared();
+        let task = EnsembleTask::new("s".to_string(), "p".to_string(), false);
+        shared.put_task(&task).unwrap();
+        assert_eq!(shared.get_pending_tasks("s").unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,106 @@
+//! Crash recovery for append-only JSON-lines logs
+//!
+//! Writers append one JSON record per line, so a crash can only damage the
+//! final line. [`parse`] stops before a bad final line (a torn write) and
+//! reports a bad line anywhere else as corruption; [`repair_tail`] then
+//! truncates the torn bytes, or restores the newline a complete final
+//! record may be missing, so later appends start on a fresh line.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+
+/// A record that failed to parse and is followed by more data
+#[derive(Debug)]
+pub(crate) struct CorruptRecord {
+    pub offset: usize,
+    pub error: serde_json::Error,
+}
+
+impl std::fmt::Display for CorruptRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "corrupt record at byte {}: {}", self.offset, self.error)
+    }
+}
+
+/// Parse newline-delimited JSON records
+///
+/// Returns the records and the byte offset just past the last good one.
+/// Blank lines are skipped.
+pub(crate) fn parse<T: DeserializeOwned>(bytes: &[u8]) -> Result<(Vec<T>, usize), CorruptRecord> {
+    let mut records = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let end = bytes[offset..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map(|i| offset + i + 1);
+        let line = bytes[offset..end.unwrap_or(bytes.len())].trim_ascii();
+        if !line.is_empty() {
+            match serde_json::from_slice(line) {
+                Ok(record) => records.push(record),
+                Err(_) if end.is_none_or(|e| bytes[e..].trim_ascii().is_empty()) => break,
+                Err(error) => return Err(CorruptRecord { offset, error }),
+            }
+        }
+        offset = end.unwrap_or(bytes.len());
+    }
+    Ok((records, offset))
+}
+
+/// Make the log at `path` safe to append to after [`parse`]
+///
+/// `bytes` is the content that was parsed and `good` the offset it returned.
+/// Returns the number of torn bytes removed.
+pub(crate) fn repair_tail(path: &Path, bytes: &[u8], good: usize) -> std::io::Result<u64> {
+    if good < bytes.len() {
+        let file = OpenOptions::new().write(true).open(path)?;
+        file.set_len(good as u64)?;
+        file.sync_all()?;
+        Ok((bytes.len() - good) as u64)
+    } else {
+        if bytes.last().is_some_and(|b| *b != b'\n') {
+            // A complete final record may be missing only its newline
+            OpenOptions::new()
+                .append(true)
+                .open(path)?
+                .write_all(b"\n")?;
+        }
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stops_at_torn_tail() {
+        let bytes = b"{\"a\":1}\n\n{\"a\":2}\n{\"a\":";
+        let (records, good) = parse::<serde_json::Value>(bytes).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(good, 17);
+
+        let err = parse::<serde_json::Value>(b"{\"a\":1}\nnot json\n{\"a\":2}\n").unwrap_err();
+        assert_eq!(err.offset, 8);
+    }
+
+    #[test]
+    fn test_repair_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.jsonl");
+
+        let torn = b"{\"a\":1}\n{\"a\":";
+        std::fs::write(&path, torn).unwrap();
+        let (_, good) = parse::<serde_json::Value>(torn).unwrap();
+        assert_eq!(repair_tail(&path, torn, good).unwrap(), 5);
+        assert_eq!(std::fs::read(&path).unwrap(), b"{\"a\":1}\n");
+
+        let unterminated = b"{\"a\":1}";
+        std::fs::write(&path, unterminated).unwrap();
+        assert_eq!(repair_tail(&path, unterminated, 7).unwrap(), 0);
+        assert_eq!(std::fs::read(&path).unwrap(), b"{\"a\":1}\n");
+    }
+}
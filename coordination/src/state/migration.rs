@@ -0,0 +1,692 @@
+//! Schema versioning and ordered migrations for persisted state
+//!
+//! The store records the schema version it was written with under
+//! [`keys::SCHEMA_VERSION`](super::schema::keys::SCHEMA_VERSION) in the `meta`
+//! column family. On open, [`migrate`] runs every entry of [`MIGRATIONS`]
+//! newer than the stored version, in order. Each step's rewrites and its
+//! version bump are committed as one atomic batch, so an interrupted upgrade
+//! leaves the store at the last completed step and resumes from there:
+//!
+//! ```text
+//! stored v0 ──► v1 ──► … ──► CURRENT_SCHEMA_VERSION
+//! ```
+//!
+//! A store without a version key is stamped current when empty and treated
+//! as v0 otherwise. A store newer than this build is refused with
+//! [`StoreError::SchemaTooNew`] rather than decoded into the wrong shape.
+//!
+//! Migrations work on raw bytes through [`RawStore`], decoding old records
+//! with mirror types frozen at the previous layout. Adding a migration:
+//! freeze the old shape in a `vN` module, append a [`Migration`] to
+//! [`MIGRATIONS`], and bump [`CURRENT_SCHEMA_VERSION`].
+
+use std::cell::RefCell;
+
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::info;
+
+use super::backend::{StoreError, StoreResult};
+use super::schema::{self, ALL_CFS, CF_META};
+
+/// Schema version written by this build
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// Byte-level access to column families, for migrations
+pub trait RawStore {
+    /// Read the raw value at `key`
+    fn get_raw(&self, cf: &str, key: &str) -> StoreResult<Option<Vec<u8>>>;
+
+    /// Overwrite the raw value at `key`
+    fn put_raw(&self, cf: &str, key: &str, value: &[u8]) -> StoreResult<()>;
+
+    /// Every key and raw value in a column family, in key order
+    fn scan_raw(&self, cf: &str) -> StoreResult<Vec<(String, Vec<u8>)>>;
+
+    /// Apply `(cf, key, value)` writes atomically: all of them or none
+    fn put_raw_batch(&self, writes: &[(String, String, Vec<u8>)]) -> StoreResult<()>;
+}
+
+/// How a backend encodes record values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordCodec {
+    /// serde_json (`FileStateStore`, event payloads)
+    Json,
+    /// bincode (`StateStore`)
+    #[cfg(feature = "heavy-state")]
+    Bincode,
+}
+
+impl RecordCodec {
+    /// Encode a record
+    pub fn encode<T: Serialize>(self, value: &T) -> StoreResult<Vec<u8>> {
+        match self {
+            Self::Json => {
+                serde_json::to_vec(value).map_err(|e| StoreError::Serialization(e.to_string()))
+            }
+            #[cfg(feature = "heavy-state")]
+            Self::Bincode => {
+                bincode::serialize(value).map_err(|e| StoreError::Serialization(e.to_string()))
+            }
+        }
+    }
+
+    /// Decode a record
+    pub fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> StoreResult<T> {
+        match self {
+            Self::Json => serde_json::from_slice(bytes)
+                .map_err(|e| StoreError::Deserialization(e.to_string())),
+            #[cfg(feature = "heavy-state")]
+            Self::Bincode => {
+                bincode::deserialize(bytes).map_err(|e| StoreError::Deserialization(e.to_string()))
+            }
+        }
+    }
+}
+
+/// What a migration sees: the raw store and its record codec
+///
+/// Writes are staged rather than applied, and committed by [`migrate`]
+/// together with the version bump once the step succeeds.
+pub struct MigrationContext<'a> {
+    store: &'a dyn RawStore,
+    codec: RecordCodec,
+    staged: RefCell<Vec<(String, String, Vec<u8>)>>,
+}
+
+impl<'a> MigrationContext<'a> {
+    pub fn new(store: &'a dyn RawStore, codec: RecordCodec) -> Self {
+        Self {
+            store,
+            codec,
+            staged: RefCell::default(),
+        }
+    }
+
+    /// Re-encode every record in `cf` from `Old` to `New`, returning the count
+    ///
+    /// Records are read from the store as it was before the step, and the
+    /// new encodings are staged; a record that does not match `Old` fails
+    /// the migration without touching the store.
+    pub fn rewrite<Old, New>(&self, cf: &str, convert: impl Fn(Old) -> New) -> StoreResult<usize>
+    where
+        Old: DeserializeOwned,
+        New: Serialize,
+    {
+        let mut converted = Vec::new();
+        for (key, bytes) in self.store.scan_raw(cf)? {
+            let old: Old = self
+                .codec
+                .decode(&bytes)
+                .map_err(|e| StoreError::Deserialization(format!("{}/{}: {}", cf, key, e)))?;
+            converted.push((key, self.codec.encode(&convert(old))?));
+        }
+        let count = converted.len();
+        self.staged.borrow_mut().extend(
+            converted
+                .into_iter()
+                .map(|(key, bytes)| (cf.to_string(), key, bytes)),
+        );
+        Ok(count)
+    }
+
+    /// Take the staged writes, leaving none
+    fn take_staged(&self) -> Vec<(String, String, Vec<u8>)> {
+        self.staged.take()
+    }
+}
+
+/// One schema upgrade step
+pub struct Migration {
+    /// Version the store is at after this step
+    pub version: u32,
+    /// Shown in logs and reports
+    pub description: &'static str,
+    /// Rewrites records, returning how many were touched
+    pub run: fn(&MigrationContext<'_>) -> StoreResult<usize>,
+}
+
+/// Every migration, oldest first
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "store model ids as catalog strings",
+    run: v0::upgrade,
+}];
+
+/// A migration that ran
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub description: &'static str,
+    pub records: usize,
+}
+
+/// Outcome of [`migrate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub applied: Vec<AppliedMigration>,
+}
+
+impl MigrationReport {
+    /// Whether any migration ran
+    pub fn migrated(&self) -> bool {
+        !self.applied.is_empty()
+    }
+}
+
+/// Bring a store up to [`CURRENT_SCHEMA_VERSION`]
+pub fn migrate(store: &dyn RawStore, codec: RecordCodec) -> StoreResult<MigrationReport> {
+    migrate_with(store, codec, MIGRATIONS)
+}
+
+/// Bring a store up to the last of `migrations` (which must be in order)
+pub fn migrate_with(
+    store: &dyn RawStore,
+    codec: RecordCodec,
+    migrations: &[Migration],
+) -> StoreResult<MigrationReport> {
+    let target = migrations.last().map_or(0, |m| m.version);
+    let from_version = match schema_version(store)? {
+        Some(version) => version,
+        None if is_empty(store)? => {
+            set_schema_version(store, target)?;
+            target
+        }
+        None => 0,
+    };
+    if from_version > target {
+        return Err(StoreError::SchemaTooNew {
+            found: from_version,
+            supported: target,
+        });
+    }
+
+    let mut applied = Vec::new();
+    for migration in migrations.iter().filter(|m| m.version > from_version) {
+        let ctx = MigrationContext::new(store, codec);
+        let records = (migration.run)(&ctx).map_err(|e| StoreError::Migration {
+            version: migration.version,
+            reason: e.to_string(),
+        })?;
+        let mut writes = ctx.take_staged();
+        writes.push(version_write(migration.version));
+        store.put_raw_batch(&writes)?;
+        info!(
+            version = migration.version,
+            records, "Migrated state store: {}", migration.description
+        );
+        applied.push(AppliedMigration {
+            version: migration.version,
+            description: migration.description,
+            records,
+        });
+    }
+
+    Ok(MigrationReport {
+        from_version,
+        to_version: target,
+        applied,
+    })
+}
+
+/// Stored schema version (`None` if never stamped)
+///
+/// The version is ASCII decimal whatever the record codec, so it can be read
+/// with generic tooling.
+pub fn schema_version(store: &dyn RawStore) -> StoreResult<Option<u32>> {
+    store
+        .get_raw(CF_META, schema::keys::SCHEMA_VERSION)?
+        .map(|bytes| {
+            std::str::from_utf8(&bytes)
+                .ok()
+                .and_then(|s| s.trim().parse().ok())
+                .ok_or_else(|| {
+                    StoreError::Deserialization(format!(
+                        "bad schema version: {:?}",
+                        String::from_utf8_lossy(&bytes)
+                    ))
+                })
+        })
+        .transpose()
+}
+
+fn set_schema_version(store: &dyn RawStore, version: u32) -> StoreResult<()> {
+    store.put_raw_batch(&[version_write(version)])
+}
+
+fn version_write(version: u32) -> (String, String, Vec<u8>) {
+    (
+        CF_META.to_string(),
+        schema::keys::SCHEMA_VERSION.to_string(),
+        version.to_string().into_bytes(),
+    )
+}
+
+fn is_empty(store: &dyn RawStore) -> StoreResult<bool> {
+    for cf in ALL_CFS.iter().filter(|cf| **cf != CF_META) {
+        if !store.scan_raw(cf)?.is_empty() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// ── v0: enum model ids ──────────────────────────────────────────────
+
+/// Layout before model ids came from the catalog
+///
+/// `ModelId` was a closed enum, which bincode stores as a variant index; the
+/// structs below mirror the v0 field order exactly.
+mod v0 {
+    use std::collections::HashMap;
+
+    use chrono::{DateTime, Utc};
+    use serde::Deserialize;
+
+    use super::MigrationContext;
+    use crate::state::backend::StoreResult;
+    use crate::state::schema;
+    use crate::state::types::{self, TaskStatus, VotingStrategy};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ModelId {
+        #[serde(alias = "behemoth")]
+        Opus45,
+        #[serde(alias = "strand_coder")]
+        Gemini3Pro,
+        Qwen35,
+        HydraCoder,
+    }
+
+    impl From<ModelId> for types::ModelId {
+        fn from(id: ModelId) -> Self {
+            match id {
+                ModelId::Opus45 => Self::OPUS_45,
+                ModelId::Gemini3Pro => Self::GEMINI_3_PRO,
+                ModelId::Qwen35 => Self::QWEN35,
+                ModelId::HydraCoder => Self::HYDRA_CODER,
+            }
+        }
+    }
+
+    fn ids(ids: Vec<ModelId>) -> Vec<types::ModelId> {
+        ids.into_iter().map(Into::into).collect()
+    }
+
+    #[derive(Deserialize)]
+    pub struct EnsembleSession {
+        id: String,
+        created_at: DateTime<Utc>,
+        updated_at: DateTime<Utc>,
+        active_model: Option<ModelId>,
+        pending_tasks: Vec<String>,
+        completed_tasks: Vec<String>,
+        context_version: u64,
+        harness_session_id: Option<String>,
+        active: bool,
+    }
+
+    impl From<EnsembleSession> for types::EnsembleSession {
+        fn from(old: EnsembleSession) -> Self {
+            Self {
+                id: old.id,
+                created_at: old.created_at,
+                updated_at: old.updated_at,
+                active_model: old.active_model.map(Into::into),
+                pending_tasks: old.pending_tasks,
+                completed_tasks: old.completed_tasks,
+                context_version: old.context_version,
+                harness_session_id: old.harness_session_id,
+                active: old.active,
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub struct EnsembleTask {
+        id: String,
+        session_id: String,
+        created_at: DateTime<Utc>,
+        updated_at: DateTime<Utc>,
+        prompt: String,
+        code_context: Option<String>,
+        status: TaskStatus,
+        assigned_models: Vec<ModelId>,
+        completed_models: Vec<ModelId>,
+        require_consensus: bool,
+        max_tokens: u32,
+        final_response: Option<String>,
+        winning_model: Option<ModelId>,
+    }
+
+    impl From<EnsembleTask> for types::EnsembleTask {
+        fn from(old: EnsembleTask) -> Self {
+            Self {
+                id: old.id,
+                session_id: old.session_id,
+                created_at: old.created_at,
+                updated_at: old.updated_at,
+                prompt: old.prompt,
+                code_context: old.code_context,
+                status: old.status,
+                assigned_models: ids(old.assigned_models),
+                completed_models: ids(old.completed_models),
+                require_consensus: old.require_consensus,
+                max_tokens: old.max_tokens,
+                final_response: old.final_response,
+                winning_model: old.winning_model.map(Into::into),
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub struct ModelResult {
+        task_id: String,
+        model_id: ModelId,
+        timestamp: DateTime<Utc>,
+        response: String,
+        reasoning: Option<String>,
+        confidence: f32,
+        tokens_used: u32,
+        latency_ms: u64,
+        selected: bool,
+    }
+
+    impl From<ModelResult> for types::ModelResult {
+        fn from(old: ModelResult) -> Self {
+            Self {
+                task_id: old.task_id,
+                model_id: old.model_id.into(),
+                timestamp: old.timestamp,
+                response: old.response,
+                reasoning: old.reasoning,
+                confidence: old.confidence,
+                tokens_used: old.tokens_used,
+                latency_ms: old.latency_ms,
+                selected: old.selected,
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub struct VoteRecord {
+        task_id: String,
+        strategy: VotingStrategy,
+        timestamp: DateTime<Utc>,
+        votes: HashMap<ModelId, (ModelId, f32)>,
+        winner: Option<ModelId>,
+        arbitrated: bool,
+        arbitration_reason: Option<String>,
+        notes: Option<String>,
+    }
+
+    impl From<VoteRecord> for types::VoteRecord {
+        fn from(old: VoteRecord) -> Self {
+            Self {
+                task_id: old.task_id,
+                strategy: old.strategy,
+                timestamp: old.timestamp,
+                votes: old
+                    .votes
+                    .into_iter()
+                    .map(|(voter, (choice, weight))| (voter.into(), (choice.into(), weight)))
+                    .collect(),
+                winner: old.winner.map(Into::into),
+                arbitrated: old.arbitrated,
+                arbitration_reason: old.arbitration_reason,
+                notes: old.notes,
+            }
+        }
+    }
+
+    /// v0 → v1: re-encode every record that holds a model id
+    ///
+    /// Result keys already used the display id (`result:<task>:opus_45`), so
+    /// only values change. Events are JSON and read legacy ids as aliases.
+    pub fn upgrade(ctx: &MigrationContext<'_>) -> StoreResult<usize> {
+        Ok(ctx
+            .rewrite::<EnsembleSession, types::EnsembleSession>(schema::CF_SESSIONS, Into::into)?
+            + ctx.rewrite::<EnsembleTask, types::EnsembleTask>(schema::CF_TASKS, Into::into)?
+            + ctx.rewrite::<ModelResult, types::ModelResult>(schema::CF_RESULTS, Into::into)?
+            + ctx.rewrite::<VoteRecord, types::VoteRecord>(schema::CF_VOTING, Into::into)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+
+    use crate::state::types::{EnsembleTask, ModelId, ModelResult, VoteRecord};
+
+    /// Raw store over nested maps, JSON-encoded
+    ///
+    /// With `crash` set, batches fail as if the process died before the
+    /// write was committed.
+    #[derive(Default)]
+    struct MapStore(Mutex<BTreeMap<(String, String), Vec<u8>>>, AtomicBool);
+
+    impl RawStore for MapStore {
+        fn get_raw(&self, cf: &str, key: &str) -> StoreResult<Option<Vec<u8>>> {
+            let map = self.0.lock().unwrap();
+            Ok(map.get(&(cf.to_string(), key.to_string())).cloned())
+        }
+
+        fn put_raw(&self, cf: &str, key: &str, value: &[u8]) -> StoreResult<()> {
+            let mut map = self.0.lock().unwrap();
+            map.insert((cf.to_string(), key.to_string()), value.to_vec());
+            Ok(())
+        }
+
+        fn scan_raw(&self, cf: &str) -> StoreResult<Vec<(String, Vec<u8>)>> {
+            let map = self.0.lock().unwrap();
+            Ok(map
+                .iter()
+                .filter(|((c, _), _)| c == cf)
+                .map(|((_, key), value)| (key.clone(), value.clone()))
+                .collect())
+        }
+
+        fn put_raw_batch(&self, writes: &[(String, String, Vec<u8>)]) -> StoreResult<()> {
+            if self.1.load(Ordering::SeqCst) {
+                return Err(std::io::Error::other("crashed").into());
+            }
+            let mut map = self.0.lock().unwrap();
+            for (cf, key, value) in writes {
+                map.insert((cf.clone(), key.clone()), value.clone());
+            }
+            Ok(())
+        }
+    }
+
+    fn put_json(store: &MapStore, cf: &str, key: &str, value: serde_json::Value) {
+        store
+            .put_raw(cf, key, value.to_string().as_bytes())
+            .unwrap();
+    }
+
+    fn get_json<T: DeserializeOwned>(store: &MapStore, cf: &str, key: &str) -> T {
+        RecordCodec::Json
+            .decode(&store.get_raw(cf, key).unwrap().unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_current_version_matches_migrations() {
+        assert_eq!(
+            MIGRATIONS.last().map(|m| m.version),
+            Some(CURRENT_SCHEMA_VERSION)
+        );
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+    }
+
+    #[test]
+    fn test_empty_store_is_stamped_current() {
+        let store = MapStore::default();
+        let report = migrate(&store, RecordCodec::Json).unwrap();
+        assert!(!report.migrated());
+        assert_eq!(report.to_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(
+            schema_version(&store).unwrap(),
+            Some(CURRENT_SCHEMA_VERSION)
+        );
+
+        // Newer than this build: refuse rather than misread
+        store
+            .put_raw(CF_META, schema::keys::SCHEMA_VERSION, b"99")
+            .unwrap();
+        assert!(matches!(
+            migrate(&store, RecordCodec::Json),
+            Err(StoreError::SchemaTooNew { found: 99, .. })
+        ));
+    }
+
+    #[test]
+    fn test_v0_records_are_upgraded() {
+        let store = MapStore::default();
+        let now = chrono::Utc::now();
+        put_json(
+            &store,
+            schema::CF_TASKS,
+            "task:t1",
+            serde_json::json!({
+                "id": "t1", "session_id": "s1", "created_at": now, "updated_at": now,
+                "prompt": "p", "code_context": null, "status": "completed",
+                "assigned_models": ["opus45", "qwen35"], "completed_models": ["behemoth"],
+                "require_consensus": true, "max_tokens": 2048,
+                "final_response": "done", "winning_model": "gemini3_pro"
+            }),
+        );
+        put_json(
+            &store,
+            schema::CF_RESULTS,
+            "result:t1:hydra_coder",
+            serde_json::json!({
+                "task_id": "t1", "model_id": "hydra_coder", "timestamp": now,
+                "response": "r", "reasoning": null, "confidence": 0.5,
+                "tokens_used": 10, "latency_ms": 20, "selected": false
+            }),
+        );
+        put_json(
+            &store,
+            schema::CF_VOTING,
+            "vote:t1",
+            serde_json::json!({
+                "task_id": "t1", "strategy": "majority", "timestamp": now,
+                "votes": { "opus45": ["strand_coder", 1.0] }, "winner": "gemini3_pro",
+                "arbitrated": false, "arbitration_reason": null, "notes": null
+            }),
+        );
+
+        let report = migrate(&store, RecordCodec::Json).unwrap();
+        assert_eq!(report.from_version, 0);
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(report.applied[0].records, 3);
+        assert_eq!(schema_version(&store).unwrap(), Some(1));
+
+        let task: EnsembleTask = get_json(&store, schema::CF_TASKS, "task:t1");
+        assert_eq!(
+            task.assigned_models,
+            vec![ModelId::OPUS_45, ModelId::QWEN35]
+        );
+        assert_eq!(task.winning_model, Some(ModelId::GEMINI_3_PRO));
+        let raw = store.get_raw(schema::CF_TASKS, "task:t1").unwrap().unwrap();
+        assert!(String::from_utf8(raw).unwrap().contains("\"opus_45\""));
+
+        let result: ModelResult = get_json(&store, schema::CF_RESULTS, "result:t1:hydra_coder");
+        assert_eq!(result.model_id, ModelId::HYDRA_CODER);
+        let vote: VoteRecord = get_json(&store, schema::CF_VOTING, "vote:t1");
+        assert_eq!(vote.votes[&ModelId::OPUS_45], (ModelId::GEMINI_3_PRO, 1.0));
+
+        // Already current: nothing runs again
+        assert!(!migrate(&store, RecordCodec::Json).unwrap().migrated());
+    }
+
+    #[test]
+    fn test_interrupted_upgrade_resumes() {
+        let store = MapStore::default();
+        let now = chrono::Utc::now();
+        put_json(
+            &store,
+            schema::CF_TASKS,
+            "task:t1",
+            serde_json::json!({
+                "id": "t1", "session_id": "s1", "created_at": now, "updated_at": now,
+                "prompt": "p", "code_context": null, "status": "pending",
+                "assigned_models": ["opus45"], "completed_models": [],
+                "require_consensus": false, "max_tokens": 1024,
+                "final_response": null, "winning_model": null
+            }),
+        );
+        put_json(
+            &store,
+            schema::CF_RESULTS,
+            "result:t1:opus_45",
+            serde_json::json!({ "task_id": "t1", "model_id": "opus45" }),
+        );
+        let v0_task = store.get_raw(schema::CF_TASKS, "task:t1").unwrap();
+
+        // A later record fails to decode: the task rewrite is not applied
+        let err = migrate(&store, RecordCodec::Json).unwrap_err();
+        assert!(
+            matches!(&err, StoreError::Migration { version: 1, reason } if reason.contains("results/")),
+            "{err}"
+        );
+        assert_eq!(store.get_raw(schema::CF_TASKS, "task:t1").unwrap(), v0_task);
+        assert_eq!(schema_version(&store).unwrap(), None);
+
+        // Crash while committing: still nothing applied
+        put_json(
+            &store,
+            schema::CF_RESULTS,
+            "result:t1:opus_45",
+            serde_json::json!({
+                "task_id": "t1", "model_id": "opus45", "timestamp": now,
+                "response": "r", "reasoning": null, "confidence": 0.5,
+                "tokens_used": 10, "latency_ms": 20, "selected": false
+            }),
+        );
+        store.1.store(true, Ordering::SeqCst);
+        assert!(migrate(&store, RecordCodec::Json).is_err());
+        assert_eq!(store.get_raw(schema::CF_TASKS, "task:t1").unwrap(), v0_task);
+        assert_eq!(schema_version(&store).unwrap(), None);
+
+        // Reopen: the upgrade runs from v0 against untouched records
+        store.1.store(false, Ordering::SeqCst);
+        let report = migrate(&store, RecordCodec::Json).unwrap();
+        assert_eq!(report.from_version, 0);
+        assert_eq!(report.applied[0].records, 2);
+        let task: EnsembleTask = get_json(&store, schema::CF_TASKS, "task:t1");
+        assert_eq!(task.assigned_models, vec![ModelId::OPUS_45]);
+    }
+
+    #[test]
+    fn test_failed_migration_leaves_version() {
+        fn broken(_: &MigrationContext<'_>) -> StoreResult<usize> {
+            Err(StoreError::NotFound("x".into()))
+        }
+        let steps = [
+            Migration {
+                version: 1,
+                description: "ok",
+                run: |_| Ok(0),
+            },
+            Migration {
+                version: 2,
+                description: "broken",
+                run: broken,
+            },
+        ];
+        let store = MapStore::default();
+        put_json(&store, schema::CF_CONTEXT, "ctx:s", serde_json::json!({}));
+
+        let err = migrate_with(&store, RecordCodec::Json, &steps).unwrap_err();
+        assert!(matches!(err, StoreError::Migration { version: 2, .. }));
+        // Step 1 is recorded, so the next open resumes at step 2
+        assert_eq!(schema_version(&store).unwrap(), Some(1));
+    }
+}
@@ -1,16 +1,24 @@
 //! State persistence module for multi-agent ensemble coordination
 //!
-//! This module provides RocksDB-backed persistent storage for:
+//! This module provides persistent storage for:
 //! - Ensemble sessions that survive model swaps
 //! - Tasks and their results from multiple models
-//! - Voting records for consensus decisions
+//! - Voting records for consensus decisions, tallied by `VotingEngine`
 //! - Shared context across model executions
 //! - Event history for replay and debugging
 //!
+//! Participating models are described by the [`ModelCatalog`] (loaded from
+//! `config/models.toml`); a [`ModelId`] is a validated id into it.
+//!
 //! # Architecture
 //!
-//! The state store uses RocksDB column families to logically separate different
-//! data types while sharing a single database instance:
+//! Storage sits behind the [`StateBackend`] trait, so consumers hold a
+//! [`SharedStateStore`] and never depend on the engine:
+//!
+//! - `StateStore` (`heavy-state`): RocksDB, the production backend
+//! - [`FileStateStore`] (`full`): in-memory or JSON-lines file, no C++ build
+//!
+//! Both use the same logical column families and compound keys:
 //!
 //! - `sessions`: EnsembleSession tracking overall coordination
 //! - `tasks`: EnsembleTask for work items
@@ -18,14 +26,23 @@
 //! - `voting`: VoteRecord for consensus decisions
 //! - `context`: SharedContext maintained across model swaps
 //! - `events`: Event history for replay
+//! - `meta`: schema version
+//!
+//! # Schema evolution
+//!
+//! Stores record the schema version they were written with; opening a
+//! `StateStore` runs the pending [`migration`]s in order and refuses stores
+//! from newer builds. [`StateDump`] exports any backend to one JSON document
+//! (and imports it back) for debugging, and `StateStore` adds online
+//! checkpoints, backups and restore.
 //!
 //! # Usage
 //!
 //! ```ignore
-//! use rust_cluster_mcp::state::{StateStore, EnsembleSession, EnsembleTask};
+//! use rust_cluster_mcp::state::{EnsembleSession, EnsembleTask, FileStateStore, StateBackend};
 //!
-//! // Open or create the state store
-//! let store = StateStore::open("./ensemble-state")?;
+//! // Open or create the state store (or `StateStore::open` with heavy-state)
+//! let store = FileStateStore::open("./ensemble-state.jsonl")?;
 //!
 //! // Create and store a session
 //! let session = EnsembleSession::new();
@@ -40,18 +57,44 @@
 //! store.put_task(&task)?;
 //! ```
 
-#[cfg(feature = "heavy-state")]
+#[cfg(feature = "full")]
+pub mod backend;
+pub mod catalog;
+#[cfg(feature = "full")]
+pub mod dump;
+#[cfg(feature = "full")]
+pub mod file_store;
+#[cfg(feature = "full")]
+pub(crate) mod jsonl;
+#[cfg(feature = "full")]
+pub mod migration;
+#[cfg(feature = "full")]
 pub mod schema;
 #[cfg(feature = "heavy-state")]
 pub mod store;
 pub mod types;
+pub mod voting;
 
-// Re-export RocksDB-backed store types (only with heavy-state)
+// Re-export the backend trait and the pure-Rust store (full)
+#[cfg(feature = "full")]
+pub use backend::{SharedStateStore, StateBackend, StateBackendExt, StoreError, StoreResult};
+#[cfg(feature = "full")]
+pub use dump::StateDump;
+#[cfg(feature = "full")]
+pub use file_store::FileStateStore;
+#[cfg(feature = "full")]
+pub use migration::{MigrationReport, CURRENT_SCHEMA_VERSION};
+
+// Re-export RocksDB-backed store (only with heavy-state)
 #[cfg(feature = "heavy-state")]
-pub use store::{SharedStateStore, StateStore, StoreError, StoreResult};
+pub use store::{BackupInfo, StateStore};
 
 // Re-export core types (always available)
+pub use catalog::{
+    CatalogError, CatalogResult, ModelCapabilities, ModelCatalog, ModelCosts, ModelSpec,
+};
 pub use types::{
-    EnsembleSession, EnsembleTask, ModelId, ModelResult, SessionId, SharedContext, TaskId,
-    TaskStatus, VoteRecord, VotingStrategy,
+    ArbitrationReason, EnsembleSession, EnsembleTask, ModelId, ModelIdError, ModelKind,
+    ModelResult, SessionId, SharedContext, TaskId, TaskStatus, VoteRecord, VotingStrategy,
 };
+pub use voting::{Ballot, CandidateTally, VoteDecision, VoteOutcome, VotingEngine};
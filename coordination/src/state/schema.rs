@@ -1,7 +1,8 @@
-//! Column family definitions for RocksDB state store
+//! Column family definitions for the state store
 //!
 //! Each column family provides logical separation of data types
-//! while sharing the same RocksDB instance.
+//! while sharing the same RocksDB instance. `FileStateStore` keeps one
+//! table per column family with the same keys.
 
 /// Column family for ensemble sessions
 pub const CF_SESSIONS: &str = "sessions";
@@ -24,6 +25,9 @@ pub const CF_EVENTS: &str = "events";
 /// Column family for inter-manager delegation tracking
 pub const CF_DELEGATIONS: &str = "delegations";
 
+/// Column family for store metadata (schema version)
+pub const CF_META: &str = "meta";
+
 /// All column family names
 pub const ALL_CFS: &[&str] = &[
     CF_SESSIONS,
@@ -33,6 +37,7 @@ pub const ALL_CFS: &[&str] = &[
     CF_CONTEXT,
     CF_EVENTS,
     CF_DELEGATIONS,
+    CF_META,
 ];
 
 /// Key prefixes for compound keys
@@ -72,6 +77,9 @@ pub mod keys {
         format!("deleg:{}:{}", session_id, delegation_id)
     }
 
+    /// Key holding the schema version in [`CF_META`](super::CF_META)
+    pub const SCHEMA_VERSION: &str = "meta:schema_version";
+
     /// Parse event timestamp from key
     pub fn parse_event_timestamp(key: &str) -> Option<i64> {
         let parts: Vec<&str> = key.split(':').collect();
@@ -2,43 +2,50 @@
 //!
 //! Provides persistent storage with column families for logical data separation.
 //! Uses bincode for efficient binary serialization internally.
+//!
+//! Opening a store runs any pending schema [`migration`](super::migration).
+//! For safekeeping, [`StateStore::checkpoint`] takes a cheap hard-linked
+//! snapshot and [`StateStore::backup`] an incremental backup, both while the
+//! store stays online; [`StateStore::restore_backup`] rebuilds a database
+//! directory from a backup.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
-use rocksdb::{ColumnFamilyDescriptor, Options, DB};
+use chrono::{DateTime, TimeZone, Utc};
+use rocksdb::backup::{BackupEngine, BackupEngineInfo, BackupEngineOptions, RestoreOptions};
+use rocksdb::checkpoint::Checkpoint;
+use rocksdb::{ColumnFamilyDescriptor, Env, Options, WriteBatch, DB};
 use serde::{de::DeserializeOwned, Serialize};
+use tracing::info;
 
+use super::backend::{SharedStateStore, StateBackend, StoreError, StoreResult};
+use super::migration::{self, RawStore, RecordCodec};
 use super::schema::{self, ALL_CFS};
 use super::types::*;
 
-/// Error type for state store operations
-#[derive(Debug, thiserror::Error)]
-pub enum StoreError {
-    #[error("RocksDB error: {0}")]
-    RocksDb(#[from] rocksdb::Error),
-
-    #[error("Serialization error: {0}")]
-    Serialization(String),
-
-    #[error("Deserialization error: {0}")]
-    Deserialization(String),
-
-    #[error("Key not found: {0}")]
-    NotFound(String),
-
-    #[error("Lock poisoned")]
-    LockPoisoned,
-
-    #[error("Column family not found: {0}")]
-    ColumnFamilyNotFound(String),
+/// One backup in a backup directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupInfo {
+    pub id: u32,
+    pub created_at: DateTime<Utc>,
+    pub size_bytes: u64,
+    pub files: u32,
 }
 
-/// Result type for state store operations
-pub type StoreResult<T> = Result<T, StoreError>;
-
-/// Shared reference to StateStore
-pub type SharedStateStore = Arc<StateStore>;
+impl From<BackupEngineInfo> for BackupInfo {
+    fn from(info: BackupEngineInfo) -> Self {
+        Self {
+            id: info.backup_id,
+            created_at: Utc
+                .timestamp_opt(info.timestamp, 0)
+                .single()
+                .unwrap_or_default(),
+            size_bytes: info.size,
+            files: info.num_files,
+        }
+    }
+}
 
 /// RocksDB-backed persistent state store
 pub struct StateStore {
@@ -65,10 +72,20 @@ impl StateStore {
         // Open database with column families
         let db = DB::open_cf_descriptors(&opts, &path, cf_descriptors)?;
 
-        Ok(Self {
+        let store = Self {
             db: RwLock::new(db),
             path,
-        })
+        };
+        let report = migration::migrate(&store, RecordCodec::Bincode)?;
+        if report.migrated() {
+            info!(
+                path = %store.path.display(),
+                from = report.from_version,
+                to = report.to_version,
+                "State store schema upgraded"
+            );
+        }
+        Ok(store)
     }
 
     /// Create a shared reference to this store
@@ -81,6 +98,78 @@ impl StateStore {
         &self.path
     }
 
+    /// Schema version recorded in the store
+    pub fn schema_version(&self) -> StoreResult<u32> {
+        Ok(migration::schema_version(self)?.unwrap_or(0))
+    }
+
+    // =========================================================================
+    // Checkpoints and backups
+    // =========================================================================
+
+    /// Write an openable copy of the database to `dir`, which must not exist
+    ///
+    /// Files are hard-linked where the filesystem allows, so a checkpoint on
+    /// the same volume costs almost nothing.
+    pub fn checkpoint(&self, dir: impl AsRef<Path>) -> StoreResult<()> {
+        let db = self.db.read().map_err(|_| StoreError::LockPoisoned)?;
+        Checkpoint::new(&*db)?.create_checkpoint(dir.as_ref())?;
+        Ok(())
+    }
+
+    /// Add an incremental backup to the backup directory `dir`
+    ///
+    /// Memtables are flushed first so the backup holds every acknowledged
+    /// write. Files shared with earlier backups are not copied again.
+    pub fn backup(&self, dir: impl AsRef<Path>) -> StoreResult<BackupInfo> {
+        let db = self.db.read().map_err(|_| StoreError::LockPoisoned)?;
+        let mut engine = backup_engine(dir.as_ref())?;
+        engine.create_new_backup_flush(&*db, true)?;
+        engine
+            .get_backup_info()
+            .into_iter()
+            .max_by_key(|info| info.backup_id)
+            .map(BackupInfo::from)
+            .ok_or_else(|| StoreError::NotFound(dir.as_ref().display().to_string()))
+    }
+
+    /// List the backups in `dir`, oldest first
+    pub fn list_backups(dir: impl AsRef<Path>) -> StoreResult<Vec<BackupInfo>> {
+        let mut backups: Vec<BackupInfo> = backup_engine(dir.as_ref())?
+            .get_backup_info()
+            .into_iter()
+            .map(BackupInfo::from)
+            .collect();
+        backups.sort_by_key(|b| b.id);
+        Ok(backups)
+    }
+
+    /// Delete all but the newest `keep` backups in `dir`
+    pub fn purge_old_backups(dir: impl AsRef<Path>, keep: usize) -> StoreResult<()> {
+        backup_engine(dir.as_ref())?.purge_old_backups(keep)?;
+        Ok(())
+    }
+
+    /// Restore a backup from `backup_dir` into the database directory `db_path`
+    ///
+    /// Restores the newest backup unless `backup_id` is given. No store may
+    /// be open on `db_path`; open it afterwards with [`StateStore::open`],
+    /// which also migrates backups taken by older builds.
+    pub fn restore_backup(
+        backup_dir: impl AsRef<Path>,
+        db_path: impl AsRef<Path>,
+        backup_id: Option<u32>,
+    ) -> StoreResult<()> {
+        let mut engine = backup_engine(backup_dir.as_ref())?;
+        let db_path = db_path.as_ref();
+        let opts = RestoreOptions::default();
+        match backup_id {
+            Some(id) => engine.restore_from_backup(db_path, db_path, &opts, id)?,
+            None => engine.restore_from_latest_backup(db_path, db_path, &opts)?,
+        }
+        Ok(())
+    }
+
     // =========================================================================
     // Generic operations
     // =========================================================================
@@ -152,39 +241,91 @@ impl StateStore {
         Ok(keys)
     }
 
+    /// Decode every value with a key prefix, failing on undecodable records
+    fn list_values<T: DeserializeOwned>(&self, cf_name: &str, prefix: &str) -> StoreResult<Vec<T>> {
+        self.list_keys(cf_name, prefix)?
+            .iter()
+            .filter_map(|key| self.get(cf_name, key).transpose())
+            .collect()
+    }
+}
+
+impl RawStore for StateStore {
+    fn get_raw(&self, cf_name: &str, key: &str) -> StoreResult<Option<Vec<u8>>> {
+        let db = self.db.read().map_err(|_| StoreError::LockPoisoned)?;
+        let cf = db
+            .cf_handle(cf_name)
+            .ok_or_else(|| StoreError::ColumnFamilyNotFound(cf_name.to_string()))?;
+        Ok(db.get_cf(&cf, key.as_bytes())?)
+    }
+
+    fn put_raw(&self, cf_name: &str, key: &str, value: &[u8]) -> StoreResult<()> {
+        let db = self.db.read().map_err(|_| StoreError::LockPoisoned)?;
+        let cf = db
+            .cf_handle(cf_name)
+            .ok_or_else(|| StoreError::ColumnFamilyNotFound(cf_name.to_string()))?;
+        db.put_cf(&cf, key.as_bytes(), value)?;
+        Ok(())
+    }
+
+    fn scan_raw(&self, cf_name: &str) -> StoreResult<Vec<(String, Vec<u8>)>> {
+        let db = self.db.read().map_err(|_| StoreError::LockPoisoned)?;
+        let cf = db
+            .cf_handle(cf_name)
+            .ok_or_else(|| StoreError::ColumnFamilyNotFound(cf_name.to_string()))?;
+
+        let mut entries = Vec::new();
+        for result in db.iterator_cf(&cf, rocksdb::IteratorMode::Start) {
+            let (key, value) = result?;
+            let key = String::from_utf8(key.to_vec())
+                .map_err(|e| StoreError::Deserialization(e.to_string()))?;
+            entries.push((key, value.to_vec()));
+        }
+        Ok(entries)
+    }
+
+    fn put_raw_batch(&self, writes: &[(String, String, Vec<u8>)]) -> StoreResult<()> {
+        let db = self.db.read().map_err(|_| StoreError::LockPoisoned)?;
+        let mut batch = WriteBatch::default();
+        for (cf_name, key, value) in writes {
+            let cf = db
+                .cf_handle(cf_name)
+                .ok_or_else(|| StoreError::ColumnFamilyNotFound(cf_name.to_string()))?;
+            batch.put_cf(cf, key.as_bytes(), value);
+        }
+        db.write(batch)?;
+        Ok(())
+    }
+}
+
+fn backup_engine(dir: &Path) -> StoreResult<BackupEngine> {
+    let opts = BackupEngineOptions::new(dir)?;
+    Ok(BackupEngine::open(&opts, &Env::new()?)?)
+}
+
+impl StateBackend for StateStore {
+    fn backend_name(&self) -> &'static str {
+        "rocksdb"
+    }
+
     // =========================================================================
     // Session operations
     // =========================================================================
 
     /// Store an ensemble session
-    pub fn put_session(&self, session: &EnsembleSession) -> StoreResult<()> {
+    fn put_session(&self, session: &EnsembleSession) -> StoreResult<()> {
         let key = schema::keys::session(&session.id);
         self.put(schema::CF_SESSIONS, &key, session)
     }
 
     /// Get an ensemble session by ID
-    pub fn get_session(&self, session_id: &str) -> StoreResult<Option<EnsembleSession>> {
+    fn get_session(&self, session_id: &str) -> StoreResult<Option<EnsembleSession>> {
         let key = schema::keys::session(session_id);
         self.get(schema::CF_SESSIONS, &key)
     }
 
-    /// Get the most recent active session
-    pub fn get_active_session(&self) -> StoreResult<Option<EnsembleSession>> {
-        let keys = self.list_keys(schema::CF_SESSIONS, "sess:")?;
-
-        let mut sessions: Vec<EnsembleSession> = keys
-            .iter()
-            .filter_map(|key| self.get::<EnsembleSession>(schema::CF_SESSIONS, key).ok()?)
-            .filter(|s| s.active)
-            .collect();
-
-        // Sort by updated_at descending
-        sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
-        Ok(sessions.into_iter().next())
-    }
-
     /// List all sessions
-    pub fn list_sessions(&self) -> StoreResult<Vec<EnsembleSession>> {
+    fn list_sessions(&self) -> StoreResult<Vec<EnsembleSession>> {
         let keys = self.list_keys(schema::CF_SESSIONS, "sess:")?;
 
         let mut sessions: Vec<EnsembleSession> = keys
@@ -201,19 +342,19 @@ impl StateStore {
     // =========================================================================
 
     /// Store an ensemble task
-    pub fn put_task(&self, task: &EnsembleTask) -> StoreResult<()> {
+    fn put_task(&self, task: &EnsembleTask) -> StoreResult<()> {
         let key = schema::keys::task(&task.id);
         self.put(schema::CF_TASKS, &key, task)
     }
 
     /// Get a task by ID
-    pub fn get_task(&self, task_id: &str) -> StoreResult<Option<EnsembleTask>> {
+    fn get_task(&self, task_id: &str) -> StoreResult<Option<EnsembleTask>> {
         let key = schema::keys::task(task_id);
         self.get(schema::CF_TASKS, &key)
     }
 
     /// Get tasks for a session
-    pub fn get_session_tasks(&self, session_id: &str) -> StoreResult<Vec<EnsembleTask>> {
+    fn get_session_tasks(&self, session_id: &str) -> StoreResult<Vec<EnsembleTask>> {
         let keys = self.list_keys(schema::CF_TASKS, "task:")?;
 
         let tasks: Vec<EnsembleTask> = keys
@@ -225,13 +366,9 @@ impl StateStore {
         Ok(tasks)
     }
 
-    /// Get pending tasks for a session
-    pub fn get_pending_tasks(&self, session_id: &str) -> StoreResult<Vec<EnsembleTask>> {
-        Ok(self
-            .get_session_tasks(session_id)?
-            .into_iter()
-            .filter(|t| matches!(t.status, TaskStatus::Pending | TaskStatus::InProgress))
-            .collect())
+    /// List every task
+    fn list_tasks(&self) -> StoreResult<Vec<EnsembleTask>> {
+        self.list_values(schema::CF_TASKS, "task:")
     }
 
     // =========================================================================
@@ -239,23 +376,19 @@ impl StateStore {
     // =========================================================================
 
     /// Store a model result
-    pub fn put_result(&self, result: &ModelResult) -> StoreResult<()> {
+    fn put_result(&self, result: &ModelResult) -> StoreResult<()> {
         let key = schema::keys::result(&result.task_id, &result.model_id.to_string());
         self.put(schema::CF_RESULTS, &key, result)
     }
 
     /// Get a specific model's result for a task
-    pub fn get_result(
-        &self,
-        task_id: &str,
-        model_id: &ModelId,
-    ) -> StoreResult<Option<ModelResult>> {
+    fn get_result(&self, task_id: &str, model_id: &ModelId) -> StoreResult<Option<ModelResult>> {
         let key = schema::keys::result(task_id, &model_id.to_string());
         self.get(schema::CF_RESULTS, &key)
     }
 
     /// Get all results for a task
-    pub fn get_task_results(&self, task_id: &str) -> StoreResult<Vec<ModelResult>> {
+    fn get_task_results(&self, task_id: &str) -> StoreResult<Vec<ModelResult>> {
         let prefix = format!("result:{}:", task_id);
         let keys = self.list_keys(schema::CF_RESULTS, &prefix)?;
 
@@ -267,80 +400,76 @@ impl StateStore {
         Ok(results)
     }
 
+    /// List every result
+    fn list_results(&self) -> StoreResult<Vec<ModelResult>> {
+        self.list_values(schema::CF_RESULTS, "result:")
+    }
+
     // =========================================================================
     // Voting operations
     // =========================================================================
 
     /// Store a vote record
-    pub fn put_vote(&self, vote: &VoteRecord) -> StoreResult<()> {
+    fn put_vote(&self, vote: &VoteRecord) -> StoreResult<()> {
         let key = schema::keys::vote(&vote.task_id);
         self.put(schema::CF_VOTING, &key, vote)
     }
 
     /// Get a vote record for a task
-    pub fn get_vote(&self, task_id: &str) -> StoreResult<Option<VoteRecord>> {
+    fn get_vote(&self, task_id: &str) -> StoreResult<Option<VoteRecord>> {
         let key = schema::keys::vote(task_id);
         self.get(schema::CF_VOTING, &key)
     }
 
+    /// List every vote record
+    fn list_votes(&self) -> StoreResult<Vec<VoteRecord>> {
+        self.list_values(schema::CF_VOTING, "vote:")
+    }
+
     // =========================================================================
     // Context operations
     // =========================================================================
 
     /// Store shared context
-    pub fn put_context(&self, context: &SharedContext) -> StoreResult<()> {
+    fn put_context(&self, context: &SharedContext) -> StoreResult<()> {
         let key = schema::keys::context(&context.session_id);
         self.put(schema::CF_CONTEXT, &key, context)
     }
 
     /// Get shared context for a session
-    pub fn get_context(&self, session_id: &str) -> StoreResult<Option<SharedContext>> {
+    fn get_context(&self, session_id: &str) -> StoreResult<Option<SharedContext>> {
         let key = schema::keys::context(session_id);
         self.get(schema::CF_CONTEXT, &key)
     }
 
-    /// Get or create context for a session
-    pub fn get_or_create_context(&self, session_id: &str) -> StoreResult<SharedContext> {
-        match self.get_context(session_id)? {
-            Some(ctx) => Ok(ctx),
-            None => {
-                let ctx = SharedContext::new(session_id.to_string());
-                self.put_context(&ctx)?;
-                Ok(ctx)
-            }
-        }
+    /// List every shared context
+    fn list_contexts(&self) -> StoreResult<Vec<SharedContext>> {
+        self.list_values(schema::CF_CONTEXT, "ctx:")
     }
 
     // =========================================================================
     // Event operations (for replay)
     // =========================================================================
 
-    /// Store an event (serialized as JSON for debuggability)
-    pub fn put_event(
-        &self,
-        timestamp_nanos: i64,
-        event_id: &str,
-        event: &impl Serialize,
-    ) -> StoreResult<()> {
+    /// Store a JSON-encoded event
+    fn put_event_raw(&self, timestamp_nanos: i64, event_id: &str, json: &[u8]) -> StoreResult<()> {
         let key = schema::keys::event(timestamp_nanos, event_id);
-        let bytes =
-            serde_json::to_vec(event).map_err(|e| StoreError::Serialization(e.to_string()))?;
 
         let db = self.db.read().map_err(|_| StoreError::LockPoisoned)?;
         let cf = db
             .cf_handle(schema::CF_EVENTS)
             .ok_or_else(|| StoreError::ColumnFamilyNotFound(schema::CF_EVENTS.to_string()))?;
 
-        db.put_cf(&cf, key.as_bytes(), bytes)?;
+        db.put_cf(&cf, key.as_bytes(), json)?;
         Ok(())
     }
 
-    /// Get events in a time range
-    pub fn get_events_range<T: DeserializeOwned>(
+    /// Get JSON-encoded events in a time range
+    fn get_events_range_raw(
         &self,
         start_nanos: i64,
         end_nanos: i64,
-    ) -> StoreResult<Vec<(i64, T)>> {
+    ) -> StoreResult<Vec<(i64, Vec<u8>)>> {
         let db = self.db.read().map_err(|_| StoreError::LockPoisoned)?;
         let cf = db
             .cf_handle(schema::CF_EVENTS)
@@ -362,9 +491,7 @@ impl StateStore {
                 if ts > end_nanos {
                     break;
                 }
-                let event: T = serde_json::from_slice(&value)
-                    .map_err(|e| StoreError::Deserialization(e.to_string()))?;
-                events.push((ts, event));
+                events.push((ts, value.to_vec()));
             }
         }
 
@@ -372,7 +499,7 @@ impl StateStore {
     }
 
     /// Delete old events before a timestamp
-    pub fn prune_events_before(&self, timestamp_nanos: i64) -> StoreResult<usize> {
+    fn prune_events_before(&self, timestamp_nanos: i64) -> StoreResult<usize> {
         let db = self.db.read().map_err(|_| StoreError::LockPoisoned)?;
         let cf = db
             .cf_handle(schema::CF_EVENTS)
@@ -412,6 +539,7 @@ impl StateStore {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::backend::conformance;
     use tempfile::tempdir;
 
     fn test_store() -> (StateStore, tempfile::TempDir) {
@@ -457,7 +585,7 @@ mod tests {
 
         let result = ModelResult::new(
             "task-1".to_string(),
-            ModelId::Opus45,
+            ModelId::OPUS_45,
             "Test response".to_string(),
             100,
             500,
@@ -465,7 +593,7 @@ mod tests {
 
         store.put_result(&result).unwrap();
         let retrieved = store
-            .get_result("task-1", &ModelId::Opus45)
+            .get_result("task-1", &ModelId::OPUS_45)
             .unwrap()
             .unwrap();
 
@@ -517,4 +645,69 @@ mod tests {
         let active = store.get_active_session().unwrap().unwrap();
         assert_eq!(active.id, session2.id);
     }
+
+    #[test]
+    fn test_conformance() {
+        let (store, _dir) = test_store();
+        conformance::run(&store);
+    }
+
+    #[test]
+    fn test_new_store_is_stamped_current() {
+        let (store, _dir) = test_store();
+        assert_eq!(
+            store.schema_version().unwrap(),
+            migration::CURRENT_SCHEMA_VERSION
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_opens_as_store() {
+        let (store, dir) = test_store();
+        let session = EnsembleSession::new();
+        store.put_session(&session).unwrap();
+
+        let snapshot = dir.path().join("snapshot");
+        store.checkpoint(&snapshot).unwrap();
+        // Writes after the checkpoint do not leak into it
+        store.put_session(&EnsembleSession::new()).unwrap();
+
+        let copy = StateStore::open(&snapshot).unwrap();
+        assert_eq!(copy.list_sessions().unwrap().len(), 1);
+        assert!(copy.get_session(&session.id).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_backup_and_restore() {
+        let (store, dir) = test_store();
+        let backups = dir.path().join("backups");
+        let task = EnsembleTask::new("s".to_string(), "first".to_string(), false);
+        store.put_task(&task).unwrap();
+        let first = store.backup(&backups).unwrap();
+
+        store
+            .put_task(&EnsembleTask::new(
+                "s".to_string(),
+                "second".to_string(),
+                false,
+            ))
+            .unwrap();
+        let second = store.backup(&backups).unwrap();
+        assert!(second.id > first.id);
+        assert_eq!(StateStore::list_backups(&backups).unwrap().len(), 2);
+
+        let restored = dir.path().join("restored");
+        StateStore::restore_backup(&backups, &restored, Some(first.id)).unwrap();
+        let copy = StateStore::open(&restored).unwrap();
+        assert_eq!(copy.list_tasks().unwrap().len(), 1);
+        assert_eq!(copy.get_task(&task.id).unwrap().unwrap().prompt, "first");
+        drop(copy);
+
+        StateStore::purge_old_backups(&backups, 1).unwrap();
+        let left = StateStore::list_backups(&backups).unwrap();
+        assert_eq!(
+            left.iter().map(|b| b.id).collect::<Vec<_>>(),
+            vec![second.id]
+        );
+    }
 }
@@ -5,7 +5,10 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+
+use super::catalog::ModelCatalog;
 
 /// Unique identifier for ensemble sessions
 pub type SessionId = String;
@@ -23,88 +26,236 @@ pub enum ModelKind {
     Worker,
 }
 
+/// Ids accepted from records written before the model catalog existed
+///
+/// The enum-era serde names and the old `behemoth` / `strand_coder` aliases
+/// map onto the built-in catalog ids, whatever catalog is installed.
+const LEGACY_MODEL_IDS: &[(&str, &str)] = &[
+    ("opus45", "opus_45"),
+    ("behemoth", "opus_45"),
+    ("gemini3_pro", "gemini_3_pro"),
+    ("strand_coder", "gemini_3_pro"),
+];
+
+/// Longest accepted model id
+const MAX_MODEL_ID_LEN: usize = 64;
+
+/// Voting weight for models missing from the catalog
+const UNKNOWN_MODEL_WEIGHT: f32 = 0.5;
+
+/// Most uncatalogued ids interned from records before they read as
+/// [`ModelId::UNKNOWN`]
+const MAX_UNCATALOGUED_IDS: usize = 256;
+
+/// Error for malformed model ids
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid model id '{id}': {reason}")]
+pub struct ModelIdError {
+    pub id: String,
+    pub reason: &'static str,
+}
+
 /// Model identifier for participating LLMs
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum ModelId {
-    // --- Manager tier (peers) ---
+///
+/// A validated, interned string naming an entry in the [`ModelCatalog`]:
+/// lowercase ASCII letters, digits, `_`, `-` and `.`, starting with a letter
+/// or digit. Ids are `Copy` and compare by value. Parsing ([`ModelId::new`],
+/// `FromStr`) accepts only catalogued ids, so untrusted input cannot grow
+/// the intern table. Deserializing keeps well-formed uncatalogued ids, so
+/// records written under another catalog still load; at most
+/// `MAX_UNCATALOGUED_IDS` of them are interned, later ones read as
+/// [`ModelId::UNKNOWN`]. Metadata (kind, weight, API name) comes from the
+/// installed catalog; other ids are treated as low-weight cloud workers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ModelId(&'static str);
+
+impl ModelId {
+    // --- Built-in catalog (config/models.toml) ---
     /// Claude Opus 4.5 — Architect role (cloud, Anthropic API)
-    #[serde(alias = "behemoth")]
-    Opus45,
+    pub const OPUS_45: ModelId = ModelId("opus_45");
     /// Gemini 3 Pro — Librarian role (cloud, Google API)
-    #[serde(alias = "strand_coder")]
-    Gemini3Pro,
+    pub const GEMINI_3_PRO: ModelId = ModelId("gemini_3_pro");
     /// Qwen3.5-397B-A17B — Strategist role (local, vasp-01+03)
-    Qwen35,
-
-    // --- Worker tier ---
+    pub const QWEN35: ModelId = ModelId("qwen35");
     /// HydraCoder 30B-A3B MoE — Rust specialist (local, vasp-02)
-    HydraCoder,
-}
+    pub const HYDRA_CODER: ModelId = ModelId("hydra_coder");
+    /// Stand-in for uncatalogued ids read once the intern budget is spent
+    pub const UNKNOWN: ModelId = ModelId("unknown");
+
+    /// Parse an id against the installed catalog, resolving legacy and
+    /// catalog aliases
+    ///
+    /// Ids missing from the catalog are rejected; use
+    /// [`ModelCatalog::resolve`] to look up a model in another catalog.
+    pub fn new(id: &str) -> Result<Self, ModelIdError> {
+        Self::validate(id)?;
+        Self::catalogued(id).ok_or_else(|| ModelIdError {
+            id: id.to_string(),
+            reason: "not in the model catalog",
+        })
+    }
+
+    /// Parse an id read from a persisted record
+    ///
+    /// Like [`Self::new`], but a well-formed id missing from the catalog is
+    /// kept (or mapped to [`Self::UNKNOWN`] once the intern budget is spent)
+    /// instead of failing the whole record.
+    pub fn from_record(id: &str) -> Result<Self, ModelIdError> {
+        Self::validate(id)?;
+        Ok(Self::catalogued(id)
+            .or_else(|| Self::intern_bounded(id, true))
+            .unwrap_or(Self::UNKNOWN))
+    }
+
+    /// Interned catalog id for a legacy name, id or alias
+    fn catalogued(id: &str) -> Option<Self> {
+        if let Some(canonical) = Self::legacy(id) {
+            return Some(Self::intern(canonical));
+        }
+        ModelCatalog::active().canonical(id).map(Self::intern)
+    }
+
+    /// Built-in id for an enum-era name or alias
+    pub(crate) fn legacy(id: &str) -> Option<&'static str> {
+        LEGACY_MODEL_IDS
+            .iter()
+            .find(|(old, _)| *old == id)
+            .map(|(_, canonical)| *canonical)
+    }
+
+    /// Check id syntax without resolving aliases
+    pub fn validate(id: &str) -> Result<(), ModelIdError> {
+        let fail = |reason| {
+            Err(ModelIdError {
+                id: id.to_string(),
+                reason,
+            })
+        };
+        if id.is_empty() || id.len() > MAX_MODEL_ID_LEN {
+            return fail("must be 1-64 characters");
+        }
+        if !id.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit()) {
+            return fail("must start with a lowercase letter or digit");
+        }
+        if !id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.'))
+        {
+            return fail("only lowercase letters, digits, '_', '-' and '.' are allowed");
+        }
+        Ok(())
+    }
+
+    /// Intern an already-validated id
+    ///
+    /// Interned strings live for the whole process; only call this with ids
+    /// taken from a catalog, never with raw input.
+    pub(crate) fn intern(id: &str) -> Self {
+        Self::intern_bounded(id, false).unwrap_or(Self::UNKNOWN)
+    }
+
+    /// Intern an id, counting it against `MAX_UNCATALOGUED_IDS` when
+    /// `uncatalogued` (`None` once that budget is spent)
+    fn intern_bounded(id: &str, uncatalogued: bool) -> Option<Self> {
+        #[derive(Default)]
+        struct Interned {
+            ids: HashSet<&'static str>,
+            uncatalogued: usize,
+        }
+        static INTERNED: OnceLock<Mutex<Interned>> = OnceLock::new();
+        let mut interned = INTERNED
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(existing) = interned.ids.get(id) {
+            return Some(Self(existing));
+        }
+        if uncatalogued {
+            if interned.uncatalogued >= MAX_UNCATALOGUED_IDS {
+                return None;
+            }
+            interned.uncatalogued += 1;
+        }
+        let leaked: &'static str = Box::leak(id.to_owned().into_boxed_str());
+        interned.ids.insert(leaked);
+        Some(Self(leaked))
+    }
+
+    /// The id string
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
 
-impl ModelId {
     /// Get the kind (manager or worker) for this model
     pub fn kind(&self) -> ModelKind {
-        match self {
-            ModelId::Opus45 | ModelId::Gemini3Pro | ModelId::Qwen35 => ModelKind::Manager,
-            ModelId::HydraCoder => ModelKind::Worker,
-        }
+        ModelCatalog::active()
+            .get(*self)
+            .map_or(ModelKind::Worker, |spec| spec.kind)
     }
 
     /// Whether this model runs locally (vs cloud API)
     pub fn is_local(&self) -> bool {
-        matches!(self, ModelId::Qwen35 | ModelId::HydraCoder)
+        ModelCatalog::active()
+            .get(*self)
+            .is_some_and(|spec| spec.local)
     }
 
     /// Get the voting weight for this model
     ///
-    /// All managers have equal weight (1.0). Workers have lower weight.
+    /// Set per model in the catalog (managers 1.0, workers lower by default).
     pub fn weight(&self) -> f32 {
-        match self {
-            ModelId::Opus45 | ModelId::Gemini3Pro | ModelId::Qwen35 => 1.0,
-            ModelId::HydraCoder => 0.85,
-        }
+        ModelCatalog::active()
+            .get(*self)
+            .map_or(UNKNOWN_MODEL_WEIGHT, |spec| spec.weight)
     }
 
-    /// Get the model name as used in API requests
-    pub fn api_name(&self) -> &'static str {
-        match self {
-            ModelId::Opus45 => "claude-opus-4-5-20250514",
-            ModelId::Gemini3Pro => "gemini-3-pro",
-            ModelId::Qwen35 => "Qwen3.5-397B-A17B-UD-Q4_K_XL.gguf",
-            ModelId::HydraCoder => "HydraCoder-Q6_K.gguf",
-        }
+    /// Get the model name as used in API requests (the id if uncatalogued)
+    pub fn api_name(&self) -> String {
+        ModelCatalog::active()
+            .get(*self)
+            .map_or_else(|| self.0.to_string(), |spec| spec.api_name.clone())
     }
 
-    /// Get all model IDs
-    pub fn all() -> &'static [ModelId] {
-        &[
-            ModelId::Opus45,
-            ModelId::Gemini3Pro,
-            ModelId::Qwen35,
-            ModelId::HydraCoder,
-        ]
+    /// Get all model IDs, in catalog order
+    pub fn all() -> Vec<ModelId> {
+        ModelCatalog::active().ids()
     }
 
     /// Get all manager model IDs
-    pub fn all_managers() -> &'static [ModelId] {
-        &[ModelId::Opus45, ModelId::Gemini3Pro, ModelId::Qwen35]
+    pub fn all_managers() -> Vec<ModelId> {
+        ModelCatalog::active().ids_of_kind(ModelKind::Manager)
     }
 
     /// Get all worker model IDs
-    pub fn all_workers() -> &'static [ModelId] {
-        &[ModelId::HydraCoder]
+    pub fn all_workers() -> Vec<ModelId> {
+        ModelCatalog::active().ids_of_kind(ModelKind::Worker)
     }
 }
 
 impl std::fmt::Display for ModelId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ModelId::Opus45 => write!(f, "opus_45"),
-            ModelId::Gemini3Pro => write!(f, "gemini_3_pro"),
-            ModelId::Qwen35 => write!(f, "qwen35"),
-            ModelId::HydraCoder => write!(f, "hydra_coder"),
-        }
+        f.write_str(self.0)
+    }
+}
+
+impl std::str::FromStr for ModelId {
+    type Err = ModelIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl Serialize for ModelId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ModelId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = String::deserialize(deserializer)?;
+        Self::from_record(&id).map_err(serde::de::Error::custom)
     }
 }
 
@@ -263,9 +414,9 @@ impl EnsembleTask {
             code_context: None,
             status: TaskStatus::Pending,
             assigned_models: if require_consensus {
-                ModelId::all_managers().to_vec()
+                ModelId::all_managers()
             } else {
-                vec![ModelId::Opus45] // Default to most capable manager
+                vec![ModelCatalog::active().default_model()]
             },
             completed_models: Vec::new(),
             require_consensus,
@@ -446,6 +597,39 @@ impl VoteRecord {
     }
 }
 
+/// Reason for requesting arbitration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArbitrationReason {
+    /// Tie vote between models
+    TieVote { tied_models: Vec<ModelId> },
+    /// Low confidence from all models
+    LowConfidence { max_confidence: f32 },
+    /// Conflicting responses
+    ConflictingResponses { description: String },
+    /// Explicit arbitration request
+    ExplicitRequest { requester: String },
+}
+
+impl std::fmt::Display for ArbitrationReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArbitrationReason::TieVote { tied_models } => {
+                write!(f, "tie_vote({} models)", tied_models.len())
+            }
+            ArbitrationReason::LowConfidence { max_confidence } => {
+                write!(f, "low_confidence(max={:.2})", max_confidence)
+            }
+            ArbitrationReason::ConflictingResponses { .. } => {
+                write!(f, "conflicting_responses")
+            }
+            ArbitrationReason::ExplicitRequest { requester } => {
+                write!(f, "explicit_request({})", requester)
+            }
+        }
+    }
+}
+
 /// Shared context maintained across model swaps
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SharedContext {
@@ -523,26 +707,103 @@ mod tests {
     #[test]
     fn test_model_weights() {
         // All managers have equal weight
-        assert_eq!(ModelId::Opus45.weight(), ModelId::Gemini3Pro.weight());
-        assert_eq!(ModelId::Opus45.weight(), ModelId::Qwen35.weight());
+        assert_eq!(ModelId::OPUS_45.weight(), ModelId::GEMINI_3_PRO.weight());
+        assert_eq!(ModelId::OPUS_45.weight(), ModelId::QWEN35.weight());
         // Workers have lower weight
-        assert!(ModelId::Opus45.weight() > ModelId::HydraCoder.weight());
+        assert!(ModelId::OPUS_45.weight() > ModelId::HYDRA_CODER.weight());
     }
 
     #[test]
     fn test_model_kind() {
-        assert_eq!(ModelId::Opus45.kind(), ModelKind::Manager);
-        assert_eq!(ModelId::Gemini3Pro.kind(), ModelKind::Manager);
-        assert_eq!(ModelId::Qwen35.kind(), ModelKind::Manager);
-        assert_eq!(ModelId::HydraCoder.kind(), ModelKind::Worker);
+        assert_eq!(ModelId::OPUS_45.kind(), ModelKind::Manager);
+        assert_eq!(ModelId::GEMINI_3_PRO.kind(), ModelKind::Manager);
+        assert_eq!(ModelId::QWEN35.kind(), ModelKind::Manager);
+        assert_eq!(ModelId::HYDRA_CODER.kind(), ModelKind::Worker);
     }
 
     #[test]
     fn test_model_locality() {
-        assert!(!ModelId::Opus45.is_local());
-        assert!(!ModelId::Gemini3Pro.is_local());
-        assert!(ModelId::Qwen35.is_local());
-        assert!(ModelId::HydraCoder.is_local());
+        assert!(!ModelId::OPUS_45.is_local());
+        assert!(!ModelId::GEMINI_3_PRO.is_local());
+        assert!(ModelId::QWEN35.is_local());
+        assert!(ModelId::HYDRA_CODER.is_local());
+    }
+
+    #[test]
+    fn test_model_id_validation() {
+        assert_eq!(ModelId::new("qwen35").unwrap(), ModelId::QWEN35);
+        for bad in ["", "Opus45", "has space", "_leading", &"x".repeat(65)] {
+            assert!(ModelId::new(bad).is_err(), "{bad:?} should be rejected");
+        }
+
+        // Well-formed but uncatalogued ids are rejected when parsed...
+        let err = ModelId::new("someday-model").unwrap_err();
+        assert_eq!(err.reason, "not in the model catalog");
+        // ...but still load from records, within a bounded intern budget
+        let stored: ModelId = serde_json::from_str("\"retired-model\"").unwrap();
+        assert_eq!(stored.as_str(), "retired-model");
+        assert_eq!(serde_json::to_string(&stored).unwrap(), "\"retired-model\"");
+        assert!(serde_json::from_str::<ModelId>("\"Bad Id\"").is_err());
+        for i in 0..MAX_UNCATALOGUED_IDS {
+            ModelId::from_record(&format!("flood-{i}")).unwrap();
+        }
+        assert_eq!(
+            ModelId::from_record("one-too-many").unwrap(),
+            ModelId::UNKNOWN
+        );
+        assert_eq!(ModelId::from_record("retired-model").unwrap(), stored);
+        assert_eq!(ModelId::from_record("qwen35").unwrap(), ModelId::QWEN35);
+
+        // Ids from a catalog that is not installed carry no metadata
+        let other = ModelCatalog::from_toml_str(
+            r#"
+[[models]]
+id = "someday-model"
+kind = "manager"
+api_name = "someday"
+context_window = 8192
+"#,
+        )
+        .unwrap();
+        let unknown = other.resolve("someday-model").unwrap();
+        assert_eq!(unknown.kind(), ModelKind::Worker);
+        assert_eq!(unknown.api_name(), "someday-model");
+        assert!(unknown.weight() < ModelId::HYDRA_CODER.weight());
+    }
+
+    #[test]
+    fn test_model_id_reads_legacy_records() {
+        // Enum-era serde names and aliases map onto catalog ids
+        for (old, id) in [
+            ("opus45", ModelId::OPUS_45),
+            ("behemoth", ModelId::OPUS_45),
+            ("gemini3_pro", ModelId::GEMINI_3_PRO),
+            ("strand_coder", ModelId::GEMINI_3_PRO),
+            ("qwen35", ModelId::QWEN35),
+            ("hydra_coder", ModelId::HYDRA_CODER),
+        ] {
+            let parsed: ModelId = serde_json::from_str(&format!("\"{old}\"")).unwrap();
+            assert_eq!(parsed, id);
+        }
+        assert!(serde_json::from_str::<ModelId>("\"Not Valid\"").is_err());
+
+        let result: ModelResult = serde_json::from_value(serde_json::json!({
+            "task_id": "t1",
+            "model_id": "behemoth",
+            "timestamp": "2025-01-01T00:00:00Z",
+            "response": "ok",
+            "reasoning": null,
+            "confidence": 0.9,
+            "tokens_used": 10,
+            "latency_ms": 5,
+            "selected": false,
+        }))
+        .unwrap();
+        assert_eq!(result.model_id, ModelId::OPUS_45);
+        assert_eq!(
+            serde_json::to_value(&result).unwrap()["model_id"],
+            "opus_45"
+        );
     }
 
     #[test]
@@ -570,13 +831,13 @@ mod tests {
         assert_eq!(task.assigned_models.len(), 3);
         assert_eq!(task.status, TaskStatus::Pending);
 
-        task.mark_model_complete(ModelId::Opus45);
+        task.mark_model_complete(ModelId::OPUS_45);
         assert_eq!(task.status, TaskStatus::Pending);
 
-        task.mark_model_complete(ModelId::Gemini3Pro);
+        task.mark_model_complete(ModelId::GEMINI_3_PRO);
         assert_eq!(task.status, TaskStatus::Pending);
 
-        task.mark_model_complete(ModelId::Qwen35);
+        task.mark_model_complete(ModelId::QWEN35);
         assert_eq!(task.status, TaskStatus::AwaitingVote);
     }
 
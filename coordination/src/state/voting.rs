@@ -0,0 +1,674 @@
+//! Vote tallying and arbitration for ensemble tasks
+//!
+//! Turns the [`ModelResult`]s submitted for a task plus the ballots cast by
+//! the participating models into a [`VoteOutcome`]: a filled [`VoteRecord`],
+//! a deterministic ranking of candidates, and either a consensus winner or an
+//! [`ArbitrationReason`] explaining why the overseer has to decide.
+//!
+//! Strategies:
+//! - `Majority`: one vote per ballot, the winner needs more than half.
+//! - `Weighted`: each ballot counts `voter.weight() * voter confidence`; the
+//!   heaviest candidate wins outright.
+//! - `Unanimous`: every counted ballot must name the same candidate.
+//!
+//! Ties are never resolved silently. The ranking still orders tied candidates
+//! deterministically (weight, then ballot count, then the candidate's own
+//! confidence, then model catalog order) so arbitration always has a
+//! fallback to offer.
+
+use serde::{Deserialize, Serialize};
+
+use super::catalog::ModelCatalog;
+use super::types::{
+    ArbitrationReason, EnsembleTask, ModelId, ModelResult, TaskStatus, VoteRecord, VotingStrategy,
+};
+
+#[cfg(feature = "full")]
+use crate::events::{EnsembleEvent, EventBus, EventBusResult, VoteSummary};
+
+/// Default confidence below which every result is considered unreliable
+pub const DEFAULT_MIN_CONFIDENCE: f32 = 0.3;
+
+/// Confidence assumed for voters that did not submit a result
+const DEFAULT_VOTER_CONFIDENCE: f32 = 0.5;
+
+/// Weights closer than this are treated as equal
+const WEIGHT_EPSILON: f32 = 1e-4;
+
+// ── Ballots and tallies ─────────────────────────────────────────────
+
+/// A single model's vote for the best response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ballot {
+    /// Model casting the vote
+    pub voter: ModelId,
+    /// Model whose response the voter selected
+    pub choice: ModelId,
+}
+
+impl Ballot {
+    /// Create a new ballot
+    pub fn new(voter: ModelId, choice: ModelId) -> Self {
+        Self { voter, choice }
+    }
+}
+
+/// Accumulated votes for one candidate response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CandidateTally {
+    /// Model that produced the candidate response
+    pub model: ModelId,
+    /// Number of ballots naming this candidate
+    pub votes: u32,
+    /// Total ballot weight under the active strategy
+    pub weight: f32,
+    /// The candidate's own self-reported confidence
+    pub confidence: f32,
+}
+
+/// Final decision of a vote
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum VoteDecision {
+    /// The strategy produced a winner
+    Consensus { winner: ModelId },
+    /// The overseer must arbitrate; `fallback` is the top-ranked candidate
+    Arbitration {
+        reason: ArbitrationReason,
+        fallback: Option<ModelId>,
+    },
+}
+
+/// Everything produced by tallying a task's votes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteOutcome {
+    /// Filled vote record, ready for `StateStore::put_vote`
+    pub record: VoteRecord,
+    /// Candidates ranked best-first (deterministic)
+    pub tallies: Vec<CandidateTally>,
+    /// Models that submitted a result or cast a counted ballot
+    pub participants: Vec<ModelId>,
+    /// Mean confidence over submitted results
+    pub avg_confidence: f32,
+    /// Consensus or arbitration
+    pub decision: VoteDecision,
+}
+
+impl VoteOutcome {
+    /// Winning model, if consensus was reached
+    pub fn winner(&self) -> Option<ModelId> {
+        match &self.decision {
+            VoteDecision::Consensus { winner } => Some(*winner),
+            VoteDecision::Arbitration { .. } => None,
+        }
+    }
+
+    /// Whether the overseer has to arbitrate
+    pub fn needs_arbitration(&self) -> bool {
+        matches!(self.decision, VoteDecision::Arbitration { .. })
+    }
+
+    /// Why arbitration is required (if it is)
+    pub fn arbitration_reason(&self) -> Option<&ArbitrationReason> {
+        match &self.decision {
+            VoteDecision::Consensus { .. } => None,
+            VoteDecision::Arbitration { reason, .. } => Some(reason),
+        }
+    }
+
+    /// Candidate to fall back on when arbitration does not decide otherwise
+    pub fn fallback(&self) -> Option<ModelId> {
+        match &self.decision {
+            VoteDecision::Consensus { winner } => Some(*winner),
+            VoteDecision::Arbitration { fallback, .. } => *fallback,
+        }
+    }
+
+    /// Task status implied by this outcome
+    pub fn task_status(&self) -> TaskStatus {
+        if self.needs_arbitration() {
+            TaskStatus::AwaitingArbitration
+        } else {
+            TaskStatus::Completed
+        }
+    }
+
+    /// Apply the outcome to the task and mark the selected result
+    ///
+    /// On consensus the winner's response becomes the task's final response.
+    /// On arbitration only the status changes; `ArbitrationCompleted` decides.
+    pub fn apply(&self, task: &mut EnsembleTask, results: &mut [ModelResult]) {
+        task.status = self.task_status();
+        task.winning_model = self.winner();
+        task.updated_at = chrono::Utc::now();
+        for result in results.iter_mut() {
+            result.selected = Some(result.model_id) == self.winner();
+            if result.selected {
+                task.final_response = Some(result.response.clone());
+            }
+        }
+    }
+
+    /// One-line summary for logs
+    pub fn summary_line(&self) -> String {
+        let decision = match &self.decision {
+            VoteDecision::Consensus { winner } => format!("winner={}", winner),
+            VoteDecision::Arbitration { reason, .. } => format!("arbitration={}", reason),
+        };
+        format!(
+            "vote {}: strategy={:?} {} [{}]",
+            self.record.task_id,
+            self.record.strategy,
+            decision,
+            render_tallies(&self.tallies)
+        )
+    }
+}
+
+// ── Engine ──────────────────────────────────────────────────────────
+
+/// Applies a [`VotingStrategy`] to model results and ballots
+#[derive(Debug, Clone)]
+pub struct VotingEngine {
+    strategy: VotingStrategy,
+    min_confidence: f32,
+}
+
+impl VotingEngine {
+    /// Create an engine for the given strategy
+    pub fn new(strategy: VotingStrategy) -> Self {
+        Self {
+            strategy,
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+        }
+    }
+
+    /// Require arbitration when no result reaches this confidence
+    pub fn with_min_confidence(mut self, min_confidence: f32) -> Self {
+        self.min_confidence = min_confidence.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Strategy this engine applies
+    pub fn strategy(&self) -> VotingStrategy {
+        self.strategy
+    }
+
+    /// Tally the ballots for a task
+    ///
+    /// Only ballots naming a model that submitted a result are counted. A
+    /// voter casting several ballots keeps its last one.
+    pub fn tally(&self, task_id: &str, results: &[ModelResult], ballots: &[Ballot]) -> VoteOutcome {
+        let mut record = VoteRecord::new(task_id.to_string(), self.strategy);
+        let confidence_of = |model: ModelId| {
+            results
+                .iter()
+                .find(|r| r.model_id == model)
+                .map(|r| r.confidence)
+        };
+
+        // Last ballot per voter wins; ballots for absent candidates are dropped
+        let mut counted: Vec<Ballot> = Vec::new();
+        let mut ignored: Vec<Ballot> = Vec::new();
+        for ballot in ballots {
+            counted.retain(|b| b.voter != ballot.voter);
+            ignored.retain(|b| b.voter != ballot.voter);
+            if confidence_of(ballot.choice).is_some() {
+                counted.push(*ballot);
+            } else {
+                ignored.push(*ballot);
+            }
+        }
+
+        let mut tallies: Vec<CandidateTally> = Vec::new();
+        for result in results {
+            if !tallies.iter().any(|t| t.model == result.model_id) {
+                tallies.push(CandidateTally {
+                    model: result.model_id,
+                    votes: 0,
+                    weight: 0.0,
+                    confidence: result.confidence,
+                });
+            }
+        }
+        for ballot in &counted {
+            let weight = self.ballot_weight(ballot.voter, confidence_of(ballot.voter));
+            record.add_vote(ballot.voter, ballot.choice, weight);
+            if let Some(tally) = tallies.iter_mut().find(|t| t.model == ballot.choice) {
+                tally.votes += 1;
+                tally.weight += weight;
+            }
+        }
+        tallies.sort_by(compare_tallies);
+
+        let mut participants: Vec<ModelId> = results
+            .iter()
+            .map(|r| r.model_id)
+            .chain(counted.iter().map(|b| b.voter))
+            .collect();
+        participants.sort_by_key(|m| model_rank(*m));
+        participants.dedup();
+
+        let avg_confidence = if results.is_empty() {
+            0.0
+        } else {
+            results.iter().map(|r| r.confidence).sum::<f32>() / results.len() as f32
+        };
+
+        let decision = self.decide(results, &counted, &tallies);
+        match &decision {
+            VoteDecision::Consensus { winner } => record.set_winner(*winner),
+            VoteDecision::Arbitration { reason, .. } => record.mark_arbitrated(reason.to_string()),
+        }
+
+        let mut notes = render_tallies(&tallies);
+        if !ignored.is_empty() {
+            let dropped: Vec<String> = ignored
+                .iter()
+                .map(|b| format!("{}->{}", b.voter, b.choice))
+                .collect();
+            notes.push_str(&format!(
+                "; ignored ballots without result: {}",
+                dropped.join(", ")
+            ));
+        }
+        record.notes = Some(notes);
+
+        VoteOutcome {
+            record,
+            tallies,
+            participants,
+            avg_confidence,
+            decision,
+        }
+    }
+
+    /// Weight of one ballot under the active strategy
+    fn ballot_weight(&self, voter: ModelId, voter_confidence: Option<f32>) -> f32 {
+        match self.strategy {
+            VotingStrategy::Majority | VotingStrategy::Unanimous => 1.0,
+            VotingStrategy::Weighted => {
+                voter.weight() * voter_confidence.unwrap_or(DEFAULT_VOTER_CONFIDENCE)
+            }
+        }
+    }
+
+    /// Decide between consensus and arbitration
+    fn decide(
+        &self,
+        results: &[ModelResult],
+        counted: &[Ballot],
+        tallies: &[CandidateTally],
+    ) -> VoteDecision {
+        let fallback = tallies.first().map(|t| t.model);
+        let arbitrate = |reason| VoteDecision::Arbitration { reason, fallback };
+
+        if results.is_empty() {
+            return arbitrate(ArbitrationReason::ConflictingResponses {
+                description: "no model results submitted".to_string(),
+            });
+        }
+        if counted.is_empty() {
+            return arbitrate(ArbitrationReason::ConflictingResponses {
+                description: "no valid ballots cast".to_string(),
+            });
+        }
+
+        let max_confidence = results
+            .iter()
+            .map(|r| r.confidence)
+            .fold(f32::MIN, f32::max);
+        if max_confidence < self.min_confidence {
+            return arbitrate(ArbitrationReason::LowConfidence { max_confidence });
+        }
+
+        let top = &tallies[0];
+        let tied_models: Vec<ModelId> = tallies
+            .iter()
+            .filter(|t| t.votes > 0 && (top.weight - t.weight).abs() <= WEIGHT_EPSILON)
+            .map(|t| t.model)
+            .collect();
+        if tied_models.len() > 1 {
+            return arbitrate(ArbitrationReason::TieVote { tied_models });
+        }
+
+        let total_votes = counted.len() as u32;
+        match self.strategy {
+            VotingStrategy::Majority if top.votes * 2 <= total_votes => {
+                arbitrate(ArbitrationReason::ConflictingResponses {
+                    description: format!(
+                        "no majority: {} has {} of {} votes",
+                        top.model, top.votes, total_votes
+                    ),
+                })
+            }
+            VotingStrategy::Unanimous if top.votes != total_votes => {
+                arbitrate(ArbitrationReason::ConflictingResponses {
+                    description: format!(
+                        "not unanimous: {} has {} of {} votes",
+                        top.model, top.votes, total_votes
+                    ),
+                })
+            }
+            _ => VoteDecision::Consensus { winner: top.model },
+        }
+    }
+}
+
+impl Default for VotingEngine {
+    fn default() -> Self {
+        Self::new(VotingStrategy::Majority)
+    }
+}
+
+// ── Event publishing ────────────────────────────────────────────────
+
+#[cfg(feature = "full")]
+impl VoteOutcome {
+    /// Vote summary for `ConsensusReached`
+    pub fn vote_summary(&self) -> VoteSummary {
+        let counts: Vec<(ModelId, u32)> = self
+            .tallies
+            .iter()
+            .filter(|t| t.votes > 0)
+            .map(|t| (t.model, t.votes))
+            .collect();
+        VoteSummary::new(counts).with_avg_confidence(self.avg_confidence)
+    }
+
+    /// Events describing this vote, in publication order
+    ///
+    /// Always `VotingStarted`, followed by either `ConsensusReached` or
+    /// `ArbitrationRequested`.
+    pub fn events(&self) -> Vec<EnsembleEvent> {
+        let task_id = self.record.task_id.clone();
+        let timestamp = self.record.timestamp;
+        let started = EnsembleEvent::VotingStarted {
+            task_id: task_id.clone(),
+            strategy: self.record.strategy,
+            participating_models: self.participants.clone(),
+            timestamp,
+        };
+        let resolved = match &self.decision {
+            VoteDecision::Consensus { winner } => EnsembleEvent::ConsensusReached {
+                task_id,
+                winner: *winner,
+                vote_summary: self.vote_summary(),
+                timestamp,
+            },
+            VoteDecision::Arbitration { reason, .. } => EnsembleEvent::ArbitrationRequested {
+                task_id,
+                reason: reason.clone(),
+                timestamp,
+            },
+        };
+        vec![started, resolved]
+    }
+
+    /// Publish [`VoteOutcome::events`] on the bus
+    pub fn publish(&self, bus: &EventBus) -> EventBusResult<()> {
+        for event in self.events() {
+            bus.publish(event)?;
+        }
+        Ok(())
+    }
+}
+
+// ── Helpers ─────────────────────────────────────────────────────────
+
+/// Position of a model in catalog order, used as the final tie-break
+fn model_rank(model: ModelId) -> usize {
+    ModelCatalog::active().position(model).unwrap_or(usize::MAX)
+}
+
+/// Best-first ordering: weight, ballots, own confidence, model order
+fn compare_tallies(a: &CandidateTally, b: &CandidateTally) -> std::cmp::Ordering {
+    let by_weight = if (a.weight - b.weight).abs() <= WEIGHT_EPSILON {
+        std::cmp::Ordering::Equal
+    } else {
+        b.weight.total_cmp(&a.weight)
+    };
+    by_weight
+        .then_with(|| b.votes.cmp(&a.votes))
+        .then_with(|| b.confidence.total_cmp(&a.confidence))
+        .then_with(|| model_rank(a.model).cmp(&model_rank(b.model)))
+}
+
+fn render_tallies(tallies: &[CandidateTally]) -> String {
+    tallies
+        .iter()
+        .map(|t| format!("{}={:.2}({})", t.model, t.weight, t.votes))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(model: ModelId, confidence: f32) -> ModelResult {
+        ModelResult::new(
+            "task-1".to_string(),
+            model,
+            format!("answer from {}", model),
+            10,
+            5,
+        )
+        .with_confidence(confidence)
+    }
+
+    fn managers(confidence: f32) -> Vec<ModelResult> {
+        ModelId::all_managers()
+            .iter()
+            .map(|m| result(*m, confidence))
+            .collect()
+    }
+
+    #[test]
+    fn test_majority_winner_fills_record() {
+        let engine = VotingEngine::new(VotingStrategy::Majority);
+        let ballots = [
+            Ballot::new(ModelId::OPUS_45, ModelId::GEMINI_3_PRO),
+            Ballot::new(ModelId::GEMINI_3_PRO, ModelId::GEMINI_3_PRO),
+            Ballot::new(ModelId::QWEN35, ModelId::OPUS_45),
+        ];
+        let outcome = engine.tally("task-1", &managers(0.8), &ballots);
+
+        assert_eq!(outcome.winner(), Some(ModelId::GEMINI_3_PRO));
+        assert_eq!(outcome.record.winner, Some(ModelId::GEMINI_3_PRO));
+        assert!(!outcome.record.arbitrated);
+        assert_eq!(outcome.record.votes.len(), 3);
+        assert_eq!(
+            outcome.record.votes[&ModelId::QWEN35],
+            (ModelId::OPUS_45, 1.0)
+        );
+        assert_eq!(outcome.tallies[0].votes, 2);
+        assert_eq!(outcome.task_status(), TaskStatus::Completed);
+    }
+
+    #[test]
+    fn test_majority_plurality_requires_arbitration() {
+        let engine = VotingEngine::new(VotingStrategy::Majority);
+        let mut results = managers(0.8);
+        results.push(result(ModelId::HYDRA_CODER, 0.8));
+        let ballots = [
+            Ballot::new(ModelId::OPUS_45, ModelId::OPUS_45),
+            Ballot::new(ModelId::GEMINI_3_PRO, ModelId::OPUS_45),
+            Ballot::new(ModelId::QWEN35, ModelId::QWEN35),
+            Ballot::new(ModelId::HYDRA_CODER, ModelId::HYDRA_CODER),
+        ];
+        let outcome = engine.tally("task-1", &results, &ballots);
+
+        // 2 of 4 is not more than half
+        assert!(matches!(
+            outcome.arbitration_reason(),
+            Some(ArbitrationReason::ConflictingResponses { .. })
+        ));
+        assert!(outcome.record.arbitrated);
+        assert_eq!(outcome.record.winner, None);
+    }
+
+    #[test]
+    fn test_tie_vote_is_deterministic() {
+        let engine = VotingEngine::new(VotingStrategy::Majority);
+        let results = vec![result(ModelId::QWEN35, 0.7), result(ModelId::OPUS_45, 0.7)];
+        let ballots = [
+            Ballot::new(ModelId::OPUS_45, ModelId::QWEN35),
+            Ballot::new(ModelId::QWEN35, ModelId::OPUS_45),
+        ];
+        let outcome = engine.tally("task-1", &results, &ballots);
+
+        match &outcome.decision {
+            VoteDecision::Arbitration {
+                reason: ArbitrationReason::TieVote { tied_models },
+                fallback,
+            } => {
+                // Equal weight and confidence fall back to catalog order
+                assert_eq!(tied_models, &vec![ModelId::OPUS_45, ModelId::QWEN35]);
+                assert_eq!(*fallback, Some(ModelId::OPUS_45));
+            }
+            other => panic!("expected tie vote, got {:?}", other),
+        }
+        assert_eq!(outcome.task_status(), TaskStatus::AwaitingArbitration);
+
+        // Reordering inputs does not change the ranking
+        let reversed: Vec<ModelResult> = results.iter().rev().cloned().collect();
+        let again = engine.tally("task-1", &reversed, &ballots);
+        assert_eq!(again.tallies, outcome.tallies);
+    }
+
+    #[test]
+    fn test_weighted_uses_model_weight_and_confidence() {
+        let engine = VotingEngine::new(VotingStrategy::Weighted);
+        let results = vec![
+            result(ModelId::OPUS_45, 0.9),
+            result(ModelId::QWEN35, 0.4),
+            result(ModelId::HYDRA_CODER, 0.4),
+        ];
+        let ballots = [
+            Ballot::new(ModelId::OPUS_45, ModelId::OPUS_45),
+            Ballot::new(ModelId::QWEN35, ModelId::QWEN35),
+            Ballot::new(ModelId::HYDRA_CODER, ModelId::QWEN35),
+        ];
+        let outcome = engine.tally("task-1", &results, &ballots);
+
+        // Opus: 1.0 * 0.9 = 0.9; Qwen: 1.0 * 0.4 + 0.85 * 0.4 = 0.74
+        assert_eq!(outcome.winner(), Some(ModelId::OPUS_45));
+        let (_, hydra_weight) = outcome.record.votes[&ModelId::HYDRA_CODER];
+        assert!((hydra_weight - 0.34).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_unanimous_requires_every_ballot() {
+        let engine = VotingEngine::new(VotingStrategy::Unanimous);
+        let all_agree: Vec<Ballot> = ModelId::all_managers()
+            .iter()
+            .map(|m| Ballot::new(*m, ModelId::QWEN35))
+            .collect();
+        let outcome = engine.tally("task-1", &managers(0.6), &all_agree);
+        assert_eq!(outcome.winner(), Some(ModelId::QWEN35));
+
+        let mut split = all_agree.clone();
+        split[0].choice = ModelId::OPUS_45;
+        let outcome = engine.tally("task-1", &managers(0.6), &split);
+        assert!(outcome.needs_arbitration());
+        assert_eq!(outcome.fallback(), Some(ModelId::QWEN35));
+    }
+
+    #[test]
+    fn test_low_confidence_requires_arbitration() {
+        let engine = VotingEngine::new(VotingStrategy::Majority).with_min_confidence(0.5);
+        let ballots: Vec<Ballot> = ModelId::all_managers()
+            .iter()
+            .map(|m| Ballot::new(*m, ModelId::OPUS_45))
+            .collect();
+        let outcome = engine.tally("task-1", &managers(0.2), &ballots);
+
+        match outcome.arbitration_reason() {
+            Some(ArbitrationReason::LowConfidence { max_confidence }) => {
+                assert!((max_confidence - 0.2).abs() < 1e-6)
+            }
+            other => panic!("expected low confidence, got {:?}", other),
+        }
+        assert!(outcome
+            .record
+            .arbitration_reason
+            .as_deref()
+            .unwrap()
+            .starts_with("low_confidence"));
+    }
+
+    #[test]
+    fn test_ballots_for_missing_results_are_ignored() {
+        let engine = VotingEngine::new(VotingStrategy::Majority);
+        let results = vec![result(ModelId::OPUS_45, 0.8)];
+        let ballots = [
+            Ballot::new(ModelId::GEMINI_3_PRO, ModelId::QWEN35),
+            Ballot::new(ModelId::OPUS_45, ModelId::OPUS_45),
+            // Re-vote replaces the earlier ballot
+            Ballot::new(ModelId::GEMINI_3_PRO, ModelId::OPUS_45),
+        ];
+        let outcome = engine.tally("task-1", &results, &ballots);
+        assert_eq!(outcome.winner(), Some(ModelId::OPUS_45));
+        assert_eq!(outcome.tallies[0].votes, 2);
+
+        let outcome = engine.tally(
+            "task-1",
+            &results,
+            &[Ballot::new(ModelId::QWEN35, ModelId::HYDRA_CODER)],
+        );
+        assert!(matches!(
+            outcome.arbitration_reason(),
+            Some(ArbitrationReason::ConflictingResponses { .. })
+        ));
+        assert!(outcome
+            .record
+            .notes
+            .unwrap()
+            .contains("qwen35->hydra_coder"));
+    }
+
+    #[test]
+    fn test_apply_marks_task_and_results() {
+        let engine = VotingEngine::default();
+        let mut task = EnsembleTask::new("session-1".to_string(), "prompt".to_string(), true);
+        let mut results = managers(0.8);
+        let ballots: Vec<Ballot> = ModelId::all_managers()
+            .iter()
+            .map(|m| Ballot::new(*m, ModelId::GEMINI_3_PRO))
+            .collect();
+        let outcome = engine.tally(&task.id.clone(), &results, &ballots);
+        outcome.apply(&mut task, &mut results);
+
+        assert_eq!(task.status, TaskStatus::Completed);
+        assert_eq!(task.winning_model, Some(ModelId::GEMINI_3_PRO));
+        assert_eq!(
+            task.final_response.as_deref(),
+            Some("answer from gemini_3_pro")
+        );
+        let selected: Vec<ModelId> = results
+            .iter()
+            .filter(|r| r.selected)
+            .map(|r| r.model_id)
+            .collect();
+        assert_eq!(selected, vec![ModelId::GEMINI_3_PRO]);
+    }
+
+    #[cfg(feature = "full")]
+    #[test]
+    fn test_events_follow_decision() {
+        let engine = VotingEngine::new(VotingStrategy::Majority);
+        let ballots: Vec<Ballot> = ModelId::all_managers()
+            .iter()
+            .map(|m| Ballot::new(*m, ModelId::OPUS_45))
+            .collect();
+        let outcome = engine.tally("task-1", &managers(0.8), &ballots);
+        let types: Vec<&str> = outcome.events().iter().map(|e| e.event_type()).collect();
+        assert_eq!(types, vec!["voting_started", "consensus_reached"]);
+        assert_eq!(outcome.vote_summary().total_votes, 3);
+
+        let outcome = engine.tally("task-1", &managers(0.8), &[]);
+        let types: Vec<&str> = outcome.events().iter().map(|e| e.event_type()).collect();
+        assert_eq!(types, vec!["voting_started", "arbitration_requested"]);
+    }
+}
@@ -0,0 +1,91 @@
+//! Shared test helpers for crate unit tests.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+/// Request bodies received by a test server, parsed as JSON (`null` if empty)
+pub type SeenRequests = Arc<Mutex<Vec<serde_json::Value>>>;
+
+/// Minimal HTTP/1.1 server on a background thread.
+///
+/// Each connection carries one request; `reply` gets its parsed body and
+/// returns the `(status, body)` to answer with, or `None` to stop serving.
+/// Returns the base URL (`http://127.0.0.1:<port>`) and the recorded bodies.
+pub fn http_server<F>(mut reply: F) -> (String, SeenRequests)
+where
+    F: FnMut(&serde_json::Value) -> Option<(u16, String)> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let seen = SeenRequests::default();
+    let log = Arc::clone(&seen);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+                if let Some(v) = line.to_lowercase().strip_prefix("content-length:") {
+                    length = v.trim().parse().unwrap_or(0);
+                }
+            }
+            let mut buf = vec![0; length];
+            if reader.read_exact(&mut buf).is_err() {
+                continue;
+            }
+            let body = serde_json::from_slice(&buf).unwrap_or_default();
+            let Some((status, reply_body)) = reply(&body) else {
+                return;
+            };
+            log.lock().unwrap().push(body);
+            let mut stream = reader.into_inner();
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{reply_body}",
+                reply_body.len()
+            );
+        }
+    });
+    (url, seen)
+}
+
+/// Serve one canned `(status, body)` per connection, then stop.
+///
+/// Returns the server URL with `path` appended and the recorded bodies.
+pub fn mock_server(path: &str, replies: Vec<(u16, String)>) -> (String, SeenRequests) {
+    let mut replies = replies.into_iter();
+    let (base, seen) = http_server(move |_| replies.next());
+    (format!("{base}{path}"), seen)
+}
+
+/// Run `git <args>` in `dir`, panicking unless it succeeds.
+pub fn git(dir: &Path, args: &[&str]) {
+    let ok = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false);
+    assert!(ok, "git {args:?} failed");
+}
+
+/// `git init` in `dir` with a throwaway identity and signing off.
+pub fn git_init(dir: &Path) {
+    git(dir, &["init", "-q"]);
+    git(dir, &["config", "user.email", "t@example.com"]);
+    git(dir, &["config", "user.name", "t"]);
+    git(dir, &["config", "commit.gpgsign", "false"]);
+}
+
+/// Stage everything in `dir` and commit it.
+pub fn git_commit_all(dir: &Path, message: &str) {
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-qm", message]);
+}
@@ -107,7 +107,9 @@ pub struct DependencyCheckRequest {
 }
 
 /// Impact level of a dependency change.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// Variants are ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ImpactLevel {
     /// No downstream impact (leaf node change).
     None,
@@ -119,6 +121,18 @@ pub enum ImpactLevel {
     High,
 }
 
+impl ImpactLevel {
+    /// Classify impact by the number of dependent files.
+    pub fn from_dependents(count: usize) -> Self {
+        match count {
+            0 => Self::None,
+            1..=3 => Self::Low,
+            4..=10 => Self::Medium,
+            _ => Self::High,
+        }
+    }
+}
+
 impl std::fmt::Display for ImpactLevel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {